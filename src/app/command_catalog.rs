@@ -54,12 +54,18 @@ pub mod function_ids {
         "orchestrator.set_selection_max_retries";
     pub const WORKFLOW_LIST: &str = "workflow.list";
     pub const WORKFLOW_SHOW: &str = "workflow.show";
+    pub const WORKFLOW_GRAPH: &str = "workflow.graph";
     pub const WORKFLOW_ADD: &str = "workflow.add";
     pub const WORKFLOW_REMOVE: &str = "workflow.remove";
     pub const WORKFLOW_RUN: &str = "workflow.run";
+    pub const WORKFLOW_RERUN: &str = "workflow.rerun";
+    pub const WORKFLOW_SIMULATE: &str = "workflow.simulate";
     pub const WORKFLOW_STATUS: &str = "workflow.status";
     pub const WORKFLOW_PROGRESS: &str = "workflow.progress";
+    pub const WORKFLOW_EVENTS: &str = "workflow.events";
     pub const WORKFLOW_CANCEL: &str = "workflow.cancel";
+    pub const WORKFLOW_APPROVE: &str = "workflow.approve";
+    pub const WORKFLOW_REJECT: &str = "workflow.reject";
     pub const CHANNEL_PROFILE_LIST: &str = "channel_profile.list";
     pub const CHANNEL_PROFILE_ADD: &str = "channel_profile.add";
     pub const CHANNEL_PROFILE_SHOW: &str = "channel_profile.show";
@@ -73,6 +79,7 @@ pub mod function_ids {
     pub const SCHEDULE_RESUME: &str = "schedule.resume";
     pub const SCHEDULE_DELETE: &str = "schedule.delete";
     pub const SCHEDULE_RUN_NOW: &str = "schedule.run_now";
+    pub const USAGE_REPORT: &str = "usage.report";
     pub const UPDATE_CHECK: &str = "update.check";
     pub const UPDATE_APPLY: &str = "update.apply";
     pub const DAEMON_ATTACH: &str = "daemon.attach";
@@ -100,7 +107,7 @@ const PROVIDER_SET_ARGS: &[FunctionArgDef] = &[
         name: "provider",
         arg_type: FunctionArgTypeDef::String,
         required: true,
-        description: "Provider id: anthropic or openai",
+        description: "Provider id: anthropic, openai or gemini",
     },
     FunctionArgDef {
         name: "model",
@@ -145,6 +152,16 @@ const RUN_ID_ARG: FunctionArgDef = FunctionArgDef {
     description: "Workflow run id",
 };
 
+const HUMAN_REVIEW_ARGS: &[FunctionArgDef] = &[
+    RUN_ID_ARG,
+    FunctionArgDef {
+        name: "comment",
+        arg_type: FunctionArgTypeDef::String,
+        required: false,
+        description: "Optional reviewer feedback passed to the next step",
+    },
+];
+
 const PROFILE_ID_ARG: FunctionArgDef = FunctionArgDef {
     name: "channelProfileId",
     arg_type: FunctionArgTypeDef::String,
@@ -425,6 +442,21 @@ pub const V1_FUNCTIONS: &[FunctionDef] = &[
         ],
         read_only: true,
     },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_GRAPH,
+        description: "Render a workflow as a Mermaid or Graphviz diagram",
+        args: &[
+            ORCHESTRATOR_ID_ARG,
+            WORKFLOW_ID_ARG,
+            FunctionArgDef {
+                name: "format",
+                arg_type: FunctionArgTypeDef::String,
+                required: false,
+                description: "Diagram format: mermaid (default) or dot",
+            },
+        ],
+        read_only: true,
+    },
     FunctionDef {
         function_id: function_ids::WORKFLOW_ADD,
         description: "Add workflow to orchestrator config",
@@ -452,6 +484,35 @@ pub const V1_FUNCTIONS: &[FunctionDef] = &[
         ],
         read_only: false,
     },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_RERUN,
+        description: "Start a new run that re-executes a finished run from one step",
+        args: &[
+            RUN_ID_ARG,
+            FunctionArgDef {
+                name: "fromStepId",
+                arg_type: FunctionArgTypeDef::String,
+                required: true,
+                description: "First step to execute again; earlier step outputs are reused",
+            },
+        ],
+        read_only: false,
+    },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_SIMULATE,
+        description: "Simulate a workflow with scripted agent responses instead of provider calls",
+        args: &[
+            ORCHESTRATOR_ID_ARG,
+            WORKFLOW_ID_ARG,
+            FunctionArgDef {
+                name: "scriptPath",
+                arg_type: FunctionArgTypeDef::String,
+                required: true,
+                description: "Path to the YAML file of scripted step responses",
+            },
+        ],
+        read_only: true,
+    },
     FunctionDef {
         function_id: function_ids::WORKFLOW_STATUS,
         description: "Read workflow run status summary",
@@ -464,12 +525,30 @@ pub const V1_FUNCTIONS: &[FunctionDef] = &[
         args: &[RUN_ID_ARG],
         read_only: true,
     },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_EVENTS,
+        description: "Read the event journal of a workflow run as JSON lines",
+        args: &[RUN_ID_ARG],
+        read_only: true,
+    },
     FunctionDef {
         function_id: function_ids::WORKFLOW_CANCEL,
         description: "Cancel a workflow run",
         args: &[RUN_ID_ARG],
         read_only: false,
     },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_APPROVE,
+        description: "Approve a workflow run waiting on human review",
+        args: HUMAN_REVIEW_ARGS,
+        read_only: false,
+    },
+    FunctionDef {
+        function_id: function_ids::WORKFLOW_REJECT,
+        description: "Reject a workflow run waiting on human review",
+        args: HUMAN_REVIEW_ARGS,
+        read_only: false,
+    },
     FunctionDef {
         function_id: function_ids::CHANNEL_PROFILE_LIST,
         description: "List configured channel profile ids",
@@ -545,7 +624,7 @@ pub const V1_FUNCTIONS: &[FunctionDef] = &[
                 name: "scheduleType",
                 arg_type: FunctionArgTypeDef::String,
                 required: true,
-                description: "Schedule type: once|interval|cron",
+                description: "Schedule type: once|interval|cron|file_watch",
             },
             FunctionArgDef {
                 name: "schedule",
@@ -637,6 +716,17 @@ pub const V1_FUNCTIONS: &[FunctionDef] = &[
         args: &[JOB_ID_ARG],
         read_only: false,
     },
+    FunctionDef {
+        function_id: function_ids::USAGE_REPORT,
+        description: "Report provider token usage per workflow and agent",
+        args: &[FunctionArgDef {
+            name: "since",
+            arg_type: FunctionArgTypeDef::String,
+            required: false,
+            description: "Window start: duration like 24h or 7d, unix seconds, or RFC3339",
+        }],
+        read_only: true,
+    },
     FunctionDef {
         function_id: function_ids::UPDATE_CHECK,
         description: "Check for updates",
@@ -6,11 +6,14 @@ use crate::app::schedule_parsing::{
 use crate::config::{load_orchestrator_config, Settings};
 use crate::orchestration::diagnostics::append_security_log;
 use crate::orchestration::error::OrchestratorError;
-use crate::orchestration::run_store::{RunState, WorkflowRunStore};
+use crate::orchestration::run_store::WorkflowRunStore;
+use crate::orchestration::run_worktree::cancel_run_releasing_worktrees;
 use crate::orchestration::scheduler::{
-    JobPatch, JobStore, MisfirePolicy, NewJob, ScheduleConfig, ScheduledJob, TargetAction,
+    validate_file_watch_access, JobPatch, JobStore, MisfirePolicy, NewJob, ScheduleConfig,
+    ScheduledJob, TargetAction,
 };
 use crate::orchestration::slack_target::validate_profile_mapping;
+use crate::orchestration::usage::run_usage;
 use serde_json::{Map, Value};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -179,6 +182,7 @@ pub fn execute_internal_function(
             let progress = run_store
                 .load_progress(&run_id)
                 .map_err(|error| remap_missing_run_error(&run_id, error))?;
+            let usage = run_usage(run_store, &run_id)?;
             Ok(Value::Object(Map::from_iter([
                 ("runId".to_string(), Value::String(run_id)),
                 (
@@ -186,6 +190,11 @@ pub fn execute_internal_function(
                     serde_json::to_value(progress)
                         .map_err(|error| OrchestratorError::SelectorJson(error.to_string()))?,
                 ),
+                (
+                    "usage".to_string(),
+                    serde_json::to_value(usage)
+                        .map_err(|error| OrchestratorError::SelectorJson(error.to_string()))?,
+                ),
             ])))
         }
         InternalFunction::WorkflowCancel { run_id } => {
@@ -197,17 +206,8 @@ pub fn execute_internal_function(
             let mut run = run_store
                 .load_run(&run_id)
                 .map_err(|error| remap_missing_run_error(&run_id, error))?;
-            if !run.state.clone().is_terminal() {
-                let now = run.updated_at.saturating_add(1);
-                run_store.transition_state(
-                    &mut run,
-                    RunState::Canceled,
-                    now,
-                    "canceled by command",
-                    false,
-                    "none",
-                )?;
-            }
+            let now = run.updated_at.saturating_add(1);
+            cancel_run_releasing_worktrees(run_store, &mut run, now, "canceled by command")?;
             Ok(Value::Object(Map::from_iter([
                 ("runId".to_string(), Value::String(run_id)),
                 ("state".to_string(), Value::String(run.state.to_string())),
@@ -346,6 +346,8 @@ pub fn execute_internal_function(
                     .map_err(OrchestratorError::SelectorValidation)?;
             validate_profile_mapping(settings, &orchestrator_id, slack_target_ref.as_ref())
                 .map_err(OrchestratorError::SelectorValidation)?;
+            validate_file_watch_access(settings, &orchestrator_id, &schedule)
+                .map_err(OrchestratorError::SelectorValidation)?;
             let runtime_root = settings
                 .resolve_orchestrator_runtime_root(&orchestrator_id)
                 .map_err(|err| OrchestratorError::SelectorValidation(err.to_string()))?;
@@ -433,6 +435,10 @@ pub fn execute_internal_function(
                 .map_err(OrchestratorError::SelectorValidation)?;
             validate_profile_mapping(settings, &orchestrator_id, slack_target_ref.as_ref())
                 .map_err(OrchestratorError::SelectorValidation)?;
+            if let Some(schedule) = patch.schedule.as_ref() {
+                validate_file_watch_access(settings, &orchestrator_id, schedule)
+                    .map_err(OrchestratorError::SelectorValidation)?;
+            }
             let job = store
                 .update(&job_id, patch, now_secs())
                 .map_err(OrchestratorError::SelectorValidation)?;
@@ -747,6 +753,24 @@ pub fn plan_function_invocation(
                 },
             ))
         }
+        function_ids::WORKFLOW_GRAPH => {
+            let mut argv = vec![
+                "workflow".to_string(),
+                "graph".to_string(),
+                required_string_arg(args, "orchestratorId")?,
+                required_string_arg(args, "workflowId")?,
+            ];
+            if let Some(format) = optional_string_arg(args, "format")? {
+                argv.push("--format".to_string());
+                argv.push(format);
+            }
+            Ok(FunctionExecutionPlan::CliArgs(argv))
+        }
+        function_ids::WORKFLOW_EVENTS => Ok(FunctionExecutionPlan::CliArgs(vec![
+            "workflow".to_string(),
+            "events".to_string(),
+            required_string_arg(args, "runId")?,
+        ])),
         function_ids::WORKFLOW_ADD => {
             let orchestrator_id = required_string_arg(args, "orchestratorId")?;
             let workflow_id = required_string_arg(args, "workflowId")?;
@@ -788,6 +812,30 @@ pub fn plan_function_invocation(
             }
             Ok(FunctionExecutionPlan::CliArgs(cli_args))
         }
+        function_ids::WORKFLOW_RERUN => {
+            let run_id = required_string_arg(args, "runId")?;
+            let from_step_id = required_string_arg(args, "fromStepId")?;
+            Ok(FunctionExecutionPlan::CliArgs(vec![
+                "workflow".to_string(),
+                "rerun".to_string(),
+                run_id,
+                "--from".to_string(),
+                from_step_id,
+            ]))
+        }
+        function_ids::WORKFLOW_SIMULATE => {
+            let orchestrator_id = required_string_arg(args, "orchestratorId")?;
+            let workflow_id = required_string_arg(args, "workflowId")?;
+            let script_path = required_string_arg(args, "scriptPath")?;
+            Ok(FunctionExecutionPlan::CliArgs(vec![
+                "workflow".to_string(),
+                "simulate".to_string(),
+                orchestrator_id,
+                workflow_id,
+                "--script".to_string(),
+                script_path,
+            ]))
+        }
         function_ids::WORKFLOW_STATUS => {
             let run_id = required_string_arg(args, "runId")?;
             Ok(FunctionExecutionPlan::Internal(
@@ -806,6 +854,20 @@ pub fn plan_function_invocation(
                 InternalFunction::WorkflowCancel { run_id },
             ))
         }
+        function_ids::WORKFLOW_APPROVE | function_ids::WORKFLOW_REJECT => {
+            let run_id = required_string_arg(args, "runId")?;
+            let action = if function_id == function_ids::WORKFLOW_APPROVE {
+                "approve"
+            } else {
+                "reject"
+            };
+            let mut cli_args = vec!["workflow".to_string(), action.to_string(), run_id];
+            if let Some(comment) = optional_string_arg(args, "comment")? {
+                cli_args.push("--comment".to_string());
+                cli_args.push(comment);
+            }
+            Ok(FunctionExecutionPlan::CliArgs(cli_args))
+        }
         function_ids::ORCHESTRATOR_LIST => Ok(FunctionExecutionPlan::Internal(
             InternalFunction::OrchestratorList,
         )),
@@ -1006,6 +1068,14 @@ pub fn plan_function_invocation(
                 InternalFunction::ScheduleRunNow { job_id },
             ))
         }
+        function_ids::USAGE_REPORT => {
+            let mut cli_args = vec!["usage".to_string(), "report".to_string()];
+            if let Some(since) = optional_string_arg(args, "since")? {
+                cli_args.push("--since".to_string());
+                cli_args.push(since);
+            }
+            Ok(FunctionExecutionPlan::CliArgs(cli_args))
+        }
         function_ids::UPDATE_CHECK => Ok(FunctionExecutionPlan::CliArgs(vec![
             "update".to_string(),
             "check".to_string(),
@@ -1,7 +1,8 @@
 use crate::app::command_support::{
     load_orchestrator_or_err, load_settings, save_orchestrator_config,
 };
-use crate::config::{AgentConfig, ConfigProviderKind};
+use crate::config::AgentConfig;
+use crate::provider::ProviderKind;
 
 pub fn cmd_orchestrator_agent(args: &[String]) -> Result<String, String> {
     if args.is_empty() {
@@ -38,9 +39,11 @@ pub fn cmd_orchestrator_agent(args: &[String]) -> Result<String, String> {
             orchestrator.agents.insert(
                 agent_id.clone(),
                 AgentConfig {
-                    provider: ConfigProviderKind::Anthropic,
+                    provider: ProviderKind::ANTHROPIC,
                     model: "sonnet".to_string(),
                     can_orchestrate_workflows: false,
+                    endpoint: None,
+                    fallbacks: Vec::new(),
                 },
             );
             save_orchestrator_config(&settings, orchestrator_id, &orchestrator)?;
@@ -99,9 +102,11 @@ pub fn cmd_orchestrator_agent(args: &[String]) -> Result<String, String> {
                 .agents
                 .get_mut(&agent_id)
                 .ok_or_else(|| format!("unknown agent `{agent_id}`"))?;
-            agent.provider = ConfigProviderKind::Anthropic;
+            agent.provider = ProviderKind::ANTHROPIC;
             agent.model = "sonnet".to_string();
             agent.can_orchestrate_workflows = false;
+            agent.endpoint = None;
+            agent.fallbacks.clear();
             save_orchestrator_config(&settings, orchestrator_id, &orchestrator)?;
             Ok(format!(
                 "agent reset\norchestrator={}\nagent={}",
@@ -3,9 +3,10 @@ use crate::channels::slack;
 use crate::config::{
     default_global_config_path, load_orchestrator_config, OrchestratorConfig, Settings,
 };
+use crate::provider::backend::is_binary_available;
+use crate::provider::{builtin_registry, ProviderEndpoint, RunnerBinaries};
+use std::collections::BTreeSet;
 use std::fs;
-#[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -38,50 +39,6 @@ fn load_orchestrator_or_err(
     load_orchestrator_config(settings, orchestrator_id).map_err(map_config_err)
 }
 
-fn is_binary_available(binary: &str) -> bool {
-    if binary.trim().is_empty() {
-        return false;
-    }
-    let explicit = Path::new(binary);
-    if explicit.components().count() > 1 || explicit.is_absolute() {
-        return is_executable_file(explicit);
-    }
-
-    let Some(path) = std::env::var_os("PATH") else {
-        return false;
-    };
-    std::env::split_paths(&path).any(|dir| {
-        let candidate = dir.join(binary);
-        if is_executable_file(&candidate) {
-            return true;
-        }
-        #[cfg(windows)]
-        {
-            if is_executable_file(&dir.join(format!("{binary}.exe"))) {
-                return true;
-            }
-        }
-        false
-    })
-}
-
-fn is_executable_file(path: &Path) -> bool {
-    let Ok(metadata) = fs::metadata(path) else {
-        return false;
-    };
-    if !metadata.is_file() {
-        return false;
-    }
-    #[cfg(unix)]
-    {
-        metadata.permissions().mode() & 0o111 != 0
-    }
-    #[cfg(not(unix))]
-    {
-        true
-    }
-}
-
 fn now_nanos() -> i128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -96,6 +53,83 @@ fn can_write_directory(path: &Path) -> Result<(), String> {
     fs::remove_file(&probe).map_err(|e| format!("failed to remove {}: {e}", probe.display()))
 }
 
+/// Providers whose binaries doctor always checks; other backends are only
+/// checked once an agent is configured to use them.
+const DEFAULT_PROVIDERS: [&str; 2] = ["anthropic", "openai"];
+
+fn is_checked_provider(id: &str, configured: &BTreeSet<String>) -> bool {
+    DEFAULT_PROVIDERS.contains(&id) || configured.contains(id)
+}
+
+fn configured_providers(settings: &Settings) -> BTreeSet<String> {
+    settings
+        .orchestrators
+        .keys()
+        .filter_map(|orchestrator_id| load_orchestrator_or_err(settings, orchestrator_id).ok())
+        .flat_map(|orchestrator| {
+            orchestrator.agents.into_values().flat_map(|agent| {
+                std::iter::once(agent.provider)
+                    .chain(
+                        agent
+                            .fallbacks
+                            .into_iter()
+                            .map(|fallback| fallback.provider),
+                    )
+                    .map(|provider| provider.to_string())
+            })
+        })
+        .collect()
+}
+
+/// Checks every HTTP endpoint configured on an agent or one of its fallback
+/// entries, as `endpoint.<orchestrator>.<agent>` and
+/// `endpoint.<orchestrator>.<agent>.fallback<n>`.
+fn endpoint_findings(settings: &Settings) -> Vec<DoctorFinding> {
+    let mut findings = Vec::new();
+    for orchestrator_id in settings.orchestrators.keys() {
+        let Ok(orchestrator) = load_orchestrator_or_err(settings, orchestrator_id) else {
+            continue;
+        };
+        for (agent_id, agent) in &orchestrator.agents {
+            let entries = std::iter::once((
+                format!("endpoint.{orchestrator_id}.{agent_id}"),
+                &agent.provider,
+                &agent.endpoint,
+            ))
+            .chain(agent.fallbacks.iter().enumerate().map(|(index, fallback)| {
+                (
+                    format!(
+                        "endpoint.{orchestrator_id}.{agent_id}.fallback{}",
+                        index + 1
+                    ),
+                    &fallback.provider,
+                    &fallback.endpoint,
+                )
+            }));
+            for (id, provider, endpoint) in entries {
+                let (Ok(backend), Some(endpoint)) =
+                    (builtin_registry().backend_for(provider), endpoint)
+                else {
+                    continue;
+                };
+                let endpoint = ProviderEndpoint {
+                    base_url: endpoint.base_url.trim().to_string(),
+                    api_key_env: endpoint.api_key_env.clone(),
+                };
+                if let Some(health) = backend.endpoint_health_check(&endpoint) {
+                    findings.push(doctor_finding(
+                        id,
+                        health.ok,
+                        health.detail,
+                        health.remediation,
+                    ));
+                }
+            }
+        }
+    }
+    findings
+}
+
 pub fn cmd_doctor() -> Result<String, String> {
     let mut findings = Vec::new();
     let config_path = default_global_config_path().map_err(map_config_err)?;
@@ -127,28 +161,26 @@ pub fn cmd_doctor() -> Result<String, String> {
         }
     };
 
-    let anthropic_bin = std::env::var("DIRECLAW_PROVIDER_BIN_ANTHROPIC")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .unwrap_or_else(|| "claude".to_string());
-    let openai_bin = std::env::var("DIRECLAW_PROVIDER_BIN_OPENAI")
-        .ok()
-        .filter(|v| !v.trim().is_empty())
-        .unwrap_or_else(|| "codex".to_string());
-    findings.push(doctor_finding(
-        "binary.anthropic",
-        is_binary_available(&anthropic_bin),
-        format!("binary={anthropic_bin}"),
-        "install the Anthropic CLI or set DIRECLAW_PROVIDER_BIN_ANTHROPIC",
-    ));
-    findings.push(doctor_finding(
-        "binary.openai",
-        is_binary_available(&openai_bin),
-        format!("binary={openai_bin}"),
-        "install the OpenAI Codex CLI or set DIRECLAW_PROVIDER_BIN_OPENAI",
-    ));
+    let binaries = RunnerBinaries::from_env();
+    let configured = settings
+        .as_ref()
+        .map(configured_providers)
+        .unwrap_or_default();
+    for backend in builtin_registry()
+        .backends()
+        .filter(|backend| is_checked_provider(backend.id(), &configured))
+    {
+        let health = backend.health_check(&binaries);
+        findings.push(doctor_finding(
+            format!("binary.{}", backend.id()),
+            health.ok,
+            health.detail,
+            health.remediation,
+        ));
+    }
 
     if let Some(settings) = settings.as_ref() {
+        findings.extend(endpoint_findings(settings));
         findings.push(match can_write_directory(&settings.workspaces_path) {
             Ok(_) => doctor_finding(
                 "workspace.root",
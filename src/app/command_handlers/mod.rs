@@ -22,6 +22,7 @@ pub mod orchestrators;
 pub mod provider;
 pub mod schedule;
 pub mod update;
+pub mod usage;
 pub mod workflows;
 
 pub fn execute_function_invocation(
@@ -67,6 +68,7 @@ fn run_cli_native(args: Vec<String>) -> Result<String, String> {
         "channel-profile" => channel_profiles::cmd_channel_profile(&args[1..]),
         "auth" => auth::cmd_auth(&args[1..]),
         "schedule" => schedule::cmd_schedule(&args[1..]),
+        "usage" => usage::cmd_usage(&args[1..]),
         "__supervisor" => daemon::cmd_supervisor(&args[1..]),
         _ => Err(format!("unknown command `{}`", args[0])),
     }
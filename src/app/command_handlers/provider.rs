@@ -1,4 +1,5 @@
 use crate::app::command_support::{ensure_runtime_root, load_preferences, save_preferences};
+use crate::provider::builtin_registry;
 
 pub fn cmd_provider(args: &[String]) -> Result<String, String> {
     let paths = ensure_runtime_root()?;
@@ -13,8 +14,16 @@ pub fn cmd_provider(args: &[String]) -> Result<String, String> {
     }
 
     let provider = args[0].clone();
-    if provider != "anthropic" && provider != "openai" {
-        return Err("provider must be one of: anthropic, openai".to_string());
+    let cli_providers = builtin_registry()
+        .backends()
+        .filter(|backend| !backend.uses_endpoint())
+        .map(|backend| backend.id())
+        .collect::<Vec<_>>();
+    if !cli_providers.contains(&provider.as_str()) {
+        return Err(format!(
+            "provider must be one of: {}",
+            cli_providers.join(", ")
+        ));
     }
 
     prefs.provider = Some(provider.clone());
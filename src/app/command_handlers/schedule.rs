@@ -3,7 +3,7 @@ use crate::app::schedule_parsing::{
     normalize_patch_slack_target_ref, normalize_slack_target_ref_value, parse_job_patch,
     parse_schedule_config, parse_schedule_create_tail_args, parse_target_action_config,
 };
-use crate::orchestration::scheduler::{JobPatch, JobStore, NewJob};
+use crate::orchestration::scheduler::{validate_file_watch_access, JobPatch, JobStore, NewJob};
 use crate::orchestration::slack_target::validate_profile_mapping;
 use serde_json::{Map, Value};
 
@@ -31,6 +31,7 @@ pub fn cmd_schedule(args: &[String]) -> Result<String, String> {
             let (target_ref, slack_target_ref) =
                 normalize_slack_target_ref_value(tail.target_ref, "target_ref_json")?;
             validate_profile_mapping(&settings, &orchestrator_id, slack_target_ref.as_ref())?;
+            validate_file_watch_access(&settings, &orchestrator_id, &schedule)?;
 
             let runtime_root = settings
                 .resolve_orchestrator_runtime_root(&orchestrator_id)
@@ -115,6 +116,9 @@ pub fn cmd_schedule(args: &[String]) -> Result<String, String> {
             let mut patch = parse_patch(&args[3])?;
             let slack_target_ref = normalize_patch_slack_target_ref(&mut patch, "patch.targetRef")?;
             validate_profile_mapping(&settings, &args[1], slack_target_ref.as_ref())?;
+            if let Some(schedule) = patch.schedule.as_ref() {
+                validate_file_watch_access(&settings, &args[1], schedule)?;
+            }
             let job = store.update(&args[2], patch, now_secs())?;
             Ok(format!(
                 "schedule updated\njob_id={}\nstate={:?}",
@@ -0,0 +1,89 @@
+use crate::app::command_support::{load_settings, now_secs};
+use crate::orchestration::run_store::WorkflowRunStore;
+use crate::orchestration::usage::collect_usage_report;
+use chrono::{DateTime, NaiveDate};
+
+const USAGE: &str = "usage: usage report [--since <duration|unix_seconds|rfc3339>]";
+
+pub fn cmd_usage(args: &[String]) -> Result<String, String> {
+    if args.first().map(String::as_str) != Some("report") {
+        return Err(USAGE.to_string());
+    }
+    let since = match &args[1..] {
+        [] => None,
+        [flag, raw] if flag == "--since" => Some(parse_since(raw, now_secs())?),
+        _ => return Err(USAGE.to_string()),
+    };
+
+    let settings = load_settings()?;
+    let mut stores = Vec::new();
+    for orchestrator_id in settings.orchestrators.keys() {
+        let runtime_root = settings
+            .resolve_orchestrator_runtime_root(orchestrator_id)
+            .map_err(|e| e.to_string())?;
+        stores.push((
+            orchestrator_id.as_str(),
+            WorkflowRunStore::new(runtime_root),
+        ));
+    }
+    let report = collect_usage_report(stores.iter().map(|(id, store)| (*id, store)), since)
+        .map_err(|e| e.to_string())?;
+
+    let mut output = format!(
+        "usage report\nsince={}\nruns={}\nattempts={}\ntotal {}",
+        since
+            .map(|value| value.to_string())
+            .unwrap_or_else(|| "all".to_string()),
+        report.runs,
+        report.usage.attempts,
+        report.usage.total
+    );
+    output.push_str("\nworkflows:");
+    for (workflow, usage) in &report.by_workflow {
+        output.push_str(&format!(
+            "\n- {workflow} runs={} {}",
+            usage.runs, usage.usage
+        ));
+    }
+    output.push_str("\nagents:");
+    for (agent, usage) in &report.usage.by_agent {
+        output.push_str(&format!("\n- {agent} {usage}"));
+    }
+    Ok(output)
+}
+
+/// Resolves a `--since` value to unix seconds: a look-back duration
+/// (`90m`, `24h`, `7d`, `2w`), absolute unix seconds, an RFC3339 timestamp,
+/// or a `YYYY-MM-DD` date taken as UTC midnight.
+fn parse_since(raw: &str, now: i64) -> Result<i64, String> {
+    let raw = raw.trim();
+    let invalid = || {
+        format!("invalid --since `{raw}`: expected a duration like 24h or 7d, unix seconds, or an RFC3339 timestamp")
+    };
+    if let Ok(seconds) = raw.parse::<i64>() {
+        return Ok(seconds);
+    }
+    if let Some(unit) = raw.chars().last() {
+        let unit_seconds = match unit {
+            's' => Some(1),
+            'm' => Some(60),
+            'h' => Some(3_600),
+            'd' => Some(86_400),
+            'w' => Some(604_800),
+            _ => None,
+        };
+        if let Some(unit_seconds) = unit_seconds {
+            if let Ok(count) = raw[..raw.len() - 1].parse::<i64>() {
+                return Ok(now.saturating_sub(count.saturating_mul(unit_seconds)));
+            }
+        }
+    }
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(raw) {
+        return Ok(timestamp.timestamp());
+    }
+    NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|midnight| midnight.and_utc().timestamp())
+        .ok_or_else(invalid)
+}
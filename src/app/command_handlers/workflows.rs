@@ -5,17 +5,26 @@ use crate::config::{
     normalize_workflow_input_key, WorkflowConfig, WorkflowId, WorkflowInputs, WorkflowStepConfig,
     WorkflowStepPromptType, WorkflowStepType, WorkflowStepWorkspaceMode, WorkflowTag,
 };
-use crate::orchestration::run_store::{RunState, WorkflowRunStore};
+use crate::orchestration::run_events::{RunEvent, RunEventKind};
+use crate::orchestration::run_store::WorkflowRunStore;
+use crate::orchestration::run_worktree::{cancel_run_releasing_worktrees, remove_run_worktree};
+use crate::orchestration::simulation::{simulate_workflow, SimulationReport, SimulationScript};
+use crate::orchestration::usage::run_usage;
 use crate::orchestration::workflow_engine::WorkflowEngine;
+use crate::orchestration::workflow_graph::{workflow_graph, GraphFormat};
 use crate::orchestration::workspace_access::verify_orchestrator_workspace_access;
-use crate::prompts::default_prompt_rel_path;
+use crate::prompts::{default_prompt_rel_path, PROMPTS_DIR};
 use crate::templates::workflow_step_defaults::{
     default_step_output_contract, default_step_output_files, default_step_output_priority,
 };
 use getrandom::getrandom;
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::Path;
+use std::thread;
+use std::time::Duration;
 
 const BASE36_ALPHABET: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
 const RUN_SUFFIX_SPACE: u32 = 36 * 36 * 36 * 36;
@@ -24,7 +33,8 @@ const RUN_ID_MAX_GENERATION_ATTEMPTS: usize = 16;
 pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
     if args.is_empty() {
         return Err(
-            "usage: workflow <list|show|add|remove|run|status|progress|cancel> ...".to_string(),
+            "usage: workflow <list|show|graph|add|remove|run|rerun|simulate|status|progress|events|cancel|cleanup|approve|reject> ..."
+                .to_string(),
         );
     }
 
@@ -55,6 +65,23 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
                 .ok_or_else(|| format!("invalid workflow id `{}`", args[2]))?;
             serde_yaml::to_string(workflow).map_err(|e| format!("failed to encode workflow: {e}"))
         }
+        "graph" => {
+            const USAGE: &str =
+                "usage: workflow graph <orchestrator_id> <workflow_id> [--format mermaid|dot]";
+            let format = match &args[1..] {
+                [_, _] => GraphFormat::Mermaid,
+                [_, _, flag, format] if flag == "--format" => GraphFormat::parse(format)?,
+                _ => return Err(USAGE.to_string()),
+            };
+            let settings = load_settings()?;
+            let orchestrator = load_orchestrator_or_err(&settings, &args[1])?;
+            let workflow = orchestrator
+                .workflows
+                .iter()
+                .find(|w| w.id == args[2])
+                .ok_or_else(|| format!("invalid workflow id `{}`", args[2]))?;
+            Ok(workflow_graph(workflow).render(format))
+        }
         "add" => {
             if args.len() != 3 {
                 return Err("usage: workflow add <orchestrator_id> <workflow_id>".to_string());
@@ -75,6 +102,7 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
                 tags: vec![WorkflowTag::parse(&workflow_id)?],
                 inputs: WorkflowInputs::default(),
                 limits: None,
+                repository: None,
                 steps: vec![WorkflowStepConfig {
                     id: "step_1".to_string(),
                     step_type: WorkflowStepType::AgentTask,
@@ -85,10 +113,19 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
                     next: None,
                     on_approve: None,
                     on_reject: None,
+                    branches: Vec::new(),
+                    transitions: Vec::new(),
+                    workflow: None,
+                    inputs: BTreeMap::new(),
+                    command: Vec::new(),
+                    items: None,
+                    sequence: Vec::new(),
+                    max_concurrency: None,
                     outputs: default_step_output_contract("agent_task"),
                     output_files: default_step_output_files("agent_task"),
                     final_output_priority: default_step_output_priority("agent_task"),
                     limits: None,
+                    retry: None,
                 }],
             });
             save_orchestrator_config(&settings, orchestrator_id, &orchestrator)?;
@@ -133,9 +170,11 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
             let workspace_context =
                 verify_orchestrator_workspace_access(&settings, orchestrator_id, &orchestrator)
                     .map_err(|e| e.to_string())?;
-            if !orchestrator.workflows.iter().any(|w| &w.id == workflow_id) {
-                return Err(format!("invalid workflow id `{workflow_id}`"));
-            }
+            let workflow = orchestrator
+                .workflows
+                .iter()
+                .find(|w| &w.id == workflow_id)
+                .ok_or_else(|| format!("invalid workflow id `{workflow_id}`"))?;
             let selector = orchestrator
                 .agents
                 .get(&orchestrator.selector_agent)
@@ -154,13 +193,79 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
             let now = now_secs();
             let run_id = allocate_compact_run_id_with_retry(store.state_root(), now)?;
             store
-                .create_run_with_inputs(run_id.clone(), workflow_id.clone(), input_map, now)
+                .create_run_with_inputs(run_id.clone(), workflow, input_map, now)
                 .map_err(|e| e.to_string())?;
             let engine = WorkflowEngine::new(store.clone(), orchestrator.clone())
                 .with_workspace_access_context(workspace_context);
             engine.start(&run_id, now).map_err(|e| e.to_string())?;
             Ok(format!("workflow started\nrun_id={run_id}"))
         }
+        "rerun" => {
+            if args.len() != 4 || args[2] != "--from" {
+                return Err("usage: workflow rerun <run_id> --from <step_id>".to_string());
+            }
+            let source_run_id = &args[1];
+            let from_step_id = &args[3];
+            let settings = load_settings()?;
+            let (orchestrator_id, store) = locate_run(&settings, source_run_id)?;
+            let orchestrator = load_orchestrator_or_err(&settings, &orchestrator_id)?;
+            let workspace_context =
+                verify_orchestrator_workspace_access(&settings, &orchestrator_id, &orchestrator)
+                    .map_err(|e| e.to_string())?;
+            let source = store.load_run(source_run_id).map_err(|e| e.to_string())?;
+            let workflow = orchestrator
+                .workflows
+                .iter()
+                .find(|w| w.id == source.workflow_id)
+                .ok_or_else(|| format!("invalid workflow id `{}`", source.workflow_id))?;
+            let now = now_secs();
+            let run_id = allocate_compact_run_id_with_retry(store.state_root(), now)?;
+            store
+                .fork_run(source_run_id, run_id.clone(), workflow, from_step_id, now)
+                .map_err(|e| e.to_string())?;
+            let engine = WorkflowEngine::new(store.clone(), orchestrator.clone())
+                .with_workspace_access_context(workspace_context);
+            engine.start(&run_id, now).map_err(|e| e.to_string())?;
+            Ok(format!(
+                "workflow rerun started\nrun_id={run_id}\nsource_run_id={source_run_id}\nfrom_step={from_step_id}"
+            ))
+        }
+        "simulate" => {
+            if args.len() < 5 || args[3] != "--script" {
+                return Err(
+                    "usage: workflow simulate <orchestrator_id> <workflow_id> --script <path> [--input key=value ...]"
+                        .to_string(),
+                );
+            }
+            let settings = load_settings()?;
+            let orchestrator_id = &args[1];
+            let workflow_id = &args[2];
+            let orchestrator = load_orchestrator_or_err(&settings, orchestrator_id)?;
+            if !orchestrator.workflows.iter().any(|w| &w.id == workflow_id) {
+                return Err(format!("invalid workflow id `{workflow_id}`"));
+            }
+            let script = SimulationScript::load(Path::new(&args[4])).map_err(|e| e.to_string())?;
+            let input_map = parse_key_value_inputs(&args[5..])?;
+            let prompts_root = settings
+                .resolve_private_workspace(orchestrator_id)
+                .map_err(|e| e.to_string())?
+                .join(PROMPTS_DIR);
+            let now = now_secs();
+            let state_root = std::env::temp_dir()
+                .join(format!("direclaw-simulate-{}-{now}", std::process::id()));
+            let report = simulate_workflow(
+                &orchestrator,
+                workflow_id,
+                input_map,
+                script,
+                &state_root,
+                Some(&prompts_root),
+                now,
+            );
+            let _ = fs::remove_dir_all(&state_root);
+            let report = report.map_err(|e| e.to_string())?;
+            Ok(render_simulation_report(workflow_id, &report))
+        }
         "status" => {
             if args.len() != 2 {
                 return Err("usage: workflow status <run_id>".to_string());
@@ -171,14 +276,60 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
             let progress = store.load_progress(&args[1]).map_err(|e| e.to_string())?;
             let mut input_keys = run.inputs.keys().cloned().collect::<Vec<_>>();
             input_keys.sort();
-            Ok(format!(
+            let mut output = format!(
                 "run_id={}\nstate={}\nsummary={}\ninput_count={}\ninput_keys={}",
                 progress.run_id,
                 progress.state,
                 progress.summary,
                 run.inputs.len(),
                 input_keys.join(",")
-            ))
+            );
+            if let Some(parent_run_id) = &run.parent_run_id {
+                output.push_str(&format!("\nparent_run_id={parent_run_id}"));
+            }
+            if let Some(retry_at) = run.retry_at {
+                output.push_str(&format!("\nretry_at={retry_at}"));
+            }
+            if let Some(lineage) = &run.rerun_of {
+                output.push_str(&format!(
+                    "\nrerun_of={}\nrerun_from_step={}",
+                    lineage.source_run_id, lineage.from_step_id
+                ));
+            }
+            let usage = run_usage(&store, &args[1]).map_err(|e| e.to_string())?;
+            if !usage.is_empty() {
+                let total = &usage.total;
+                output.push_str(&format!(
+                    "\nusage_attempts={}\nusage_input_tokens={}\nusage_output_tokens={}\nusage_cache_read_tokens={}\nusage_cache_creation_tokens={}\nusage_duration_ms={}\nusage_cost_usd={}\nusage_by_agent:",
+                    usage.attempts,
+                    total.input_tokens,
+                    total.output_tokens,
+                    total.cache_read_tokens,
+                    total.cache_creation_tokens,
+                    total.duration_ms,
+                    total.cost_usd()
+                ));
+                for (agent_id, agent_usage) in &usage.by_agent {
+                    output.push_str(&format!("\n- {agent_id} {agent_usage}"));
+                }
+            }
+            let events = store
+                .load_run_events(&args[1], 0)
+                .map_err(|e| e.to_string())?;
+            render_step_providers(&events, &mut output);
+            if run.parent_run_id.is_some() || !run.child_run_ids.is_empty() {
+                let mut root_run_id = run.run_id.clone();
+                while let Some(parent_run_id) = store
+                    .load_run(&root_run_id)
+                    .map_err(|e| e.to_string())?
+                    .parent_run_id
+                {
+                    root_run_id = parent_run_id;
+                }
+                output.push_str("\nrun_tree:");
+                render_run_tree(&store, &root_run_id, 0, &mut output)?;
+            }
+            Ok(output)
         }
         "progress" => {
             if args.len() != 2 {
@@ -187,9 +338,35 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
             let settings = load_settings()?;
             let store = run_store_for_run_id(&settings, &args[1])?;
             let progress = store.load_progress(&args[1]).map_err(|e| e.to_string())?;
-            serde_json::to_string_pretty(&progress)
+            let usage = run_usage(&store, &args[1]).map_err(|e| e.to_string())?;
+            let mut payload = serde_json::to_value(&progress)
+                .map_err(|e| format!("failed to encode workflow progress: {e}"))?;
+            if let Some(object) = payload.as_object_mut().filter(|_| !usage.is_empty()) {
+                object.insert(
+                    "usage".to_string(),
+                    serde_json::to_value(&usage)
+                        .map_err(|e| format!("failed to encode workflow progress: {e}"))?,
+                );
+            }
+            serde_json::to_string_pretty(&payload)
                 .map_err(|e| format!("failed to encode workflow progress: {e}"))
         }
+        "events" => {
+            let follow = args.get(2).map(String::as_str) == Some("--follow");
+            if args.len() != 2 && !(args.len() == 3 && follow) {
+                return Err("usage: workflow events <run_id> [--follow]".to_string());
+            }
+            let settings = load_settings()?;
+            let store = run_store_for_run_id(&settings, &args[1])?;
+            if follow {
+                follow_run_events(&store, &args[1])
+            } else {
+                let events = store
+                    .load_run_events(&args[1], 0)
+                    .map_err(|e| e.to_string())?;
+                render_run_events(&events)
+            }
+        }
         "cancel" => {
             if args.len() != 2 {
                 return Err("usage: workflow cancel <run_id>".to_string());
@@ -197,23 +374,60 @@ pub fn cmd_workflow(args: &[String]) -> Result<String, String> {
             let settings = load_settings()?;
             let store = run_store_for_run_id(&settings, &args[1])?;
             let mut run = store.load_run(&args[1]).map_err(|e| e.to_string())?;
+            cancel_run_releasing_worktrees(&store, &mut run, now_secs(), "canceled by command")
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "workflow canceled\nrun_id={}\nstate={}",
+                run.run_id, run.state
+            ))
+        }
+        "cleanup" => {
+            if args.len() != 2 {
+                return Err("usage: workflow cleanup <run_id>".to_string());
+            }
+            let settings = load_settings()?;
+            let store = run_store_for_run_id(&settings, &args[1])?;
+            let run = store.load_run(&args[1]).map_err(|e| e.to_string())?;
             if !run.state.clone().is_terminal() {
-                store
-                    .transition_state(
-                        &mut run,
-                        RunState::Canceled,
-                        now_secs(),
-                        "canceled by command",
-                        false,
-                        "none",
-                    )
-                    .map_err(|e| e.to_string())?;
+                return Err(format!(
+                    "workflow run `{}` is {}; only finished runs can be cleaned up",
+                    run.run_id, run.state
+                ));
             }
+            remove_run_worktree(&store, &run.run_id).map_err(|e| e.to_string())?;
             Ok(format!(
-                "workflow canceled\nrun_id={}\nstate={}",
+                "workflow worktree removed\nrun_id={}\nstate={}",
                 run.run_id, run.state
             ))
         }
+        "approve" | "reject" => {
+            let approved = args[0] == "approve";
+            let usage = format!("usage: workflow {} <run_id> [--comment <text>]", args[0]);
+            let comment = match args.len() {
+                2 => None,
+                4 if args[2] == "--comment" => {
+                    Some(args[3].trim().to_string()).filter(|value| !value.is_empty())
+                }
+                _ => return Err(usage),
+            };
+            let settings = load_settings()?;
+            let (orchestrator_id, store) = locate_run(&settings, &args[1])?;
+            let orchestrator = load_orchestrator_or_err(&settings, &orchestrator_id)?;
+            let workspace_context =
+                verify_orchestrator_workspace_access(&settings, &orchestrator_id, &orchestrator)
+                    .map_err(|e| e.to_string())?;
+            let engine = WorkflowEngine::new(store, orchestrator)
+                .with_workspace_access_context(workspace_context);
+            let run = engine
+                .submit_human_review(&args[1], approved, comment, None, now_secs())
+                .map_err(|e| e.to_string())?;
+            Ok(format!(
+                "workflow {}\nrun_id={}\nstate={}",
+                if approved { "approved" } else { "rejected" },
+                run.run_id,
+                run.state
+            ))
+        }
         other => Err(format!("unknown workflow subcommand `{other}`")),
     }
 }
@@ -291,16 +505,98 @@ fn allocate_compact_run_id_with_retry(state_root: &Path, now: i64) -> Result<Str
     ))
 }
 
+/// Lists the provider that answered each step's latest invocation, with the
+/// fallback-chain providers that failed before it.
+fn render_step_providers(events: &[RunEvent], output: &mut String) {
+    let mut latest: Vec<(&str, String)> = Vec::new();
+    for event in events {
+        let RunEventKind::ProviderInvoked {
+            step_id,
+            agent_id,
+            provider,
+            model,
+            fallback_from,
+            ..
+        } = &event.kind
+        else {
+            continue;
+        };
+        let mut line = format!("- {step_id} agent={agent_id} provider={provider} model={model}");
+        if !fallback_from.is_empty() {
+            line.push_str(&format!(" fallback_from={}", fallback_from.join(",")));
+        }
+        match latest.iter_mut().find(|(id, _)| *id == step_id.as_str()) {
+            Some(entry) => entry.1 = line,
+            None => latest.push((step_id, line)),
+        }
+    }
+    if latest.is_empty() {
+        return;
+    }
+    output.push_str("\nproviders_by_step:");
+    for (_, line) in latest {
+        output.push('\n');
+        output.push_str(&line);
+    }
+}
+
+fn render_run_events(events: &[RunEvent]) -> Result<String, String> {
+    events
+        .iter()
+        .map(|event| {
+            serde_json::to_string(event)
+                .map_err(|e| format!("failed to encode workflow event: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(|lines| lines.join("\n"))
+}
+
+/// Prints journal events as they are appended until the run is terminal.
+/// The last batch is returned instead of printed so the output ends with the
+/// final event rather than an empty line.
+fn follow_run_events(store: &WorkflowRunStore, run_id: &str) -> Result<String, String> {
+    let mut after_seq = 0;
+    let mut stdout = std::io::stdout();
+    loop {
+        // Read the state before the events: the terminal transition is
+        // journaled before the record is updated.
+        let terminal = store
+            .load_run(run_id)
+            .map_err(|e| e.to_string())?
+            .state
+            .is_terminal();
+        let events = store
+            .load_run_events(run_id, after_seq)
+            .map_err(|e| e.to_string())?;
+        if terminal {
+            return render_run_events(&events);
+        }
+        if let Some(last) = events.last() {
+            after_seq = last.seq;
+            let _ = writeln!(stdout, "{}", render_run_events(&events)?);
+            let _ = stdout.flush();
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+}
+
 fn run_store_for_run_id(
     settings: &crate::config::Settings,
     run_id: &str,
 ) -> Result<WorkflowRunStore, String> {
+    locate_run(settings, run_id).map(|(_, store)| store)
+}
+
+fn locate_run(
+    settings: &crate::config::Settings,
+    run_id: &str,
+) -> Result<(String, WorkflowRunStore), String> {
     for orchestrator_id in settings.orchestrators.keys() {
         let runtime_root = settings
             .resolve_orchestrator_runtime_root(orchestrator_id)
             .map_err(|e| e.to_string())?;
         if run_record_exists(&runtime_root, run_id) {
-            return Ok(WorkflowRunStore::new(runtime_root));
+            return Ok((orchestrator_id.clone(), WorkflowRunStore::new(runtime_root)));
         }
     }
     Err(format!("unknown workflow run `{run_id}`"))
@@ -312,3 +608,71 @@ fn run_record_exists(runtime_root: &Path, run_id: &str) -> bool {
         .join(format!("{run_id}.json"))
         .is_file()
 }
+
+fn render_simulation_report(workflow_id: &str, report: &SimulationReport) -> String {
+    let mut output = format!(
+        "workflow simulated\nworkflow={workflow_id}\nstate={}",
+        report.state
+    );
+    if let Some(reason) = &report.terminal_reason {
+        output.push_str(&format!("\nterminal_reason={reason}"));
+    }
+    output.push_str("\npath:");
+    for attempt in &report.attempts {
+        let outcome = match (&attempt.error, &attempt.next_step_id) {
+            (Some(_), _) => "failed".to_string(),
+            (None, Some(next)) => format!("succeeded next={next}"),
+            (None, None) => "succeeded".to_string(),
+        };
+        output.push_str(&format!(
+            "\n- {} attempt={} {outcome}",
+            attempt.step_id, attempt.attempt
+        ));
+    }
+    output.push_str("\nviolations:");
+    for attempt in report.attempts.iter().filter(|a| a.error.is_some()) {
+        output.push_str(&format!(
+            "\n- {} attempt={}: {}",
+            attempt.step_id,
+            attempt.attempt,
+            attempt.error.as_deref().unwrap_or_default()
+        ));
+        for (key, detail) in &attempt.output_validation_errors {
+            output.push_str(&format!("\n  {key}: {detail}"));
+        }
+    }
+    output.push_str("\nprompts:");
+    for attempt in &report.attempts {
+        if let Some(prompt) = &attempt.prompt {
+            output.push_str(&format!(
+                "\n--- {} attempt={} ---\n{}",
+                attempt.step_id,
+                attempt.attempt,
+                prompt.trim_end()
+            ));
+        }
+    }
+    output
+}
+
+fn render_run_tree(
+    store: &WorkflowRunStore,
+    run_id: &str,
+    depth: usize,
+    output: &mut String,
+) -> Result<(), String> {
+    let run = store.load_run(run_id).map_err(|e| e.to_string())?;
+    output.push_str(&format!(
+        "\n{}- {} workflow={} state={}",
+        "  ".repeat(depth),
+        run.run_id,
+        run.workflow_id,
+        run.state
+    ));
+    for child_run_id in &run.child_run_ids {
+        if store.run_exists(child_run_id) {
+            render_run_tree(store, child_run_id, depth + 1, output)?;
+        }
+    }
+    Ok(())
+}
@@ -4,6 +4,8 @@ use crate::orchestration::slack_target::{
 };
 use serde_json::{Map, Value};
 
+const DEFAULT_FILE_WATCH_DEBOUNCE_SECONDS: u64 = 30;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ScheduleCreateTailArgs {
     pub target_ref: Option<Value>,
@@ -50,8 +52,32 @@ pub fn parse_schedule_config(
                 timezone,
             })
         }
+        "file_watch" => {
+            let workspace = schedule
+                .get("workspace")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "schedule.file_watch requires string `workspace`".to_string())?
+                .to_string();
+            let pattern = schedule
+                .get("pattern")
+                .and_then(Value::as_str)
+                .ok_or_else(|| "schedule.file_watch requires string `pattern`".to_string())?
+                .to_string();
+            let debounce_seconds = match schedule.get("debounceSeconds") {
+                None => DEFAULT_FILE_WATCH_DEBOUNCE_SECONDS,
+                Some(value) => value.as_u64().ok_or_else(|| {
+                    "schedule.file_watch `debounceSeconds` must be a non-negative integer"
+                        .to_string()
+                })?,
+            };
+            Ok(ScheduleConfig::FileWatch {
+                workspace,
+                pattern,
+                debounce_seconds,
+            })
+        }
         other => Err(format!(
-            "scheduleType must be one of: once, interval, cron (got `{other}`)"
+            "scheduleType must be one of: once, interval, cron, file_watch (got `{other}`)"
         )),
     }
 }
@@ -511,6 +511,7 @@ mod tests {
                 auth_sync: Default::default(),
                 memory: MemoryConfig::default(),
                 local_llm: Default::default(),
+                webhooks: Default::default(),
             },
             queue_paths: QueuePaths::from_state_root(PathBuf::from("/tmp/state").as_path()),
             profile_id: "local-default".to_string(),
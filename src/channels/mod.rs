@@ -1,3 +1,4 @@
 pub mod local;
 pub mod policy;
 pub mod slack;
+pub mod webhook;
@@ -60,6 +60,7 @@ mod tests {
             auth_sync: Default::default(),
             memory: Default::default(),
             local_llm: Default::default(),
+            webhooks: Default::default(),
         }
     }
 
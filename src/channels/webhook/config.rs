@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+
+pub const DEFAULT_WEBHOOK_BIND: &str = "127.0.0.1:8787";
+pub const DEFAULT_SIGNATURE_HEADER: &str = "X-Direclaw-Signature";
+pub const DEFAULT_DELIVERY_HEADER: &str = "X-Direclaw-Delivery";
+pub const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Built-in HTTP listener that turns signed JSON deliveries into queued
+/// messages or workflow runs.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    pub enabled: bool,
+    pub bind: String,
+    pub max_body_bytes: usize,
+    pub routes: BTreeMap<String, WebhookRoute>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind: DEFAULT_WEBHOOK_BIND.to_string(),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+/// One URL path served by the listener. A route either starts
+/// `workflow_id` directly with `inputs` read from the payload by JSON
+/// pointer, or enqueues the payload as a message on `channel_profile_id`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookRoute {
+    pub path: String,
+    pub secret_env: String,
+    #[serde(default = "default_signature_header")]
+    pub signature_header: String,
+    #[serde(default = "default_delivery_header")]
+    pub delivery_header: String,
+    pub orchestrator_id: String,
+    #[serde(default)]
+    pub workflow_id: Option<String>,
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+    #[serde(default)]
+    pub channel_profile_id: Option<String>,
+    #[serde(default)]
+    pub message_pointer: Option<String>,
+}
+
+/// Where a route sends an accepted delivery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookTarget<'a> {
+    Workflow {
+        workflow_id: &'a str,
+        inputs: &'a BTreeMap<String, String>,
+    },
+    Message {
+        channel_profile_id: &'a str,
+        message_pointer: Option<&'a str>,
+    },
+}
+
+impl WebhookRoute {
+    pub fn target(&self) -> Option<WebhookTarget<'_>> {
+        match (&self.workflow_id, &self.channel_profile_id) {
+            (Some(workflow_id), None) => Some(WebhookTarget::Workflow {
+                workflow_id,
+                inputs: &self.inputs,
+            }),
+            (None, Some(channel_profile_id)) => Some(WebhookTarget::Message {
+                channel_profile_id,
+                message_pointer: self.message_pointer.as_deref(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl WebhookConfig {
+    pub fn route_for_path(&self, path: &str) -> Option<(&str, &WebhookRoute)> {
+        self.routes
+            .iter()
+            .find(|(_, route)| route.path == path)
+            .map(|(route_id, route)| (route_id.as_str(), route))
+    }
+
+    pub fn bind_addr(&self) -> Result<SocketAddr, String> {
+        self.bind.trim().parse::<SocketAddr>().map_err(|err| {
+            format!(
+                "webhooks.bind `{}` is not a socket address: {err}",
+                self.bind
+            )
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled {
+            self.bind_addr()?;
+            if self.routes.is_empty() {
+                return Err(
+                    "webhooks.routes must be non-empty when webhooks.enabled=true".to_string(),
+                );
+            }
+        }
+        if self.max_body_bytes == 0 {
+            return Err("webhooks.max_body_bytes must be > 0".to_string());
+        }
+        let mut paths = BTreeSet::new();
+        for (route_id, route) in &self.routes {
+            if route_id.trim().is_empty() {
+                return Err("webhooks.routes keys must be non-empty".to_string());
+            }
+            if !route.path.starts_with('/') || route.path.contains(['?', '#', ' ']) {
+                return Err(format!(
+                    "webhook route `{route_id}` path `{}` must start with `/` and contain no query",
+                    route.path
+                ));
+            }
+            if !paths.insert(route.path.as_str()) {
+                return Err(format!(
+                    "webhook route `{route_id}` reuses path `{}`",
+                    route.path
+                ));
+            }
+            for (field, value) in [
+                ("secret_env", &route.secret_env),
+                ("signature_header", &route.signature_header),
+                ("delivery_header", &route.delivery_header),
+                ("orchestrator_id", &route.orchestrator_id),
+            ] {
+                if value.trim().is_empty() {
+                    return Err(format!(
+                        "webhook route `{route_id}` requires non-empty `{field}`"
+                    ));
+                }
+            }
+            match route.target() {
+                Some(WebhookTarget::Workflow { inputs, .. }) => {
+                    if route.message_pointer.is_some() {
+                        return Err(format!(
+                            "webhook route `{route_id}` sets `message_pointer` without `channel_profile_id`"
+                        ));
+                    }
+                    for (key, pointer) in inputs {
+                        if key.trim().is_empty() {
+                            return Err(format!(
+                                "webhook route `{route_id}` input keys must be non-empty"
+                            ));
+                        }
+                        validate_pointer(route_id, pointer)?;
+                    }
+                }
+                Some(WebhookTarget::Message {
+                    message_pointer, ..
+                }) => {
+                    if !route.inputs.is_empty() {
+                        return Err(format!(
+                            "webhook route `{route_id}` sets `inputs` without `workflow_id`"
+                        ));
+                    }
+                    if let Some(pointer) = message_pointer {
+                        validate_pointer(route_id, pointer)?;
+                    }
+                }
+                None => {
+                    return Err(format!(
+                        "webhook route `{route_id}` must set exactly one of `workflow_id` or `channel_profile_id`"
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_pointer(route_id: &str, pointer: &str) -> Result<(), String> {
+    if pointer.is_empty() || pointer.starts_with('/') {
+        return Ok(());
+    }
+    Err(format!(
+        "webhook route `{route_id}` pointer `{pointer}` must be empty or start with `/`"
+    ))
+}
+
+fn default_signature_header() -> String {
+    DEFAULT_SIGNATURE_HEADER.to_string()
+}
+
+fn default_delivery_header() -> String {
+    DEFAULT_DELIVERY_HEADER.to_string()
+}
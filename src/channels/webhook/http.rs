@@ -0,0 +1,134 @@
+use super::{WebhookRequest, WebhookResponse};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+
+const MAX_HEADER_BYTES: usize = 16 * 1024;
+const MAX_HEADER_COUNT: usize = 100;
+
+/// Reads one HTTP/1.1 request. Bodies must be sized by `Content-Length`;
+/// failures come back as the response to send instead.
+pub fn read_request(
+    stream: impl Read,
+    max_body_bytes: usize,
+) -> Result<WebhookRequest, WebhookResponse> {
+    let mut reader = BufReader::new(stream);
+    let mut header_bytes = 0_usize;
+    let request_line = read_line(&mut reader, &mut header_bytes)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(error_response(400, "malformed request line"));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(error_response(505, "only HTTP/1.x is supported"));
+    }
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+
+    let mut headers = BTreeMap::new();
+    loop {
+        let line = read_line(&mut reader, &mut header_bytes)?;
+        if line.is_empty() {
+            break;
+        }
+        if headers.len() >= MAX_HEADER_COUNT {
+            return Err(error_response(431, "too many request headers"));
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(error_response(400, "malformed request header"));
+        };
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let mut body = Vec::new();
+    if method.eq_ignore_ascii_case("POST") {
+        if headers.contains_key("transfer-encoding") {
+            return Err(error_response(411, "chunked bodies are not supported"));
+        }
+        let Some(length) = headers.get("content-length") else {
+            return Err(error_response(411, "Content-Length is required"));
+        };
+        let length = length
+            .parse::<usize>()
+            .map_err(|_| error_response(400, "invalid Content-Length"))?;
+        if length > max_body_bytes {
+            return Err(error_response(
+                413,
+                &format!("payload exceeds {max_body_bytes} bytes"),
+            ));
+        }
+        body.resize(length, 0);
+        reader
+            .read_exact(&mut body)
+            .map_err(|_| error_response(400, "request body ended early"))?;
+    }
+
+    Ok(WebhookRequest {
+        method: method.to_ascii_uppercase(),
+        path: path.to_string(),
+        headers,
+        body,
+    })
+}
+
+fn read_line(
+    reader: &mut impl BufRead,
+    header_bytes: &mut usize,
+) -> Result<String, WebhookResponse> {
+    let mut line = Vec::new();
+    let read = reader
+        .take((MAX_HEADER_BYTES - *header_bytes) as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .map_err(|_| error_response(400, "failed to read request"))?;
+    *header_bytes += read;
+    if *header_bytes > MAX_HEADER_BYTES {
+        return Err(error_response(431, "request headers are too large"));
+    }
+    if !line.ends_with(b"\n") {
+        return Err(error_response(
+            400,
+            "request ended before headers completed",
+        ));
+    }
+    let line = String::from_utf8(line)
+        .map_err(|_| error_response(400, "request headers are not UTF-8"))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+pub fn write_response(mut stream: impl Write, response: &WebhookResponse) -> std::io::Result<()> {
+    let body = response.body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+pub(crate) fn error_response(status: u16, message: &str) -> WebhookResponse {
+    WebhookResponse {
+        status,
+        body: json!({ "error": message }),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        505 => "HTTP Version Not Supported",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,601 @@
+use crate::config::{load_orchestrator_config, Settings};
+use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::run_store::{RunState, WorkflowRunStore};
+use crate::orchestration::transitions::{
+    allocate_compact_run_id_with_retry, generate_compact_run_id,
+};
+use crate::orchestration::workspace_access::verify_orchestrator_workspace_access;
+use crate::queue::{IncomingMessage, QueuePaths};
+use crate::runtime::atomic_write_file;
+use crate::shared::fs_atomic::atomic_create_file;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+pub mod config;
+pub mod http;
+pub mod signature;
+
+pub use config::{WebhookConfig, WebhookRoute, WebhookTarget};
+
+/// Channel name stamped on messages enqueued by webhook routes.
+pub const WEBHOOK_CHANNEL: &str = "webhook";
+
+/// A parsed HTTP request. Header names are lowercased.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl WebhookRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+            .filter(|value| !value.trim().is_empty())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    #[error("no webhook route serves `{0}`")]
+    UnknownRoute(String),
+    #[error("webhook routes only accept POST, got `{0}`")]
+    MethodNotAllowed(String),
+    #[error("env var `{env}` holding the secret for webhook route `{route_id}` is not set")]
+    MissingSecret { route_id: String, env: String },
+    #[error("missing `{0}` signature header")]
+    MissingSignature(String),
+    #[error("signature does not match the payload")]
+    InvalidSignature,
+    #[error("missing `{0}` delivery id header")]
+    MissingDeliveryId(String),
+    #[error("payload is not valid JSON: {0}")]
+    InvalidPayload(String),
+    #[error("payload has no value at `{pointer}` for `{field}`")]
+    MissingPayloadField { field: String, pointer: String },
+    #[error("workflow `{workflow_id}` is not declared by orchestrator `{orchestrator_id}`")]
+    UnknownWorkflow {
+        orchestrator_id: String,
+        workflow_id: String,
+    },
+    #[error(transparent)]
+    Orchestrator(#[from] OrchestratorError),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl WebhookError {
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::UnknownRoute(_) => 404,
+            Self::MethodNotAllowed(_) => 405,
+            Self::MissingSignature(_) | Self::InvalidSignature => 401,
+            Self::MissingDeliveryId(_) | Self::InvalidPayload(_) => 400,
+            Self::MissingPayloadField { .. } | Self::UnknownWorkflow { .. } => 422,
+            Self::Orchestrator(OrchestratorError::WorkflowInputValidation { .. }) => 422,
+            Self::MissingSecret { .. } | Self::Orchestrator(_) | Self::Internal(_) => 500,
+        }
+    }
+}
+
+/// Stored outcome of one delivery, replayed for retried deliveries. The
+/// record is reserved as `pending` before any run is created or message
+/// enqueued, and completed with the response once both are done.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryRecord {
+    pub route_id: String,
+    pub delivery_id: String,
+    pub received_at: i64,
+    #[serde(default)]
+    pub pending: bool,
+    #[serde(default)]
+    pub run_id: Option<String>,
+    #[serde(default)]
+    pub response: Value,
+}
+
+/// Handles webhook deliveries against the routes in `settings.webhooks`.
+/// Accepted deliveries only create runs and enqueue messages; the queue
+/// worker executes them. Clones share the set of deliveries in flight, so a
+/// delivery retried while its first request is still handled gets `409`
+/// while other deliveries proceed concurrently.
+#[derive(Debug, Clone)]
+pub struct WebhookIngress {
+    settings: Settings,
+    in_flight: Arc<Mutex<BTreeSet<PathBuf>>>,
+}
+
+/// Claim on one delivery record path, released when dropped.
+struct DeliveryClaim<'a> {
+    in_flight: &'a Mutex<BTreeSet<PathBuf>>,
+    path: PathBuf,
+}
+
+impl Drop for DeliveryClaim<'_> {
+    fn drop(&mut self) {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(&self.path);
+    }
+}
+
+impl WebhookIngress {
+    pub fn new(settings: Settings) -> Self {
+        Self {
+            settings,
+            in_flight: Arc::new(Mutex::new(BTreeSet::new())),
+        }
+    }
+
+    pub fn handle(&self, request: &WebhookRequest, now: i64) -> WebhookResponse {
+        match self.accept(request, now) {
+            Ok(response) => response,
+            Err(err) => http::error_response(err.status(), &err.to_string()),
+        }
+    }
+
+    fn accept(&self, request: &WebhookRequest, now: i64) -> Result<WebhookResponse, WebhookError> {
+        let (route_id, route) = self
+            .settings
+            .webhooks
+            .route_for_path(&request.path)
+            .ok_or_else(|| WebhookError::UnknownRoute(request.path.clone()))?;
+        if request.method != "POST" {
+            return Err(WebhookError::MethodNotAllowed(request.method.clone()));
+        }
+        let secret = std::env::var(&route.secret_env)
+            .ok()
+            .filter(|value| !value.is_empty())
+            .ok_or_else(|| WebhookError::MissingSecret {
+                route_id: route_id.to_string(),
+                env: route.secret_env.clone(),
+            })?;
+        let provided = request
+            .header(&route.signature_header)
+            .ok_or_else(|| WebhookError::MissingSignature(route.signature_header.clone()))?;
+        if !signature::verify_signature(&secret, &request.body, provided) {
+            return Err(WebhookError::InvalidSignature);
+        }
+        let delivery_id = request
+            .header(&route.delivery_header)
+            .map(str::trim)
+            .ok_or_else(|| WebhookError::MissingDeliveryId(route.delivery_header.clone()))?;
+
+        let runtime_root = self
+            .settings
+            .resolve_orchestrator_runtime_root(&route.orchestrator_id)
+            .map_err(|err| WebhookError::Internal(err.to_string()))?;
+        let record_path = delivery_record_path(&runtime_root, route_id, delivery_id);
+        let Some(_claim) = self.claim_delivery(&record_path) else {
+            return Ok(in_progress_response(route_id, delivery_id));
+        };
+        let stored = load_delivery_record(&record_path)?;
+        let on_disk = stored.is_some();
+        let reserved = match stored {
+            Some(record) if !record.pending => {
+                let mut body = record.response;
+                if let Some(object) = body.as_object_mut() {
+                    object.insert("duplicate".to_string(), Value::Bool(true));
+                }
+                return Ok(WebhookResponse { status: 200, body });
+            }
+            // A pending record without a claim was left by a request that
+            // died midway; this retry finishes it under the reserved run id.
+            Some(record) => record,
+            None => DeliveryRecord {
+                route_id: route_id.to_string(),
+                delivery_id: delivery_id.to_string(),
+                received_at: now,
+                pending: true,
+                run_id: None,
+                response: Value::Null,
+            },
+        };
+
+        let result = self.deliver(
+            request,
+            &runtime_root,
+            route_id,
+            route,
+            delivery_id,
+            &record_path,
+            reserved,
+            on_disk,
+            now,
+        );
+        if result.is_err() {
+            release_pending_record(&runtime_root, &record_path);
+        }
+        result
+    }
+
+    /// Marks the delivery as handled by this request, or returns `None` when
+    /// another request is handling it right now.
+    fn claim_delivery(&self, record_path: &Path) -> Option<DeliveryClaim<'_>> {
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        in_flight
+            .insert(record_path.to_path_buf())
+            .then(|| DeliveryClaim {
+                in_flight: &self.in_flight,
+                path: record_path.to_path_buf(),
+            })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn deliver(
+        &self,
+        request: &WebhookRequest,
+        runtime_root: &Path,
+        route_id: &str,
+        route: &WebhookRoute,
+        delivery_id: &str,
+        record_path: &Path,
+        mut record: DeliveryRecord,
+        mut on_disk: bool,
+        now: i64,
+    ) -> Result<WebhookResponse, WebhookError> {
+        let payload: Value = serde_json::from_slice(&request.body)
+            .map_err(|err| WebhookError::InvalidPayload(err.to_string()))?;
+        let body = match route.target() {
+            Some(WebhookTarget::Workflow {
+                workflow_id,
+                inputs,
+            }) => {
+                let inputs = mapped_inputs(&payload, inputs)?;
+                let store = WorkflowRunStore::new(runtime_root);
+                let run_id = match record.run_id.clone() {
+                    Some(run_id) => run_id,
+                    None => {
+                        let run_id = self.reserve_workflow_run(
+                            &store,
+                            runtime_root,
+                            &route.orchestrator_id,
+                            workflow_id,
+                            now,
+                        )?;
+                        record.run_id = Some(run_id.clone());
+                        if !reserve_delivery_record(record_path, &record, &mut on_disk)? {
+                            return Ok(in_progress_response(route_id, delivery_id));
+                        }
+                        run_id
+                    }
+                };
+                let start = if store.run_exists(&run_id) {
+                    store.load_run(&run_id)?.state == RunState::Queued
+                } else {
+                    self.create_workflow_run(
+                        &store,
+                        &route.orchestrator_id,
+                        workflow_id,
+                        &run_id,
+                        inputs,
+                        now,
+                    )?;
+                    true
+                };
+                let message_id = message_id_for(route_id, delivery_id);
+                if start {
+                    enqueue_message(
+                        runtime_root,
+                        route_id,
+                        None,
+                        delivery_id,
+                        format!("start workflow run {run_id}"),
+                        Some(&run_id),
+                        now,
+                    )?;
+                }
+                let mut body = accepted_body(route_id, delivery_id, "started", Some(&run_id));
+                body.insert("workflowId".to_string(), json!(workflow_id));
+                body.insert("messageId".to_string(), json!(message_id));
+                body
+            }
+            Some(WebhookTarget::Message {
+                channel_profile_id,
+                message_pointer,
+            }) => {
+                let message = match message_pointer {
+                    Some(pointer) => payload_text(pointer_value(&payload, pointer, "message")?),
+                    None => payload.to_string(),
+                };
+                if !reserve_delivery_record(record_path, &record, &mut on_disk)? {
+                    return Ok(in_progress_response(route_id, delivery_id));
+                }
+                let message_id = enqueue_message(
+                    runtime_root,
+                    route_id,
+                    Some(channel_profile_id),
+                    delivery_id,
+                    message,
+                    None,
+                    now,
+                )?;
+                let mut body = accepted_body(route_id, delivery_id, "queued", None);
+                body.insert("messageId".to_string(), json!(message_id));
+                body
+            }
+            None => {
+                return Err(WebhookError::Internal(format!(
+                    "webhook route `{route_id}` has no target"
+                )))
+            }
+        };
+        let body = Value::Object(body);
+        record.pending = false;
+        record.response = body.clone();
+        save_delivery_record(record_path, &record)?;
+        Ok(WebhookResponse { status: 202, body })
+    }
+
+    /// Checks that the route's workflow exists and allocates the id of the
+    /// run the delivery will start, so the id can be reserved with the
+    /// delivery before the run is created.
+    fn reserve_workflow_run(
+        &self,
+        store: &WorkflowRunStore,
+        runtime_root: &Path,
+        orchestrator_id: &str,
+        workflow_id: &str,
+        now: i64,
+    ) -> Result<String, WebhookError> {
+        let orchestrator = load_orchestrator_config(&self.settings, orchestrator_id)
+            .map_err(|err| WebhookError::Internal(err.to_string()))?;
+        if !orchestrator
+            .workflows
+            .iter()
+            .any(|workflow| workflow.id == workflow_id)
+        {
+            return Err(WebhookError::UnknownWorkflow {
+                orchestrator_id: orchestrator_id.to_string(),
+                workflow_id: workflow_id.to_string(),
+            });
+        }
+        verify_orchestrator_workspace_access(&self.settings, orchestrator_id, &orchestrator)?;
+        fs::create_dir_all(runtime_root).map_err(|err| {
+            WebhookError::Internal(format!(
+                "failed to create {}: {err}",
+                runtime_root.display()
+            ))
+        })?;
+        Ok(allocate_compact_run_id_with_retry(
+            store,
+            now,
+            generate_compact_run_id,
+        )?)
+    }
+
+    /// Creates the run up front so its id can be returned before the queue
+    /// worker starts executing it.
+    fn create_workflow_run(
+        &self,
+        store: &WorkflowRunStore,
+        orchestrator_id: &str,
+        workflow_id: &str,
+        run_id: &str,
+        inputs: Map<String, Value>,
+        now: i64,
+    ) -> Result<(), WebhookError> {
+        let orchestrator = load_orchestrator_config(&self.settings, orchestrator_id)
+            .map_err(|err| WebhookError::Internal(err.to_string()))?;
+        let workflow = orchestrator
+            .workflows
+            .iter()
+            .find(|workflow| workflow.id == workflow_id)
+            .ok_or_else(|| WebhookError::UnknownWorkflow {
+                orchestrator_id: orchestrator_id.to_string(),
+                workflow_id: workflow_id.to_string(),
+            })?;
+        store.create_run_with_inputs(run_id.to_string(), workflow, inputs, now)?;
+        Ok(())
+    }
+}
+
+/// Answer for a delivery whose first request is still being handled; the
+/// sender retries it later and gets the recorded response.
+fn in_progress_response(route_id: &str, delivery_id: &str) -> WebhookResponse {
+    let mut body = accepted_body(route_id, delivery_id, "in_progress", None);
+    body.remove("runId");
+    WebhookResponse {
+        status: 409,
+        body: Value::Object(body),
+    }
+}
+
+fn accepted_body(
+    route_id: &str,
+    delivery_id: &str,
+    status: &str,
+    run_id: Option<&str>,
+) -> Map<String, Value> {
+    Map::from_iter([
+        ("status".to_string(), json!(status)),
+        ("route".to_string(), json!(route_id)),
+        ("deliveryId".to_string(), json!(delivery_id)),
+        ("runId".to_string(), json!(run_id)),
+    ])
+}
+
+fn mapped_inputs(
+    payload: &Value,
+    inputs: &BTreeMap<String, String>,
+) -> Result<Map<String, Value>, WebhookError> {
+    inputs
+        .iter()
+        .map(|(key, pointer)| {
+            pointer_value(payload, pointer, key)
+                .map(|value| (key.clone(), Value::String(payload_text(value))))
+        })
+        .collect()
+}
+
+fn pointer_value<'a>(
+    payload: &'a Value,
+    pointer: &str,
+    field: &str,
+) -> Result<&'a Value, WebhookError> {
+    payload
+        .pointer(pointer)
+        .filter(|value| !value.is_null())
+        .ok_or_else(|| WebhookError::MissingPayloadField {
+            field: field.to_string(),
+            pointer: pointer.to_string(),
+        })
+}
+
+/// Workflow inputs and message text are strings; other JSON values are
+/// passed on as their JSON text.
+fn payload_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Queues one delivery for the queue worker. Messages carrying a
+/// `workflow_run_id` resume that run; the others are routed through the
+/// channel profile's orchestrator.
+fn enqueue_message(
+    runtime_root: &Path,
+    route_id: &str,
+    channel_profile_id: Option<&str>,
+    delivery_id: &str,
+    message: String,
+    workflow_run_id: Option<&str>,
+    now: i64,
+) -> Result<String, WebhookError> {
+    let message_id = message_id_for(route_id, delivery_id);
+    let incoming = IncomingMessage {
+        channel: WEBHOOK_CHANNEL.to_string(),
+        channel_profile_id: channel_profile_id.map(str::to_string),
+        sender: format!("webhook:{route_id}"),
+        sender_id: route_id.to_string(),
+        message,
+        timestamp: now,
+        message_id: message_id.clone(),
+        conversation_id: Some(format!("webhook:{route_id}:{delivery_id}")),
+        is_direct: true,
+        is_thread_reply: false,
+        is_mentioned: false,
+        files: Vec::new(),
+        workflow_run_id: workflow_run_id.map(str::to_string),
+        workflow_step_id: None,
+    };
+    let queue_paths = QueuePaths::from_state_root(runtime_root);
+    fs::create_dir_all(&queue_paths.incoming).map_err(|err| {
+        WebhookError::Internal(format!(
+            "failed to create {}: {err}",
+            queue_paths.incoming.display()
+        ))
+    })?;
+    let path = queue_paths.incoming.join(format!("{message_id}.json"));
+    let body = serde_json::to_vec_pretty(&incoming)
+        .map_err(|err| WebhookError::Internal(format!("failed to encode queue payload: {err}")))?;
+    atomic_write_file(&path, &body).map_err(|err| {
+        WebhookError::Internal(format!("failed to write {}: {err}", path.display()))
+    })?;
+    Ok(message_id)
+}
+
+fn message_id_for(route_id: &str, delivery_id: &str) -> String {
+    format!("webhook-{route_id}-{}", delivery_digest(delivery_id))
+}
+
+fn delivery_digest(delivery_id: &str) -> String {
+    Sha256::digest(delivery_id.as_bytes())
+        .iter()
+        .take(16)
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn delivery_record_path(runtime_root: &Path, route_id: &str, delivery_id: &str) -> PathBuf {
+    runtime_root
+        .join("webhooks/deliveries")
+        .join(route_id)
+        .join(format!("{}.json", delivery_digest(delivery_id)))
+}
+
+fn load_delivery_record(path: &Path) -> Result<Option<DeliveryRecord>, WebhookError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(WebhookError::Internal(format!(
+                "failed to read {}: {err}",
+                path.display()
+            )))
+        }
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|err| WebhookError::Internal(format!("failed to parse {}: {err}", path.display())))
+}
+
+/// Persists the pending record. The first write creates the file
+/// exclusively; `false` means another process reserved the delivery first.
+fn reserve_delivery_record(
+    path: &Path,
+    record: &DeliveryRecord,
+    on_disk: &mut bool,
+) -> Result<bool, WebhookError> {
+    if *on_disk {
+        save_delivery_record(path, record)?;
+        return Ok(true);
+    }
+    let body = encode_delivery_record(path, record)?;
+    let created = atomic_create_file(path, &body).map_err(|err| {
+        WebhookError::Internal(format!("failed to write {}: {err}", path.display()))
+    })?;
+    *on_disk = created;
+    Ok(created)
+}
+
+/// Drops the pending record of a failed delivery so its retry starts over,
+/// unless the reserved run already exists and the retry must reuse it.
+fn release_pending_record(runtime_root: &Path, path: &Path) {
+    let Ok(Some(record)) = load_delivery_record(path) else {
+        return;
+    };
+    let run_created = record
+        .run_id
+        .as_deref()
+        .is_some_and(|run_id| WorkflowRunStore::new(runtime_root).run_exists(run_id));
+    if record.pending && !run_created {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn save_delivery_record(path: &Path, record: &DeliveryRecord) -> Result<(), WebhookError> {
+    let body = encode_delivery_record(path, record)?;
+    atomic_write_file(path, &body)
+        .map_err(|err| WebhookError::Internal(format!("failed to write {}: {err}", path.display())))
+}
+
+fn encode_delivery_record(path: &Path, record: &DeliveryRecord) -> Result<Vec<u8>, WebhookError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            WebhookError::Internal(format!("failed to create {}: {err}", parent.display()))
+        })?;
+    }
+    serde_json::to_vec_pretty(record)
+        .map_err(|err| WebhookError::Internal(format!("failed to encode delivery: {err}")))
+}
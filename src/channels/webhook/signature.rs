@@ -0,0 +1,60 @@
+use sha2::{Digest, Sha256};
+
+const HMAC_BLOCK_BYTES: usize = 64;
+
+/// HMAC-SHA256 per RFC 2104.
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0_u8; HMAC_BLOCK_BYTES];
+    if key.len() > HMAC_BLOCK_BYTES {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let inner = inner.finalize();
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner);
+    outer.finalize().into()
+}
+
+/// Renders the signature header value a sender computes for `body`.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let digest = hmac_sha256(secret.as_bytes(), body);
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    format!("sha256={hex}")
+}
+
+/// Checks a `sha256=<hex>` (or bare hex) signature header against `body`
+/// without short-circuiting on the first differing byte.
+pub fn verify_signature(secret: &str, body: &[u8], header_value: &str) -> bool {
+    let raw = header_value.trim();
+    let hex = raw.strip_prefix("sha256=").unwrap_or(raw);
+    let Some(provided) = decode_hex(hex) else {
+        return false;
+    };
+    let expected = hmac_sha256(secret.as_bytes(), body);
+    if provided.len() != expected.len() {
+        return false;
+    }
+    provided
+        .iter()
+        .zip(expected.iter())
+        .fold(0_u8, |diff, (left, right)| diff | (left ^ right))
+        == 0
+}
+
+fn decode_hex(raw: &str) -> Option<Vec<u8>> {
+    if !raw.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..raw.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(raw.get(index..index + 2)?, 16).ok())
+        .collect()
+}
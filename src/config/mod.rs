@@ -10,17 +10,21 @@ pub mod paths;
 pub mod save;
 pub mod settings;
 pub(crate) mod setup_draft;
+pub mod transition_expression;
 pub mod typed_fields;
 pub mod validate;
+pub use crate::channels::webhook::{WebhookConfig, WebhookRoute};
 pub use crate::memory::{
     MemoryBulletinMode, MemoryConfig, MemoryIngestConfig, MemoryRetrievalConfig, MemoryScopeConfig,
 };
 pub use error::ConfigError;
 pub use load::{load_global_settings, load_orchestrator_config};
 pub use orchestrator_file::{
-    agent_editable_fields, AgentConfig, AgentEditableField, ConfigProviderKind, OrchestratorConfig,
-    StepLimitsConfig, WorkflowConfig, WorkflowLimitsConfig, WorkflowOrchestrationConfig,
-    WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType, WorkflowStepWorkspaceMode,
+    agent_editable_fields, AgentConfig, AgentEditableField, AgentEndpointConfig,
+    AgentFallbackConfig, InterruptedAttemptPolicy, OrchestratorConfig, RetryErrorClass,
+    RetryPolicyConfig, StepLimitsConfig, WorkflowConfig, WorkflowLimitsConfig,
+    WorkflowOrchestrationConfig, WorkflowStepConfig, WorkflowStepPromptType,
+    WorkflowStepTransition, WorkflowStepType, WorkflowStepWorkspaceMode, COMMAND_STEP_OUTPUT_KEYS,
 };
 pub use orchestrators_registry::{remove_orchestrator_config, save_orchestrator_registry};
 pub use paths::{
@@ -34,16 +38,18 @@ pub use settings::{
     SlackInboundMode, ThreadResponseMode, ValidationOptions,
 };
 pub(crate) use setup_draft::{OrchestrationLimitField, SetupDraft};
+pub use transition_expression::TransitionExpression;
 pub use typed_fields::{
     normalize_workflow_input_key, parse_output_contract_key, AgentId, OrchestratorId,
-    OutputContractKey, OutputKey, PathTemplate, StepId, WorkflowId, WorkflowInputKey,
-    WorkflowInputs, WorkflowTag,
+    OutputContractKey, OutputKey, OutputSchema, OutputValueType, PathTemplate, StepId, WorkflowId,
+    WorkflowInputKey, WorkflowInputSpec, WorkflowInputType, WorkflowInputs, WorkflowTag,
 };
 pub use validate::{validate_orchestrator_config, validate_settings};
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::provider::ProviderKind;
     use std::fs;
     use std::path::PathBuf;
     use std::sync::Mutex;
@@ -347,6 +353,205 @@ workflows:
         }
     }
 
+    #[test]
+    fn orchestrator_validation_checks_openai_compatible_endpoints() {
+        let settings: Settings = serde_yaml::from_str(
+            r#"
+workspaces_path: /tmp/workspace
+shared_workspaces: {}
+orchestrators:
+  alpha:
+    shared_access: []
+channel_profiles: {}
+monitoring: {}
+channels: {}
+"#,
+        )
+        .expect("parse settings");
+
+        let mut config: OrchestratorConfig = serde_yaml::from_str(
+            r#"
+id: alpha
+selector_agent: router
+default_workflow: real
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  summarizer:
+    provider: openai_compatible
+    model: qwen2.5-7b-instruct
+    endpoint:
+      base_url: http://127.0.0.1:8000/v1
+      api_key_env: LOCAL_LLM_KEY
+workflows:
+  - id: real
+    version: 1
+    description: summarize
+    tags: [summary]
+    steps:
+      - id: step_1
+        type: agent_task
+        agent: summarizer
+        prompt: hello
+        outputs: [summary]
+        output_files:
+          summary: summary.txt
+"#,
+        )
+        .expect("parse orchestrator");
+        config.validate(&settings, "alpha").expect("valid endpoint");
+
+        let validation_message = |config: &OrchestratorConfig| match config
+            .validate(&settings, "alpha")
+            .expect_err("validation should fail")
+        {
+            ConfigError::Orchestrator(message) => message,
+            other => panic!("unexpected error: {other:?}"),
+        };
+
+        let mut missing = config.clone();
+        missing
+            .agents
+            .get_mut("summarizer")
+            .expect("agent")
+            .endpoint = None;
+        assert!(validation_message(&missing).contains("requires `endpoint.base_url`"));
+
+        let mut bad_url = config.clone();
+        bad_url
+            .agents
+            .get_mut("summarizer")
+            .and_then(|agent| agent.endpoint.as_mut())
+            .expect("endpoint")
+            .base_url = "localhost:8000".to_string();
+        assert!(validation_message(&bad_url).contains("must start with http:// or https://"));
+
+        let mut cli_with_endpoint = config.clone();
+        cli_with_endpoint
+            .agents
+            .get_mut("summarizer")
+            .expect("agent")
+            .provider = ProviderKind::OPENAI;
+        assert!(validation_message(&cli_with_endpoint).contains("only applies to provider"));
+
+        config.selector_agent = "summarizer".to_string();
+        config
+            .agents
+            .get_mut("summarizer")
+            .expect("agent")
+            .can_orchestrate_workflows = true;
+        assert!(validation_message(&config).contains("must use a CLI provider"));
+    }
+
+    #[test]
+    fn orchestrator_validation_checks_agent_fallbacks() {
+        let settings: Settings = serde_yaml::from_str(
+            r#"
+workspaces_path: /tmp/workspace
+shared_workspaces: {}
+orchestrators:
+  alpha:
+    shared_access: []
+channel_profiles: {}
+monitoring: {}
+channels: {}
+"#,
+        )
+        .expect("parse settings");
+
+        let config: OrchestratorConfig = serde_yaml::from_str(
+            r#"
+id: alpha
+selector_agent: router
+default_workflow: real
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+    fallbacks:
+      - provider: openai
+        model: gpt-5.2
+  worker:
+    provider: anthropic
+    model: opus
+    fallbacks:
+      - provider: gemini
+        model: gemini-2.5-pro
+      - provider: openai_compatible
+        model: qwen2.5-7b-instruct
+        endpoint:
+          base_url: http://127.0.0.1:8000/v1
+workflows:
+  - id: real
+    version: 1
+    description: work
+    tags: [work]
+    steps:
+      - id: step_1
+        type: agent_task
+        agent: worker
+        prompt: hello
+        outputs: [summary]
+        output_files:
+          summary: summary.txt
+"#,
+        )
+        .expect("parse orchestrator");
+        config
+            .validate(&settings, "alpha")
+            .expect("valid fallbacks");
+
+        let validation_message = |config: &OrchestratorConfig| match config
+            .validate(&settings, "alpha")
+            .expect_err("validation should fail")
+        {
+            ConfigError::Orchestrator(message) => message,
+            other => panic!("unexpected error: {other:?}"),
+        };
+
+        let mut empty_model = config.clone();
+        empty_model
+            .agents
+            .get_mut("worker")
+            .expect("agent")
+            .fallbacks[0]
+            .model = " ".to_string();
+        assert_eq!(
+            validation_message(&empty_model),
+            "agent `worker` fallback 1 requires non-empty `model`"
+        );
+
+        let mut missing_endpoint = config.clone();
+        missing_endpoint
+            .agents
+            .get_mut("worker")
+            .expect("agent")
+            .fallbacks[1]
+            .endpoint = None;
+        assert!(validation_message(&missing_endpoint)
+            .contains("agent `worker` fallback 2 with provider `openai_compatible` requires"));
+
+        let mut http_selector_fallback = config.clone();
+        let worker_fallback = config.agents["worker"].fallbacks[1].clone();
+        http_selector_fallback
+            .agents
+            .get_mut("router")
+            .expect("agent")
+            .fallbacks
+            .push(worker_fallback);
+        assert!(validation_message(&http_selector_fallback).contains("must use a CLI provider"));
+
+        assert!(serde_yaml::from_str::<AgentConfig>(
+            "provider: anthropic\nmodel: sonnet\nfallbacks:\n  - provider: openai\n    model: gpt-5.2\n    timeout: 5\n"
+        )
+        .is_err());
+    }
+
     #[test]
     fn orchestrator_validation_rejects_output_keys_with_non_trailing_optional_marker() {
         let _settings: Settings = serde_yaml::from_str(
@@ -501,7 +706,7 @@ output_files:
         .expect_err("unknown workspace_mode must fail");
         assert!(err.to_string().contains("workspace_mode"));
 
-        let agent_mode = serde_yaml::from_str::<WorkflowStepConfig>(
+        let agent_mode: WorkflowStepConfig = serde_yaml::from_str(
             r#"
 id: step_1
 type: agent_task
@@ -513,8 +718,11 @@ output_files:
   summary: outputs/summary.txt
 "#,
         )
-        .expect_err("agent_workspace mode is no longer supported");
-        assert!(agent_mode.to_string().contains("agent_workspace"));
+        .expect("parse agent_workspace");
+        assert_eq!(
+            agent_mode.workspace_mode,
+            WorkflowStepWorkspaceMode::AgentWorkspace
+        );
     }
 
     #[test]
@@ -655,7 +863,7 @@ steps:
             .inputs
             .as_slice()
             .iter()
-            .map(|key| key.as_str().to_string())
+            .map(|spec| spec.key.as_str().to_string())
             .collect::<Vec<_>>();
         assert_eq!(keys, vec!["ticket".to_string(), "priority".to_string()]);
 
@@ -708,6 +916,110 @@ steps:
         assert!(err.to_string().contains("workflow input key"));
     }
 
+    #[test]
+    fn workflow_step_outputs_parse_schemas_and_round_trip() {
+        let step = serde_yaml::from_str::<WorkflowStepConfig>(
+            r#"
+id: review
+type: agent_task
+agent: worker
+prompt: hello
+outputs:
+  - summary
+  - key: decision
+    type: enum
+    values: [approve, reject]
+  - key: tasks?
+    type: array
+    items:
+      type: object
+      fields:
+        title:
+          type: string
+output_files:
+  summary: outputs/summary.txt
+  decision: outputs/decision.txt
+  tasks: outputs/tasks.json
+"#,
+        )
+        .expect("parse step");
+        assert!(step.outputs[0].schema.is_none());
+        let decision = step.outputs[1].schema.as_ref().expect("decision schema");
+        assert_eq!(decision.value_type, OutputValueType::Enum);
+        assert!(!step.outputs[2].required);
+
+        let encoded = serde_yaml::to_string(&step).expect("encode step");
+        assert!(encoded.contains("- summary"));
+        let reloaded: WorkflowStepConfig = serde_yaml::from_str(&encoded).expect("reload step");
+        assert_eq!(reloaded.outputs, step.outputs);
+    }
+
+    #[test]
+    fn workflow_step_outputs_reject_invalid_schemas() {
+        let err = serde_yaml::from_str::<WorkflowStepConfig>(
+            r#"
+id: review
+type: agent_task
+agent: worker
+prompt: hello
+outputs:
+  - key: summary
+    type: string
+    values: [a]
+output_files:
+  summary: outputs/summary.txt
+"#,
+        )
+        .expect_err("values on string output should fail");
+        assert!(err
+            .to_string()
+            .contains("`values` is only supported for type enum"));
+    }
+
+    #[test]
+    fn workflow_inputs_parse_typed_definitions_and_round_trip() {
+        let inputs: WorkflowInputs = serde_yaml::from_str(
+            r#"
+- ticket
+- key: priority
+  type: enum
+  values: [low, high]
+  default: low
+  description: Ticket priority
+- key: attempts
+  type: integer
+  required: true
+"#,
+        )
+        .expect("parse typed inputs");
+        let priority = inputs.get("priority").expect("priority input");
+        assert_eq!(priority.input_type, WorkflowInputType::Enum);
+        assert_eq!(priority.default, Some(serde_json::json!("low")));
+        assert!(inputs.get("attempts").expect("attempts input").required);
+
+        let encoded = serde_yaml::to_string(&inputs).expect("encode inputs");
+        assert!(encoded.contains("- ticket\n"));
+        let reloaded: WorkflowInputs = serde_yaml::from_str(&encoded).expect("reload inputs");
+        assert_eq!(reloaded, inputs);
+    }
+
+    #[test]
+    fn workflow_inputs_reject_invalid_typed_definitions() {
+        let err = serde_yaml::from_str::<WorkflowInputs>("- key: mode\n  type: enum\n")
+            .expect_err("enum without values should fail");
+        assert!(err.to_string().contains("requires non-empty `values`"));
+
+        let err = serde_yaml::from_str::<WorkflowInputs>(
+            "- key: count\n  type: integer\n  default: lots\n",
+        )
+        .expect_err("mismatched default should fail");
+        assert!(err.to_string().contains("must be an integer"));
+
+        let err = serde_yaml::from_str::<WorkflowInputs>("- key: count\n  kind: integer\n")
+            .expect_err("unknown definition field should fail");
+        assert!(err.to_string().contains("unknown field `kind`"));
+    }
+
     #[test]
     fn workflow_step_requires_outputs_and_output_files_fields() {
         let err = serde_yaml::from_str::<WorkflowStepConfig>(
@@ -749,7 +1061,7 @@ can_orchestrate_workflows: false
 "#,
         )
         .expect("parse agent");
-        assert_eq!(agent.provider, ConfigProviderKind::OpenAi);
+        assert_eq!(agent.provider, ProviderKind::OPENAI);
         let encoded = serde_yaml::to_string(&agent).expect("encode agent");
         assert!(encoded.contains("provider: openai"));
 
@@ -1,12 +1,13 @@
 use super::{
-    AgentId, ConfigError, OrchestratorId, OutputKey, PathTemplate, Settings, StepId, WorkflowId,
-    WorkflowInputs, WorkflowTag,
+    AgentId, ConfigError, OrchestratorId, OutputKey, PathTemplate, Settings, StepId,
+    TransitionExpression, WorkflowId, WorkflowInputs, WorkflowTag,
 };
+use crate::provider::{builtin_registry, ProviderKind};
 use serde::de::Error as _;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 fn deserialize_optional_output_files<'de, D>(
     deserializer: D,
@@ -60,42 +61,16 @@ fn default_final_output_priority(outputs: &[OutputKey]) -> Vec<OutputKey> {
     priority
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
-#[serde(rename_all = "snake_case")]
-pub enum ConfigProviderKind {
-    Anthropic,
-    #[serde(rename = "openai")]
-    OpenAi,
-}
-
-impl ConfigProviderKind {
-    pub fn as_str(self) -> &'static str {
-        match self {
-            Self::Anthropic => "anthropic",
-            Self::OpenAi => "openai",
-        }
-    }
-
-    pub fn parse(raw: &str) -> Result<Self, String> {
-        match raw.trim().to_ascii_lowercase().as_str() {
-            "anthropic" => Ok(Self::Anthropic),
-            "openai" => Ok(Self::OpenAi),
-            _ => Err("provider must be one of: anthropic, openai".to_string()),
-        }
-    }
-}
-
-impl std::fmt::Display for ConfigProviderKind {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_str())
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum WorkflowStepType {
     AgentTask,
     AgentReview,
+    HumanReview,
+    Parallel,
+    WorkflowCall,
+    Command,
+    Foreach,
 }
 
 impl WorkflowStepType {
@@ -103,6 +78,11 @@ impl WorkflowStepType {
         match self {
             Self::AgentTask => "agent_task",
             Self::AgentReview => "agent_review",
+            Self::HumanReview => "human_review",
+            Self::Parallel => "parallel",
+            Self::WorkflowCall => "workflow_call",
+            Self::Command => "command",
+            Self::Foreach => "foreach",
         }
     }
 
@@ -110,9 +90,32 @@ impl WorkflowStepType {
         match raw.trim().to_ascii_lowercase().as_str() {
             "agent_task" => Ok(Self::AgentTask),
             "agent_review" => Ok(Self::AgentReview),
-            _ => Err("step type must be one of: agent_task, agent_review".to_string()),
+            "human_review" => Ok(Self::HumanReview),
+            "parallel" => Ok(Self::Parallel),
+            "workflow_call" => Ok(Self::WorkflowCall),
+            "command" => Ok(Self::Command),
+            "foreach" => Ok(Self::Foreach),
+            _ => Err(
+                "step type must be one of: agent_task, agent_review, human_review, parallel, workflow_call, command, foreach"
+                    .to_string(),
+            ),
         }
     }
+
+    pub fn requires_agent(self) -> bool {
+        matches!(self, Self::AgentTask | Self::AgentReview)
+    }
+
+    pub fn routes_on_decision(self) -> bool {
+        matches!(self, Self::AgentReview | Self::HumanReview)
+    }
+
+    pub fn renders_prompt(self) -> bool {
+        !matches!(
+            self,
+            Self::Parallel | Self::WorkflowCall | Self::Command | Self::Foreach
+        )
+    }
 }
 
 impl std::fmt::Display for WorkflowStepType {
@@ -143,10 +146,40 @@ pub struct OrchestratorConfig {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct AgentConfig {
-    pub provider: ConfigProviderKind,
+    pub provider: ProviderKind,
     pub model: String,
     #[serde(default)]
     pub can_orchestrate_workflows: bool,
+    /// Chat completions endpoint; required by `openai_compatible` agents.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<AgentEndpointConfig>,
+    /// Providers tried in order when this agent's provider is unavailable,
+    /// rate-limited or rejects its credentials.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<AgentFallbackConfig>,
+}
+
+/// One entry of an agent's fallback chain.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentFallbackConfig {
+    pub provider: ProviderKind,
+    pub model: String,
+    /// Chat completions endpoint; required by `openai_compatible` entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<AgentEndpointConfig>,
+}
+
+/// OpenAI-compatible HTTP endpoint an `openai_compatible` agent sends its
+/// prompt to as `POST {base_url}/chat/completions`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentEndpointConfig {
+    pub base_url: String,
+    /// Environment variable holding the bearer token; unauthenticated when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -204,6 +237,8 @@ pub struct WorkflowConfig {
     pub inputs: WorkflowInputs,
     #[serde(default)]
     pub limits: Option<WorkflowLimitsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<PathBuf>,
     #[serde(default)]
     pub steps: Vec<WorkflowStepConfig>,
 }
@@ -213,6 +248,7 @@ pub struct WorkflowStepConfig {
     pub id: String,
     #[serde(rename = "type")]
     pub step_type: WorkflowStepType,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub agent: String,
     pub prompt: String,
     #[serde(default = "default_workflow_step_prompt_type")]
@@ -225,11 +261,38 @@ pub struct WorkflowStepConfig {
     pub on_approve: Option<String>,
     #[serde(default)]
     pub on_reject: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transitions: Vec<WorkflowStepTransition>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workflow: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inputs: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequence: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrency: Option<u32>,
     pub outputs: Vec<OutputKey>,
     pub output_files: BTreeMap<OutputKey, PathTemplate>,
     pub final_output_priority: Vec<OutputKey>,
     #[serde(default)]
     pub limits: Option<StepLimitsConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicyConfig>,
+}
+
+impl WorkflowStepConfig {
+    /// Whether this step routes via `on_approve` / `on_reject`: review steps
+    /// always do, `command` steps do when they declare those targets.
+    pub fn routes_on_decision(&self) -> bool {
+        self.step_type.routes_on_decision()
+            || (self.step_type == WorkflowStepType::Command && self.on_approve.is_some())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -237,7 +300,9 @@ struct WorkflowStepConfigRaw {
     pub id: String,
     #[serde(rename = "type")]
     pub step_type: WorkflowStepType,
+    #[serde(default)]
     pub agent: String,
+    #[serde(default)]
     pub prompt: String,
     #[serde(default = "default_workflow_step_prompt_type")]
     pub prompt_type: WorkflowStepPromptType,
@@ -249,6 +314,22 @@ struct WorkflowStepConfigRaw {
     pub on_approve: Option<String>,
     #[serde(default)]
     pub on_reject: Option<String>,
+    #[serde(default)]
+    pub branches: Vec<String>,
+    #[serde(default)]
+    pub transitions: Vec<WorkflowStepTransition>,
+    #[serde(default)]
+    pub workflow: Option<String>,
+    #[serde(default)]
+    pub inputs: BTreeMap<String, String>,
+    #[serde(default)]
+    pub command: Vec<String>,
+    #[serde(default)]
+    pub items: Option<String>,
+    #[serde(default)]
+    pub sequence: Vec<String>,
+    #[serde(default)]
+    pub max_concurrency: Option<u32>,
     pub outputs: Option<Vec<OutputKey>>,
     #[serde(default, deserialize_with = "deserialize_optional_output_files")]
     pub output_files: Option<BTreeMap<OutputKey, PathTemplate>>,
@@ -256,6 +337,8 @@ struct WorkflowStepConfigRaw {
     pub final_output_priority: Option<Vec<OutputKey>>,
     #[serde(default)]
     pub limits: Option<StepLimitsConfig>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicyConfig>,
 }
 
 impl<'de> Deserialize<'de> for WorkflowStepConfig {
@@ -287,14 +370,42 @@ impl<'de> Deserialize<'de> for WorkflowStepConfig {
             next: raw.next,
             on_approve: raw.on_approve,
             on_reject: raw.on_reject,
+            branches: raw.branches,
+            transitions: raw.transitions,
+            workflow: raw.workflow,
+            inputs: raw.inputs,
+            command: raw.command,
+            items: raw.items,
+            sequence: raw.sequence,
+            max_concurrency: raw.max_concurrency,
             outputs,
             output_files,
             final_output_priority,
             limits: raw.limits,
+            retry: raw.retry,
         })
     }
 }
 
+/// One entry of a step's `transitions` list: either a conditional route
+/// (`{when: "outputs.risk == 'high'", next: deep_review}`) or the fallback
+/// (`{default: ship}`).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum WorkflowStepTransition {
+    When { when: String, next: String },
+    Default { default: String },
+}
+
+impl WorkflowStepTransition {
+    pub fn target(&self) -> &str {
+        match self {
+            Self::When { next, .. } => next,
+            Self::Default { default } => default,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum WorkflowStepPromptType {
@@ -311,6 +422,17 @@ fn default_workflow_step_prompt_type() -> WorkflowStepPromptType {
 pub enum WorkflowStepWorkspaceMode {
     OrchestratorWorkspace,
     RunWorkspace,
+    AgentWorkspace,
+}
+
+impl WorkflowStepWorkspaceMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::OrchestratorWorkspace => "orchestrator_workspace",
+            Self::RunWorkspace => "run_workspace",
+            Self::AgentWorkspace => "agent_workspace",
+        }
+    }
 }
 
 fn default_workflow_step_workspace_mode() -> WorkflowStepWorkspaceMode {
@@ -327,6 +449,22 @@ pub struct WorkflowOrchestrationConfig {
     pub default_step_timeout_seconds: Option<u64>,
     #[serde(default)]
     pub max_step_timeout_seconds: Option<u64>,
+    /// What runtime startup does with a step attempt that was still running
+    /// when the previous process died. Defaults to `retry`.
+    #[serde(default)]
+    pub interrupted_attempt_policy: Option<InterruptedAttemptPolicy>,
+    /// Retry policy for every step attempt; a step's own `retry` block
+    /// overrides it field by field.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicyConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptedAttemptPolicy {
+    #[default]
+    Retry,
+    Fail,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -343,6 +481,96 @@ pub struct StepLimitsConfig {
     pub max_retries: Option<u32>,
 }
 
+/// Why a step attempt failed, as far as retry decisions are concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryErrorClass {
+    /// The provider or command ran past the step timeout.
+    Timeout,
+    /// The provider or command exited with a non-zero status.
+    NonZeroExit,
+    /// A provider exited non-zero with a rate-limit or quota error on stderr.
+    RateLimit,
+    /// The attempt finished but its result broke the output contract.
+    OutputContract,
+    /// Any other retryable failure, such as a missing binary or unreadable output.
+    Execution,
+}
+
+impl RetryErrorClass {
+    pub const ALL: [RetryErrorClass; 5] = [
+        RetryErrorClass::Timeout,
+        RetryErrorClass::NonZeroExit,
+        RetryErrorClass::RateLimit,
+        RetryErrorClass::OutputContract,
+        RetryErrorClass::Execution,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Timeout => "timeout",
+            Self::NonZeroExit => "non_zero_exit",
+            Self::RateLimit => "rate_limit",
+            Self::OutputContract => "output_contract",
+            Self::Execution => "execution",
+        }
+    }
+}
+
+impl std::fmt::Display for RetryErrorClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `retry:` block under `workflow_orchestration` or on a step. Unset fields
+/// fall back to the orchestrator block, then to built-in defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryPolicyConfig {
+    /// Delay before the first retry; `0` retries immediately.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_backoff_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_seconds: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_multiplier: Option<u32>,
+    /// Up to this percentage of each delay is randomly taken off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter_percent: Option<u32>,
+    /// Retries allowed per error class; classes without an entry use the
+    /// step's `limits.max_retries`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub max_retries: BTreeMap<RetryErrorClass, u32>,
+    /// Error classes that fail the step without retrying.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_retryable: Option<Vec<RetryErrorClass>>,
+}
+
+impl RetryPolicyConfig {
+    fn validate(&self, scope: &str) -> Result<(), ConfigError> {
+        if self.backoff_multiplier == Some(0) {
+            return Err(ConfigError::Orchestrator(format!(
+                "{scope} `retry.backoff_multiplier` must be >= 1"
+            )));
+        }
+        if self.jitter_percent.is_some_and(|value| value > 100) {
+            return Err(ConfigError::Orchestrator(format!(
+                "{scope} `retry.jitter_percent` must be <= 100"
+            )));
+        }
+        if let (Some(initial), Some(max)) = (self.initial_backoff_seconds, self.max_backoff_seconds)
+        {
+            if initial > max {
+                return Err(ConfigError::Orchestrator(format!(
+                    "{scope} `retry.initial_backoff_seconds` must be <= `retry.max_backoff_seconds`"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl OrchestratorConfig {
     pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
         let raw = fs::read_to_string(path).map_err(|source| ConfigError::Read {
@@ -388,6 +616,17 @@ impl OrchestratorConfig {
                     "agent `{agent_id}` requires non-empty `model`"
                 )));
             }
+            let owner = format!("agent `{agent_id}`");
+            validate_provider_endpoint(&owner, &agent.provider, agent.endpoint.as_ref())?;
+            for (index, fallback) in agent.fallbacks.iter().enumerate() {
+                let owner = format!("agent `{agent_id}` fallback {}", index + 1);
+                if fallback.model.trim().is_empty() {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "{owner} requires non-empty `model`"
+                    )));
+                }
+                validate_provider_endpoint(&owner, &fallback.provider, fallback.endpoint.as_ref())?;
+            }
         }
 
         for workflow in &self.workflows {
@@ -404,6 +643,9 @@ impl OrchestratorConfig {
                     workflow.id
                 )));
             }
+            if let Some(repository) = &workflow.repository {
+                validate_workflow_repository(settings, orchestrator_id, workflow, repository)?;
+            }
             let mut seen_tags = HashSet::new();
             for tag in &workflow.tags {
                 if !seen_tags.insert(tag.as_str()) {
@@ -415,8 +657,10 @@ impl OrchestratorConfig {
             }
             for step in &workflow.steps {
                 StepId::parse(&step.id).map_err(ConfigError::Orchestrator)?;
-                AgentId::parse(&step.agent).map_err(ConfigError::Orchestrator)?;
-                if step.prompt.trim().is_empty() {
+                if step.step_type.requires_agent() {
+                    AgentId::parse(&step.agent).map_err(ConfigError::Orchestrator)?;
+                }
+                if step.step_type.renders_prompt() && step.prompt.trim().is_empty() {
                     return Err(ConfigError::Orchestrator(format!(
                         "workflow `{}` step `{}` requires non-empty prompt",
                         workflow.id, step.id
@@ -455,6 +699,15 @@ impl OrchestratorConfig {
                 self.selector_agent
             )));
         }
+        if let Some(provider) = std::iter::once(&selector.provider)
+            .chain(selector.fallbacks.iter().map(|fallback| &fallback.provider))
+            .find(|provider| provider.uses_endpoint())
+        {
+            return Err(ConfigError::Orchestrator(format!(
+                "selector agent `{}` must use a CLI provider; `{provider}` agents cannot write the selector result file",
+                self.selector_agent
+            )));
+        }
 
         let mut workflow_ids = HashSet::new();
         for workflow in &self.workflows {
@@ -472,6 +725,14 @@ impl OrchestratorConfig {
             )));
         }
 
+        if let Some(retry) = self
+            .workflow_orchestration
+            .as_ref()
+            .and_then(|value| value.retry.as_ref())
+        {
+            retry.validate("`workflow_orchestration`")?;
+        }
+
         for workflow in &self.workflows {
             if workflow.steps.is_empty() {
                 return Err(ConfigError::Orchestrator(format!(
@@ -487,12 +748,75 @@ impl OrchestratorConfig {
                         workflow.id, step.id
                     )));
                 }
-                if !self.agents.contains_key(&step.agent) {
+                if let Some(retry) = &step.retry {
+                    retry.validate(&format!("workflow `{}` step `{}`", workflow.id, step.id))?;
+                }
+                if step.step_type.requires_agent() && !self.agents.contains_key(&step.agent) {
                     return Err(ConfigError::Orchestrator(format!(
                         "workflow `{}` step `{}` references unknown agent `{}`",
                         workflow.id, step.id, step.agent
                     )));
                 }
+                if step.workspace_mode == WorkflowStepWorkspaceMode::AgentWorkspace
+                    && !step.step_type.requires_agent()
+                {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` uses `workspace_mode: agent_workspace` but has no agent",
+                        workflow.id, step.id
+                    )));
+                }
+                if step.step_type == WorkflowStepType::HumanReview {
+                    if !step.outputs.iter().any(|key| key.as_str() == "decision") {
+                        return Err(ConfigError::Orchestrator(format!(
+                            "workflow `{}` step `{}` requires `decision` in `outputs`",
+                            workflow.id, step.id
+                        )));
+                    }
+                    if step.on_approve.is_none() || step.on_reject.is_none() {
+                        return Err(ConfigError::Orchestrator(format!(
+                            "workflow `{}` step `{}` requires both `on_approve` and `on_reject`",
+                            workflow.id, step.id
+                        )));
+                    }
+                }
+                if step.step_type == WorkflowStepType::Parallel {
+                    validate_parallel_step(workflow, step)?;
+                } else if !step.branches.is_empty() {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` declares `branches` but is not a `parallel` step",
+                        workflow.id, step.id
+                    )));
+                }
+                if !step.transitions.is_empty() {
+                    validate_step_transitions(workflow, step)?;
+                }
+                if step.step_type == WorkflowStepType::WorkflowCall {
+                    self.validate_workflow_call_step(workflow, step)?;
+                } else if step.workflow.is_some() || !step.inputs.is_empty() {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` declares `workflow`/`inputs` but is not a `workflow_call` step",
+                        workflow.id, step.id
+                    )));
+                }
+                if step.step_type == WorkflowStepType::Command {
+                    validate_command_step(workflow, step)?;
+                } else if !step.command.is_empty() {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` declares `command` but is not a `command` step",
+                        workflow.id, step.id
+                    )));
+                }
+                if step.step_type == WorkflowStepType::Foreach {
+                    validate_foreach_step(workflow, step)?;
+                } else if step.items.is_some()
+                    || !step.sequence.is_empty()
+                    || step.max_concurrency.is_some()
+                {
+                    return Err(ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` declares `items`/`sequence`/`max_concurrency` but is not a `foreach` step",
+                        workflow.id, step.id
+                    )));
+                }
                 if step.outputs.is_empty() {
                     return Err(ConfigError::Orchestrator(format!(
                         "workflow `{}` step `{}` requires non-empty `outputs`",
@@ -557,3 +881,449 @@ impl OrchestratorConfig {
         Ok(())
     }
 }
+
+impl OrchestratorConfig {
+    fn validate_workflow_call_step(
+        &self,
+        workflow: &WorkflowConfig,
+        step: &WorkflowStepConfig,
+    ) -> Result<(), ConfigError> {
+        let Some(target) = step.workflow.as_deref() else {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` requires `workflow` naming the workflow to call",
+                workflow.id, step.id
+            )));
+        };
+        if !self
+            .workflows
+            .iter()
+            .any(|candidate| candidate.id == target)
+        {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` calls unknown workflow `{target}`",
+                workflow.id, step.id
+            )));
+        }
+        if let Some(cycle) = self.workflow_call_cycle(&workflow.id, target, &mut Vec::new()) {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` creates a workflow_call cycle: {}",
+                workflow.id,
+                step.id,
+                cycle.join(" -> ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the call chain when `callee` (transitively) calls back into `origin`.
+    fn workflow_call_cycle(
+        &self,
+        origin: &str,
+        callee: &str,
+        chain: &mut Vec<String>,
+    ) -> Option<Vec<String>> {
+        if chain.iter().any(|seen| seen == callee) {
+            return None;
+        }
+        chain.push(callee.to_string());
+        if callee == origin {
+            let mut cycle = vec![origin.to_string()];
+            cycle.extend(chain.iter().cloned());
+            return Some(cycle);
+        }
+        let called = self
+            .workflows
+            .iter()
+            .find(|candidate| candidate.id == callee)
+            .into_iter()
+            .flat_map(|candidate| candidate.steps.iter())
+            .filter(|candidate| candidate.step_type == WorkflowStepType::WorkflowCall)
+            .filter_map(|candidate| candidate.workflow.clone())
+            .collect::<Vec<_>>();
+        for next in called {
+            if let Some(cycle) = self.workflow_call_cycle(origin, &next, chain) {
+                return Some(cycle);
+            }
+        }
+        chain.pop();
+        None
+    }
+}
+
+/// Output keys a `command` step can produce from the finished process.
+pub const COMMAND_STEP_OUTPUT_KEYS: &[&str] =
+    &["summary", "decision", "exit_code", "stdout", "stderr"];
+
+/// Checks the endpoint of an agent or one of its fallbacks; `owner` names it
+/// in errors.
+fn validate_provider_endpoint(
+    owner: &str,
+    provider: &ProviderKind,
+    endpoint: Option<&AgentEndpointConfig>,
+) -> Result<(), ConfigError> {
+    let Some(endpoint) = endpoint else {
+        if provider.uses_endpoint() {
+            return Err(ConfigError::Orchestrator(format!(
+                "{owner} with provider `{provider}` requires `endpoint.base_url`"
+            )));
+        }
+        return Ok(());
+    };
+    if !provider.uses_endpoint() {
+        return Err(ConfigError::Orchestrator(format!(
+            "{owner} sets `endpoint`, which only applies to providers called over http: {}",
+            builtin_registry()
+                .backends()
+                .filter(|backend| backend.uses_endpoint())
+                .map(|backend| backend.id())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    let base_url = endpoint.base_url.trim();
+    if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+        return Err(ConfigError::Orchestrator(format!(
+            "{owner} `endpoint.base_url` must start with http:// or https://"
+        )));
+    }
+    if endpoint
+        .api_key_env
+        .as_deref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Err(ConfigError::Orchestrator(format!(
+            "{owner} `endpoint.api_key_env` must be non-empty when set"
+        )));
+    }
+    Ok(())
+}
+
+/// A workflow `repository` must be a git checkout inside the orchestrator
+/// private workspace or one of its granted shared workspaces. Relative paths
+/// resolve against the private workspace, e.g. `shared/code/app`.
+fn validate_workflow_repository(
+    settings: &Settings,
+    orchestrator_id: &str,
+    workflow: &WorkflowConfig,
+    repository: &Path,
+) -> Result<(), ConfigError> {
+    if repository.as_os_str().is_empty() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` requires non-empty `repository` when set",
+            workflow.id
+        )));
+    }
+    if repository
+        .components()
+        .any(|component| matches!(component, std::path::Component::ParentDir))
+    {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` repository `{}` must not contain `..`",
+            workflow.id,
+            repository.display()
+        )));
+    }
+    let private_workspace = settings.resolve_private_workspace(orchestrator_id)?;
+    if repository.is_relative() {
+        return Ok(());
+    }
+    let granted = settings
+        .orchestrators
+        .get(orchestrator_id)
+        .map(|orchestrator| orchestrator.shared_access.as_slice())
+        .unwrap_or_default();
+    let allowed = repository.starts_with(&private_workspace)
+        || granted.iter().any(|grant| {
+            settings
+                .shared_workspaces
+                .get(grant)
+                .is_some_and(|shared| repository.starts_with(&shared.path))
+        });
+    if !allowed {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` repository `{}` must be inside the orchestrator private workspace or a granted shared workspace",
+            workflow.id,
+            repository.display()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_command_step(
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+) -> Result<(), ConfigError> {
+    if step
+        .command
+        .first()
+        .is_none_or(|program| program.trim().is_empty())
+    {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires `command` with a non-empty program",
+            workflow.id, step.id
+        )));
+    }
+    if step.on_approve.is_some() != step.on_reject.is_some() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` must declare both `on_approve` and `on_reject` or neither",
+            workflow.id, step.id
+        )));
+    }
+    if step.on_approve.is_some() && step.next.is_some() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` cannot combine `next` with `on_approve`/`on_reject`",
+            workflow.id, step.id
+        )));
+    }
+    for key in &step.outputs {
+        if !COMMAND_STEP_OUTPUT_KEYS.contains(&key.as_str()) {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` output `{}` is not produced by command steps (expected one of: {})",
+                workflow.id,
+                step.id,
+                key.as_str(),
+                COMMAND_STEP_OUTPUT_KEYS.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_foreach_step(
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+) -> Result<(), ConfigError> {
+    if step
+        .items
+        .as_deref()
+        .is_none_or(|items| items.trim().is_empty())
+    {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires `items` naming the list to iterate, e.g. `{{{{steps.plan.outputs.tasks}}}}`",
+            workflow.id, step.id
+        )));
+    }
+    if step.next.is_none() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires `next` naming the step after all items",
+            workflow.id, step.id
+        )));
+    }
+    if step.sequence.is_empty() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires a non-empty `sequence`",
+            workflow.id, step.id
+        )));
+    }
+    if step.max_concurrency == Some(0) {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires `max_concurrency` of at least 1",
+            workflow.id, step.id
+        )));
+    }
+    if let Some(key) = step
+        .outputs
+        .iter()
+        .find(|key| !matches!(key.as_str(), "summary" | "results"))
+    {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` may only declare `summary` and `results` in `outputs`, found `{}`",
+            workflow.id,
+            step.id,
+            key.as_str()
+        )));
+    }
+    let mut seen = HashSet::new();
+    for body_id in &step.sequence {
+        if !seen.insert(body_id.as_str()) || *body_id == step.id {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` lists sequence step `{body_id}` more than once or itself",
+                workflow.id, step.id
+            )));
+        }
+        let Some(body) = workflow.steps.iter().find(|s| s.id == *body_id) else {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` references unknown sequence step `{body_id}`",
+                workflow.id, step.id
+            )));
+        };
+        if !matches!(
+            body.step_type,
+            WorkflowStepType::AgentTask | WorkflowStepType::Command
+        ) {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` sequence step `{body_id}` must be `agent_task` or `command`",
+                workflow.id
+            )));
+        }
+        if body.next.is_some()
+            || body.on_approve.is_some()
+            || body.on_reject.is_some()
+            || !body.transitions.is_empty()
+        {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` sequence step `{body_id}` must not declare routes; `sequence` order defines its flow",
+                workflow.id
+            )));
+        }
+    }
+    for other in &workflow.steps {
+        let targets = [&other.next, &other.on_approve, &other.on_reject]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(other.transitions.iter().map(WorkflowStepTransition::target))
+            .chain(other.branches.iter().map(String::as_str))
+            .chain(
+                (other.id != step.id)
+                    .then_some(other.sequence.iter().map(String::as_str))
+                    .into_iter()
+                    .flatten(),
+            );
+        for target in targets {
+            if step.sequence.iter().any(|body_id| body_id == target) {
+                return Err(ConfigError::Orchestrator(format!(
+                    "workflow `{}` sequence step `{target}` of `{}` must only run inside the foreach, but step `{}` routes to it",
+                    workflow.id, step.id, other.id
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_parallel_step(
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+) -> Result<(), ConfigError> {
+    let Some(join) = step.next.as_deref() else {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires `next` naming the join step",
+            workflow.id, step.id
+        )));
+    };
+    if step.branches.len() < 2 {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` requires at least two `branches`",
+            workflow.id, step.id
+        )));
+    }
+    if let Some(key) = step.outputs.iter().find(|key| key.as_str() != "summary") {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` may only declare `summary` in `outputs`, found `{}`",
+            workflow.id,
+            step.id,
+            key.as_str()
+        )));
+    }
+    let mut seen = HashSet::new();
+    for branch_id in &step.branches {
+        if !seen.insert(branch_id.as_str()) {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` lists branch `{branch_id}` more than once",
+                workflow.id, step.id
+            )));
+        }
+        let Some(branch) = workflow.steps.iter().find(|s| s.id == *branch_id) else {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` references unknown branch step `{branch_id}`",
+                workflow.id, step.id
+            )));
+        };
+        if !matches!(
+            branch.step_type,
+            WorkflowStepType::AgentTask | WorkflowStepType::AgentReview
+        ) {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` branch step `{branch_id}` must be `agent_task` or `agent_review`",
+                workflow.id
+            )));
+        }
+        let routes = [&branch.next, &branch.on_approve, &branch.on_reject];
+        if routes
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(
+                branch
+                    .transitions
+                    .iter()
+                    .map(WorkflowStepTransition::target),
+            )
+            .any(|target| target != join)
+        {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` branch step `{branch_id}` may only route to join step `{join}`",
+                workflow.id
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_step_transitions(
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+) -> Result<(), ConfigError> {
+    if step.routes_on_decision() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` routes on its decision through `on_approve`/`on_reject` and must not declare `transitions`",
+            workflow.id, step.id
+        )));
+    }
+    if !matches!(
+        step.step_type,
+        WorkflowStepType::AgentTask | WorkflowStepType::Command
+    ) {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` declares `transitions`, which are only supported on `agent_task` and `command` steps",
+            workflow.id, step.id
+        )));
+    }
+    if step.next.is_some() {
+        return Err(ConfigError::Orchestrator(format!(
+            "workflow `{}` step `{}` must not declare both `next` and `transitions`",
+            workflow.id, step.id
+        )));
+    }
+    let last = step.transitions.len() - 1;
+    for (idx, transition) in step.transitions.iter().enumerate() {
+        let target = transition.target();
+        if !workflow
+            .steps
+            .iter()
+            .any(|candidate| candidate.id == target)
+        {
+            return Err(ConfigError::Orchestrator(format!(
+                "workflow `{}` step `{}` transition targets unknown step `{target}`",
+                workflow.id, step.id
+            )));
+        }
+        match transition {
+            WorkflowStepTransition::Default { .. } if idx != last => {
+                return Err(ConfigError::Orchestrator(format!(
+                    "workflow `{}` step `{}` `default` transition must be the last entry",
+                    workflow.id, step.id
+                )));
+            }
+            WorkflowStepTransition::Default { .. } => {}
+            WorkflowStepTransition::When { when, .. } => {
+                let expression = TransitionExpression::parse(when).map_err(|err| {
+                    ConfigError::Orchestrator(format!(
+                        "workflow `{}` step `{}` has malformed transition `when: {when}`: {err}",
+                        workflow.id, step.id
+                    ))
+                })?;
+                for key in expression.output_keys() {
+                    if !step.outputs.iter().any(|output| output.as_str() == key) {
+                        return Err(ConfigError::Orchestrator(format!(
+                            "workflow `{}` step `{}` transition references output `{key}` not declared in `outputs`",
+                            workflow.id, step.id
+                        )));
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
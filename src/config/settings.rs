@@ -1,4 +1,5 @@
 use super::{ConfigError, OrchestratorId};
+use crate::channels::webhook::{WebhookConfig, WebhookTarget};
 use crate::local_llm::LocalLlmConfig;
 use crate::memory::MemoryConfig;
 use serde::{Deserialize, Serialize};
@@ -14,6 +15,7 @@ pub enum ChannelKind {
     Discord,
     Telegram,
     Whatsapp,
+    Webhook,
 }
 
 impl ChannelKind {
@@ -24,6 +26,7 @@ impl ChannelKind {
             Self::Discord => "discord",
             Self::Telegram => "telegram",
             Self::Whatsapp => "whatsapp",
+            Self::Webhook => "webhook",
         }
     }
 
@@ -34,9 +37,11 @@ impl ChannelKind {
             "discord" => Ok(Self::Discord),
             "telegram" => Ok(Self::Telegram),
             "whatsapp" => Ok(Self::Whatsapp),
-            _ => {
-                Err("channel must be one of: local, slack, discord, telegram, whatsapp".to_string())
-            }
+            "webhook" => Ok(Self::Webhook),
+            _ => Err(
+                "channel must be one of: local, slack, discord, telegram, whatsapp, webhook"
+                    .to_string(),
+            ),
         }
     }
 }
@@ -66,6 +71,8 @@ pub struct Settings {
     pub memory: MemoryConfig,
     #[serde(default)]
     pub local_llm: LocalLlmConfig,
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -342,6 +349,34 @@ impl Settings {
 
         self.memory.validate().map_err(ConfigError::Settings)?;
         self.local_llm.validate().map_err(ConfigError::Settings)?;
+        self.webhooks.validate().map_err(ConfigError::Settings)?;
+        for (route_id, route) in &self.webhooks.routes {
+            if !self.orchestrators.contains_key(&route.orchestrator_id) {
+                return Err(ConfigError::Settings(format!(
+                    "webhook route `{route_id}` references unknown orchestrator `{}`",
+                    route.orchestrator_id
+                )));
+            }
+            let Some(WebhookTarget::Message {
+                channel_profile_id, ..
+            }) = route.target()
+            else {
+                continue;
+            };
+            let profile = self.channel_profiles.get(channel_profile_id).ok_or_else(|| {
+                ConfigError::Settings(format!(
+                    "webhook route `{route_id}` references unknown channel profile `{channel_profile_id}`"
+                ))
+            })?;
+            if profile.channel != ChannelKind::Webhook
+                || profile.orchestrator_id != route.orchestrator_id
+            {
+                return Err(ConfigError::Settings(format!(
+                    "webhook route `{route_id}` requires channel profile `{channel_profile_id}` to be a `webhook` profile of orchestrator `{}`",
+                    route.orchestrator_id
+                )));
+            }
+        }
 
         Ok(())
     }
@@ -1,12 +1,13 @@
 use crate::config::{
-    AgentConfig, AuthSyncConfig, ChannelKind, ChannelProfile, ConfigProviderKind,
-    OrchestratorConfig, OutputKey, PathTemplate, Settings, SettingsOrchestrator, StepLimitsConfig,
-    ThreadResponseMode, ValidationOptions, WorkflowConfig, WorkflowInputs, WorkflowLimitsConfig,
+    AgentConfig, AuthSyncConfig, ChannelKind, ChannelProfile, OrchestratorConfig, OutputKey,
+    PathTemplate, Settings, SettingsOrchestrator, StepLimitsConfig, ThreadResponseMode,
+    ValidationOptions, WorkflowConfig, WorkflowInputs, WorkflowLimitsConfig,
     WorkflowOrchestrationConfig, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType,
     WorkflowStepWorkspaceMode, WorkflowTag,
 };
 use crate::memory::MemoryConfig;
 use crate::prompts::default_prompt_rel_path;
+use crate::provider::ProviderKind;
 use crate::templates::orchestrator_templates::{
     initial_orchestrator_config, WorkflowTemplate as SetupWorkflowTemplate,
 };
@@ -225,6 +226,8 @@ impl SetupDraft {
                         default_run_timeout_seconds: None,
                         default_step_timeout_seconds: None,
                         max_step_timeout_seconds: None,
+                        interrupted_attempt_policy: None,
+                        retry: None,
                     });
             match field {
                 OrchestrationLimitField::MaxTotalIterations => {
@@ -337,6 +340,7 @@ impl SetupDraft {
             tags: vec![WorkflowTag::parse(workflow_id)?],
             inputs: WorkflowInputs::default(),
             limits: None,
+            repository: None,
             steps: vec![WorkflowStepConfig {
                 id: "step_1".to_string(),
                 step_type: WorkflowStepType::AgentTask,
@@ -347,10 +351,19 @@ impl SetupDraft {
                 next: None,
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: default_step_output_contract("agent_task"),
                 output_files: default_step_output_files("agent_task"),
                 final_output_priority: default_step_output_priority("agent_task"),
                 limits: None,
+                retry: None,
             }],
         });
         validate_orchestrator_invariants(cfg)
@@ -436,11 +449,10 @@ impl SetupDraft {
         workflow_id: &str,
         keys: Vec<String>,
     ) -> Result<(), String> {
-        let parsed = WorkflowInputs::parse_keys(keys)?;
         let workflow = self
             .workflow_mut(orchestrator_id, workflow_id)
             .ok_or_else(|| "workflow no longer exists".to_string())?;
-        workflow.inputs = parsed;
+        workflow.inputs = workflow.inputs.with_keys(keys)?;
         Ok(())
     }
 
@@ -528,10 +540,19 @@ impl SetupDraft {
             next: None,
             on_approve: None,
             on_reject: None,
+            branches: Vec::new(),
+            transitions: Vec::new(),
+            workflow: None,
+            inputs: BTreeMap::new(),
+            command: Vec::new(),
+            items: None,
+            sequence: Vec::new(),
+            max_concurrency: None,
             outputs: default_step_output_contract("agent_task"),
             output_files: default_step_output_files("agent_task"),
             final_output_priority: default_step_output_priority("agent_task"),
             limits: None,
+            retry: None,
         });
         validate_orchestrator_invariants(cfg)
     }
@@ -669,7 +690,10 @@ impl SetupDraft {
             WorkflowStepWorkspaceMode::OrchestratorWorkspace => {
                 WorkflowStepWorkspaceMode::RunWorkspace
             }
-            WorkflowStepWorkspaceMode::RunWorkspace => {
+            WorkflowStepWorkspaceMode::RunWorkspace if step.step_type.requires_agent() => {
+                WorkflowStepWorkspaceMode::AgentWorkspace
+            }
+            WorkflowStepWorkspaceMode::RunWorkspace | WorkflowStepWorkspaceMode::AgentWorkspace => {
                 WorkflowStepWorkspaceMode::OrchestratorWorkspace
             }
         };
@@ -740,7 +764,19 @@ impl SetupDraft {
                 missing
             ));
         }
-        step.outputs = outputs;
+        step.outputs = outputs
+            .into_iter()
+            .map(|mut key| {
+                if key.schema.is_none() {
+                    key.schema = step
+                        .outputs
+                        .iter()
+                        .find(|existing| existing.name == key.name)
+                        .and_then(|existing| existing.schema.clone());
+                }
+                key
+            })
+            .collect();
         Ok(())
     }
 
@@ -811,10 +847,12 @@ impl SetupDraft {
         cfg.agents.insert(
             agent_id.to_string(),
             AgentConfig {
-                provider: ConfigProviderKind::parse(&self.provider)
+                provider: ProviderKind::parse(&self.provider)
                     .expect("setup provider remains valid"),
                 model: self.model.clone(),
                 can_orchestrate_workflows: false,
+                endpoint: None,
+                fallbacks: Vec::new(),
             },
         );
         validate_orchestrator_invariants(cfg)
@@ -850,7 +888,7 @@ impl SetupDraft {
         agent_id: &str,
         provider: &str,
     ) -> Result<(), String> {
-        let parsed = ConfigProviderKind::parse(provider)?;
+        let parsed = ProviderKind::parse(provider)?;
         let cfg = self
             .orchestrator_configs
             .get_mut(orchestrator_id)
@@ -859,6 +897,9 @@ impl SetupDraft {
             .agents
             .get_mut(agent_id)
             .ok_or_else(|| "agent no longer exists".to_string())?;
+        if !parsed.uses_endpoint() {
+            agent.endpoint = None;
+        }
         agent.provider = parsed;
         Ok(())
     }
@@ -961,6 +1002,7 @@ impl SetupDraft {
             auth_sync: AuthSyncConfig::default(),
             memory: MemoryConfig::default(),
             local_llm: Default::default(),
+            webhooks: Default::default(),
         });
 
         settings.workspaces_path = self.workspaces_path.clone();
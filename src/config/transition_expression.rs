@@ -0,0 +1,430 @@
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+
+/// Parsed `when` condition of a conditional step transition, e.g.
+/// `outputs.risk == 'high' && outputs.score >= 7`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransitionExpression {
+    root: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, CompareOp, Operand),
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Output(Vec<String>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Dot,
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Op(CompareOp),
+}
+
+impl TransitionExpression {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let tokens = tokenize(raw)?;
+        if tokens.is_empty() {
+            return Err("transition expression must be non-empty".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let root = parser.parse_or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!(
+                "unexpected token {} in transition expression",
+                describe(&parser.tokens[parser.pos])
+            ));
+        }
+        Ok(Self { root })
+    }
+
+    /// Top-level output keys referenced through `outputs.<key>`.
+    pub fn output_keys(&self) -> Vec<String> {
+        let mut keys = Vec::new();
+        collect_output_keys(&self.root, &mut keys);
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Evaluates the expression against a step's parsed outputs. Missing
+    /// outputs resolve to `null`.
+    pub fn evaluate(&self, outputs: &Map<String, Value>) -> bool {
+        eval(&self.root, outputs)
+    }
+}
+
+fn collect_output_keys(expr: &Expr, keys: &mut Vec<String>) {
+    let mut push = |operand: &Operand| {
+        if let Operand::Output(path) = operand {
+            keys.push(path[0].clone());
+        }
+    };
+    match expr {
+        Expr::Or(left, right) | Expr::And(left, right) => {
+            collect_output_keys(left, keys);
+            collect_output_keys(right, keys);
+        }
+        Expr::Not(inner) => collect_output_keys(inner, keys),
+        Expr::Compare(left, _, right) => {
+            push(left);
+            push(right);
+        }
+        Expr::Truthy(operand) => push(operand),
+    }
+}
+
+fn eval(expr: &Expr, outputs: &Map<String, Value>) -> bool {
+    match expr {
+        Expr::Or(left, right) => eval(left, outputs) || eval(right, outputs),
+        Expr::And(left, right) => eval(left, outputs) && eval(right, outputs),
+        Expr::Not(inner) => !eval(inner, outputs),
+        Expr::Truthy(operand) => is_truthy(&resolve(operand, outputs)),
+        Expr::Compare(left, op, right) => {
+            let left = resolve(left, outputs);
+            let right = resolve(right, outputs);
+            match op {
+                CompareOp::Eq => loose_eq(&left, &right),
+                CompareOp::Ne => !loose_eq(&left, &right),
+                CompareOp::Lt => compare(&left, &right) == Some(Ordering::Less),
+                CompareOp::Le => matches!(
+                    compare(&left, &right),
+                    Some(Ordering::Less | Ordering::Equal)
+                ),
+                CompareOp::Gt => compare(&left, &right) == Some(Ordering::Greater),
+                CompareOp::Ge => matches!(
+                    compare(&left, &right),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ),
+            }
+        }
+    }
+}
+
+fn resolve(operand: &Operand, outputs: &Map<String, Value>) -> Value {
+    match operand {
+        Operand::Literal(value) => value.clone(),
+        Operand::Output(path) => {
+            let mut current = outputs.get(&path[0]);
+            for segment in &path[1..] {
+                current = current.and_then(|value| match value {
+                    Value::Array(items) => segment
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|index| items.get(index)),
+                    _ => value.get(segment),
+                });
+            }
+            current.cloned().unwrap_or(Value::Null)
+        }
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(text) => {
+            let text = text.trim();
+            !text.is_empty() && !text.eq_ignore_ascii_case("false") && text != "0"
+        }
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+fn loose_eq(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => a.trim() == b.trim(),
+        (Value::Number(_), _) | (_, Value::Number(_)) => {
+            match (as_number(left), as_number(right)) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            }
+        }
+        (Value::Bool(flag), Value::String(text)) | (Value::String(text), Value::Bool(flag)) => text
+            .trim()
+            .eq_ignore_ascii_case(if *flag { "true" } else { "false" }),
+        _ => left == right,
+    }
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    if let (Some(a), Some(b)) = (as_number(left), as_number(right)) {
+        return a.partial_cmp(&b);
+    }
+    match (left, right) {
+        (Value::String(a), Value::String(b)) => Some(a.trim().cmp(b.trim())),
+        _ => None,
+    }
+}
+
+fn tokenize(raw: &str) -> Result<Vec<Token>, String> {
+    let chars = raw.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let c = chars[idx];
+        let next = chars.get(idx + 1).copied();
+        match c {
+            c if c.is_whitespace() => idx += 1,
+            '.' if in_path(&tokens, &chars, idx) || !next.is_some_and(|n| n.is_ascii_digit()) => {
+                tokens.push(Token::Dot);
+                idx += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                idx += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                idx += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                idx += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                idx += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                idx += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                idx += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                idx += 1;
+            }
+            '<' | '>' => {
+                let inclusive = next == Some('=');
+                tokens.push(Token::Op(match (c, inclusive) {
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    _ => CompareOp::Ge,
+                }));
+                idx += if inclusive { 2 } else { 1 };
+            }
+            '\'' | '"' => {
+                let quote = c;
+                let start = idx + 1;
+                let Some(len) = chars[start..].iter().position(|ch| *ch == quote) else {
+                    return Err("unterminated string literal in transition expression".to_string());
+                };
+                tokens.push(Token::Str(chars[start..start + len].iter().collect()));
+                idx = start + len + 1;
+            }
+            c if c.is_ascii_digit() && tokens.last() == Some(&Token::Dot) => {
+                // A digit segment after `.` in a path is an array index.
+                let start = idx;
+                while idx < chars.len() && chars[idx].is_ascii_digit() {
+                    idx += 1;
+                }
+                tokens.push(Token::Ident(chars[start..idx].iter().collect()));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let start = idx;
+                idx += 1;
+                while idx < chars.len() && (chars[idx].is_ascii_digit() || chars[idx] == '.') {
+                    idx += 1;
+                }
+                let literal = chars[start..idx].iter().collect::<String>();
+                let number = literal
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number `{literal}` in transition expression"))?;
+                tokens.push(Token::Num(number));
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let start = idx;
+                while idx < chars.len()
+                    && (chars[idx].is_ascii_alphanumeric() || matches!(chars[idx], '_' | '-'))
+                {
+                    idx += 1;
+                }
+                let word = chars[start..idx].iter().collect::<String>();
+                tokens.push(match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Ident(word),
+                });
+            }
+            other => {
+                return Err(format!(
+                    "unexpected character `{other}` in transition expression"
+                ))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// Whether the character at `idx` directly follows a path segment, so a `.`
+/// there separates segments rather than starting a number.
+fn in_path(tokens: &[Token], chars: &[char], idx: usize) -> bool {
+    matches!(tokens.last(), Some(Token::Ident(_))) && idx > 0 && !chars[idx - 1].is_whitespace()
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Ident(word) => format!("`{word}`"),
+        Token::Str(text) => format!("'{text}'"),
+        Token::Num(number) => format!("`{number}`"),
+        Token::Dot => "`.`".to_string(),
+        Token::LParen => "`(`".to_string(),
+        Token::RParen => "`)`".to_string(),
+        Token::Not => "`!`".to_string(),
+        Token::And => "`&&`".to_string(),
+        Token::Or => "`||`".to_string(),
+        Token::Op(_) => "comparison operator".to_string(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.pos += 1;
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.pos += 1;
+            left = Expr::And(Box::new(left), Box::new(self.parse_unary()?));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some(Token::Not) => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some(Token::LParen) => {
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                match self.advance() {
+                    Some(Token::RParen) => Ok(inner),
+                    _ => Err("missing `)` in transition expression".to_string()),
+                }
+            }
+            _ => self.parse_comparison(),
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_operand()?;
+        let Some(Token::Op(op)) = self.peek().cloned() else {
+            return Ok(Expr::Truthy(left));
+        };
+        self.pos += 1;
+        let right = self.parse_operand()?;
+        Ok(Expr::Compare(left, op, right))
+    }
+
+    fn parse_operand(&mut self) -> Result<Operand, String> {
+        match self.advance() {
+            Some(Token::Str(text)) => Ok(Operand::Literal(Value::String(text))),
+            Some(Token::Num(number)) => Ok(Operand::Literal(
+                serde_json::Number::from_f64(number)
+                    .map(Value::Number)
+                    .unwrap_or(Value::Null),
+            )),
+            Some(Token::Ident(word)) => match word.as_str() {
+                "true" => Ok(Operand::Literal(Value::Bool(true))),
+                "false" => Ok(Operand::Literal(Value::Bool(false))),
+                "null" => Ok(Operand::Literal(Value::Null)),
+                "outputs" => {
+                    let mut path = Vec::new();
+                    while self.peek() == Some(&Token::Dot) {
+                        self.pos += 1;
+                        match self.advance() {
+                            Some(Token::Ident(segment)) => path.push(segment),
+                            _ => {
+                                return Err(
+                                    "expected output key after `.` in transition expression"
+                                        .to_string(),
+                                )
+                            }
+                        }
+                    }
+                    if path.is_empty() {
+                        return Err(
+                            "`outputs` must be followed by `.<key>` in transition expression"
+                                .to_string(),
+                        );
+                    }
+                    Ok(Operand::Output(path))
+                }
+                other => Err(format!(
+                    "unknown identifier `{other}` in transition expression; reference outputs as `outputs.<key>`"
+                )),
+            },
+            Some(token) => Err(format!(
+                "unexpected token {} in transition expression",
+                describe(&token)
+            )),
+            None => Err("unexpected end of transition expression".to_string()),
+        }
+    }
+}
@@ -4,7 +4,7 @@ use crate::shared::serde_ext::parse_via_string;
 use serde::de::Error as _;
 use serde::ser::Serializer;
 use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 #[serde(transparent)]
@@ -35,9 +35,191 @@ impl<'de> Deserialize<'de> for WorkflowInputKey {
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
-#[serde(transparent)]
-pub struct WorkflowInputs(Vec<WorkflowInputKey>);
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkflowInputType {
+    #[default]
+    String,
+    Integer,
+    Boolean,
+    Enum,
+    Array,
+    Path,
+}
+
+impl WorkflowInputType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Boolean => "boolean",
+            Self::Enum => "enum",
+            Self::Array => "array",
+            Self::Path => "path",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkflowInputSpec {
+    pub key: WorkflowInputKey,
+    #[serde(rename = "type")]
+    pub input_type: WorkflowInputType,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawWorkflowInputSpec {
+    key: String,
+    #[serde(default, rename = "type")]
+    input_type: WorkflowInputType,
+    #[serde(default)]
+    required: bool,
+    #[serde(default)]
+    default: Option<serde_json::Value>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    values: Vec<String>,
+}
+
+impl WorkflowInputSpec {
+    /// Untyped optional string input, the shape of a bare key in `inputs`.
+    pub fn untyped(key: WorkflowInputKey) -> Self {
+        Self {
+            key,
+            input_type: WorkflowInputType::String,
+            required: false,
+            default: None,
+            description: None,
+            values: Vec::new(),
+        }
+    }
+
+    fn from_raw(raw: RawWorkflowInputSpec) -> Result<Self, String> {
+        let key = WorkflowInputKey::parse(&raw.key)?;
+        let values = raw
+            .values
+            .iter()
+            .map(|value| value.trim().to_string())
+            .collect::<Vec<_>>();
+        match raw.input_type {
+            WorkflowInputType::Enum
+                if values.is_empty() || values.iter().any(|value| value.is_empty()) =>
+            {
+                return Err(format!(
+                    "workflow input `{key}` of type enum requires non-empty `values`"
+                ));
+            }
+            WorkflowInputType::Enum => {}
+            _ if !values.is_empty() => {
+                return Err(format!(
+                    "workflow input `{key}` only supports `values` for type enum"
+                ));
+            }
+            _ => {}
+        }
+        let mut spec = Self {
+            key,
+            input_type: raw.input_type,
+            required: raw.required,
+            default: None,
+            description: raw
+                .description
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty()),
+            values,
+        };
+        if let Some(default) = raw.default {
+            spec.default = Some(
+                spec.coerce(&default)
+                    .map_err(|err| format!("invalid default: {err}"))?,
+            );
+        }
+        Ok(spec)
+    }
+
+    fn is_untyped(&self) -> bool {
+        self.input_type == WorkflowInputType::String
+            && !self.required
+            && self.default.is_none()
+            && self.description.is_none()
+    }
+
+    /// Checks a provided value against the declared type, converting the
+    /// string forms accepted from `--input key=value` into typed values.
+    pub fn coerce(&self, value: &serde_json::Value) -> Result<serde_json::Value, String> {
+        use serde_json::Value;
+        let key = &self.key;
+        match self.input_type {
+            WorkflowInputType::String => match value {
+                Value::String(_) => Ok(value.clone()),
+                _ => Err(format!("workflow input `{key}` must be a string")),
+            },
+            WorkflowInputType::Path => match value {
+                Value::String(raw) if !raw.trim().is_empty() && !raw.contains('\0') => {
+                    Ok(Value::String(raw.trim().to_string()))
+                }
+                _ => Err(format!("workflow input `{key}` must be a non-empty path")),
+            },
+            WorkflowInputType::Integer => match value {
+                Value::Number(number) if number.is_i64() || number.is_u64() => Ok(value.clone()),
+                Value::String(raw) => raw
+                    .trim()
+                    .parse::<i64>()
+                    .map(Value::from)
+                    .map_err(|_| format!("workflow input `{key}` must be an integer")),
+                _ => Err(format!("workflow input `{key}` must be an integer")),
+            },
+            WorkflowInputType::Boolean => match value {
+                Value::Bool(_) => Ok(value.clone()),
+                Value::String(raw) => match raw.trim().to_ascii_lowercase().as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ => Err(format!("workflow input `{key}` must be a boolean")),
+                },
+                _ => Err(format!("workflow input `{key}` must be a boolean")),
+            },
+            WorkflowInputType::Enum => match value {
+                Value::String(raw) if self.values.iter().any(|v| v == raw.trim()) => {
+                    Ok(Value::String(raw.trim().to_string()))
+                }
+                _ => Err(format!(
+                    "workflow input `{key}` must be one of: {}",
+                    self.values.join(", ")
+                )),
+            },
+            WorkflowInputType::Array => match value {
+                Value::Array(_) => Ok(value.clone()),
+                Value::String(raw) if raw.trim_start().starts_with('[') => {
+                    match serde_json::from_str::<Value>(raw) {
+                        Ok(parsed @ Value::Array(_)) => Ok(parsed),
+                        _ => Err(format!("workflow input `{key}` must be an array")),
+                    }
+                }
+                Value::String(raw) => Ok(Value::Array(
+                    raw.split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| Value::String(item.to_string()))
+                        .collect(),
+                )),
+                _ => Err(format!("workflow input `{key}` must be an array")),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkflowInputs(Vec<WorkflowInputSpec>);
 
 impl WorkflowInputs {
     pub fn parse_keys<I, S>(keys: I) -> Result<Self, String>
@@ -45,20 +227,104 @@ impl WorkflowInputs {
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
+        let mut specs = Vec::new();
+        for raw in keys {
+            specs.push(WorkflowInputSpec::untyped(WorkflowInputKey::parse(
+                raw.as_ref(),
+            )?));
+        }
+        Ok(Self::from_specs(specs))
+    }
+
+    pub fn from_specs(specs: Vec<WorkflowInputSpec>) -> Self {
         let mut values = Vec::new();
         let mut seen = HashSet::new();
-        for raw in keys {
-            let key = WorkflowInputKey::parse(raw.as_ref())?;
-            if seen.insert(key.as_str().to_string()) {
-                values.push(key);
+        for spec in specs {
+            if seen.insert(spec.key.as_str().to_string()) {
+                values.push(spec);
             }
         }
-        Ok(Self(values))
+        Self(values)
     }
 
-    pub fn as_slice(&self) -> &[WorkflowInputKey] {
+    /// Replaces the key list, keeping existing definitions for retained keys.
+    pub fn with_keys<I, S>(&self, keys: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let parsed = Self::parse_keys(keys)?;
+        Ok(Self(
+            parsed
+                .0
+                .into_iter()
+                .map(|spec| self.get(spec.key.as_str()).cloned().unwrap_or(spec))
+                .collect(),
+        ))
+    }
+
+    pub fn as_slice(&self) -> &[WorkflowInputSpec] {
         &self.0
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&WorkflowInputSpec> {
+        self.0.iter().find(|spec| spec.key.as_str() == key)
+    }
+
+    /// Validates run inputs against the declared inputs: coerces typed
+    /// values, fills defaults, and rejects missing required inputs. Keys
+    /// that are not declared pass through unchanged.
+    pub fn resolve(
+        &self,
+        provided: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+        let mut resolved = provided.clone();
+        let mut errors = Vec::new();
+        for spec in &self.0 {
+            match provided.get(spec.key.as_str()) {
+                Some(value) => match spec.coerce(value) {
+                    Ok(value) => {
+                        resolved.insert(spec.key.as_str().to_string(), value);
+                    }
+                    Err(err) => errors.push(err),
+                },
+                None => {
+                    if let Some(default) = &spec.default {
+                        resolved.insert(spec.key.as_str().to_string(), default.clone());
+                    } else if spec.required {
+                        errors.push(format!("missing required workflow input `{}`", spec.key));
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+impl Serialize for WorkflowInputs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+        for spec in &self.0 {
+            if spec.is_untyped() {
+                seq.serialize_element(&spec.key)?;
+            } else {
+                seq.serialize_element(spec)?;
+            }
+        }
+        seq.end()
+    }
 }
 
 impl<'de> Deserialize<'de> for WorkflowInputs {
@@ -66,23 +332,31 @@ impl<'de> Deserialize<'de> for WorkflowInputs {
     where
         D: Deserializer<'de>,
     {
+        const SHAPE_ERROR: &str =
+            "workflow inputs must be a sequence of string keys or input definitions";
         let value = serde_yaml::Value::deserialize(deserializer)?;
         match value {
             serde_yaml::Value::Null => Ok(Self::default()),
             serde_yaml::Value::String(raw) => Self::parse_keys([raw]).map_err(D::Error::custom),
             serde_yaml::Value::Sequence(values) => {
-                let mut keys = Vec::new();
+                let mut specs = Vec::new();
                 for value in values {
-                    let raw = value.as_str().ok_or_else(|| {
-                        D::Error::custom("workflow inputs must be a sequence of string keys")
-                    })?;
-                    keys.push(raw.to_string());
+                    let spec = match value {
+                        serde_yaml::Value::String(raw) => WorkflowInputKey::parse(&raw)
+                            .map(WorkflowInputSpec::untyped)
+                            .map_err(D::Error::custom)?,
+                        serde_yaml::Value::Mapping(_) => {
+                            let raw: RawWorkflowInputSpec =
+                                serde_yaml::from_value(value).map_err(D::Error::custom)?;
+                            WorkflowInputSpec::from_raw(raw).map_err(D::Error::custom)?
+                        }
+                        _ => return Err(D::Error::custom(SHAPE_ERROR)),
+                    };
+                    specs.push(spec);
                 }
-                Self::parse_keys(keys).map_err(D::Error::custom)
+                Ok(Self::from_specs(specs))
             }
-            _ => Err(D::Error::custom(
-                "workflow inputs must be a sequence of string keys",
-            )),
+            _ => Err(D::Error::custom(SHAPE_ERROR)),
         }
     }
 }
@@ -141,6 +415,7 @@ pub fn normalize_workflow_tag(raw: &str) -> Result<String, String> {
 pub struct OutputKey {
     pub name: String,
     pub required: bool,
+    pub schema: Option<OutputSchema>,
 }
 
 impl OutputKey {
@@ -164,6 +439,7 @@ impl OutputKey {
         Ok(Self {
             name: name.to_string(),
             required,
+            schema: None,
         })
     }
 
@@ -204,16 +480,163 @@ impl Serialize for OutputKey {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        #[derive(Serialize)]
+        struct OutputKeyWithSchema<'a> {
+            key: String,
+            #[serde(flatten)]
+            schema: &'a OutputSchema,
+        }
+
+        match &self.schema {
+            None => serializer.serialize_str(&self.to_string()),
+            Some(schema) => OutputKeyWithSchema {
+                key: self.to_string(),
+                schema,
+            }
+            .serialize(serializer),
+        }
     }
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OutputKeyWithSchemaRaw {
+    key: String,
+    #[serde(rename = "type")]
+    value_type: OutputValueType,
+    #[serde(default)]
+    values: Vec<String>,
+    #[serde(default)]
+    items: Option<Box<OutputSchema>>,
+    #[serde(default)]
+    fields: BTreeMap<String, OutputSchema>,
+}
+
 impl<'de> Deserialize<'de> for OutputKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        parse_via_string(deserializer, "output key", Self::parse)
+        let value = serde_yaml::Value::deserialize(deserializer)?;
+        match value {
+            serde_yaml::Value::Mapping(_) => {
+                let raw: OutputKeyWithSchemaRaw =
+                    serde_yaml::from_value(value).map_err(D::Error::custom)?;
+                let mut key = Self::parse(&raw.key).map_err(|err| {
+                    D::Error::custom(format!("invalid output key `{}`: {err}", raw.key))
+                })?;
+                key.schema = Some(
+                    OutputSchema::from_parts(raw.value_type, raw.values, raw.items, raw.fields)
+                        .map_err(|err| {
+                            D::Error::custom(format!("invalid schema for output `{}`: {err}", key))
+                        })?,
+                );
+                Ok(key)
+            }
+            other => parse_via_string(other, "output key", Self::parse)
+                .map_err(|err: serde_yaml::Error| D::Error::custom(err)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputValueType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Enum,
+    Array,
+    Object,
+}
+
+impl OutputValueType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::String => "string",
+            Self::Integer => "integer",
+            Self::Number => "number",
+            Self::Boolean => "boolean",
+            Self::Enum => "enum",
+            Self::Array => "array",
+            Self::Object => "object",
+        }
+    }
+}
+
+/// Shape an output value must have. Object `fields` use the same trailing
+/// `?` optional marker as `outputs` keys.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct OutputSchema {
+    #[serde(rename = "type")]
+    pub value_type: OutputValueType,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<OutputSchema>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, OutputSchema>,
+}
+
+impl OutputSchema {
+    pub fn from_parts(
+        value_type: OutputValueType,
+        values: Vec<String>,
+        items: Option<Box<OutputSchema>>,
+        fields: BTreeMap<String, OutputSchema>,
+    ) -> Result<Self, String> {
+        let values = values
+            .iter()
+            .map(|value| value.trim().to_string())
+            .collect::<Vec<_>>();
+        if value_type == OutputValueType::Enum
+            && (values.is_empty() || values.iter().any(|value| value.is_empty()))
+        {
+            return Err("type enum requires non-empty `values`".to_string());
+        }
+        if value_type != OutputValueType::Enum && !values.is_empty() {
+            return Err("`values` is only supported for type enum".to_string());
+        }
+        if value_type != OutputValueType::Array && items.is_some() {
+            return Err("`items` is only supported for type array".to_string());
+        }
+        if value_type != OutputValueType::Object && !fields.is_empty() {
+            return Err("`fields` is only supported for type object".to_string());
+        }
+        for field in fields.keys() {
+            OutputKey::parse(field).map_err(|err| format!("invalid field `{field}`: {err}"))?;
+        }
+        Ok(Self {
+            value_type,
+            values,
+            items,
+            fields,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for OutputSchema {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct OutputSchemaRaw {
+            #[serde(rename = "type")]
+            value_type: OutputValueType,
+            #[serde(default)]
+            values: Vec<String>,
+            #[serde(default)]
+            items: Option<Box<OutputSchema>>,
+            #[serde(default)]
+            fields: BTreeMap<String, OutputSchema>,
+        }
+
+        let raw = OutputSchemaRaw::deserialize(deserializer)?;
+        Self::from_parts(raw.value_type, raw.values, raw.items, raw.fields)
+            .map_err(D::Error::custom)
     }
 }
 
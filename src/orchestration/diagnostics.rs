@@ -1,8 +1,9 @@
-use crate::provider::{InvocationLog, ProviderError};
+use crate::orchestration::run_store::StepUsage;
+use crate::provider::{InvocationLog, ProviderError, ProviderUsage};
 use crate::shared::logging::append_orchestrator_log_line;
 use serde_json::{Map, Value};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn append_security_log(state_root: &Path, line: &str) {
@@ -14,9 +15,12 @@ pub fn provider_error_log(error: &ProviderError) -> Option<&InvocationLog> {
         ProviderError::MissingBinary { log, .. } => Some(log),
         ProviderError::NonZeroExit { log, .. } => Some(log),
         ProviderError::Timeout { log, .. } => Some(log),
+        ProviderError::Canceled { log, .. } => Some(log),
         ProviderError::ParseFailure { log, .. } => log.as_deref(),
         ProviderError::UnknownProvider(_)
         | ProviderError::UnsupportedAnthropicModel(_)
+        | ProviderError::MissingEndpoint { .. }
+        | ProviderError::MissingApiKey { .. }
         | ProviderError::Io { .. } => None,
     }
 }
@@ -62,6 +66,108 @@ pub fn persist_provider_invocation_log(
             },
         ),
         ("timedOut".to_string(), Value::Bool(log.timed_out)),
+        (
+            "usage".to_string(),
+            match &log.usage {
+                Some(usage) => serde_json::to_value(usage).map_err(std::io::Error::other)?,
+                None => Value::Null,
+            },
+        ),
+        (
+            "sessionId".to_string(),
+            match &log.session_id {
+                Some(session_id) => Value::String(session_id.clone()),
+                None => Value::Null,
+            },
+        ),
+        (
+            "priorAttempts".to_string(),
+            Value::Array(
+                log.prior_attempts
+                    .iter()
+                    .map(|attempt| {
+                        Value::Object(Map::from_iter([
+                            (
+                                "provider".to_string(),
+                                Value::String(attempt.provider.to_string()),
+                            ),
+                            ("model".to_string(), Value::String(attempt.model.clone())),
+                            ("error".to_string(), Value::String(attempt.error.clone())),
+                        ]))
+                    })
+                    .collect(),
+            ),
+        ),
+    ]));
+    let body = serde_json::to_vec_pretty(&payload).map_err(std::io::Error::other)?;
+    fs::write(path, body)
+}
+
+/// Reads the provider usage recorded in `provider_invocation.json` under
+/// `path_root`, attributed to the invoking agent. `None` when no invocation
+/// was logged or the provider reported no usage.
+pub fn load_provider_usage(path_root: &Path) -> Option<StepUsage> {
+    let raw = fs::read_to_string(path_root.join("provider_invocation.json")).ok()?;
+    let payload: Value = serde_json::from_str(&raw).ok()?;
+    let tokens: ProviderUsage = serde_json::from_value(payload.get("usage")?.clone()).ok()?;
+    let field = |key: &str| {
+        payload
+            .get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    Some(StepUsage {
+        agent_id: field("agentId"),
+        provider: field("provider"),
+        model: field("model"),
+        tokens,
+    })
+}
+
+/// Diagnostics for one `command` step process, persisted as
+/// `command_invocation.json` next to the step attempt result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandInvocationLog {
+    pub argv: Vec<String>,
+    pub working_directory: PathBuf,
+    pub timeout_ms: u64,
+    pub duration_ms: u64,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+}
+
+impl CommandInvocationLog {
+    pub fn command_form(&self) -> String {
+        self.argv.join(" ")
+    }
+}
+
+pub fn persist_command_invocation_log(
+    path_root: &Path,
+    log: &CommandInvocationLog,
+) -> std::io::Result<()> {
+    let path = path_root.join("command_invocation.json");
+    let payload = Value::Object(Map::from_iter([
+        (
+            "argv".to_string(),
+            Value::Array(log.argv.iter().cloned().map(Value::String).collect()),
+        ),
+        ("commandForm".to_string(), Value::String(log.command_form())),
+        (
+            "workingDirectory".to_string(),
+            Value::String(log.working_directory.display().to_string()),
+        ),
+        ("timeoutMs".to_string(), Value::from(log.timeout_ms)),
+        ("durationMs".to_string(), Value::from(log.duration_ms)),
+        (
+            "exitCode".to_string(),
+            match log.exit_code {
+                Some(value) => Value::from(value),
+                None => Value::Null,
+            },
+        ),
+        ("timedOut".to_string(), Value::Bool(log.timed_out)),
     ]));
     let body = serde_json::to_vec_pretty(&payload).map_err(std::io::Error::other)?;
     fs::write(path, body)
@@ -1,5 +1,6 @@
 use crate::config::ConfigError;
 use crate::orchestration::run_store::RunState;
+use std::collections::BTreeMap;
 
 #[derive(Debug, thiserror::Error)]
 pub enum OrchestratorError {
@@ -23,8 +24,16 @@ pub enum OrchestratorError {
         arg: String,
         expected: String,
     },
+    #[error("workflow `{workflow_id}` inputs are invalid: {reason}")]
+    WorkflowInputValidation { workflow_id: String, reason: String },
+    #[error("cannot rerun workflow run `{run_id}`: {reason}")]
+    RunRerun { run_id: String, reason: String },
+    #[error("simulation script is invalid: {0}")]
+    SimulationScript(String),
     #[error("workflow run `{run_id}` not found")]
     UnknownRunId { run_id: String },
+    #[error("workflow run `{run_id}` has no pending human review")]
+    NoPendingHumanReview { run_id: String },
     #[error("workflow run state transition `{from}` -> `{to}` is invalid")]
     InvalidRunTransition { from: RunState, to: RunState },
     #[error("workflow result envelope parse failed: {0}")]
@@ -35,12 +44,22 @@ pub enum OrchestratorError {
     StepPromptRender { step_id: String, reason: String },
     #[error("step execution failed for step `{step_id}`: {reason}")]
     StepExecution { step_id: String, reason: String },
+    /// A provider or command process exited non-zero. Displays like
+    /// `StepExecution` but is classified separately for retry budgets.
+    #[error("step execution failed for step `{step_id}`: {reason}")]
+    StepNonZeroExit {
+        step_id: String,
+        reason: String,
+        rate_limited: bool,
+    },
     #[error("workflow execution exceeded max total iterations ({max_total_iterations})")]
     MaxIterationsExceeded { max_total_iterations: u32 },
     #[error("workflow run timed out after {run_timeout_seconds}s")]
     RunTimeout { run_timeout_seconds: u64 },
     #[error("workflow step timed out after {step_timeout_seconds}s")]
     StepTimeout { step_timeout_seconds: u64 },
+    #[error("step `{step_id}` attempt {attempt} canceled")]
+    StepCanceled { step_id: String, attempt: u32 },
     #[error("workspace access denied for orchestrator `{orchestrator_id}` at path `{path}`")]
     WorkspaceAccessDenied {
         orchestrator_id: String,
@@ -56,6 +75,12 @@ pub enum OrchestratorError {
     },
     #[error("step `{step_id}` output contract validation failed: {reason}")]
     OutputContractValidation { step_id: String, reason: String },
+    #[error("step `{step_id}` output schema validation failed: {reason}")]
+    OutputSchemaValidation {
+        step_id: String,
+        reason: String,
+        violations: BTreeMap<String, String>,
+    },
     #[error("step `{step_id}` transition validation failed: {reason}")]
     TransitionValidation { step_id: String, reason: String },
     #[error(
@@ -66,6 +91,8 @@ pub enum OrchestratorError {
         execution_id: String,
         reason: String,
     },
+    #[error("run worktree failed for run `{run_id}`: {reason}")]
+    RunWorktree { run_id: String, reason: String },
     #[error("config error: {0}")]
     Config(String),
     #[error("io error at {path}: {source}")]
@@ -156,15 +156,14 @@ impl FunctionRegistry {
         }
         for (arg, arg_schema) in &schema.args {
             match call.args.get(arg) {
-                Some(value) => {
-                    if !arg_schema.arg_type.matches(value) {
-                        return Err(OrchestratorError::InvalidFunctionArgType {
-                            function_id: call.function_id.clone(),
-                            arg: arg.clone(),
-                            expected: arg_schema.arg_type.to_string(),
-                        });
-                    }
+                Some(value) if !arg_schema.arg_type.matches(value) => {
+                    return Err(OrchestratorError::InvalidFunctionArgType {
+                        function_id: call.function_id.clone(),
+                        arg: arg.clone(),
+                        expected: arg_schema.arg_type.to_string(),
+                    });
                 }
+                Some(_) => {}
                 None if arg_schema.required => {
                     return Err(OrchestratorError::MissingFunctionArg { arg: arg.clone() });
                 }
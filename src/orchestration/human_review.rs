@@ -0,0 +1,299 @@
+use crate::config::WorkflowStepConfig;
+use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::run_store::{RunState, WorkflowRunStore};
+use crate::shared::fs_atomic::atomic_create_file;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+pub const HUMAN_REVIEW_REQUEST_FILE: &str = "human_review_request.json";
+pub const HUMAN_REVIEW_DECISION_FILE: &str = "human_review_decision.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HumanReviewRequest {
+    pub run_id: String,
+    pub step_id: String,
+    pub attempt: u32,
+    pub requested_at: i64,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HumanReviewDecision {
+    pub run_id: String,
+    pub step_id: String,
+    pub attempt: u32,
+    pub approved: bool,
+    #[serde(default)]
+    pub comment: Option<String>,
+    #[serde(default)]
+    pub decided_by: Option<String>,
+    pub decided_at: i64,
+}
+
+impl HumanReviewDecision {
+    pub fn decision(&self) -> &'static str {
+        if self.approved {
+            "approve"
+        } else {
+            "reject"
+        }
+    }
+
+    pub(crate) fn step_outputs(&self, step: &WorkflowStepConfig) -> Map<String, Value> {
+        let comment = self
+            .comment
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty());
+        let verdict = if self.approved {
+            "approved"
+        } else {
+            "rejected"
+        };
+        let reviewer = self.decided_by.as_deref().unwrap_or("human reviewer");
+
+        let mut outputs = Map::new();
+        outputs.insert(
+            "decision".to_string(),
+            Value::String(self.decision().to_string()),
+        );
+        for key in &step.outputs {
+            let value = match key.as_str() {
+                "summary" => match comment {
+                    Some(comment) => format!("{verdict} by {reviewer}: {comment}"),
+                    None => format!("{verdict} by {reviewer}"),
+                },
+                "feedback" => comment.unwrap_or("no feedback provided").to_string(),
+                "reviewer" => reviewer.to_string(),
+                _ => continue,
+            };
+            outputs.insert(key.name.clone(), Value::String(value));
+        }
+        outputs
+    }
+}
+
+/// Parses a conversation reply such as `approve`, `lgtm`, or `reject: add tests`.
+pub fn parse_human_review_reply(message: &str) -> Option<(bool, Option<String>)> {
+    let trimmed = message.trim();
+    let split_at = trimmed
+        .find(|c: char| c.is_whitespace() || c == ':' || c == ',')
+        .unwrap_or(trimmed.len());
+    let (head, rest) = trimmed.split_at(split_at);
+    let head = head
+        .trim_start_matches('/')
+        .trim_end_matches(['.', '!'])
+        .to_ascii_lowercase();
+    let approved = match head.as_str() {
+        "approve" | "approved" | "lgtm" => true,
+        "reject" | "rejected" => false,
+        _ => return None,
+    };
+    let comment = rest
+        .trim_start_matches(|c: char| c.is_whitespace() || c == ':' || c == ',' || c == '-')
+        .trim();
+    Some((approved, (!comment.is_empty()).then(|| comment.to_string())))
+}
+
+pub fn load_pending_human_review(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+) -> Result<Option<HumanReviewRequest>, OrchestratorError> {
+    let run = run_store.load_run(run_id)?;
+    if run.state != RunState::Waiting {
+        return Ok(None);
+    }
+    let (Some(step_id), Some(attempt)) = (run.current_step_id.as_deref(), run.current_attempt)
+    else {
+        return Ok(None);
+    };
+    if load_human_review_decision(run_store, run_id, step_id, attempt)?.is_some() {
+        return Ok(None);
+    }
+    load_human_review_request(run_store, run_id, step_id, attempt)
+}
+
+/// Finds the run holding the pending human review for `run_id`, descending
+/// into child runs started by `workflow_call` steps (most recent first).
+pub fn find_pending_human_review_run(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+) -> Result<Option<String>, OrchestratorError> {
+    if load_pending_human_review(run_store, run_id)?.is_some() {
+        return Ok(Some(run_id.to_string()));
+    }
+    let run = run_store.load_run(run_id)?;
+    if run.state != RunState::Waiting {
+        return Ok(None);
+    }
+    for child_run_id in run.child_run_ids.iter().rev() {
+        if !run_store.run_exists(child_run_id) {
+            continue;
+        }
+        if let Some(found) = find_pending_human_review_run(run_store, child_run_id)? {
+            return Ok(Some(found));
+        }
+    }
+    Ok(None)
+}
+
+/// Records the reviewer decision for the pending review of `run_id`. The
+/// decision file is created exclusively, so when two submissions race only
+/// the first is recorded and the other fails with `NoPendingHumanReview`.
+pub fn record_human_review_decision(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+    approved: bool,
+    comment: Option<String>,
+    decided_by: Option<String>,
+    now: i64,
+) -> Result<HumanReviewDecision, OrchestratorError> {
+    let request = load_pending_human_review(run_store, run_id)?.ok_or_else(|| {
+        OrchestratorError::NoPendingHumanReview {
+            run_id: run_id.to_string(),
+        }
+    })?;
+    let decision = HumanReviewDecision {
+        run_id: request.run_id,
+        step_id: request.step_id,
+        attempt: request.attempt,
+        approved,
+        comment,
+        decided_by,
+        decided_at: now,
+    };
+    let path = run_store
+        .step_attempt_dir(run_id, &decision.step_id, decision.attempt)
+        .join(HUMAN_REVIEW_DECISION_FILE);
+    if !create_json(&path, &decision)? {
+        return Err(OrchestratorError::NoPendingHumanReview {
+            run_id: run_id.to_string(),
+        });
+    }
+    run_store.append_engine_log(
+        run_id,
+        now,
+        format!(
+            "run_id={run_id} step_id={} attempt={} human_review={}",
+            decision.step_id,
+            decision.attempt,
+            decision.decision()
+        ),
+    )?;
+    Ok(decision)
+}
+
+pub(crate) fn load_human_review_request(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+    step_id: &str,
+    attempt: u32,
+) -> Result<Option<HumanReviewRequest>, OrchestratorError> {
+    read_optional_json(
+        &run_store
+            .step_attempt_dir(run_id, step_id, attempt)
+            .join(HUMAN_REVIEW_REQUEST_FILE),
+    )
+}
+
+pub(crate) fn load_human_review_decision(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+    step_id: &str,
+    attempt: u32,
+) -> Result<Option<HumanReviewDecision>, OrchestratorError> {
+    read_optional_json(
+        &run_store
+            .step_attempt_dir(run_id, step_id, attempt)
+            .join(HUMAN_REVIEW_DECISION_FILE),
+    )
+}
+
+pub(crate) fn persist_human_review_request(
+    run_store: &WorkflowRunStore,
+    request: &HumanReviewRequest,
+) -> Result<(), OrchestratorError> {
+    write_json(
+        &run_store
+            .step_attempt_dir(&request.run_id, &request.step_id, request.attempt)
+            .join(HUMAN_REVIEW_REQUEST_FILE),
+        request,
+    )
+}
+
+/// Removes review artifacts left by an earlier visit of the same step attempt,
+/// e.g. when a reject loops back through a human review gate. The stale
+/// `result.json` is removed too so a parked run resumes at the gate instead of
+/// following the previous decision.
+pub(crate) fn clear_human_review_artifacts(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+    step_id: &str,
+    attempt: u32,
+) -> Result<(), OrchestratorError> {
+    let attempt_dir = run_store.step_attempt_dir(run_id, step_id, attempt);
+    for file in [
+        HUMAN_REVIEW_REQUEST_FILE,
+        HUMAN_REVIEW_DECISION_FILE,
+        "result.json",
+    ] {
+        let path = attempt_dir.join(file);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => return Err(io_error(&path, err)),
+        }
+    }
+    Ok(())
+}
+
+fn read_optional_json<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, OrchestratorError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_error(path, err)),
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|source| OrchestratorError::Json {
+            path: path.display().to_string(),
+            source,
+        })
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), OrchestratorError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| io_error(parent, err))?;
+    }
+    let body = serde_json::to_vec_pretty(value).map_err(|source| OrchestratorError::Json {
+        path: path.display().to_string(),
+        source,
+    })?;
+    fs::write(path, body).map_err(|err| io_error(path, err))
+}
+
+/// Writes `value` to a new file at `path`; returns `false` without writing
+/// when the file already exists.
+fn create_json<T: Serialize>(path: &Path, value: &T) -> Result<bool, OrchestratorError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| io_error(parent, err))?;
+    }
+    let body = serde_json::to_vec_pretty(value).map_err(|source| OrchestratorError::Json {
+        path: path.display().to_string(),
+        source,
+    })?;
+    atomic_create_file(path, &body).map_err(|err| io_error(path, err))
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> OrchestratorError {
+    OrchestratorError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
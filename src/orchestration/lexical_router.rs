@@ -164,6 +164,7 @@ pub fn resolve_lexical_decision(
             selected_workflow,
             function_id,
             function_args,
+            workflow_inputs: None,
             reason: Some(reason),
         },
         confidence,
@@ -2,20 +2,27 @@ pub mod conversation_context;
 pub mod diagnostics;
 pub mod error;
 pub mod function_registry;
+pub mod human_review;
 pub mod lexical_router;
 pub mod output_contract;
 pub mod progress;
 pub mod prompt_render;
+pub mod retry_policy;
 pub mod routing;
+pub mod run_events;
 pub mod run_store;
+pub mod run_worktree;
 pub mod scheduler;
 pub mod selector;
 pub mod selector_artifacts;
 pub mod shared_mounts;
+pub mod simulation;
 pub mod skills_mounts;
 pub mod slack_target;
 pub mod step_execution;
 pub mod transitions;
+pub mod usage;
+pub mod workflow_graph;
 pub mod workspace_access;
 
 pub mod workflow_engine;
@@ -1,5 +1,6 @@
 use crate::config::{
-    OutputContractKey, WorkflowConfig, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType,
+    OutputContractKey, OutputKey, OutputSchema, OutputValueType, TransitionExpression,
+    WorkflowConfig, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepTransition,
 };
 use crate::orchestration::error::OrchestratorError;
 use crate::orchestration::workspace_access::normalize_absolute_path;
@@ -134,8 +135,134 @@ fn validate_outputs_contract(
     })
 }
 
+fn validate_output_schemas(
+    step: &WorkflowStepConfig,
+    outputs: &mut Map<String, Value>,
+) -> Result<(), OrchestratorError> {
+    let mut violations = BTreeMap::new();
+    for key in &step.outputs {
+        let Some(schema) = &key.schema else {
+            continue;
+        };
+        if let Some(value) = outputs.get_mut(&key.name) {
+            check_output_value(&key.name, schema, value, &mut violations);
+        }
+    }
+    if violations.is_empty() {
+        return Ok(());
+    }
+    let reason = violations
+        .iter()
+        .map(|(path, detail)| format!("{path}: {detail}"))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(OrchestratorError::OutputSchemaValidation {
+        step_id: step.id.clone(),
+        reason,
+        violations,
+    })
+}
+
+fn check_output_value(
+    path: &str,
+    schema: &OutputSchema,
+    value: &mut Value,
+    violations: &mut BTreeMap<String, String>,
+) {
+    // File-backed outputs and loosely formatted envelopes may carry JSON
+    // values as text; accept them when the decoded value has the right kind.
+    if let Value::String(raw) = value {
+        if !matches!(
+            schema.value_type,
+            OutputValueType::String | OutputValueType::Enum
+        ) {
+            if let Ok(decoded) = serde_json::from_str::<Value>(raw.trim()) {
+                if value_kind_matches(schema.value_type, &decoded) {
+                    *value = decoded;
+                }
+            }
+        }
+    }
+
+    if !value_kind_matches(schema.value_type, value) {
+        violations.insert(
+            path.to_string(),
+            format!(
+                "expected {}, got {}",
+                schema.value_type.as_str(),
+                json_kind(value)
+            ),
+        );
+        return;
+    }
+
+    match schema.value_type {
+        OutputValueType::Enum => {
+            let raw = value.as_str().unwrap_or_default().trim();
+            if !schema.values.iter().any(|allowed| allowed == raw) {
+                violations.insert(
+                    path.to_string(),
+                    format!(
+                        "expected one of [{}], got `{raw}`",
+                        schema.values.join(", ")
+                    ),
+                );
+            }
+        }
+        OutputValueType::Array => {
+            if let (Some(items), Value::Array(entries)) = (&schema.items, value) {
+                for (index, entry) in entries.iter_mut().enumerate() {
+                    check_output_value(&format!("{path}[{index}]"), items, entry, violations);
+                }
+            }
+        }
+        OutputValueType::Object => {
+            let Value::Object(object) = value else {
+                return;
+            };
+            for (raw_field, field_schema) in &schema.fields {
+                let Ok(field) = OutputKey::parse(raw_field) else {
+                    continue;
+                };
+                let field_path = format!("{path}.{}", field.name);
+                match object.get_mut(&field.name) {
+                    Some(entry) => check_output_value(&field_path, field_schema, entry, violations),
+                    None if field.required => {
+                        violations.insert(field_path, "missing".to_string());
+                    }
+                    None => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn value_kind_matches(value_type: OutputValueType, value: &Value) -> bool {
+    match value_type {
+        OutputValueType::String | OutputValueType::Enum => value.is_string(),
+        OutputValueType::Integer => value.is_i64() || value.is_u64(),
+        OutputValueType::Number => value.is_number(),
+        OutputValueType::Boolean => value.is_boolean(),
+        OutputValueType::Array => value.is_array(),
+        OutputValueType::Object => value.is_object(),
+    }
+}
+
+fn json_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
 pub(crate) fn output_validation_errors_for(error: &OrchestratorError) -> BTreeMap<String, String> {
     match error {
+        OrchestratorError::OutputSchemaValidation { violations, .. } => violations.clone(),
         OrchestratorError::OutputContractValidation { reason, .. } => {
             let details = reason
                 .trim()
@@ -241,8 +368,17 @@ pub fn evaluate_step_result(
         }
         WorkflowStepPromptType::FileOutput => load_outputs_from_files(step, output_paths)?,
     };
+    evaluate_step_outputs(workflow, step, parsed)
+}
+
+pub fn evaluate_step_outputs(
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    mut parsed: Map<String, Value>,
+) -> Result<StepEvaluation, OrchestratorError> {
     validate_outputs_contract(step, &parsed)?;
-    if step.step_type == WorkflowStepType::AgentReview {
+    validate_output_schemas(step, &mut parsed)?;
+    if step.routes_on_decision() {
         let approve = parse_review_decision(&parsed)?;
         let next = if approve {
             step.on_approve.clone()
@@ -267,6 +403,17 @@ pub fn evaluate_step_result(
         });
     }
 
+    if !step.transitions.is_empty() {
+        let next = resolve_conditional_transition(step, &parsed)?;
+        let next =
+            validate_transition_target(workflow, step, Some(next), "conditional transition")?;
+        return Ok(StepEvaluation {
+            outputs: parsed,
+            output_files: BTreeMap::new(),
+            next_step_id: next,
+        });
+    }
+
     let next = step
         .next
         .clone()
@@ -280,6 +427,34 @@ pub fn evaluate_step_result(
     })
 }
 
+/// Picks the first `transitions` entry whose `when` holds for the parsed
+/// outputs, falling back to the `default` entry.
+pub fn resolve_conditional_transition(
+    step: &WorkflowStepConfig,
+    outputs: &Map<String, Value>,
+) -> Result<String, OrchestratorError> {
+    for transition in &step.transitions {
+        match transition {
+            WorkflowStepTransition::When { when, next } => {
+                let expression = TransitionExpression::parse(when).map_err(|err| {
+                    OrchestratorError::TransitionValidation {
+                        step_id: step.id.clone(),
+                        reason: format!("malformed transition `when: {when}`: {err}"),
+                    }
+                })?;
+                if expression.evaluate(outputs) {
+                    return Ok(next.clone());
+                }
+            }
+            WorkflowStepTransition::Default { default } => return Ok(default.clone()),
+        }
+    }
+    Err(OrchestratorError::TransitionValidation {
+        step_id: step.id.clone(),
+        reason: "no conditional transition matched and no `default` is declared".to_string(),
+    })
+}
+
 fn load_outputs_from_files(
     step: &WorkflowStepConfig,
     output_paths: &BTreeMap<String, PathBuf>,
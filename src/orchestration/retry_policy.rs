@@ -0,0 +1,145 @@
+use crate::config::{OrchestratorConfig, RetryErrorClass, RetryPolicyConfig, WorkflowStepConfig};
+use crate::orchestration::error::OrchestratorError;
+use getrandom::getrandom;
+use std::collections::{BTreeMap, BTreeSet};
+
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const DEFAULT_BACKOFF_MULTIPLIER: u32 = 2;
+const DEFAULT_MAX_BACKOFF_SECONDS: u64 = 300;
+
+/// Retry policy of one step after merging its `retry` block over the
+/// orchestrator-level `workflow_orchestration.retry` block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepRetryPolicy {
+    pub default_max_retries: u32,
+    pub max_retries: BTreeMap<RetryErrorClass, u32>,
+    pub non_retryable: BTreeSet<RetryErrorClass>,
+    pub initial_backoff_seconds: u64,
+    pub max_backoff_seconds: u64,
+    pub backoff_multiplier: u32,
+    pub jitter_percent: u32,
+}
+
+impl StepRetryPolicy {
+    /// Retries allowed for failures of `class` within one visit of the step.
+    pub fn budget(&self, class: RetryErrorClass) -> u32 {
+        if self.non_retryable.contains(&class) {
+            return 0;
+        }
+        self.max_retries
+            .get(&class)
+            .copied()
+            .unwrap_or(self.default_max_retries)
+    }
+
+    /// Most retries any mix of error classes can use; bounds the number of
+    /// attempts of one step visit.
+    pub fn retry_ceiling(&self) -> u32 {
+        RetryErrorClass::ALL
+            .iter()
+            .map(|class| self.budget(*class))
+            .max()
+            .unwrap_or(0)
+            .max(self.default_max_retries)
+    }
+
+    /// Whether an attempt that failed with `class` may be retried, given how
+    /// many earlier attempts of the same visit already failed with it.
+    pub fn allows_retry(&self, class: RetryErrorClass, prior_failures: u32, attempt: u32) -> bool {
+        prior_failures < self.budget(class) && attempt <= self.retry_ceiling()
+    }
+
+    /// Delay before retrying after failed attempt `attempt`, before jitter:
+    /// `initial * multiplier^(attempt - 1)`, capped at `max_backoff_seconds`.
+    pub fn backoff_seconds(&self, attempt: u32) -> u64 {
+        let mut delay = self.initial_backoff_seconds;
+        for _ in 1..attempt {
+            if delay >= self.max_backoff_seconds {
+                break;
+            }
+            delay = delay.saturating_mul(u64::from(self.backoff_multiplier));
+        }
+        delay.min(self.max_backoff_seconds)
+    }
+
+    /// `backoff_seconds` with up to `jitter_percent` of it randomly removed.
+    pub fn jittered_backoff_seconds(&self, attempt: u32) -> u64 {
+        let delay = self.backoff_seconds(attempt);
+        let spread = delay.saturating_mul(u64::from(self.jitter_percent)) / 100;
+        if spread == 0 {
+            return delay;
+        }
+        let mut bytes = [0u8; 8];
+        if getrandom(&mut bytes).is_err() {
+            return delay;
+        }
+        delay - u64::from_le_bytes(bytes) % (spread + 1)
+    }
+}
+
+pub fn resolve_step_retry_policy(
+    orchestrator: &OrchestratorConfig,
+    step: &WorkflowStepConfig,
+) -> StepRetryPolicy {
+    let default_retry = RetryPolicyConfig::default();
+    let base = orchestrator
+        .workflow_orchestration
+        .as_ref()
+        .and_then(|value| value.retry.as_ref())
+        .unwrap_or(&default_retry);
+    let overlay = step.retry.as_ref().unwrap_or(&default_retry);
+
+    let mut max_retries = base.max_retries.clone();
+    max_retries.extend(overlay.max_retries.clone());
+    let non_retryable = overlay
+        .non_retryable
+        .as_ref()
+        .or(base.non_retryable.as_ref())
+        .map(|classes| classes.iter().copied().collect())
+        .unwrap_or_default();
+    StepRetryPolicy {
+        default_max_retries: step
+            .limits
+            .as_ref()
+            .and_then(|value| value.max_retries)
+            .unwrap_or(DEFAULT_MAX_RETRIES),
+        max_retries,
+        non_retryable,
+        initial_backoff_seconds: overlay
+            .initial_backoff_seconds
+            .or(base.initial_backoff_seconds)
+            .unwrap_or(0),
+        max_backoff_seconds: overlay
+            .max_backoff_seconds
+            .or(base.max_backoff_seconds)
+            .unwrap_or(DEFAULT_MAX_BACKOFF_SECONDS),
+        backoff_multiplier: overlay
+            .backoff_multiplier
+            .or(base.backoff_multiplier)
+            .unwrap_or(DEFAULT_BACKOFF_MULTIPLIER)
+            .max(1),
+        jitter_percent: overlay
+            .jitter_percent
+            .or(base.jitter_percent)
+            .unwrap_or(0)
+            .min(100),
+    }
+}
+
+/// Retry class of a step attempt failure, or `None` when the failure is
+/// never retried (cancellation, workspace access, configuration errors).
+pub fn classify_step_error(error: &OrchestratorError) -> Option<RetryErrorClass> {
+    match error {
+        OrchestratorError::StepTimeout { .. } => Some(RetryErrorClass::Timeout),
+        OrchestratorError::StepNonZeroExit {
+            rate_limited: true, ..
+        } => Some(RetryErrorClass::RateLimit),
+        OrchestratorError::StepNonZeroExit { .. } => Some(RetryErrorClass::NonZeroExit),
+        OrchestratorError::WorkflowEnvelope(_)
+        | OrchestratorError::InvalidReviewDecision(_)
+        | OrchestratorError::OutputContractValidation { .. }
+        | OrchestratorError::OutputSchemaValidation { .. } => Some(RetryErrorClass::OutputContract),
+        OrchestratorError::StepExecution { .. } => Some(RetryErrorClass::Execution),
+        _ => None,
+    }
+}
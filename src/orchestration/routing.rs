@@ -14,14 +14,15 @@ use crate::orchestration::conversation_context::{
 use crate::orchestration::diagnostics::append_security_log;
 use crate::orchestration::error::OrchestratorError;
 pub use crate::orchestration::function_registry::{FunctionCall, FunctionRegistry};
-use crate::orchestration::run_store::WorkflowRunStore;
+use crate::orchestration::human_review::{find_pending_human_review_run, parse_human_review_reply};
+use crate::orchestration::run_store::{RunState, WorkflowRunStore};
 use crate::orchestration::scheduler::{
     complete_scheduled_execution, parse_trigger_envelope, ScheduledTriggerEnvelope,
 };
 use crate::orchestration::selector::{
-    parse_and_validate_selector_result, resolve_orchestrator_id, resolve_selector_with_retries,
-    run_selector_attempt_with_provider, SelectionResolution, SelectorAction, SelectorRequest,
-    SelectorResult, SelectorStatus,
+    available_workflow_inputs, parse_and_validate_selector_result, resolve_orchestrator_id,
+    resolve_selector_with_retries, run_selector_attempt_with_provider, SelectionResolution,
+    SelectorAction, SelectorRequest, SelectorResult, SelectorStatus,
 };
 use crate::orchestration::selector_artifacts::SelectorArtifactStore;
 use crate::orchestration::slack_target::{parse_slack_target_ref, validate_profile_mapping};
@@ -308,6 +309,7 @@ where
             selected_workflow: None,
             function_id: None,
             function_args: None,
+            workflow_inputs: None,
             reason: Some("selector_retry_exhausted_no_response".to_string()),
         },
         retries_used: OPPORTUNISTIC_SELECTOR_RETRY_COUNT as u32,
@@ -389,6 +391,25 @@ where
         .resolve_orchestrator_runtime_root(&orchestrator_id)
         .map_err(|err| OrchestratorError::Config(err.to_string()))?;
     let run_store = WorkflowRunStore::new(&runtime_root);
+    if inbound.workflow_run_id.is_none() {
+        // A message requeued after a restart resumes the run it already
+        // started instead of selecting a second one.
+        if let Some(run) = run_store
+            .latest_run_for_source_message_id(&inbound.message_id)?
+            .filter(|run| matches!(run.state, RunState::Queued | RunState::Running))
+        {
+            return resume_inbound_run(
+                settings,
+                inbound,
+                &orchestrator_id,
+                &runtime_root,
+                &run_store,
+                &runner_binaries,
+                &run.run_id,
+                now,
+            );
+        }
+    }
     if inbound.channel == "scheduler" {
         return route_scheduled_trigger(
             inbound,
@@ -402,6 +423,44 @@ where
             now,
         );
     }
+    if let Some((approved, comment)) = parse_human_review_reply(&inbound.message) {
+        let review_run_id = resolve_status_run_id(
+            &StatusResolutionInput {
+                explicit_run_id: None,
+                inbound_workflow_run_id: inbound.workflow_run_id.clone(),
+                channel_profile_id: inbound.channel_profile_id.clone(),
+                conversation_id: inbound.conversation_id.clone(),
+            },
+            active_conversation_runs,
+        );
+        if let Some(run_id) = review_run_id.filter(|run_id| {
+            matches!(
+                find_pending_human_review_run(&run_store, run_id),
+                Ok(Some(_))
+            )
+        }) {
+            let engine = inbound_workflow_engine(
+                settings,
+                inbound,
+                &orchestrator_id,
+                &runtime_root,
+                &run_store,
+                &runner_binaries,
+            )?;
+            let run = engine.submit_human_review(
+                &run_id,
+                approved,
+                comment,
+                Some(inbound.sender.clone()).filter(|sender| !sender.trim().is_empty()),
+                now,
+            )?;
+            return Ok(RoutedSelectorAction::WorkflowStart {
+                run_id: run.run_id,
+                workflow_id: run.workflow_id,
+            });
+        }
+    }
+
     let inbound_message = inbound.message.trim().to_ascii_lowercase();
     let is_status_command = matches!(
         inbound_message.as_str(),
@@ -413,62 +472,16 @@ where
         .filter(|v| !v.trim().is_empty())
     {
         if !is_status_command {
-            let orchestrator = load_orchestrator_config(settings, &orchestrator_id)?;
-            let workspace_context = match verify_orchestrator_workspace_access(
+            return resume_inbound_run(
                 settings,
+                inbound,
                 &orchestrator_id,
-                &orchestrator,
-            ) {
-                Ok(context) => context,
-                Err(err) => {
-                    append_security_log(
-                        &runtime_root,
-                        &format!(
-                            "workspace access denied for orchestrator `{orchestrator_id}` message `{}`: {err}",
-                            inbound.message_id
-                        ),
-                    );
-                    return Err(err);
-                }
-            };
-
-            let engine = WorkflowEngine::new(run_store.clone(), orchestrator.clone())
-                .with_runner_binaries(runner_binaries.clone())
-                .with_workspace_access_context(workspace_context)
-                .with_memory_enabled(settings.memory.enabled);
-            let resumed = match engine
-                .resume(run_id, now)
-                .map_err(|e| missing_run_for_io(run_id, &e).unwrap_or(e))
-            {
-                Ok(run) => run,
-                Err(OrchestratorError::UnknownRunId { .. }) => {
-                    return Ok(RoutedSelectorAction::WorkflowStatus {
-                        run_id: Some(run_id.to_string()),
-                        progress: None,
-                        message: format!("workflow run `{run_id}` was not found"),
-                    });
-                }
-                Err(err) => return Err(err),
-            };
-            let progress = match run_store
-                .load_progress(run_id)
-                .map_err(|e| missing_run_for_io(run_id, &e).unwrap_or(e))
-            {
-                Ok(progress) => progress,
-                Err(OrchestratorError::UnknownRunId { .. }) => {
-                    return Ok(RoutedSelectorAction::WorkflowStatus {
-                        run_id: Some(run_id.to_string()),
-                        progress: None,
-                        message: format!("workflow run `{run_id}` was not found"),
-                    });
-                }
-                Err(err) => return Err(err),
-            };
-            return Ok(RoutedSelectorAction::WorkflowStatus {
-                run_id: Some(resumed.run_id),
-                progress: Some(progress),
-                message: "workflow progress loaded".to_string(),
-            });
+                &runtime_root,
+                &run_store,
+                &runner_binaries,
+                run_id,
+                now,
+            );
         }
     }
 
@@ -653,6 +666,7 @@ where
         default_workflow: orchestrator.default_workflow.clone(),
         available_functions: functions.available_function_ids(),
         available_function_schemas: functions.available_function_schemas(),
+        available_workflow_inputs: available_workflow_inputs(&orchestrator),
     };
 
     let artifact_store = SelectorArtifactStore::new(&runtime_root);
@@ -729,6 +743,94 @@ where
     )
 }
 
+fn inbound_workflow_engine(
+    settings: &Settings,
+    inbound: &IncomingMessage,
+    orchestrator_id: &str,
+    runtime_root: &Path,
+    run_store: &WorkflowRunStore,
+    runner_binaries: &RunnerBinaries,
+) -> Result<WorkflowEngine, OrchestratorError> {
+    let orchestrator = load_orchestrator_config(settings, orchestrator_id)?;
+    let workspace_context = match verify_orchestrator_workspace_access(
+        settings,
+        orchestrator_id,
+        &orchestrator,
+    ) {
+        Ok(context) => context,
+        Err(err) => {
+            append_security_log(
+                runtime_root,
+                &format!(
+                    "workspace access denied for orchestrator `{orchestrator_id}` message `{}`: {err}",
+                    inbound.message_id
+                ),
+            );
+            return Err(err);
+        }
+    };
+
+    Ok(WorkflowEngine::new(run_store.clone(), orchestrator)
+        .with_runner_binaries(runner_binaries.clone())
+        .with_workspace_access_context(workspace_context)
+        .with_memory_enabled(settings.memory.enabled))
+}
+
+/// Resumes `run_id` on behalf of an inbound message and reports its progress.
+#[allow(clippy::too_many_arguments)]
+fn resume_inbound_run(
+    settings: &Settings,
+    inbound: &IncomingMessage,
+    orchestrator_id: &str,
+    runtime_root: &Path,
+    run_store: &WorkflowRunStore,
+    runner_binaries: &RunnerBinaries,
+    run_id: &str,
+    now: i64,
+) -> Result<RoutedSelectorAction, OrchestratorError> {
+    let engine = inbound_workflow_engine(
+        settings,
+        inbound,
+        orchestrator_id,
+        runtime_root,
+        run_store,
+        runner_binaries,
+    )?;
+    let resumed = match engine
+        .resume(run_id, now)
+        .map_err(|e| missing_run_for_io(run_id, &e).unwrap_or(e))
+    {
+        Ok(run) => run,
+        Err(OrchestratorError::UnknownRunId { .. }) => {
+            return Ok(RoutedSelectorAction::WorkflowStatus {
+                run_id: Some(run_id.to_string()),
+                progress: None,
+                message: format!("workflow run `{run_id}` was not found"),
+            });
+        }
+        Err(err) => return Err(err),
+    };
+    let progress = match run_store
+        .load_progress(run_id)
+        .map_err(|e| missing_run_for_io(run_id, &e).unwrap_or(e))
+    {
+        Ok(progress) => progress,
+        Err(OrchestratorError::UnknownRunId { .. }) => {
+            return Ok(RoutedSelectorAction::WorkflowStatus {
+                run_id: Some(run_id.to_string()),
+                progress: None,
+                message: format!("workflow run `{run_id}` was not found"),
+            });
+        }
+        Err(err) => return Err(err),
+    };
+    Ok(RoutedSelectorAction::WorkflowStatus {
+        run_id: Some(resumed.run_id),
+        progress: Some(progress),
+        message: "workflow progress loaded".to_string(),
+    })
+}
+
 #[allow(clippy::too_many_arguments)]
 fn route_scheduled_trigger(
     inbound: &IncomingMessage,
@@ -783,6 +885,7 @@ fn route_scheduled_trigger(
         default_workflow: orchestrator.default_workflow.clone(),
         available_functions: functions.available_function_ids(),
         available_function_schemas: functions.available_function_schemas(),
+        available_workflow_inputs: BTreeMap::new(),
     };
 
     let mut workflow_inputs = None;
@@ -799,6 +902,7 @@ fn route_scheduled_trigger(
                 selected_workflow: Some(workflow_id),
                 function_id: None,
                 function_args: None,
+                workflow_inputs: None,
                 reason: Some(format!(
                     "scheduled_trigger job_id={} execution_id={}",
                     envelope.job_id, envelope.execution_id
@@ -818,6 +922,7 @@ fn route_scheduled_trigger(
                 selected_workflow: None,
                 function_id: Some(function_id),
                 function_args: Some(function_args),
+                workflow_inputs: None,
                 reason: Some(format!(
                     "scheduled_trigger job_id={} execution_id={}",
                     envelope.job_id, envelope.execution_id
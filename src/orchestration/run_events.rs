@@ -0,0 +1,165 @@
+use crate::config::RetryErrorClass;
+use crate::orchestration::progress::ProgressSnapshot;
+use crate::orchestration::run_store::RunState;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// One line of a run's append-only `events.jsonl` journal. `seq` starts at
+/// 1 and increases by one per event of the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEvent {
+    pub seq: u64,
+    pub at: i64,
+    pub run_id: String,
+    #[serde(flatten)]
+    pub kind: RunEventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum RunEventKind {
+    RunCreated {
+        workflow_id: String,
+        input_keys: Vec<String>,
+    },
+    StateChanged {
+        from: RunState,
+        to: RunState,
+        current_step_id: Option<String>,
+        current_attempt: Option<u32>,
+        summary: String,
+        pending_human_input: bool,
+        next_expected_action: String,
+    },
+    StepStarted {
+        step_id: String,
+        attempt: u32,
+    },
+    ProviderInvoked {
+        step_id: String,
+        attempt: u32,
+        agent_id: String,
+        provider: String,
+        model: String,
+        exit_code: Option<i32>,
+        timed_out: bool,
+        /// Fallback-chain providers that failed before `provider`, in order.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        fallback_from: Vec<String>,
+    },
+    OutputValidated {
+        step_id: String,
+        attempt: u32,
+        valid: bool,
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        errors: BTreeMap<String, String>,
+    },
+    TransitionChosen {
+        step_id: String,
+        attempt: u32,
+        next_step_id: Option<String>,
+    },
+    StepFailed {
+        step_id: String,
+        attempt: u32,
+        error: Option<String>,
+        error_class: Option<RetryErrorClass>,
+    },
+    RetryScheduled {
+        step_id: String,
+        attempt: u32,
+        delay_seconds: u64,
+        retry_at: i64,
+        error_class: Option<RetryErrorClass>,
+    },
+}
+
+/// Replays a run's journal into the progress snapshot it implies. Returns
+/// `None` when the journal does not start with `run_created`.
+pub fn progress_from_events(events: &[RunEvent]) -> Option<ProgressSnapshot> {
+    let (first, rest) = events.split_first()?;
+    let RunEventKind::RunCreated {
+        workflow_id,
+        input_keys,
+    } = &first.kind
+    else {
+        return None;
+    };
+    let mut progress = ProgressSnapshot {
+        run_id: first.run_id.clone(),
+        workflow_id: workflow_id.clone(),
+        state: RunState::Queued,
+        input_count: input_keys.len(),
+        input_keys: input_keys.clone(),
+        current_step_id: None,
+        current_attempt: None,
+        started_at: first.at,
+        updated_at: first.at,
+        last_progress_at: first.at,
+        summary: "queued".to_string(),
+        pending_human_input: false,
+        next_expected_action: "workflow start".to_string(),
+    };
+    for event in rest {
+        progress.updated_at = event.at;
+        progress.last_progress_at = event.at;
+        match &event.kind {
+            RunEventKind::RunCreated { .. } => {}
+            RunEventKind::StateChanged {
+                to,
+                current_step_id,
+                current_attempt,
+                summary,
+                pending_human_input,
+                next_expected_action,
+                ..
+            } => {
+                progress.state = to.clone();
+                progress.current_step_id = current_step_id.clone();
+                progress.current_attempt = *current_attempt;
+                progress.summary = summary.clone();
+                progress.pending_human_input = *pending_human_input;
+                progress.next_expected_action = next_expected_action.clone();
+            }
+            RunEventKind::StepStarted { step_id, attempt } => {
+                progress.current_step_id = Some(step_id.clone());
+                progress.current_attempt = Some(*attempt);
+                progress.summary = format!("step {step_id} attempt {attempt} running");
+                progress.pending_human_input = false;
+                progress.next_expected_action = "await step output".to_string();
+            }
+            RunEventKind::TransitionChosen {
+                step_id,
+                attempt,
+                next_step_id,
+            } => {
+                progress.summary = format!("step {step_id} attempt {attempt} succeeded");
+                progress.next_expected_action = next_step_id
+                    .clone()
+                    .unwrap_or_else(|| "workflow terminal transition".to_string());
+            }
+            RunEventKind::StepFailed {
+                step_id, attempt, ..
+            } => {
+                progress.summary = format!("step {step_id} attempt {attempt} failed");
+            }
+            RunEventKind::RetryScheduled {
+                step_id,
+                attempt,
+                retry_at,
+                ..
+            } => {
+                progress.current_step_id = Some(step_id.clone());
+                progress.current_attempt = Some(*attempt);
+                progress.next_expected_action = format!("retry step {step_id} at {retry_at}");
+            }
+            RunEventKind::ProviderInvoked { .. } | RunEventKind::OutputValidated { .. } => {}
+        }
+    }
+    Some(progress)
+}
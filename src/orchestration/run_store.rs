@@ -1,12 +1,28 @@
+use crate::config::{RetryErrorClass, WorkflowConfig};
 use crate::orchestration::error::OrchestratorError;
 pub use crate::orchestration::progress::ProgressSnapshot;
+use crate::orchestration::run_events::{RunEvent, RunEventKind};
+use crate::provider::{CancellationToken, ProviderUsage};
 use crate::shared::logging::{append_orchestrator_log_line, orchestrator_log_path};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::collections::BTreeMap;
 use std::fs;
-use std::io::ErrorKind;
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+static PROVIDER_SESSION_LOCK: Mutex<()> = Mutex::new(());
+
+const RETRY_DUE_DIR: &str = "workflows/retry_due";
+
+/// Entry of the index of runs parked in `waiting` for retry backoff.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryDueEntry {
+    pub run_id: String,
+    pub retry_at: i64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -75,6 +91,13 @@ impl RunMemoryContext {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextStepPointer {
+    pub step_id: String,
+    pub attempt: u32,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct WorkflowRunRecord {
@@ -104,6 +127,32 @@ pub struct WorkflowRunRecord {
     pub status_conversation_id: Option<String>,
     #[serde(default)]
     pub terminal_reason: Option<String>,
+    #[serde(default)]
+    pub waiting_seconds: u64,
+    /// Branch or item attempts still executing under the current `parallel`
+    /// or `foreach` step.
+    #[serde(default)]
+    pub active_steps: Vec<NextStepPointer>,
+    /// Run that started this one from a `workflow_call` step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_run_id: Option<String>,
+    /// Runs started by this run's `workflow_call` steps, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub child_run_ids: Vec<String>,
+    /// Source run and step this run was re-executed from by `workflow rerun`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<RunLineage>,
+    /// Unix time at which a run parked in `waiting` for retry backoff is due
+    /// to retry its current step.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunLineage {
+    pub source_run_id: String,
+    pub from_step_id: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -127,6 +176,43 @@ pub struct StepAttemptRecord {
     pub error: Option<String>,
     #[serde(default)]
     pub output_validation_errors: BTreeMap<String, String>,
+    /// Set on branch attempts to the `parallel` step that fanned them out.
+    #[serde(default)]
+    pub parallel_step_id: Option<String>,
+    /// Set on sequence attempts to the `foreach` step that iterated them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub foreach_step_id: Option<String>,
+    /// Zero-based index of the `foreach` item this attempt ran for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_index: Option<usize>,
+    /// Retry class of a failed attempt, counted against the step's per-class
+    /// retry budget.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_class: Option<RetryErrorClass>,
+    /// Provider token usage of the agent invocation behind this attempt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<StepUsage>,
+}
+
+/// Provider usage of one step attempt, attributed to the agent that ran it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepUsage {
+    pub agent_id: String,
+    pub provider: String,
+    pub model: String,
+    #[serde(flatten)]
+    pub tokens: ProviderUsage,
+}
+
+/// Provider session an agent last ran in within a run, resumed by the
+/// agent's next attempt in that run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSessionRecord {
+    pub provider: String,
+    pub session_id: String,
+    pub updated_at: i64,
 }
 
 #[derive(Debug, Clone)]
@@ -161,19 +247,27 @@ impl WorkflowRunStore {
         workflow_id: impl Into<String>,
         now: i64,
     ) -> Result<WorkflowRunRecord, OrchestratorError> {
-        self.create_run_with_inputs(run_id, workflow_id, Map::new(), now)
+        self.insert_run(
+            run_id.into(),
+            workflow_id.into(),
+            SelectorStartedRunMetadata::default(),
+            Map::new(),
+            now,
+        )
     }
 
+    /// Creates a run after validating `inputs` against the workflow's
+    /// declared inputs, so bad arguments fail before any step executes.
     pub fn create_run_with_inputs(
         &self,
         run_id: impl Into<String>,
-        workflow_id: impl Into<String>,
+        workflow: &WorkflowConfig,
         inputs: Map<String, Value>,
         now: i64,
     ) -> Result<WorkflowRunRecord, OrchestratorError> {
         self.create_run_with_metadata(
             run_id,
-            workflow_id,
+            workflow,
             SelectorStartedRunMetadata::default(),
             inputs,
             now,
@@ -183,15 +277,138 @@ impl WorkflowRunStore {
     pub fn create_run_with_metadata(
         &self,
         run_id: impl Into<String>,
-        workflow_id: impl Into<String>,
+        workflow: &WorkflowConfig,
+        metadata: SelectorStartedRunMetadata,
+        inputs: Map<String, Value>,
+        now: i64,
+    ) -> Result<WorkflowRunRecord, OrchestratorError> {
+        let inputs = workflow.inputs.resolve(&inputs).map_err(|reason| {
+            OrchestratorError::WorkflowInputValidation {
+                workflow_id: workflow.id.clone(),
+                reason,
+            }
+        })?;
+        self.insert_run(run_id.into(), workflow.id.clone(), metadata, inputs, now)
+    }
+
+    /// Creates a run that re-executes terminal run `source_run_id` from
+    /// `from_step_id` under the current workflow definition. The source
+    /// inputs and the latest succeeded attempt of every step declared before
+    /// `from_step_id` are copied, so earlier steps are not executed again.
+    pub fn fork_run(
+        &self,
+        source_run_id: &str,
+        run_id: impl Into<String>,
+        workflow: &WorkflowConfig,
+        from_step_id: &str,
+        now: i64,
+    ) -> Result<WorkflowRunRecord, OrchestratorError> {
+        let rerun_error = |reason: String| OrchestratorError::RunRerun {
+            run_id: source_run_id.to_string(),
+            reason,
+        };
+        let source = self.load_run(source_run_id)?;
+        if !source.state.clone().is_terminal() {
+            return Err(rerun_error(format!(
+                "run is `{}`; only terminal runs can be rerun",
+                source.state
+            )));
+        }
+        if source.workflow_id != workflow.id {
+            return Err(rerun_error(format!(
+                "run belongs to workflow `{}`, not `{}`",
+                source.workflow_id, workflow.id
+            )));
+        }
+        if !workflow.steps.iter().any(|step| step.id == from_step_id) {
+            return Err(rerun_error(format!(
+                "step `{from_step_id}` is not declared in workflow `{}`",
+                workflow.id
+            )));
+        }
+
+        // Attempts that succeeded before the source run first reached
+        // `from_step_id`, in journal order, including fan-out branch and item
+        // attempts.
+        let mut copied = Vec::new();
+        let mut reached = false;
+        for event in self.load_run_events(source_run_id, 0)? {
+            match event.kind {
+                RunEventKind::StepStarted { step_id, .. }
+                | RunEventKind::StepFailed { step_id, .. }
+                    if step_id == from_step_id =>
+                {
+                    reached = true;
+                    break;
+                }
+                RunEventKind::TransitionChosen {
+                    step_id, attempt, ..
+                } => {
+                    if step_id == from_step_id {
+                        reached = true;
+                        break;
+                    }
+                    let record = self.load_step_attempt(source_run_id, &step_id, attempt)?;
+                    if record.state == "succeeded" {
+                        copied.push(record);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !reached {
+            return Err(rerun_error(format!(
+                "step `{from_step_id}` was never reached by run `{source_run_id}`"
+            )));
+        }
+
+        let mut run = self.create_run_with_metadata(
+            run_id,
+            workflow,
+            SelectorStartedRunMetadata {
+                channel_profile_id: source.channel_profile_id.clone(),
+                status_conversation_id: source.status_conversation_id.clone(),
+                memory_context: source.memory_context.clone(),
+                ..SelectorStartedRunMetadata::default()
+            },
+            source.inputs.clone(),
+            now,
+        )?;
+        for mut attempt in copied {
+            let source_dir =
+                self.step_attempt_dir(source_run_id, &attempt.step_id, attempt.attempt);
+            let target_dir = self.step_attempt_dir(&run.run_id, &attempt.step_id, attempt.attempt);
+            copy_dir_all(&source_dir, &target_dir)?;
+            for path in attempt.output_files.values_mut() {
+                if let Ok(relative) = Path::new(path.as_str()).strip_prefix(&source_dir) {
+                    *path = target_dir.join(relative).display().to_string();
+                }
+            }
+            attempt.run_id = run.run_id.clone();
+            attempt.usage = None;
+            self.write_step_attempt(&attempt)?;
+        }
+        run.current_step_id = Some(from_step_id.to_string());
+        run.rerun_of = Some(RunLineage {
+            source_run_id: source_run_id.to_string(),
+            from_step_id: from_step_id.to_string(),
+        });
+        self.persist_run(&run)?;
+        Ok(run)
+    }
+
+    fn insert_run(
+        &self,
+        run_id: String,
+        workflow_id: String,
         metadata: SelectorStartedRunMetadata,
         inputs: Map<String, Value>,
         now: i64,
     ) -> Result<WorkflowRunRecord, OrchestratorError> {
         let input_keys = sorted_input_keys(&inputs);
         let run = WorkflowRunRecord {
-            run_id: run_id.into(),
-            workflow_id: workflow_id.into(),
+            run_id,
+            workflow_id,
             state: RunState::Queued,
             channel_profile_id: metadata.channel_profile_id,
             inputs,
@@ -206,8 +423,22 @@ impl WorkflowRunStore {
             selected_workflow: metadata.selected_workflow,
             status_conversation_id: metadata.status_conversation_id,
             terminal_reason: None,
+            waiting_seconds: 0,
+            active_steps: Vec::new(),
+            parent_run_id: None,
+            child_run_ids: Vec::new(),
+            rerun_of: None,
+            retry_at: None,
         };
         self.persist_run(&run)?;
+        self.append_run_event(
+            &run.run_id,
+            now,
+            RunEventKind::RunCreated {
+                workflow_id: run.workflow_id.clone(),
+                input_keys: input_keys.clone(),
+            },
+        )?;
         self.persist_progress(&ProgressSnapshot {
             run_id: run.run_id.clone(),
             workflow_id: run.workflow_id.clone(),
@@ -226,6 +457,10 @@ impl WorkflowRunStore {
         Ok(run)
     }
 
+    pub fn run_exists(&self, run_id: &str) -> bool {
+        self.run_metadata_path(run_id).is_file()
+    }
+
     pub fn load_run(&self, run_id: &str) -> Result<WorkflowRunRecord, OrchestratorError> {
         let path = self.run_metadata_path(run_id);
         let raw = fs::read_to_string(&path).map_err(|err| io_error(&path, err))?;
@@ -258,13 +493,31 @@ impl WorkflowRunStore {
                 to: next,
             });
         }
-        run.state = next;
+        let from = std::mem::replace(&mut run.state, next);
         run.updated_at = now;
+        if run.state == RunState::Canceled {
+            self.signal_cancellation(&run.run_id)?;
+        }
         if run.state.clone().is_terminal() {
             run.terminal_reason = Some(summary.clone());
         } else {
             run.terminal_reason = None;
         }
+        // Journal first so a reader that sees the new state on the record
+        // also finds its event.
+        self.append_run_event(
+            &run.run_id,
+            now,
+            RunEventKind::StateChanged {
+                from,
+                to: run.state.clone(),
+                current_step_id: run.current_step_id.clone(),
+                current_attempt: run.current_attempt,
+                summary: summary.clone(),
+                pending_human_input,
+                next_expected_action: next_expected_action.clone(),
+            },
+        )?;
         self.persist_run(run)?;
         self.persist_progress(&ProgressSnapshot {
             run_id: run.run_id.clone(),
@@ -301,11 +554,11 @@ impl WorkflowRunStore {
             updated_at: now,
             last_progress_at: now,
             summary: summary.into(),
-            pending_human_input: run.state == RunState::Waiting,
-            next_expected_action: if run.state == RunState::Waiting {
-                "await human response".to_string()
-            } else {
-                "continue workflow".to_string()
+            pending_human_input: run.state == RunState::Waiting && run.retry_at.is_none(),
+            next_expected_action: match (&run.state, run.retry_at) {
+                (RunState::Waiting, Some(retry_at)) => format!("retry step at {retry_at}"),
+                (RunState::Waiting, None) => "await human response".to_string(),
+                _ => "continue workflow".to_string(),
             },
         })
     }
@@ -321,6 +574,14 @@ impl WorkflowRunStore {
         run.current_attempt = Some(attempt);
         run.updated_at = now;
         self.persist_run(run)?;
+        self.append_run_event(
+            &run.run_id,
+            now,
+            RunEventKind::StepStarted {
+                step_id: step_id.to_string(),
+                attempt,
+            },
+        )?;
         self.persist_progress(&ProgressSnapshot {
             run_id: run.run_id.clone(),
             workflow_id: run.workflow_id.clone(),
@@ -357,23 +618,13 @@ impl WorkflowRunStore {
         &self,
         attempt: &StepAttemptRecord,
     ) -> Result<PathBuf, OrchestratorError> {
-        let path = self
-            .run_dir(&attempt.run_id)
-            .join("steps")
-            .join(&attempt.step_id)
-            .join("attempts")
-            .join(attempt.attempt.to_string())
-            .join("result.json");
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
-        }
-        let body = serde_json::to_vec_pretty(attempt).map_err(|e| json_error(&path, e))?;
-        fs::write(&path, body).map_err(|e| io_error(&path, e))?;
+        let path = self.write_step_attempt(attempt)?;
 
         let mut run = self.load_run(&attempt.run_id)?;
         run.total_iterations = run.total_iterations.saturating_add(1);
         run.updated_at = attempt.ended_at;
         self.persist_run(&run)?;
+        self.append_attempt_events(attempt)?;
         self.persist_progress(&ProgressSnapshot {
             run_id: run.run_id.clone(),
             workflow_id: run.workflow_id.clone(),
@@ -399,10 +650,200 @@ impl WorkflowRunStore {
         Ok(path)
     }
 
+    fn append_attempt_events(&self, attempt: &StepAttemptRecord) -> Result<(), OrchestratorError> {
+        let succeeded = attempt.state == "succeeded";
+        if succeeded || !attempt.output_validation_errors.is_empty() {
+            self.append_run_event(
+                &attempt.run_id,
+                attempt.ended_at,
+                RunEventKind::OutputValidated {
+                    step_id: attempt.step_id.clone(),
+                    attempt: attempt.attempt,
+                    valid: attempt.output_validation_errors.is_empty(),
+                    errors: attempt.output_validation_errors.clone(),
+                },
+            )?;
+        }
+        let kind = if succeeded {
+            RunEventKind::TransitionChosen {
+                step_id: attempt.step_id.clone(),
+                attempt: attempt.attempt,
+                next_step_id: attempt.next_step_id.clone(),
+            }
+        } else {
+            RunEventKind::StepFailed {
+                step_id: attempt.step_id.clone(),
+                attempt: attempt.attempt,
+                error: attempt.error.clone(),
+                error_class: attempt.error_class,
+            }
+        };
+        self.append_run_event(&attempt.run_id, attempt.ended_at, kind)?;
+        Ok(())
+    }
+
+    /// Appends one event to the run's journal under the next sequence number.
+    /// The journal is held under an exclusive file lock while its last
+    /// sequence number is read and the event written, so the daemon and CLI
+    /// commands appending from other processes never reuse a number.
+    pub fn append_run_event(
+        &self,
+        run_id: &str,
+        at: i64,
+        kind: RunEventKind,
+    ) -> Result<RunEvent, OrchestratorError> {
+        let path = self.run_events_path(run_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        file.lock().map_err(|e| io_error(&path, e))?;
+        let last_seq = match last_journal_line(&mut file).map_err(|e| io_error(&path, e))? {
+            Some(line) => {
+                serde_json::from_slice::<RunEvent>(&line)
+                    .map_err(|e| json_error(&path, e))?
+                    .seq
+            }
+            None => 0,
+        };
+        let event = RunEvent {
+            seq: last_seq + 1,
+            at,
+            run_id: run_id.to_string(),
+            kind,
+        };
+        let mut line = serde_json::to_string(&event).map_err(|e| json_error(&path, e))?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .map_err(|e| io_error(&path, e))?;
+        Ok(event)
+    }
+
+    /// Loads the run's journal events with a sequence number above
+    /// `after_seq`. A run without a journal has no events; a trailing line
+    /// still being written is skipped.
+    pub fn load_run_events(
+        &self,
+        run_id: &str,
+        after_seq: u64,
+    ) -> Result<Vec<RunEvent>, OrchestratorError> {
+        let path = self.run_events_path(run_id);
+        let raw = match fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+        let mut events = Vec::new();
+        for line in raw.split_inclusive('\n') {
+            if !line.ends_with('\n') || line.trim().is_empty() {
+                continue;
+            }
+            let event: RunEvent = serde_json::from_str(line).map_err(|e| json_error(&path, e))?;
+            if event.seq > after_seq {
+                events.push(event);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Provider sessions recorded for the run, keyed by agent id.
+    pub fn load_provider_sessions(
+        &self,
+        run_id: &str,
+    ) -> Result<BTreeMap<String, ProviderSessionRecord>, OrchestratorError> {
+        let path = self.provider_sessions_path(run_id);
+        let raw = match fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+        serde_json::from_str(&raw).map_err(|e| json_error(&path, e))
+    }
+
+    /// Session `agent_id` last ran in within the run, if it was recorded for
+    /// the same `provider`.
+    pub fn provider_session(
+        &self,
+        run_id: &str,
+        agent_id: &str,
+        provider: &str,
+    ) -> Result<Option<String>, OrchestratorError> {
+        Ok(self
+            .load_provider_sessions(run_id)?
+            .remove(agent_id)
+            .filter(|record| record.provider == provider)
+            .map(|record| record.session_id))
+    }
+
+    /// Records the session `agent_id` ran in, replacing its previous one.
+    pub fn record_provider_session(
+        &self,
+        run_id: &str,
+        agent_id: &str,
+        provider: &str,
+        session_id: &str,
+        now: i64,
+    ) -> Result<(), OrchestratorError> {
+        let _guard = PROVIDER_SESSION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut sessions = self.load_provider_sessions(run_id)?;
+        sessions.insert(
+            agent_id.to_string(),
+            ProviderSessionRecord {
+                provider: provider.to_string(),
+                session_id: session_id.to_string(),
+                updated_at: now,
+            },
+        );
+        let path = self.provider_sessions_path(run_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let body = serde_json::to_vec_pretty(&sessions).map_err(|e| json_error(&path, e))?;
+        fs::write(&path, body).map_err(|e| io_error(&path, e))
+    }
+
+    fn provider_sessions_path(&self, run_id: &str) -> PathBuf {
+        self.run_dir(run_id).join("provider_sessions.json")
+    }
+
+    pub fn run_events_path(&self, run_id: &str) -> PathBuf {
+        self.run_dir(run_id).join("events.jsonl")
+    }
+
     fn run_dir(&self, run_id: &str) -> PathBuf {
         self.state_root.join("workflows/runs").join(run_id)
     }
 
+    fn write_step_attempt(
+        &self,
+        attempt: &StepAttemptRecord,
+    ) -> Result<PathBuf, OrchestratorError> {
+        let path = self
+            .step_attempt_dir(&attempt.run_id, &attempt.step_id, attempt.attempt)
+            .join("result.json");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let body = serde_json::to_vec_pretty(attempt).map_err(|e| json_error(&path, e))?;
+        fs::write(&path, body).map_err(|e| io_error(&path, e))?;
+        Ok(path)
+    }
+
+    pub fn step_attempt_dir(&self, run_id: &str, step_id: &str, attempt: u32) -> PathBuf {
+        self.run_dir(run_id)
+            .join("steps")
+            .join(step_id)
+            .join("attempts")
+            .join(attempt.to_string())
+    }
+
     fn run_metadata_path(&self, run_id: &str) -> PathBuf {
         self.state_root
             .join("workflows/runs")
@@ -413,6 +854,24 @@ impl WorkflowRunStore {
         self.run_dir(run_id).join("progress.json")
     }
 
+    fn cancel_marker_path(&self, run_id: &str) -> PathBuf {
+        self.run_dir(run_id).join("cancel_requested")
+    }
+
+    /// Token that fires once the run is canceled from any process, so an
+    /// in-flight provider or command attempt can be killed.
+    pub fn cancellation_token(&self, run_id: &str) -> CancellationToken {
+        CancellationToken::with_marker(self.cancel_marker_path(run_id))
+    }
+
+    fn signal_cancellation(&self, run_id: &str) -> Result<(), OrchestratorError> {
+        let path = self.cancel_marker_path(run_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        fs::write(&path, b"canceled").map_err(|e| io_error(&path, e))
+    }
+
     pub fn append_engine_log(
         &self,
         run_id: &str,
@@ -441,6 +900,12 @@ impl WorkflowRunStore {
         serde_json::from_str(&raw).map_err(|e| json_error(&path, e))
     }
 
+    pub fn step_attempt_exists(&self, run_id: &str, step_id: &str, attempt: u32) -> bool {
+        self.step_attempt_dir(run_id, step_id, attempt)
+            .join("result.json")
+            .is_file()
+    }
+
     pub fn checkpoint(
         &self,
         run: &mut WorkflowRunRecord,
@@ -468,6 +933,160 @@ impl WorkflowRunStore {
         })
     }
 
+    /// Cancels a non-terminal run and every non-terminal descendant started by
+    /// its `workflow_call` steps.
+    pub fn cancel_run(
+        &self,
+        run: &mut WorkflowRunRecord,
+        now: i64,
+        summary: &str,
+    ) -> Result<(), OrchestratorError> {
+        for child_run_id in run.child_run_ids.clone() {
+            if !self.run_exists(&child_run_id) {
+                continue;
+            }
+            let mut child = self.load_run(&child_run_id)?;
+            self.cancel_run(
+                &mut child,
+                now,
+                &format!("parent run {} canceled", run.run_id),
+            )?;
+        }
+        if run.state.clone().is_terminal() {
+            return Ok(());
+        }
+        self.transition_state(run, RunState::Canceled, now, summary, false, "none")
+    }
+
+    /// Loads every persisted step attempt result of a run ordered by completion.
+    pub fn load_step_attempts(
+        &self,
+        run_id: &str,
+    ) -> Result<Vec<StepAttemptRecord>, OrchestratorError> {
+        let steps_root = self.run_dir(run_id).join("steps");
+        let step_entries = match fs::read_dir(&steps_root) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => return Err(io_error(&steps_root, source)),
+        };
+
+        let mut attempts = Vec::new();
+        for step_entry in step_entries {
+            let step_entry = step_entry.map_err(|source| io_error(&steps_root, source))?;
+            let attempts_root = step_entry.path().join("attempts");
+            let attempt_entries = match fs::read_dir(&attempts_root) {
+                Ok(entries) => entries,
+                Err(source) if source.kind() == ErrorKind::NotFound => continue,
+                Err(source) => return Err(io_error(&attempts_root, source)),
+            };
+            for attempt_entry in attempt_entries {
+                let attempt_entry =
+                    attempt_entry.map_err(|source| io_error(&attempts_root, source))?;
+                let path = attempt_entry.path().join("result.json");
+                let raw = match fs::read_to_string(&path) {
+                    Ok(raw) => raw,
+                    Err(source) if source.kind() == ErrorKind::NotFound => continue,
+                    Err(source) => return Err(io_error(&path, source)),
+                };
+                let record: StepAttemptRecord =
+                    serde_json::from_str(&raw).map_err(|source| json_error(&path, source))?;
+                attempts.push(record);
+            }
+        }
+        attempts.sort_by(|left, right| {
+            (left.ended_at, left.started_at, &left.step_id, left.attempt).cmp(&(
+                right.ended_at,
+                right.started_at,
+                &right.step_id,
+                right.attempt,
+            ))
+        });
+        Ok(attempts)
+    }
+
+    /// Loads every persisted run record ordered by start time.
+    pub fn load_runs(&self) -> Result<Vec<WorkflowRunRecord>, OrchestratorError> {
+        let runs_root = self.state_root.join("workflows/runs");
+        let entries = match fs::read_dir(&runs_root) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => return Err(io_error(&runs_root, source)),
+        };
+
+        let mut runs = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|source| io_error(&runs_root, source))?;
+            let path = entry.path();
+            if !path.is_file() {
+                continue;
+            }
+            if path.extension().and_then(|value| value.to_str()) != Some("json") {
+                continue;
+            }
+
+            let raw = fs::read_to_string(&path).map_err(|source| io_error(&path, source))?;
+            let run: WorkflowRunRecord =
+                serde_json::from_str(&raw).map_err(|source| json_error(&path, source))?;
+            runs.push(run);
+        }
+        runs.sort_by(|left, right| {
+            (left.started_at, &left.run_id).cmp(&(right.started_at, &right.run_id))
+        });
+        Ok(runs)
+    }
+
+    /// Indexes a run parked for retry under `workflows/retry_due`, so the
+    /// runtime finds due retries without parsing every run record.
+    pub fn record_retry_due(&self, run_id: &str, retry_at: i64) -> Result<(), OrchestratorError> {
+        let path = self.retry_due_path(run_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        let entry = RetryDueEntry {
+            run_id: run_id.to_string(),
+            retry_at,
+        };
+        let body = serde_json::to_vec_pretty(&entry).map_err(|e| json_error(&path, e))?;
+        fs::write(&path, body).map_err(|e| io_error(&path, e))
+    }
+
+    pub fn clear_retry_due(&self, run_id: &str) -> Result<(), OrchestratorError> {
+        let path = self.retry_due_path(run_id);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(source) if source.kind() == ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(io_error(&path, source)),
+        }
+    }
+
+    /// Lists the retry index entries whose `retryAt` has passed, oldest first.
+    /// Unreadable entries are skipped so one bad file does not block the
+    /// other retries.
+    pub fn due_retry_entries(&self, now: i64) -> Result<Vec<RetryDueEntry>, OrchestratorError> {
+        let index_root = self.state_root.join(RETRY_DUE_DIR);
+        let entries = match fs::read_dir(&index_root) {
+            Ok(entries) => entries,
+            Err(source) if source.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => return Err(io_error(&index_root, source)),
+        };
+        let mut due = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+            .filter_map(|raw| serde_json::from_str::<RetryDueEntry>(&raw).ok())
+            .filter(|entry| entry.retry_at <= now)
+            .collect::<Vec<_>>();
+        due.sort_by(|left, right| {
+            (left.retry_at, &left.run_id).cmp(&(right.retry_at, &right.run_id))
+        });
+        Ok(due)
+    }
+
+    fn retry_due_path(&self, run_id: &str) -> PathBuf {
+        self.state_root
+            .join(RETRY_DUE_DIR)
+            .join(format!("{run_id}.json"))
+    }
+
     pub fn latest_run_for_source_message_id(
         &self,
         source_message_id: &str,
@@ -544,6 +1163,10 @@ impl WorkflowRunStore {
             if run.status_conversation_id.as_deref() != Some(conversation_id) {
                 continue;
             }
+            // Child runs are reached through their parent's `child_run_ids`.
+            if run.parent_run_id.is_some() {
+                continue;
+            }
             if !include_terminal && run.state.clone().is_terminal() {
                 continue;
             }
@@ -570,6 +1193,58 @@ fn sorted_input_keys(inputs: &Map<String, Value>) -> Vec<String> {
     keys
 }
 
+/// Reads the last complete line of a journal from its end, dropping a
+/// trailing line torn by a writer that died mid-append.
+fn last_journal_line(file: &mut fs::File) -> std::io::Result<Option<Vec<u8>>> {
+    const CHUNK: u64 = 4096;
+    let len = file.seek(SeekFrom::End(0))?;
+    let mut pos = len;
+    let mut tail = Vec::new();
+    while pos > 0 {
+        let read = CHUNK.min(pos);
+        pos -= read;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk = vec![0; read as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        let Some(end) = tail.iter().rposition(|byte| *byte == b'\n') else {
+            continue;
+        };
+        let complete = pos + end as u64 + 1;
+        if complete < len {
+            file.set_len(complete)?;
+        }
+        if let Some(start) = tail[..end].iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(tail[start + 1..end].to_vec()));
+        }
+        if pos == 0 {
+            return Ok(Some(tail[..end].to_vec()));
+        }
+    }
+    if len > 0 {
+        file.set_len(0)?;
+    }
+    Ok(None)
+}
+
+/// Copies a step attempt directory, output files included, into another run.
+fn copy_dir_all(from: &Path, to: &Path) -> Result<(), OrchestratorError> {
+    fs::create_dir_all(to).map_err(|e| io_error(to, e))?;
+    for entry in fs::read_dir(from).map_err(|e| io_error(from, e))? {
+        let entry = entry.map_err(|e| io_error(from, e))?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type().map_err(|e| io_error(&source, e))?;
+        if file_type.is_dir() {
+            copy_dir_all(&source, &target)?;
+        } else if file_type.is_file() {
+            fs::copy(&source, &target).map_err(|e| io_error(&source, e))?;
+        }
+    }
+    Ok(())
+}
+
 fn io_error(path: &Path, source: std::io::Error) -> OrchestratorError {
     OrchestratorError::Io {
         path: path.display().to_string(),
@@ -0,0 +1,284 @@
+use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::run_store::{RunState, WorkflowRunRecord, WorkflowRunStore};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+pub const RUN_WORKTREE_FILE: &str = "worktree.json";
+
+const COMMIT_AUTHOR_NAME: &str = "DireClaw";
+const COMMIT_AUTHOR_EMAIL: &str = "direclaw@localhost";
+
+/// `git worktree add/remove` rewrite shared `.git/worktrees` metadata and
+/// race when concurrent runs of one repository start or finish together.
+static WORKTREE_ADMIN_LOCK: Mutex<()> = Mutex::new(());
+
+/// Git worktree backing the run workspace of a workflow with `repository`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunWorktree {
+    pub run_id: String,
+    pub repository: PathBuf,
+    pub path: PathBuf,
+    pub branch: String,
+    pub base_commit: String,
+    pub approved_commit: String,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+pub fn run_worktree_branch(run_id: &str) -> String {
+    format!("direclaw/{run_id}")
+}
+
+pub fn load_run_worktree(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+) -> Result<Option<RunWorktree>, OrchestratorError> {
+    let path = worktree_record_path(run_store, run_id);
+    let raw = match fs::read_to_string(&path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(io_error(&path, err)),
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|source| OrchestratorError::Json {
+            path: path.display().to_string(),
+            source,
+        })
+}
+
+/// Returns the run's worktree, creating it on branch `direclaw/<run_id>` from
+/// the repository's current `HEAD` the first time the run needs it.
+pub(crate) fn ensure_run_worktree(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+    repository: &Path,
+    path: &Path,
+) -> Result<RunWorktree, OrchestratorError> {
+    if let Some(existing) = load_run_worktree(run_store, run_id)? {
+        if !existing.removed {
+            return Ok(existing);
+        }
+    }
+    let base_commit = git(run_id, repository, &["rev-parse", "HEAD"])?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| io_error(parent, err))?;
+    }
+    let branch = run_worktree_branch(run_id);
+    let target = path.display().to_string();
+    {
+        let _guard = WORKTREE_ADMIN_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        git(
+            run_id,
+            repository,
+            &["worktree", "add", "-b", &branch, &target, &base_commit],
+        )?;
+    }
+    let worktree = RunWorktree {
+        run_id: run_id.to_string(),
+        repository: repository.to_path_buf(),
+        path: path.to_path_buf(),
+        branch,
+        approved_commit: base_commit.clone(),
+        base_commit,
+        removed: false,
+    };
+    persist_run_worktree(run_store, &worktree)?;
+    Ok(worktree)
+}
+
+/// Commits every change in the worktree, returning the new commit or `None`
+/// when the step left the tree unchanged.
+pub(crate) fn commit_run_worktree(
+    worktree: &RunWorktree,
+    message: &str,
+) -> Result<Option<String>, OrchestratorError> {
+    let run_id = worktree.run_id.as_str();
+    git(run_id, &worktree.path, &["add", "-A"])?;
+    if git(run_id, &worktree.path, &["status", "--porcelain"])?.is_empty() {
+        return Ok(None);
+    }
+    git(
+        run_id,
+        &worktree.path,
+        &[
+            "-c",
+            &format!("user.name={COMMIT_AUTHOR_NAME}"),
+            "-c",
+            &format!("user.email={COMMIT_AUTHOR_EMAIL}"),
+            "commit",
+            "--quiet",
+            "--no-verify",
+            "-m",
+            message,
+        ],
+    )?;
+    git(run_id, &worktree.path, &["rev-parse", "HEAD"]).map(Some)
+}
+
+/// Records the worktree `HEAD` as the commit reject loops reset to.
+pub(crate) fn approve_run_worktree(
+    run_store: &WorkflowRunStore,
+    worktree: &mut RunWorktree,
+) -> Result<(), OrchestratorError> {
+    worktree.approved_commit = git(&worktree.run_id, &worktree.path, &["rev-parse", "HEAD"])?;
+    persist_run_worktree(run_store, worktree)
+}
+
+/// Discards commits and uncommitted changes made since the last approval.
+/// Ignored files such as build caches are kept.
+pub(crate) fn reset_run_worktree(worktree: &RunWorktree) -> Result<(), OrchestratorError> {
+    let run_id = worktree.run_id.as_str();
+    git(
+        run_id,
+        &worktree.path,
+        &["reset", "--hard", "--quiet", &worktree.approved_commit],
+    )?;
+    git(run_id, &worktree.path, &["clean", "-fd", "--quiet"])?;
+    Ok(())
+}
+
+/// Removes the run's worktree checkout. The branch is kept so the run's
+/// commits stay available for review or merging.
+pub fn remove_run_worktree(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+) -> Result<(), OrchestratorError> {
+    let Some(mut worktree) = load_run_worktree(run_store, run_id)? else {
+        return Ok(());
+    };
+    if worktree.removed {
+        return Ok(());
+    }
+    let target = worktree.path.display().to_string();
+    {
+        let _guard = WORKTREE_ADMIN_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        git(
+            run_id,
+            &worktree.repository,
+            &["worktree", "remove", "--force", &target],
+        )?;
+    }
+    worktree.removed = true;
+    persist_run_worktree(run_store, &worktree)
+}
+
+/// Removes the worktree of a run that stopped executing. Failed runs keep
+/// theirs, uncommitted changes included, until an explicit cleanup. A
+/// failure to remove is logged rather than returned.
+pub(crate) fn release_run_worktree(
+    run_store: &WorkflowRunStore,
+    run: &WorkflowRunRecord,
+    now: i64,
+) -> Result<(), OrchestratorError> {
+    if !matches!(run.state, RunState::Succeeded | RunState::Canceled) {
+        return Ok(());
+    }
+    if let Err(err) = remove_run_worktree(run_store, &run.run_id) {
+        run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!("run_id={} worktree_cleanup_failed={err}", run.run_id),
+        )?;
+    }
+    Ok(())
+}
+
+/// Cancels `run` with its descendants and releases the worktrees of the runs
+/// that were parked rather than executing. An executing run's engine releases
+/// its worktree itself once it observes the cancellation and stops.
+pub fn cancel_run_releasing_worktrees(
+    run_store: &WorkflowRunStore,
+    run: &mut WorkflowRunRecord,
+    now: i64,
+    summary: &str,
+) -> Result<(), OrchestratorError> {
+    let mut parked = Vec::new();
+    collect_parked_runs(run_store, run, &mut parked)?;
+    run_store.cancel_run(run, now, summary)?;
+    for run_id in parked {
+        let canceled = run_store.load_run(&run_id)?;
+        release_run_worktree(run_store, &canceled, now)?;
+    }
+    Ok(())
+}
+
+fn collect_parked_runs(
+    run_store: &WorkflowRunStore,
+    run: &WorkflowRunRecord,
+    parked: &mut Vec<String>,
+) -> Result<(), OrchestratorError> {
+    for child_run_id in &run.child_run_ids {
+        if run_store.run_exists(child_run_id) {
+            collect_parked_runs(run_store, &run_store.load_run(child_run_id)?, parked)?;
+        }
+    }
+    if matches!(run.state, RunState::Queued | RunState::Waiting) {
+        parked.push(run.run_id.clone());
+    }
+    Ok(())
+}
+
+fn persist_run_worktree(
+    run_store: &WorkflowRunStore,
+    worktree: &RunWorktree,
+) -> Result<(), OrchestratorError> {
+    let path = worktree_record_path(run_store, &worktree.run_id);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| io_error(parent, err))?;
+    }
+    let body = serde_json::to_vec_pretty(worktree).map_err(|source| OrchestratorError::Json {
+        path: path.display().to_string(),
+        source,
+    })?;
+    fs::write(&path, body).map_err(|err| io_error(&path, err))
+}
+
+fn worktree_record_path(run_store: &WorkflowRunStore, run_id: &str) -> PathBuf {
+    run_store
+        .state_root()
+        .join("workflows/runs")
+        .join(run_id)
+        .join(RUN_WORKTREE_FILE)
+}
+
+fn git(run_id: &str, dir: &Path, args: &[&str]) -> Result<String, OrchestratorError> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|err| OrchestratorError::RunWorktree {
+            run_id: run_id.to_string(),
+            reason: format!("failed to run git: {err}"),
+        })?;
+    if !output.status.success() {
+        return Err(OrchestratorError::RunWorktree {
+            run_id: run_id.to_string(),
+            reason: format!(
+                "`git {}` in {} failed: {}",
+                args.join(" "),
+                dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> OrchestratorError {
+    OrchestratorError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
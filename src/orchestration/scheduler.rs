@@ -1,3 +1,4 @@
+use crate::config::Settings;
 use crate::orchestration::slack_target::{parse_slack_target_ref, slack_target_ref_to_value};
 use crate::queue::{IncomingMessage, QueuePaths};
 use crate::shared::logging::append_orchestrator_log_line;
@@ -5,12 +6,13 @@ use chrono::{Datelike, TimeZone, Timelike, Utc};
 use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const MAX_CRON_SEARCH_MINUTES: i64 = 60 * 24 * 366 * 5;
+const MAX_FILE_WATCH_DEBOUNCE_SECONDS: u64 = 86_400;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -42,6 +44,14 @@ pub enum ScheduleConfig {
         expression: String,
         timezone: String,
     },
+    /// Fires when files matching `pattern` (relative to the granted shared
+    /// workspace `workspace`) are created or modified, once no further
+    /// change has been seen for `debounce_seconds`.
+    FileWatch {
+        workspace: String,
+        pattern: String,
+        debounce_seconds: u64,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -155,6 +165,26 @@ struct SchedulerState {
 
 const STALE_ACTIVE_EXECUTION_MAX_AGE_SECS: i64 = 86_400;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileStamp {
+    modified_ms: i64,
+    len: u64,
+}
+
+/// Last seen snapshot of a file-watch job's matching files and the changes
+/// still waiting out the debounce window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct FileWatchState {
+    #[serde(default)]
+    files: BTreeMap<String, FileStamp>,
+    #[serde(default)]
+    pending: BTreeSet<String>,
+    #[serde(default)]
+    last_change_at: Option<i64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DispatchedRun {
     pub job_id: String,
@@ -177,6 +207,7 @@ impl JobStore {
     pub fn create(&self, input: NewJob, now: i64) -> Result<ScheduledJob, String> {
         validate_schedule(&input.schedule)?;
         validate_target_action(&input.target_action)?;
+        validate_schedule_target(&input.schedule, &input.target_action)?;
         validate_target_ref(&input.target_ref)?;
         if input.orchestrator_id.trim().is_empty() {
             return Err("orchestrator_id must be non-empty".to_string());
@@ -275,6 +306,7 @@ impl JobStore {
         if let Some(allow_overlap) = patch.allow_overlap {
             job.allow_overlap = allow_overlap;
         }
+        validate_schedule_target(&job.schedule, &job.target_action)?;
 
         job.updated_at = now;
         self.persist_job(&job)?;
@@ -349,6 +381,7 @@ impl JobStore {
 pub struct SchedulerWorker {
     runtime_root: PathBuf,
     store: JobStore,
+    shared_workspaces: BTreeMap<String, PathBuf>,
 }
 
 impl SchedulerWorker {
@@ -357,9 +390,17 @@ impl SchedulerWorker {
         Self {
             store: JobStore::new(&runtime_root),
             runtime_root,
+            shared_workspaces: BTreeMap::new(),
         }
     }
 
+    /// Shared workspaces the orchestrator is granted, by name; file-watch
+    /// jobs only observe these.
+    pub fn with_shared_workspaces(mut self, shared_workspaces: BTreeMap<String, PathBuf>) -> Self {
+        self.shared_workspaces = shared_workspaces;
+        self
+    }
+
     pub fn tick(&mut self, now: i64) -> Result<Vec<DispatchedRun>, String> {
         let mut state = self.load_state()?;
         self.recover_stale_active_executions(&mut state, now);
@@ -369,6 +410,12 @@ impl SchedulerWorker {
             if job.state != JobState::Enabled {
                 continue;
             }
+            if matches!(job.schedule, ScheduleConfig::FileWatch { .. }) {
+                if let Some(run) = self.tick_file_watch(&mut state, &mut job, now)? {
+                    dispatched.push(run);
+                }
+                continue;
+            }
             let Some(next_run_at) = job.next_run_at else {
                 continue;
             };
@@ -453,23 +500,7 @@ impl SchedulerWorker {
             job.updated_at = now;
             self.store.persist_job(&job)?;
             self.persist_run_record(&job, &execution_id, now, RunStatus::Dispatched, now)?;
-
-            state.recent_execution_ids.push(execution_id.clone());
-            if state.recent_execution_ids.len() > 2048 {
-                let start = state.recent_execution_ids.len() - 2048;
-                state.recent_execution_ids = state.recent_execution_ids[start..].to_vec();
-            }
-            state.active_executions.push(ActiveExecution {
-                job_id: job.job_id.clone(),
-                execution_id: execution_id.clone(),
-                started_at: now,
-            });
-
-            dispatched.push(DispatchedRun {
-                job_id: job.job_id.clone(),
-                execution_id,
-                triggered_at: now,
-            });
+            dispatched.push(record_dispatch(&mut state, &job, execution_id, now));
         }
 
         state.last_tick_at = Some(now);
@@ -477,6 +508,122 @@ impl SchedulerWorker {
         Ok(dispatched)
     }
 
+    /// Rescans a file-watch job's workspace and dispatches its workflow with
+    /// the changed paths once changes have settled for the debounce window.
+    /// `run_now` flushes pending changes without waiting. The first scan only
+    /// records a baseline.
+    fn tick_file_watch(
+        &self,
+        state: &mut SchedulerState,
+        job: &mut ScheduledJob,
+        now: i64,
+    ) -> Result<Option<DispatchedRun>, String> {
+        let ScheduleConfig::FileWatch {
+            workspace,
+            pattern,
+            debounce_seconds,
+        } = &job.schedule
+        else {
+            return Ok(None);
+        };
+        let Some(root) = self
+            .shared_workspaces
+            .get(workspace)
+            .filter(|root| root.is_dir())
+        else {
+            self.append_scheduler_event("scheduler.file_watch.unavailable", job, None, now);
+            return Ok(None);
+        };
+
+        let path = self.file_watch_state_path(&job.job_id);
+        let baseline = !path.exists();
+        let mut watch = self.load_file_watch_state(&job.job_id)?;
+        let previous = watch.clone();
+        let files = scan_file_watch(root, pattern)?;
+        for (relative, stamp) in &files {
+            if !baseline && watch.files.get(relative) != Some(stamp) {
+                watch.pending.insert(relative.clone());
+                watch.last_change_at = Some(now);
+            }
+        }
+        watch
+            .pending
+            .retain(|relative| files.contains_key(relative));
+        watch.files = files;
+
+        let forced = job.next_run_at.is_some_and(|run_at| run_at <= now);
+        let settled = !watch.pending.is_empty()
+            && watch
+                .last_change_at
+                .is_some_and(|changed| now.saturating_sub(changed) >= *debounce_seconds as i64);
+        let blocked = !job.allow_overlap
+            && state
+                .active_executions
+                .iter()
+                .any(|active| active.job_id == job.job_id);
+        if !(forced || settled) || blocked {
+            if baseline || watch != previous {
+                self.save_file_watch_state(&job.job_id, &watch)?;
+            }
+            return Ok(None);
+        }
+
+        let execution_id = format!("exec-{}-{}", sanitize_id(&job.job_id), now);
+        if state
+            .recent_execution_ids
+            .iter()
+            .any(|existing| existing == &execution_id)
+            || self.execution_exists(&job.job_id, &execution_id)?
+        {
+            if baseline || watch != previous {
+                self.save_file_watch_state(&job.job_id, &watch)?;
+            }
+            return Ok(None);
+        }
+        let mut target_action = job.target_action.clone();
+        if let TargetAction::WorkflowStart { inputs, .. } = &mut target_action {
+            inputs.insert(
+                "changed_paths".to_string(),
+                Value::Array(
+                    watch
+                        .pending
+                        .iter()
+                        .map(|relative| Value::String(root.join(relative).display().to_string()))
+                        .collect(),
+                ),
+            );
+            inputs.insert(
+                "watch_workspace".to_string(),
+                Value::String(workspace.clone()),
+            );
+        }
+        self.enqueue_trigger(&ScheduledTriggerEnvelope {
+            job_id: job.job_id.clone(),
+            execution_id: execution_id.clone(),
+            triggered_at: now,
+            orchestrator_id: job.orchestrator_id.clone(),
+            target_action,
+            target_ref: job.target_ref.clone(),
+        })?;
+        self.append_scheduler_event(
+            "scheduler.trigger.dispatched",
+            job,
+            Some(&execution_id),
+            now,
+        );
+        watch.pending.clear();
+        watch.last_change_at = None;
+        self.save_file_watch_state(&job.job_id, &watch)?;
+
+        job.next_run_at = None;
+        job.last_run_at = Some(now);
+        job.last_result = Some("dispatched".to_string());
+        job.updated_at = now;
+        self.store.persist_job(job)?;
+        self.persist_run_record(job, &execution_id, now, RunStatus::Dispatched, now)?;
+        Ok(Some(record_dispatch(state, job, execution_id, now)))
+    }
+
     fn recover_stale_active_executions(&self, state: &mut SchedulerState, now: i64) {
         let mut retained = Vec::with_capacity(state.active_executions.len());
         for active in state.active_executions.drain(..) {
@@ -656,6 +803,34 @@ impl SchedulerWorker {
         Ok(false)
     }
 
+    fn file_watch_state_path(&self, job_id: &str) -> PathBuf {
+        self.runtime_root
+            .join("automation/watch")
+            .join(format!("{job_id}.json"))
+    }
+
+    fn load_file_watch_state(&self, job_id: &str) -> Result<FileWatchState, String> {
+        let path = self.file_watch_state_path(job_id);
+        if !path.exists() {
+            return Ok(FileWatchState::default());
+        }
+        let raw = fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+        serde_json::from_str(&raw)
+            .map_err(|err| format!("failed to parse {}: {err}", path.display()))
+    }
+
+    fn save_file_watch_state(&self, job_id: &str, watch: &FileWatchState) -> Result<(), String> {
+        let path = self.file_watch_state_path(job_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create {}: {err}", parent.display()))?;
+        }
+        let body = serde_json::to_vec_pretty(watch)
+            .map_err(|err| format!("failed to encode file watch state: {err}"))?;
+        fs::write(&path, body).map_err(|err| format!("failed to write {}: {err}", path.display()))
+    }
+
     fn scheduler_state_path(&self) -> PathBuf {
         self.runtime_root.join("automation/scheduler_state.json")
     }
@@ -738,6 +913,190 @@ impl SchedulerWorker {
     }
 }
 
+fn record_dispatch(
+    state: &mut SchedulerState,
+    job: &ScheduledJob,
+    execution_id: String,
+    now: i64,
+) -> DispatchedRun {
+    state.recent_execution_ids.push(execution_id.clone());
+    if state.recent_execution_ids.len() > 2048 {
+        let start = state.recent_execution_ids.len() - 2048;
+        state.recent_execution_ids = state.recent_execution_ids[start..].to_vec();
+    }
+    state.active_executions.push(ActiveExecution {
+        job_id: job.job_id.clone(),
+        execution_id: execution_id.clone(),
+        started_at: now,
+    });
+    DispatchedRun {
+        job_id: job.job_id.clone(),
+        execution_id,
+        triggered_at: now,
+    }
+}
+
+/// Shared workspaces granted to `orchestrator_id` through `shared_access`,
+/// by name.
+pub fn granted_shared_workspaces(
+    settings: &Settings,
+    orchestrator_id: &str,
+) -> BTreeMap<String, PathBuf> {
+    settings
+        .orchestrators
+        .get(orchestrator_id)
+        .map(|orchestrator| orchestrator.shared_access.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|name| {
+            settings
+                .shared_workspaces
+                .get(name)
+                .map(|shared| (name.clone(), shared.path.clone()))
+        })
+        .collect()
+}
+
+/// Rejects file-watch schedules on shared workspaces the orchestrator has
+/// not been granted.
+pub fn validate_file_watch_access(
+    settings: &Settings,
+    orchestrator_id: &str,
+    schedule: &ScheduleConfig,
+) -> Result<(), String> {
+    let ScheduleConfig::FileWatch { workspace, .. } = schedule else {
+        return Ok(());
+    };
+    if granted_shared_workspaces(settings, orchestrator_id).contains_key(workspace) {
+        return Ok(());
+    }
+    Err(format!(
+        "file_watch workspace `{workspace}` is not in shared_access of orchestrator `{orchestrator_id}`"
+    ))
+}
+
+/// Matches a `/`-separated relative path against a glob where `*` and `?`
+/// stay within one path segment and a `**` segment spans any number of them.
+pub fn file_watch_glob_matches(pattern: &str, relative_path: &str) -> bool {
+    let pattern = pattern.split('/').collect::<Vec<_>>();
+    let path = relative_path.split('/').collect::<Vec<_>>();
+    glob_segments_match(&pattern, &path)
+}
+
+fn glob_segments_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skip| glob_segments_match(rest, &path[skip..]))
+        }
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path_rest)| {
+            glob_segment_matches(segment.as_bytes(), name.as_bytes())
+                && glob_segments_match(rest, path_rest)
+        }),
+    }
+}
+
+fn glob_segment_matches(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => {
+            (0..=name.len()).any(|skip| glob_segment_matches(rest, &name[skip..]))
+        }
+        Some((b'?', rest)) => !name.is_empty() && glob_segment_matches(rest, &name[1..]),
+        Some((byte, rest)) => name.first() == Some(byte) && glob_segment_matches(rest, &name[1..]),
+    }
+}
+
+/// Reports whether some path below the `/`-separated `relative_dir` could
+/// still match `pattern`, so scans can skip directories that never will.
+fn file_watch_glob_may_descend(pattern: &[&str], relative_dir: &[&str]) -> bool {
+    match (pattern.split_first(), relative_dir.split_first()) {
+        (Some((&"**", _)), _) => true,
+        (Some(_), None) => true,
+        (None, Some(_)) => false,
+        (None, None) => false,
+        (Some((segment, rest)), Some((name, dir_rest))) => {
+            glob_segment_matches(segment.as_bytes(), name.as_bytes())
+                && file_watch_glob_may_descend(rest, dir_rest)
+        }
+    }
+}
+
+/// Entries that vanish or cannot be read mid-scan are skipped rather than
+/// failing the scheduler tick.
+fn skippable_scan_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+    )
+}
+
+fn scan_file_watch(root: &Path, pattern: &str) -> Result<BTreeMap<String, FileStamp>, String> {
+    let pattern_segments = pattern.split('/').collect::<Vec<_>>();
+    let mut files = BTreeMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if skippable_scan_error(&err) => continue,
+            Err(err) => return Err(format!("failed to read {}: {err}", dir.display())),
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) if skippable_scan_error(&err) => continue,
+                Err(err) => return Err(format!("failed to read {}: {err}", dir.display())),
+            };
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            if file_type.is_dir() {
+                if entry.file_name() != ".git"
+                    && file_watch_glob_may_descend(
+                        &pattern_segments,
+                        &relative.split('/').collect::<Vec<_>>(),
+                    )
+                {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if !file_watch_glob_matches(pattern, &relative) {
+                continue;
+            }
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_millis() as i64)
+                .unwrap_or(0);
+            files.insert(
+                relative,
+                FileStamp {
+                    modified_ms,
+                    len: metadata.len(),
+                },
+            );
+        }
+    }
+    Ok(files)
+}
+
 pub fn complete_scheduled_execution(
     runtime_root: impl AsRef<Path>,
     job_id: &str,
@@ -854,6 +1213,7 @@ pub fn compute_next_run_at(
             };
             Ok(Some(base))
         }
+        ScheduleConfig::FileWatch { .. } => Ok(None),
         ScheduleConfig::Cron {
             expression,
             timezone,
@@ -881,7 +1241,7 @@ fn compute_recovery_next_run_at(
     now: i64,
 ) -> Result<Option<i64>, String> {
     match schedule {
-        ScheduleConfig::Once { .. } => Ok(None),
+        ScheduleConfig::Once { .. } | ScheduleConfig::FileWatch { .. } => Ok(None),
         ScheduleConfig::Interval { .. } | ScheduleConfig::Cron { .. } => {
             compute_next_run_at(schedule, now, Some(now))
         }
@@ -907,7 +1267,46 @@ fn validate_schedule(schedule: &ScheduleConfig) -> Result<(), String> {
             parse_cron_expression(expression)?;
             validate_iana_timezone(timezone)
         }
+        ScheduleConfig::FileWatch {
+            workspace,
+            pattern,
+            debounce_seconds,
+        } => {
+            if workspace.trim().is_empty() {
+                return Err("file_watch.workspace must be non-empty".to_string());
+            }
+            if pattern.trim().is_empty() {
+                return Err("file_watch.pattern must be non-empty".to_string());
+            }
+            if pattern.starts_with('/')
+                || pattern.contains('\\')
+                || pattern.split('/').any(|segment| segment == "..")
+            {
+                return Err(
+                    "file_watch.pattern must be a relative `/`-separated glob without `..`"
+                        .to_string(),
+                );
+            }
+            if *debounce_seconds > MAX_FILE_WATCH_DEBOUNCE_SECONDS {
+                return Err(format!(
+                    "file_watch.debounce_seconds must be <= {MAX_FILE_WATCH_DEBOUNCE_SECONDS}"
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+fn validate_schedule_target(
+    schedule: &ScheduleConfig,
+    action: &TargetAction,
+) -> Result<(), String> {
+    if matches!(schedule, ScheduleConfig::FileWatch { .. })
+        && !matches!(action, TargetAction::WorkflowStart { .. })
+    {
+        return Err("file_watch schedules require a workflow_start target_action".to_string());
     }
+    Ok(())
 }
 
 fn validate_target_action(action: &TargetAction) -> Result<(), String> {
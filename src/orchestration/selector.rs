@@ -1,14 +1,19 @@
-use crate::config::{OrchestratorConfig, Settings};
+use crate::channels::webhook::WEBHOOK_CHANNEL;
+use crate::config::{OrchestratorConfig, Settings, WorkflowInputs};
 use crate::orchestration::diagnostics::{persist_selector_invocation_log, provider_error_log};
 use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::selector_artifacts::SelectorArtifactStore;
+use crate::orchestration::step_execution::{agent_provider_endpoint, agent_provider_fallbacks};
 use crate::prompts::{
     default_selector_context, default_selector_prompt, render_template_with_placeholders,
     resolve_prompt_template_path, PROMPTS_DIR, SELECTOR_CONTEXT_REL_PATH, SELECTOR_PROMPT_REL_PATH,
 };
 use crate::provider::{
-    run_provider, write_file_backed_prompt, ProviderKind, ProviderRequest, RunnerBinaries,
+    run_provider, write_file_backed_prompt, CancellationToken, ProviderKind, ProviderRequest,
+    RunnerBinaries,
 };
 use crate::queue::IncomingMessage;
+use crate::shared::time::now_secs;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::collections::BTreeMap;
@@ -37,6 +42,11 @@ pub fn resolve_orchestrator_id(
             }
         }
     }
+    if channel_profile_id.is_none() && inbound.channel == WEBHOOK_CHANNEL {
+        if let Some(route) = settings.webhooks.routes.get(inbound.sender_id.trim()) {
+            return Ok(route.orchestrator_id.clone());
+        }
+    }
     if channel_profile_id.is_none() && inbound.channel == "scheduler" {
         if let Some(orchestrator_id) = inbound
             .sender
@@ -148,6 +158,10 @@ pub fn run_selector_attempt_with_provider(
     )
     .map_err(|err| err.to_string())?;
 
+    let artifact_store = SelectorArtifactStore::new(state_root);
+    let session_id = artifact_store
+        .selector_session(&orchestrator.selector_agent, provider.as_str())
+        .map_err(|err| err.to_string())?;
     let provider_request = ProviderRequest {
         agent_id: orchestrator.selector_agent.clone(),
         provider,
@@ -168,9 +182,28 @@ pub fn run_selector_attempt_with_provider(
         reset_requested: false,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: agent_provider_endpoint(selector_agent),
+        session_id,
+        fallbacks: agent_provider_fallbacks(selector_agent).map_err(|err| err.to_string())?,
     };
 
-    match run_provider(&provider_request, binaries) {
+    let result = run_provider(&provider_request, binaries);
+    let log = match &result {
+        Ok(result) => Some(&result.log),
+        Err(err) => provider_error_log(err),
+    };
+    if let Some((log, session_id)) =
+        log.and_then(|log| log.session_id.as_deref().map(|id| (log, id)))
+    {
+        let _ = artifact_store.record_selector_session(
+            &log.agent_id,
+            &log.provider.to_string(),
+            session_id,
+            now_secs(),
+        );
+    }
+    match result {
         Ok(result) => {
             persist_selector_invocation_log(
                 state_root,
@@ -272,6 +305,20 @@ pub struct SelectorRequest {
     pub available_functions: Vec<String>,
     #[serde(default)]
     pub available_function_schemas: Vec<FunctionSchema>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub available_workflow_inputs: BTreeMap<String, WorkflowInputs>,
+}
+
+/// Declared inputs per workflow, omitting workflows that declare none.
+pub fn available_workflow_inputs(
+    orchestrator: &OrchestratorConfig,
+) -> BTreeMap<String, WorkflowInputs> {
+    orchestrator
+        .workflows
+        .iter()
+        .filter(|workflow| !workflow.inputs.is_empty())
+        .map(|workflow| (workflow.id.clone(), workflow.inputs.clone()))
+        .collect()
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -304,6 +351,8 @@ pub struct SelectorResult {
     #[serde(default)]
     pub function_args: Option<Map<String, Value>>,
     #[serde(default)]
+    pub workflow_inputs: Option<Map<String, Value>>,
+    #[serde(default)]
     pub reason: Option<String>,
 }
 
@@ -366,6 +415,19 @@ pub fn parse_and_validate_selector_result(
                             "workflow `{selected}` is not in availableWorkflows"
                         )));
                     }
+                    if let Some(inputs) = request.available_workflow_inputs.get(selected) {
+                        let provided = result.workflow_inputs.clone().unwrap_or_default();
+                        if let Some(key) = provided.keys().find(|key| inputs.get(key).is_none()) {
+                            return Err(OrchestratorError::SelectorValidation(format!(
+                                "workflow_start has unknown input `{key}` for workflow `{selected}`"
+                            )));
+                        }
+                        inputs.resolve(&provided).map_err(|err| {
+                            OrchestratorError::SelectorValidation(format!(
+                                "workflow_start inputs for workflow `{selected}` are invalid: {err}"
+                            ))
+                        })?;
+                    }
                 }
                 SelectorAction::WorkflowStatus => {}
                 SelectorAction::CommandInvoke => {
@@ -469,6 +531,7 @@ where
             selected_workflow: Some(orchestrator.default_workflow.clone()),
             function_id: None,
             function_args: None,
+            workflow_inputs: None,
             reason: Some("fallback_to_default_workflow_after_retry_limit".to_string()),
         },
         retries_used: orchestrator.selection_max_retries,
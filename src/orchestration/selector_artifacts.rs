@@ -1,10 +1,16 @@
 use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::run_store::ProviderSessionRecord;
 use crate::orchestration::selector::{SelectorRequest, SelectorResult};
 use crate::queue::IncomingMessage;
 use crate::shared::logging::{append_orchestrator_log_line, orchestrator_log_path};
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+static SELECTOR_SESSION_LOCK: Mutex<()> = Mutex::new(());
 
 pub struct SelectorArtifactStore {
     state_root: PathBuf,
@@ -82,6 +88,63 @@ impl SelectorArtifactStore {
         Ok(path)
     }
 
+    /// Session the selector agent last ran in, if it was recorded for the
+    /// same `provider`. Selector attempts resume it so the selector keeps its
+    /// context across messages.
+    pub fn selector_session(
+        &self,
+        agent_id: &str,
+        provider: &str,
+    ) -> Result<Option<String>, OrchestratorError> {
+        Ok(self
+            .load_selector_sessions()?
+            .remove(agent_id)
+            .filter(|record| record.provider == provider)
+            .map(|record| record.session_id))
+    }
+
+    /// Records the session the selector agent ran in, replacing its previous
+    /// one.
+    pub fn record_selector_session(
+        &self,
+        agent_id: &str,
+        provider: &str,
+        session_id: &str,
+        now: i64,
+    ) -> Result<(), OrchestratorError> {
+        let _guard = SELECTOR_SESSION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut sessions = self.load_selector_sessions()?;
+        sessions.insert(
+            agent_id.to_string(),
+            ProviderSessionRecord {
+                provider: provider.to_string(),
+                session_id: session_id.to_string(),
+                updated_at: now,
+            },
+        );
+        self.write_json(&self.selector_sessions_path(), &sessions)
+            .map(|_| ())
+    }
+
+    fn load_selector_sessions(
+        &self,
+    ) -> Result<BTreeMap<String, ProviderSessionRecord>, OrchestratorError> {
+        let path = self.selector_sessions_path();
+        let raw = match fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(BTreeMap::new()),
+            Err(err) => return Err(io_error(&path, err)),
+        };
+        serde_json::from_str(&raw).map_err(|source| json_error(&path, source))
+    }
+
+    fn selector_sessions_path(&self) -> PathBuf {
+        self.state_root
+            .join("orchestrator/selector/provider_sessions.json")
+    }
+
     fn write_json<T: Serialize>(
         &self,
         path: &Path,
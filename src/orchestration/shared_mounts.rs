@@ -29,7 +29,6 @@ pub fn reconcile_orchestrator_shared_mounts(
         ))
     })?;
     let mount_root = private_workspace.join("shared");
-    fs::create_dir_all(&mount_root).map_err(|err| io_error(&mount_root, err))?;
 
     let mut desired: BTreeMap<String, PathBuf> = BTreeMap::new();
     for key in &orchestrator.shared_access {
@@ -46,12 +45,22 @@ pub fn reconcile_orchestrator_shared_mounts(
         })?;
         desired.insert(key.clone(), normalize_absolute_path(&canonical_target)?);
     }
+    reconcile_shared_mounts_at(orchestrator_id, &mount_root, &desired)
+}
 
-    for (key, target) in &desired {
+/// Links each `desired` shared workspace under `mount_root` and removes
+/// symlinks for grants that no longer exist.
+pub(crate) fn reconcile_shared_mounts_at(
+    orchestrator_id: &str,
+    mount_root: &Path,
+    desired: &BTreeMap<String, PathBuf>,
+) -> Result<(), OrchestratorError> {
+    fs::create_dir_all(mount_root).map_err(|err| io_error(mount_root, err))?;
+    for (key, target) in desired {
         let mount_path = mount_root.join(key);
         reconcile_mount_path(orchestrator_id, &mount_path, target)?;
     }
-    remove_stale_symlink_mounts(&mount_root, &desired)?;
+    remove_stale_symlink_mounts(mount_root, desired)?;
     Ok(())
 }
 
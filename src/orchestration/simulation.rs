@@ -0,0 +1,380 @@
+use crate::config::{
+    OrchestratorConfig, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType,
+};
+use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::output_contract::{output_validation_errors_for, StepEvaluation};
+use crate::orchestration::run_store::{RunState, WorkflowRunStore};
+use crate::orchestration::workflow_engine::WorkflowEngine;
+use crate::prompts::PROMPTS_DIR;
+use crate::provider::{InvocationLog, ProviderError, ProviderRequest, ProviderResult};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Run id of the throwaway run a simulation drives.
+pub const SIMULATION_RUN_ID: &str = "simulation";
+
+/// Canned agent and command responses for `workflow simulate`, keyed by step
+/// id. The n-th execution of a step, counting retries and revisits alike,
+/// gets the n-th response; the last one answers any later execution.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SimulationScript {
+    #[serde(default)]
+    pub steps: BTreeMap<String, Vec<ScriptedResponse>>,
+}
+
+/// One scripted provider reply or command result. `outputs` is wrapped in a
+/// `[workflow_result]` envelope, `message` is returned verbatim, `files` are
+/// written to the step's declared output files, and a non-zero `exit_code`
+/// fails the attempt like a provider process exit with `stdout` and
+/// `stderr`. Command steps take only `exit_code`, `stdout` and `stderr`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptedResponse {
+    #[serde(default)]
+    pub outputs: Option<Map<String, Value>>,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+    #[serde(default)]
+    pub exit_code: i32,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+impl SimulationScript {
+    pub fn load(path: &Path) -> Result<Self, OrchestratorError> {
+        let raw = fs::read_to_string(path).map_err(|err| OrchestratorError::Io {
+            path: path.display().to_string(),
+            source: err,
+        })?;
+        serde_yaml::from_str(&raw).map_err(|err| {
+            OrchestratorError::SimulationScript(format!("{}: {err}", path.display()))
+        })
+    }
+
+    /// Checks every scripted step is an agent or command step of the
+    /// orchestrator and only writes output files that step declares.
+    pub fn validate(&self, orchestrator: &OrchestratorConfig) -> Result<(), OrchestratorError> {
+        for (step_id, responses) in &self.steps {
+            let step = orchestrator
+                .workflows
+                .iter()
+                .flat_map(|workflow| workflow.steps.iter())
+                .find(|step| &step.id == step_id)
+                .ok_or_else(|| {
+                    OrchestratorError::SimulationScript(format!(
+                        "step `{step_id}` is not declared by any workflow"
+                    ))
+                })?;
+            if !matches!(
+                step.step_type,
+                WorkflowStepType::AgentTask
+                    | WorkflowStepType::AgentReview
+                    | WorkflowStepType::Command
+            ) {
+                return Err(OrchestratorError::SimulationScript(format!(
+                    "step `{step_id}` is a `{}` step; only agent and command steps take scripted responses",
+                    step.step_type.as_str()
+                )));
+            }
+            if responses.is_empty() {
+                return Err(OrchestratorError::SimulationScript(format!(
+                    "step `{step_id}` has no responses"
+                )));
+            }
+            for (index, response) in responses.iter().enumerate() {
+                if step.step_type == WorkflowStepType::Command
+                    && (response.outputs.is_some()
+                        || response.message.is_some()
+                        || !response.files.is_empty())
+                {
+                    return Err(OrchestratorError::SimulationScript(format!(
+                        "step `{step_id}` response {} is for a command step, which only takes `exit_code`, `stdout` and `stderr`",
+                        index + 1
+                    )));
+                }
+                if response.outputs.is_some() && response.message.is_some() {
+                    return Err(OrchestratorError::SimulationScript(format!(
+                        "step `{step_id}` response {} sets both `outputs` and `message`",
+                        index + 1
+                    )));
+                }
+                if !response.files.is_empty()
+                    && step.prompt_type != WorkflowStepPromptType::FileOutput
+                {
+                    return Err(OrchestratorError::SimulationScript(format!(
+                        "step `{step_id}` response {} sets `files` but the step does not use `prompt_type: file_output`",
+                        index + 1
+                    )));
+                }
+                if let Some(key) = response
+                    .files
+                    .keys()
+                    .find(|key| !step.output_files.keys().any(|name| name.as_str() == *key))
+                {
+                    return Err(OrchestratorError::SimulationScript(format!(
+                        "step `{step_id}` response {} writes undeclared output file `{key}`",
+                        index + 1
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn response(&self, step_id: &str, execution: usize) -> Option<&ScriptedResponse> {
+        let responses = self.steps.get(step_id)?;
+        responses.get(execution).or_else(|| responses.last())
+    }
+}
+
+/// One step attempt executed during a simulation, in execution order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimulatedAttempt {
+    pub step_id: String,
+    pub attempt: u32,
+    /// Prompt rendered for the agent; `None` for steps that invoke no agent.
+    pub prompt: Option<String>,
+    pub next_step_id: Option<String>,
+    pub error: Option<String>,
+    pub output_validation_errors: BTreeMap<String, String>,
+}
+
+/// Stub provider runner that answers agent steps from a `SimulationScript`
+/// and keeps a transcript of every step attempt the engine executed.
+#[derive(Debug, Default)]
+pub struct Simulation {
+    script: SimulationScript,
+    executions: Mutex<BTreeMap<String, usize>>,
+    prompts: Mutex<BTreeMap<(String, u32), String>>,
+    transcript: Mutex<Vec<SimulatedAttempt>>,
+}
+
+impl Simulation {
+    pub fn new(script: SimulationScript) -> Self {
+        Self {
+            script,
+            ..Self::default()
+        }
+    }
+
+    pub fn transcript(&self) -> Vec<SimulatedAttempt> {
+        self.transcript
+            .lock()
+            .map(|transcript| transcript.clone())
+            .unwrap_or_default()
+    }
+
+    /// Answers a provider request for `step` in place of running the
+    /// provider CLI.
+    pub(crate) fn respond(
+        &self,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        request: &ProviderRequest,
+        output_paths: &BTreeMap<String, PathBuf>,
+    ) -> Result<ProviderResult, ProviderError> {
+        if let Ok(prompt) = fs::read_to_string(&request.prompt_artifacts.prompt_file) {
+            if let Ok(mut prompts) = self.prompts.lock() {
+                prompts.insert((step.id.clone(), attempt), prompt);
+            }
+        }
+        let log = InvocationLog {
+            agent_id: request.agent_id.clone(),
+            provider: request.provider.clone(),
+            model: request.model.clone(),
+            command_form: format!("simulated {} attempt {attempt}", step.id),
+            working_directory: request.cwd.clone(),
+            prompt_file: request.prompt_artifacts.prompt_file.clone(),
+            context_files: request.prompt_artifacts.context_files.clone(),
+            exit_code: Some(0),
+            timed_out: false,
+            usage: None,
+            session_id: None,
+            prior_attempts: Vec::new(),
+        };
+        let Some(response) = self.script.response(&step.id, self.next_execution(step)) else {
+            return Err(ProviderError::ParseFailure {
+                provider: request.provider.clone(),
+                reason: format!("simulation script has no response for step `{}`", step.id),
+                log: Some(Box::new(log)),
+            });
+        };
+        if response.exit_code != 0 {
+            return Err(ProviderError::NonZeroExit {
+                provider: request.provider.clone(),
+                exit_code: response.exit_code,
+                stderr: response.stderr.clone(),
+                stdout: response.stdout.clone(),
+                log: Box::new(InvocationLog {
+                    exit_code: Some(response.exit_code),
+                    ..log
+                }),
+            });
+        }
+        for (key, content) in &response.files {
+            let Some(path) = output_paths.get(key) else {
+                continue;
+            };
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| io_error(parent, err))?;
+            }
+            fs::write(path, content).map_err(|err| io_error(path, err))?;
+        }
+        let message = match (&response.message, &response.outputs) {
+            (Some(message), _) => message.clone(),
+            (None, Some(outputs)) => format!(
+                "[workflow_result]{}[/workflow_result]",
+                Value::Object(outputs.clone())
+            ),
+            (None, None) => "simulated".to_string(),
+        };
+        Ok(ProviderResult { message, log })
+    }
+
+    /// Answers a `command` step in place of running its argv. A command step
+    /// without a scripted response exits `0` with no output.
+    pub(crate) fn run_command(&self, step: &WorkflowStepConfig) -> ScriptedResponse {
+        self.script
+            .response(&step.id, self.next_execution(step))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Counts one more execution of `step` and returns the zero-based index
+    /// of this one.
+    fn next_execution(&self, step: &WorkflowStepConfig) -> usize {
+        self.executions
+            .lock()
+            .map(|mut executions| {
+                let count = executions.entry(step.id.clone()).or_default();
+                *count += 1;
+                *count - 1
+            })
+            .unwrap_or_default()
+    }
+
+    /// Appends the outcome of one executed step attempt to the transcript.
+    pub(crate) fn record(
+        &self,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        result: &Result<StepEvaluation, OrchestratorError>,
+    ) {
+        let prompt = self
+            .prompts
+            .lock()
+            .ok()
+            .and_then(|mut prompts| prompts.remove(&(step.id.clone(), attempt)));
+        let (next_step_id, error, output_validation_errors) = match result {
+            Ok(evaluation) => (evaluation.next_step_id.clone(), None, BTreeMap::new()),
+            Err(err) => (
+                None,
+                Some(err.to_string()),
+                output_validation_errors_for(err),
+            ),
+        };
+        if let Ok(mut transcript) = self.transcript.lock() {
+            transcript.push(SimulatedAttempt {
+                step_id: step.id.clone(),
+                attempt,
+                prompt,
+                next_step_id,
+                error,
+                output_validation_errors,
+            });
+        }
+    }
+}
+
+/// Outcome of a simulated workflow run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationReport {
+    pub run_id: String,
+    pub state: RunState,
+    pub terminal_reason: Option<String>,
+    pub attempts: Vec<SimulatedAttempt>,
+}
+
+/// Drives `workflow_id` through `WorkflowEngine` in a throwaway `state_root`
+/// with agent and command steps answered by `script`. Prompt templates are
+/// read from `prompts_root` when given. Runs parked for retry backoff are
+/// resumed at once; a run parked for human review ends the simulation in
+/// `waiting`.
+pub fn simulate_workflow(
+    orchestrator: &OrchestratorConfig,
+    workflow_id: &str,
+    inputs: Map<String, Value>,
+    script: SimulationScript,
+    state_root: &Path,
+    prompts_root: Option<&Path>,
+    now: i64,
+) -> Result<SimulationReport, OrchestratorError> {
+    script.validate(orchestrator)?;
+    let workflow = orchestrator
+        .workflows
+        .iter()
+        .find(|workflow| workflow.id == workflow_id)
+        .ok_or_else(|| {
+            OrchestratorError::Config(format!("workflow `{workflow_id}` is not declared"))
+        })?;
+    fs::create_dir_all(state_root).map_err(|err| OrchestratorError::Io {
+        path: state_root.display().to_string(),
+        source: err,
+    })?;
+    if let Some(prompts_root) = prompts_root.filter(|path| path.is_dir()) {
+        let link = state_root.join(PROMPTS_DIR);
+        create_symlink(prompts_root, &link).map_err(|err| OrchestratorError::Io {
+            path: link.display().to_string(),
+            source: err,
+        })?;
+    }
+
+    let store = WorkflowRunStore::new(state_root);
+    store.create_run_with_inputs(SIMULATION_RUN_ID, workflow, inputs, now)?;
+    let simulation = Arc::new(Simulation::new(script));
+    let engine = WorkflowEngine::new(store.clone(), orchestrator.clone())
+        .with_simulation(Arc::clone(&simulation));
+    let mut outcome = engine.start(SIMULATION_RUN_ID, now);
+    while let Ok(run) = &outcome {
+        match run.retry_at {
+            Some(retry_at) if run.state == RunState::Waiting => {
+                outcome = engine.resume(SIMULATION_RUN_ID, retry_at);
+            }
+            _ => break,
+        }
+    }
+
+    let run = store.load_run(SIMULATION_RUN_ID)?;
+    Ok(SimulationReport {
+        run_id: run.run_id,
+        state: run.state,
+        terminal_reason: run.terminal_reason,
+        attempts: simulation.transcript(),
+    })
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_dir(target, link)
+}
+
+fn io_error(path: &Path, source: std::io::Error) -> ProviderError {
+    ProviderError::Io {
+        path: path.display().to_string(),
+        source,
+    }
+}
@@ -23,23 +23,32 @@ pub fn reconcile_orchestrator_skill_mounts(
 
     let skills_root = private_workspace.join("skills");
     fs::create_dir_all(&skills_root).map_err(|err| io_error(&skills_root, err))?;
+    reconcile_skill_mounts_at(orchestrator_id, &private_workspace, &skills_root)
+}
 
-    let agents_root = private_workspace.join(".agents");
+/// Points `<workspace>/.agents/skills` and `<workspace>/.claude/skills` at
+/// the orchestrator `skills_root`, repairing links that target elsewhere.
+pub(crate) fn reconcile_skill_mounts_at(
+    orchestrator_id: &str,
+    workspace: &Path,
+    skills_root: &Path,
+) -> Result<(), OrchestratorError> {
+    let agents_root = workspace.join(".agents");
     fs::create_dir_all(&agents_root).map_err(|err| io_error(&agents_root, err))?;
     reconcile_mount_path(
         orchestrator_id,
         "agent",
         &agents_root.join("skills"),
-        &skills_root,
+        skills_root,
     )?;
 
-    let claude_root = private_workspace.join(".claude");
+    let claude_root = workspace.join(".claude");
     fs::create_dir_all(&claude_root).map_err(|err| io_error(&claude_root, err))?;
     reconcile_mount_path(
         orchestrator_id,
         "claude",
         &claude_root.join("skills"),
-        &skills_root,
+        skills_root,
     )
 }
 
@@ -1,40 +1,83 @@
 use crate::config::{
-    OrchestratorConfig, WorkflowConfig, WorkflowStepConfig, WorkflowStepWorkspaceMode,
+    AgentConfig, AgentEndpointConfig, OrchestratorConfig, WorkflowConfig, WorkflowStepConfig,
+    WorkflowStepType, WorkflowStepWorkspaceMode,
 };
 use crate::memory::{
     persist_workflow_output_memories, MemoryPaths, MemoryRepository, WorkflowOutputWriteback,
 };
 use crate::orchestration::diagnostics::{
-    append_security_log, persist_provider_invocation_log, provider_error_log,
+    append_security_log, persist_command_invocation_log, persist_provider_invocation_log,
+    provider_error_log, CommandInvocationLog,
 };
 use crate::orchestration::error::OrchestratorError;
 use crate::orchestration::output_contract::{
-    evaluate_step_result, materialize_output_files, resolve_step_output_paths, StepEvaluation,
+    evaluate_step_outputs, evaluate_step_result, materialize_output_files,
+    resolve_step_output_paths, StepEvaluation,
 };
 use crate::orchestration::prompt_render::{render_step_prompt, StepSharedWorkspaceContext};
+use crate::orchestration::run_events::RunEventKind;
 use crate::orchestration::run_store::{StepAttemptRecord, WorkflowRunRecord, WorkflowRunStore};
+use crate::orchestration::run_worktree::{ensure_run_worktree, RunWorktree};
+use crate::orchestration::shared_mounts::reconcile_shared_mounts_at;
+use crate::orchestration::simulation::{ScriptedResponse, Simulation};
+use crate::orchestration::skills_mounts::reconcile_skill_mounts_at;
 use crate::orchestration::workspace_access::{enforce_workspace_access, WorkspaceAccessContext};
 use crate::prompts::{
     context_path_for_prompt_reference, default_step_context, is_prompt_template_reference,
-    resolve_prompt_template_path, PROMPTS_DIR,
+    render_foreach_item_placeholders, resolve_prompt_template_path, PROMPTS_DIR,
 };
 use crate::provider::{
-    consume_reset_flag, run_provider, write_file_backed_prompt, PromptArtifacts, ProviderError,
+    consume_reset_flag, run_captured, run_provider, write_file_backed_prompt, CapturedProcess,
+    InvocationLog, PromptArtifacts, ProviderEndpoint, ProviderError, ProviderFallback,
     ProviderKind, ProviderRequest, RunnerBinaries,
 };
 use serde_json::{Map, Value};
 use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Bytes of a command's stdout or stderr kept in its step outputs.
+const COMMAND_OUTPUT_TAIL_BYTES: usize = 16 * 1024;
 
 pub(crate) struct StepExecutionContext<'a> {
     pub run_store: &'a WorkflowRunStore,
     pub orchestrator: &'a OrchestratorConfig,
     pub workspace_access_context: Option<&'a WorkspaceAccessContext>,
     pub runner_binaries: &'a RunnerBinaries,
+    /// Answers agent steps from a script instead of provider CLIs.
+    pub simulation: Option<&'a Simulation>,
     pub step_timeout_seconds: u64,
     pub memory_enabled: bool,
+    pub foreach_item: Option<ForeachItem>,
+    /// Concurrent branch the attempt runs in. Agents keep one provider
+    /// session per branch so parallel attempts never resume each other's.
+    pub session_branch: Option<String>,
+}
+
+/// The `foreach` element a sequence step attempt runs for, plus the outputs
+/// earlier sequence steps produced for the same element.
+#[derive(Debug, Clone)]
+pub(crate) struct ForeachItem {
+    pub index: usize,
+    pub value: Value,
+    pub sequence_outputs: BTreeMap<String, Map<String, Value>>,
+}
+
+impl ForeachItem {
+    fn render(
+        &self,
+        step: &WorkflowStepConfig,
+        template: &str,
+    ) -> Result<String, OrchestratorError> {
+        render_foreach_item_placeholders(template, &self.value, self.index).map_err(|reason| {
+            OrchestratorError::StepPromptRender {
+                step_id: step.id.clone(),
+                reason,
+            }
+        })
+    }
 }
 
 pub(crate) fn execute_step_attempt(
@@ -45,30 +88,26 @@ pub(crate) fn execute_step_attempt(
     attempt: u32,
     now: i64,
 ) -> Result<StepEvaluation, OrchestratorError> {
-    let orchestrator_workspace = if let Some(workspace) = context.workspace_access_context {
-        workspace.private_workspace_root.clone()
-    } else {
-        context.run_store.state_root().to_path_buf()
-    };
-    let run_workspace = if let Some(workspace) = context.workspace_access_context {
-        workspace
-            .private_workspace_root
-            .join("work")
-            .join("runs")
-            .join(&run.run_id)
+    let result = if step.step_type == WorkflowStepType::Command {
+        execute_command_attempt(context, run, workflow, step, attempt, now)
     } else {
-        context
-            .run_store
-            .state_root()
-            .join("work")
-            .join("runs")
-            .join(&run.run_id)
-    };
-    let prompt_root = if let Some(workspace) = context.workspace_access_context {
-        workspace.private_workspace_root.join(PROMPTS_DIR)
-    } else {
-        context.run_store.state_root().join(PROMPTS_DIR)
+        execute_agent_attempt(context, run, workflow, step, attempt, now)
     };
+    if let Some(simulation) = context.simulation {
+        simulation.record(step, attempt, &result);
+    }
+    result
+}
+
+fn execute_agent_attempt(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+    now: i64,
+) -> Result<StepEvaluation, OrchestratorError> {
+    let prompt_root = prompt_root(context);
 
     let agent = context
         .orchestrator
@@ -78,64 +117,38 @@ pub(crate) fn execute_step_attempt(
             step_id: step.id.clone(),
             reason: format!("step references unknown agent `{}`", step.agent),
         })?;
-    let step_workspace = match step.workspace_mode {
-        WorkflowStepWorkspaceMode::OrchestratorWorkspace => orchestrator_workspace.clone(),
-        WorkflowStepWorkspaceMode::RunWorkspace => run_workspace.clone(),
+    let StepWorkspaces {
+        orchestrator: orchestrator_workspace,
+        run: run_workspace,
+        step: step_workspace,
+    } = prepare_step_workspaces(context, run, step)?;
+    let execution_cwd = match step.workspace_mode {
+        WorkflowStepWorkspaceMode::AgentWorkspace => step_workspace.clone(),
+        _ => orchestrator_workspace.clone(),
     };
-    let execution_cwd = orchestrator_workspace.clone();
 
-    if let Some(workspace) = context.workspace_access_context {
-        if let Err(err) = enforce_workspace_access(
-            workspace,
-            &[
-                orchestrator_workspace.clone(),
-                run_workspace.clone(),
-                step_workspace.clone(),
-            ],
-        ) {
-            append_security_log(
-                context.run_store.state_root(),
-                &format!(
-                    "workspace access denied for run `{}` step `{}`: {}",
-                    run.run_id, step.id, err
-                ),
-            );
-            return Err(err);
-        }
-    }
-    fs::create_dir_all(&orchestrator_workspace)
-        .map_err(|err| io_error(&orchestrator_workspace, err))?;
-    fs::create_dir_all(&run_workspace).map_err(|err| io_error(&run_workspace, err))?;
-    fs::create_dir_all(&step_workspace).map_err(|err| io_error(&step_workspace, err))?;
-
-    let step_outputs = load_latest_step_outputs(
+    let mut step_outputs = load_latest_step_outputs(
         context.run_store.state_root(),
         &run.run_id,
         workflow,
         &step.id,
     )?;
-    let output_paths =
-        match resolve_step_output_paths(context.run_store.state_root(), &run.run_id, step, attempt)
-        {
-            Ok(paths) => paths,
-            Err(err @ OrchestratorError::OutputPathValidation { .. }) => {
-                append_security_log(
-                    context.run_store.state_root(),
-                    &format!(
-                        "output path validation denied for run `{}` step `{}` attempt `{}`: {}",
-                        run.run_id, step.id, attempt, err
-                    ),
-                );
-                return Err(err);
+    if let Some(item) = &context.foreach_item {
+        step_outputs.extend(item.sequence_outputs.clone());
+    }
+    let output_paths = resolve_attempt_output_paths(context, run, step, attempt)?;
+    let (mut prompt_template, mut context_template) =
+        load_step_templates(&prompt_root, workflow, step).map_err(|reason| {
+            OrchestratorError::StepExecution {
+                step_id: step.id.clone(),
+                reason,
             }
-            Err(err) => return Err(err),
-        };
-    let (prompt_template, context_template) = load_step_templates(&prompt_root, workflow, step)
-        .map_err(|reason| OrchestratorError::StepExecution {
-            step_id: step.id.clone(),
-            reason,
         })?;
-    let rendered = render_step_prompt(
+    if let Some(item) = &context.foreach_item {
+        prompt_template = item.render(step, &prompt_template)?;
+        context_template = item.render(step, &context_template)?;
+    }
+    let mut rendered = render_step_prompt(
         run,
         workflow,
         step,
@@ -143,34 +156,21 @@ pub(crate) fn execute_step_attempt(
         &run_workspace,
         &output_paths,
         &step_outputs,
-        &context
-            .workspace_access_context
-            .map(|workspace| {
-                workspace
-                    .shared_workspaces
-                    .iter()
-                    .map(|(name, shared)| StepSharedWorkspaceContext {
-                        name: name.clone(),
-                        path: format!("./shared/{name}"),
-                        description: shared.description.clone(),
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default(),
+        &shared_workspace_contexts(context),
         &prompt_template,
         &context_template,
     )?;
+    if let Some(feedback) = previous_attempt_validation_feedback(context, run, step, attempt) {
+        rendered.prompt.push_str(&feedback);
+    }
 
     let attempt_dir = context
         .run_store
-        .state_root()
-        .join("workflows/runs")
-        .join(&run.run_id)
-        .join("steps")
-        .join(&step.id)
-        .join("attempts")
-        .join(attempt.to_string());
+        .step_attempt_dir(&run.run_id, &step.id, attempt);
     fs::create_dir_all(&attempt_dir).map_err(|err| io_error(&attempt_dir, err))?;
+    // A revisited step reuses attempt directories; drop the previous visit's
+    // invocation log so its usage is not attributed to this attempt.
+    let _ = fs::remove_file(attempt_dir.join("provider_invocation.json"));
 
     let artifacts = write_file_backed_prompt(
         &attempt_dir,
@@ -190,12 +190,26 @@ pub(crate) fn execute_step_attempt(
             reason: err.to_string(),
         })?;
 
+    let session_id = if reset_resolution.reset_requested {
+        None
+    } else {
+        context.run_store.provider_session(
+            &run.run_id,
+            &provider_session_key(context, &step.agent),
+            agent.provider.as_str(),
+        )?
+    };
     let provider_kind = ProviderKind::try_from(agent.provider.as_str()).map_err(|err| {
         OrchestratorError::StepExecution {
             step_id: step.id.clone(),
             reason: err.to_string(),
         }
     })?;
+    let fallbacks =
+        agent_provider_fallbacks(agent).map_err(|err| OrchestratorError::StepExecution {
+            step_id: step.id.clone(),
+            reason: err.to_string(),
+        })?;
     let provider_request = ProviderRequest {
         agent_id: step.agent.clone(),
         provider: provider_kind,
@@ -207,26 +221,46 @@ pub(crate) fn execute_step_attempt(
         reset_requested: reset_resolution.reset_requested,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: context.run_store.cancellation_token(&run.run_id),
+        endpoint: agent_provider_endpoint(agent),
+        session_id,
+        fallbacks,
     };
 
-    let provider_output =
-        run_provider(&provider_request, context.runner_binaries).map_err(|err| {
-            if let Some(log) = provider_error_log(&err) {
-                let _ = persist_provider_invocation_log(&attempt_dir, log);
-            }
-            match err {
-                ProviderError::Timeout { .. } => OrchestratorError::StepTimeout {
-                    step_timeout_seconds: context.step_timeout_seconds,
-                },
-                _ => OrchestratorError::StepExecution {
-                    step_id: step.id.clone(),
-                    reason: err.to_string(),
-                },
-            }
-        })?;
+    let provider_output = match context.simulation {
+        Some(simulation) => simulation.respond(step, attempt, &provider_request, &output_paths),
+        None => run_provider(&provider_request, context.runner_binaries),
+    }
+    .map_err(|err| {
+        if let Some(log) = provider_error_log(&err) {
+            let _ = persist_provider_invocation_log(&attempt_dir, log);
+            let _ = record_provider_invoked(context, run, &step.id, attempt, now, log);
+            let _ = record_provider_session(context, run, now, log);
+        }
+        match err {
+            ProviderError::Timeout { .. } => OrchestratorError::StepTimeout {
+                step_timeout_seconds: context.step_timeout_seconds,
+            },
+            ProviderError::Canceled { .. } => OrchestratorError::StepCanceled {
+                step_id: step.id.clone(),
+                attempt,
+            },
+            ProviderError::NonZeroExit { .. } => OrchestratorError::StepNonZeroExit {
+                step_id: step.id.clone(),
+                rate_limited: err.is_rate_limited(),
+                reason: err.to_string(),
+            },
+            _ => OrchestratorError::StepExecution {
+                step_id: step.id.clone(),
+                reason: err.to_string(),
+            },
+        }
+    })?;
 
     persist_provider_invocation_log(&attempt_dir, &provider_output.log)
         .map_err(|err| io_error(&attempt_dir, err))?;
+    record_provider_invoked(context, run, &step.id, attempt, now, &provider_output.log)?;
+    record_provider_session(context, run, now, &provider_output.log)?;
 
     let mut evaluation =
         evaluate_step_result(workflow, step, &provider_output.message, &output_paths)?;
@@ -284,6 +318,602 @@ pub(crate) fn execute_step_attempt(
     Ok(evaluation)
 }
 
+struct StepWorkspaces {
+    orchestrator: PathBuf,
+    run: PathBuf,
+    step: PathBuf,
+}
+
+/// Journals a `provider_invoked` event for the invocation recorded in `log`.
+fn record_provider_invoked(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    step_id: &str,
+    attempt: u32,
+    now: i64,
+    log: &InvocationLog,
+) -> Result<(), OrchestratorError> {
+    context.run_store.append_run_event(
+        &run.run_id,
+        now,
+        RunEventKind::ProviderInvoked {
+            step_id: step_id.to_string(),
+            attempt,
+            agent_id: log.agent_id.clone(),
+            provider: log.provider.to_string(),
+            model: log.model.clone(),
+            exit_code: log.exit_code,
+            timed_out: log.timed_out,
+            fallback_from: log
+                .prior_attempts
+                .iter()
+                .map(|attempt| attempt.provider.to_string())
+                .collect(),
+        },
+    )?;
+    Ok(())
+}
+
+/// Lists the output violations that failed the previous attempt so a retry
+/// can correct them instead of repeating the same output.
+fn previous_attempt_validation_feedback(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Option<String> {
+    let previous_attempt = attempt.checked_sub(1).filter(|value| *value > 0)?;
+    let previous = context
+        .run_store
+        .load_step_attempt(&run.run_id, &step.id, previous_attempt)
+        .ok()?;
+    if previous.output_validation_errors.is_empty()
+        || previous.item_index != context.foreach_item.as_ref().map(|item| item.index)
+    {
+        return None;
+    }
+    let mut feedback = format!(
+        "\n\nAttempt {previous_attempt} was rejected because its outputs did not match the output contract:\n"
+    );
+    for (path, detail) in &previous.output_validation_errors {
+        feedback.push_str(&format!("- `{path}`: {detail}\n"));
+    }
+    feedback.push_str("Fix every violation above in this attempt's outputs.\n");
+    Some(feedback)
+}
+
+/// Resolves the orchestrator, run and step workspaces for a step attempt,
+/// enforces workspace access, and creates the directories.
+fn prepare_step_workspaces(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    step: &WorkflowStepConfig,
+) -> Result<StepWorkspaces, OrchestratorError> {
+    let orchestrator_workspace = orchestrator_workspace_root(context);
+    let run_workspace = run_workspace_root(context, run);
+    let step_workspace = match step.workspace_mode {
+        WorkflowStepWorkspaceMode::OrchestratorWorkspace => orchestrator_workspace.clone(),
+        WorkflowStepWorkspaceMode::RunWorkspace => run_workspace.clone(),
+        // Shared by every run of the orchestrator, so clones and caches survive.
+        WorkflowStepWorkspaceMode::AgentWorkspace => orchestrator_workspace
+            .join("work")
+            .join("agents")
+            .join(&step.agent),
+    };
+
+    if let Some(workspace) = context.workspace_access_context {
+        if let Err(err) = enforce_workspace_access(
+            workspace,
+            &[
+                orchestrator_workspace.clone(),
+                run_workspace.clone(),
+                step_workspace.clone(),
+            ],
+        ) {
+            append_security_log(
+                context.run_store.state_root(),
+                &format!(
+                    "workspace access denied for run `{}` step `{}`: {}",
+                    run.run_id, step.id, err
+                ),
+            );
+            return Err(err);
+        }
+    }
+    fs::create_dir_all(&orchestrator_workspace)
+        .map_err(|err| io_error(&orchestrator_workspace, err))?;
+    fs::create_dir_all(&run_workspace).map_err(|err| io_error(&run_workspace, err))?;
+    fs::create_dir_all(&step_workspace).map_err(|err| io_error(&step_workspace, err))?;
+    if step.workspace_mode == WorkflowStepWorkspaceMode::AgentWorkspace {
+        mount_agent_workspace(context, &orchestrator_workspace, &step_workspace)?;
+    }
+    Ok(StepWorkspaces {
+        orchestrator: orchestrator_workspace,
+        run: run_workspace,
+        step: step_workspace,
+    })
+}
+
+fn resolve_attempt_output_paths(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Result<BTreeMap<String, PathBuf>, OrchestratorError> {
+    match resolve_step_output_paths(context.run_store.state_root(), &run.run_id, step, attempt) {
+        Ok(paths) => Ok(paths),
+        Err(err @ OrchestratorError::OutputPathValidation { .. }) => {
+            append_security_log(
+                context.run_store.state_root(),
+                &format!(
+                    "output path validation denied for run `{}` step `{}` attempt `{}`: {}",
+                    run.run_id, step.id, attempt, err
+                ),
+            );
+            Err(err)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Runs a `command` step's argv in the step workspace, or takes its result
+/// from the simulation script, and maps the exit status to the step outputs
+/// (`exit_code`, `stdout`, `stderr`, `decision`, `summary`).
+fn execute_command_attempt(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+    now: i64,
+) -> Result<StepEvaluation, OrchestratorError> {
+    let workspaces = prepare_step_workspaces(context, run, step)?;
+    let mut step_outputs = load_latest_step_outputs(
+        context.run_store.state_root(),
+        &run.run_id,
+        workflow,
+        &step.id,
+    )?;
+    if let Some(item) = &context.foreach_item {
+        step_outputs.extend(item.sequence_outputs.clone());
+    }
+    let output_paths = resolve_attempt_output_paths(context, run, step, attempt)?;
+    let shared_workspaces = shared_workspace_contexts(context);
+    let mut argv = Vec::with_capacity(step.command.len());
+    for arg in &step.command {
+        let arg = match &context.foreach_item {
+            Some(item) => item.render(step, arg)?,
+            None => arg.clone(),
+        };
+        let rendered = render_step_prompt(
+            run,
+            workflow,
+            step,
+            attempt,
+            &workspaces.run,
+            &output_paths,
+            &step_outputs,
+            &shared_workspaces,
+            &arg,
+            "",
+        )?;
+        argv.push(rendered.prompt);
+    }
+    let Some(program) = argv.first().cloned() else {
+        return Err(OrchestratorError::StepExecution {
+            step_id: step.id.clone(),
+            reason: "command step has an empty `command`".to_string(),
+        });
+    };
+
+    let attempt_dir = context
+        .run_store
+        .step_attempt_dir(&run.run_id, &step.id, attempt);
+    fs::create_dir_all(&attempt_dir).map_err(|err| io_error(&attempt_dir, err))?;
+
+    let timeout = Duration::from_secs(context.step_timeout_seconds);
+    let started = Instant::now();
+    let captured = match context.simulation {
+        Some(simulation) => Ok(CommandOutcome::from(simulation.run_command(step))),
+        None => {
+            let mut command = Command::new(&program);
+            command
+                .args(&argv[1..])
+                .current_dir(&workspaces.step)
+                .env("DIRECLAW_RUN_ID", &run.run_id)
+                .env("DIRECLAW_STEP_ID", &step.id)
+                .env("DIRECLAW_ATTEMPT", attempt.to_string())
+                .env("DIRECLAW_RUN_WORKSPACE", &workspaces.run);
+            run_captured(
+                &mut command,
+                timeout,
+                &context.run_store.cancellation_token(&run.run_id),
+            )
+            .map(CommandOutcome::from)
+        }
+    };
+    let mut log = CommandInvocationLog {
+        argv: argv.clone(),
+        working_directory: workspaces.step.clone(),
+        timeout_ms: timeout.as_millis() as u64,
+        duration_ms: started.elapsed().as_millis() as u64,
+        exit_code: None,
+        timed_out: false,
+    };
+    let captured = match captured {
+        Ok(captured) => captured,
+        Err(err) => {
+            persist_command_invocation_log(&attempt_dir, &log)
+                .map_err(|err| io_error(&attempt_dir, err))?;
+            return Err(OrchestratorError::StepExecution {
+                step_id: step.id.clone(),
+                reason: if err.kind() == std::io::ErrorKind::NotFound {
+                    format!("command program `{program}` was not found")
+                } else {
+                    format!("failed to run command `{program}`: {err}")
+                },
+            });
+        }
+    };
+    log.exit_code = captured.exit_code;
+    log.timed_out = captured.timed_out;
+    persist_command_invocation_log(&attempt_dir, &log)
+        .map_err(|err| io_error(&attempt_dir, err))?;
+    for (file, body) in [
+        ("command_stdout.txt", &captured.stdout),
+        ("command_stderr.txt", &captured.stderr),
+    ] {
+        let path = attempt_dir.join(file);
+        fs::write(&path, body).map_err(|err| io_error(&path, err))?;
+    }
+    if captured.canceled {
+        return Err(OrchestratorError::StepCanceled {
+            step_id: step.id.clone(),
+            attempt,
+        });
+    }
+    if captured.timed_out {
+        return Err(OrchestratorError::StepTimeout {
+            step_timeout_seconds: context.step_timeout_seconds,
+        });
+    }
+
+    let command_form = log.command_form();
+    let succeeded = captured.exit_code == Some(0);
+    let status = match captured.exit_code {
+        Some(code) => format!("status {code}"),
+        None => "without an exit status".to_string(),
+    };
+    if !succeeded && !step.routes_on_decision() {
+        let stderr = command_output_tail(&captured.stderr, "command_stderr.txt");
+        let stderr = stderr.trim();
+        return Err(OrchestratorError::StepNonZeroExit {
+            step_id: step.id.clone(),
+            reason: if stderr.is_empty() {
+                format!("command `{command_form}` exited with {status}")
+            } else {
+                format!("command `{command_form}` exited with {status}: {stderr}")
+            },
+            rate_limited: false,
+        });
+    }
+
+    let mut outputs = Map::new();
+    outputs.insert(
+        "decision".to_string(),
+        Value::String(if succeeded { "approve" } else { "reject" }.to_string()),
+    );
+    for key in &step.outputs {
+        let value = match key.as_str() {
+            "summary" => Value::String(format!("command `{command_form}` exited with {status}")),
+            "exit_code" => captured.exit_code.map(Value::from).unwrap_or(Value::Null),
+            "stdout" => Value::String(command_output_tail(&captured.stdout, "command_stdout.txt")),
+            "stderr" => Value::String(command_output_tail(&captured.stderr, "command_stderr.txt")),
+            _ => continue,
+        };
+        outputs.insert(key.name.clone(), value);
+    }
+    let mut evaluation = evaluate_step_outputs(workflow, step, outputs)?;
+    evaluation.output_files = materialize_output_files(step, &evaluation.outputs, &output_paths)?;
+    context.run_store.append_engine_log(
+        &run.run_id,
+        now,
+        format!(
+            "run_id={} step_id={} attempt={} command={} exit_code={} cwd={}",
+            run.run_id,
+            step.id,
+            attempt,
+            program,
+            log.exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+            workspaces.step.display(),
+        ),
+    )?;
+    Ok(evaluation)
+}
+
+/// Keeps the last `COMMAND_OUTPUT_TAIL_BYTES` of a command's stdout or
+/// stderr for step outputs, prompts and memory; the full text is only in the
+/// attempt's `file`.
+fn command_output_tail(text: &str, file: &str) -> String {
+    if text.len() <= COMMAND_OUTPUT_TAIL_BYTES {
+        return text.to_string();
+    }
+    let mut start = text.len() - COMMAND_OUTPUT_TAIL_BYTES;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!(
+        "[{start} earlier bytes truncated; full output in {file}]\n{}",
+        &text[start..]
+    )
+}
+
+/// What a `command` step's process, or its simulated stand-in, produced.
+struct CommandOutcome {
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    timed_out: bool,
+    canceled: bool,
+}
+
+impl From<CapturedProcess> for CommandOutcome {
+    fn from(captured: CapturedProcess) -> Self {
+        Self {
+            exit_code: captured.exit_status.code(),
+            stdout: captured.stdout,
+            stderr: captured.stderr,
+            timed_out: captured.timed_out,
+            canceled: captured.canceled,
+        }
+    }
+}
+
+impl From<ScriptedResponse> for CommandOutcome {
+    fn from(response: ScriptedResponse) -> Self {
+        Self {
+            exit_code: Some(response.exit_code),
+            stdout: response.stdout,
+            stderr: response.stderr,
+            timed_out: false,
+            canceled: false,
+        }
+    }
+}
+
+/// Renders the message posted to the originating conversation when a
+/// `human_review` step parks its run.
+pub(crate) fn render_human_review_message(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Result<String, OrchestratorError> {
+    let step_outputs = load_latest_step_outputs(
+        context.run_store.state_root(),
+        &run.run_id,
+        workflow,
+        &step.id,
+    )?;
+    let output_paths =
+        resolve_step_output_paths(context.run_store.state_root(), &run.run_id, step, attempt)?;
+    let (prompt_template, context_template) =
+        load_step_templates(&prompt_root(context), workflow, step).map_err(|reason| {
+            OrchestratorError::StepExecution {
+                step_id: step.id.clone(),
+                reason,
+            }
+        })?;
+    let rendered = render_step_prompt(
+        run,
+        workflow,
+        step,
+        attempt,
+        &run_workspace_root(context, run),
+        &output_paths,
+        &step_outputs,
+        &shared_workspace_contexts(context),
+        &prompt_template,
+        &context_template,
+    )?;
+    Ok(rendered.prompt.trim().to_string())
+}
+
+/// Renders a `workflow_call` step's `inputs` templates into the child run inputs.
+pub(crate) fn render_workflow_call_inputs(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Result<Map<String, Value>, OrchestratorError> {
+    let step_outputs = load_latest_step_outputs(
+        context.run_store.state_root(),
+        &run.run_id,
+        workflow,
+        &step.id,
+    )?;
+    let output_paths =
+        resolve_step_output_paths(context.run_store.state_root(), &run.run_id, step, attempt)?;
+    let run_workspace = run_workspace_root(context, run);
+    let shared_workspaces = shared_workspace_contexts(context);
+    let mut inputs = Map::new();
+    for (key, template) in &step.inputs {
+        let rendered = render_step_prompt(
+            run,
+            workflow,
+            step,
+            attempt,
+            &run_workspace,
+            &output_paths,
+            &step_outputs,
+            &shared_workspaces,
+            template,
+            "",
+        )?;
+        inputs.insert(key.clone(), Value::String(rendered.prompt));
+    }
+    Ok(inputs)
+}
+
+/// Renders a `foreach` step's `items` template and parses it as a JSON array.
+pub(crate) fn render_foreach_items(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Result<Vec<Value>, OrchestratorError> {
+    let template = step.items.as_deref().unwrap_or_default();
+    let step_outputs = load_latest_step_outputs(
+        context.run_store.state_root(),
+        &run.run_id,
+        workflow,
+        &step.id,
+    )?;
+    let output_paths =
+        resolve_step_output_paths(context.run_store.state_root(), &run.run_id, step, attempt)?;
+    let rendered = render_step_prompt(
+        run,
+        workflow,
+        step,
+        attempt,
+        &run_workspace_root(context, run),
+        &output_paths,
+        &step_outputs,
+        &shared_workspace_contexts(context),
+        template,
+        "",
+    )?;
+    match serde_json::from_str::<Value>(rendered.prompt.trim()) {
+        Ok(Value::Array(items)) => Ok(items),
+        _ => Err(OrchestratorError::StepExecution {
+            step_id: step.id.clone(),
+            reason: format!(
+                "foreach `items` must render to a JSON array, got `{}`",
+                rendered.prompt.trim()
+            ),
+        }),
+    }
+}
+
+/// Makes the run workspace a git worktree of the workflow `repository`, if
+/// any. Relative repository paths resolve against the orchestrator workspace.
+/// Simulated runs never touch the repository.
+pub(crate) fn prepare_run_worktree(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    workflow: &WorkflowConfig,
+) -> Result<Option<RunWorktree>, OrchestratorError> {
+    if context.simulation.is_some() {
+        return Ok(None);
+    }
+    let Some(repository) = workflow.repository.as_deref() else {
+        return Ok(None);
+    };
+    let repository = orchestrator_workspace_root(context).join(repository);
+    if let Some(workspace) = context.workspace_access_context {
+        if let Err(err) = enforce_workspace_access(workspace, std::slice::from_ref(&repository)) {
+            append_security_log(
+                context.run_store.state_root(),
+                &format!(
+                    "workspace access denied for run `{}` repository `{}`: {}",
+                    run.run_id,
+                    repository.display(),
+                    err
+                ),
+            );
+            return Err(err);
+        }
+    }
+    ensure_run_worktree(
+        context.run_store,
+        &run.run_id,
+        &repository,
+        &run_workspace_root(context, run),
+    )
+    .map(Some)
+}
+
+fn orchestrator_workspace_root(context: &StepExecutionContext<'_>) -> PathBuf {
+    if let Some(workspace) = context.workspace_access_context {
+        workspace.private_workspace_root.clone()
+    } else {
+        context.run_store.state_root().to_path_buf()
+    }
+}
+
+fn run_workspace_root(context: &StepExecutionContext<'_>, run: &WorkflowRunRecord) -> PathBuf {
+    if let Some(workspace) = context.workspace_access_context {
+        workspace
+            .private_workspace_root
+            .join("work")
+            .join("runs")
+            .join(&run.run_id)
+    } else {
+        context
+            .run_store
+            .state_root()
+            .join("work")
+            .join("runs")
+            .join(&run.run_id)
+    }
+}
+
+/// Gives an agent workspace the same skill mounts and shared workspace
+/// symlinks as the orchestrator workspace, since the agent runs from it.
+fn mount_agent_workspace(
+    context: &StepExecutionContext<'_>,
+    orchestrator_workspace: &Path,
+    agent_workspace: &Path,
+) -> Result<(), OrchestratorError> {
+    let orchestrator_id = context.orchestrator.id.as_str();
+    let skills_root = orchestrator_workspace.join("skills");
+    fs::create_dir_all(&skills_root).map_err(|err| io_error(&skills_root, err))?;
+    reconcile_skill_mounts_at(orchestrator_id, agent_workspace, &skills_root)?;
+    let shared = context
+        .workspace_access_context
+        .map(|workspace| {
+            workspace
+                .shared_workspaces
+                .iter()
+                .map(|(name, shared)| (name.clone(), shared.root.clone()))
+                .collect::<BTreeMap<_, _>>()
+        })
+        .unwrap_or_default();
+    reconcile_shared_mounts_at(orchestrator_id, &agent_workspace.join("shared"), &shared)
+}
+
+fn prompt_root(context: &StepExecutionContext<'_>) -> PathBuf {
+    if let Some(workspace) = context.workspace_access_context {
+        workspace.private_workspace_root.join(PROMPTS_DIR)
+    } else {
+        context.run_store.state_root().join(PROMPTS_DIR)
+    }
+}
+
+fn shared_workspace_contexts(
+    context: &StepExecutionContext<'_>,
+) -> Vec<StepSharedWorkspaceContext> {
+    context
+        .workspace_access_context
+        .map(|workspace| {
+            workspace
+                .shared_workspaces
+                .iter()
+                .map(|(name, shared)| StepSharedWorkspaceContext {
+                    name: name.clone(),
+                    path: format!("./shared/{name}"),
+                    description: shared.description.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default()
+}
+
 fn load_step_templates(
     prompt_root: &Path,
     workflow: &WorkflowConfig,
@@ -315,15 +945,68 @@ fn load_step_templates(
     Ok((prompt_template, context_template))
 }
 
-pub fn resolve_runner_binaries() -> RunnerBinaries {
-    RunnerBinaries {
-        anthropic: std::env::var("DIRECLAW_PROVIDER_BIN_ANTHROPIC")
-            .unwrap_or_else(|_| "claude".to_string()),
-        openai: std::env::var("DIRECLAW_PROVIDER_BIN_OPENAI")
-            .unwrap_or_else(|_| "codex".to_string()),
+/// Remembers the session the agent ran in so its next attempt in the run
+/// resumes it.
+fn record_provider_session(
+    context: &StepExecutionContext<'_>,
+    run: &WorkflowRunRecord,
+    now: i64,
+    log: &InvocationLog,
+) -> Result<(), OrchestratorError> {
+    let Some(session_id) = &log.session_id else {
+        return Ok(());
+    };
+    context.run_store.record_provider_session(
+        &run.run_id,
+        &provider_session_key(context, &log.agent_id),
+        &log.provider.to_string(),
+        session_id,
+        now,
+    )
+}
+
+/// Key an agent's session is recorded under in `provider_sessions.json`:
+/// the agent id, suffixed with `@<branch>` inside a concurrent branch.
+fn provider_session_key(context: &StepExecutionContext<'_>, agent_id: &str) -> String {
+    match &context.session_branch {
+        Some(branch) => format!("{agent_id}@{branch}"),
+        None => agent_id.to_string(),
     }
 }
 
+/// HTTP endpoint of an `openai_compatible` agent, as the provider expects it.
+pub(crate) fn agent_provider_endpoint(agent: &AgentConfig) -> Option<ProviderEndpoint> {
+    agent.endpoint.as_ref().map(provider_endpoint)
+}
+
+/// Fallback chain of `agent`, as the provider expects it.
+pub(crate) fn agent_provider_fallbacks(
+    agent: &AgentConfig,
+) -> Result<Vec<ProviderFallback>, ProviderError> {
+    agent
+        .fallbacks
+        .iter()
+        .map(|fallback| {
+            Ok(ProviderFallback {
+                provider: ProviderKind::try_from(fallback.provider.as_str())?,
+                model: fallback.model.clone(),
+                endpoint: fallback.endpoint.as_ref().map(provider_endpoint),
+            })
+        })
+        .collect()
+}
+
+fn provider_endpoint(endpoint: &AgentEndpointConfig) -> ProviderEndpoint {
+    ProviderEndpoint {
+        base_url: endpoint.base_url.trim().to_string(),
+        api_key_env: endpoint.api_key_env.clone(),
+    }
+}
+
+pub fn resolve_runner_binaries() -> RunnerBinaries {
+    RunnerBinaries::from_env()
+}
+
 fn io_error(path: &Path, source: std::io::Error) -> OrchestratorError {
     OrchestratorError::Io {
         path: path.display().to_string(),
@@ -1,4 +1,4 @@
-use crate::config::OrchestratorConfig;
+use crate::config::{OrchestratorConfig, WorkflowConfig};
 use crate::orchestration::diagnostics::append_security_log;
 use crate::orchestration::error::OrchestratorError;
 use crate::orchestration::function_registry::{FunctionCall, FunctionRegistry};
@@ -44,7 +44,7 @@ fn base36_encode_fixed_u32(mut value: u32, width: usize) -> String {
     chars.into_iter().collect()
 }
 
-fn generate_compact_run_id(now: i64) -> Result<String, OrchestratorError> {
+pub(crate) fn generate_compact_run_id(now: i64) -> Result<String, OrchestratorError> {
     let timestamp = u64::try_from(now).map_err(|_| {
         OrchestratorError::SelectorValidation(
             "workflow_start requires a non-negative timestamp".to_string(),
@@ -68,7 +68,7 @@ fn run_metadata_path(state_root: &Path, run_id: &str) -> std::path::PathBuf {
         .join(format!("{run_id}.json"))
 }
 
-fn allocate_compact_run_id_with_retry<F>(
+pub(crate) fn allocate_compact_run_id_with_retry<F>(
     run_store: &WorkflowRunStore,
     now: i64,
     mut next_run_id: F,
@@ -122,6 +122,8 @@ fn validate_selected_workflow_output_paths(
 
 fn selector_start_inputs(
     request: &SelectorRequest,
+    workflow: &WorkflowConfig,
+    selected_inputs: Option<&Map<String, Value>>,
     source_message_id: Option<&str>,
     workflow_inputs: Option<&Map<String, Value>>,
 ) -> Map<String, Value> {
@@ -160,6 +162,15 @@ fn selector_start_inputs(
             Value::String(source_message_id.to_string()),
         );
     }
+    for spec in workflow.inputs.as_slice() {
+        let key = spec.key.as_str();
+        if let Some(value) = selected_inputs
+            .and_then(|values| values.get(key))
+            .or_else(|| workflow_inputs.and_then(|values| values.get(key)))
+        {
+            inputs.insert(key.to_string(), value.clone());
+        }
+    }
     if let Some(workflow_inputs) = workflow_inputs {
         inputs.insert(
             "workflow_inputs".to_string(),
@@ -231,12 +242,12 @@ pub fn route_selector_action(
                     "workflow_start requires selectedWorkflow".to_string(),
                 )
             })?;
-            if !ctx
+            let Some(workflow) = ctx
                 .orchestrator
                 .workflows
                 .iter()
-                .any(|w| w.id == workflow_id)
-            {
+                .find(|w| w.id == workflow_id)
+            else {
                 let err = OrchestratorError::SelectorValidation(format!(
                     "workflow `{workflow_id}` is not declared in orchestrator"
                 ));
@@ -245,7 +256,7 @@ pub fn route_selector_action(
                     &format!("workflow_start denied: {err}"),
                 );
                 return Err(err);
-            }
+            };
 
             let run_id = allocate_compact_run_id_with_retry(
                 ctx.run_store,
@@ -268,7 +279,7 @@ pub fn route_selector_action(
             }
             ctx.run_store.create_run_with_metadata(
                 run_id.clone(),
-                workflow_id.clone(),
+                workflow,
                 SelectorStartedRunMetadata {
                     source_message_id: ctx.source_message_id.map(|v| v.to_string()),
                     selector_id: Some(request.selector_id.clone()),
@@ -277,7 +288,13 @@ pub fn route_selector_action(
                     status_conversation_id: request.conversation_id.clone(),
                     memory_context: selector_start_memory_context(request),
                 },
-                selector_start_inputs(request, ctx.source_message_id, ctx.workflow_inputs),
+                selector_start_inputs(
+                    request,
+                    workflow,
+                    validated.workflow_inputs.as_ref(),
+                    ctx.source_message_id,
+                    ctx.workflow_inputs,
+                ),
                 ctx.now,
             )?;
             let mut engine = WorkflowEngine::new(ctx.run_store.clone(), ctx.orchestrator.clone());
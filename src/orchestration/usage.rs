@@ -0,0 +1,105 @@
+use crate::orchestration::error::OrchestratorError;
+use crate::orchestration::run_store::{StepUsage, WorkflowRunStore};
+use crate::provider::ProviderUsage;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Provider usage summed over step attempts, in total and per agent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageRollup {
+    pub attempts: u32,
+    pub total: ProviderUsage,
+    pub by_agent: BTreeMap<String, ProviderUsage>,
+}
+
+impl UsageRollup {
+    pub fn record(&mut self, agent_key: String, usage: &ProviderUsage) {
+        self.attempts = self.attempts.saturating_add(1);
+        self.total.accumulate(usage);
+        self.by_agent
+            .entry(agent_key)
+            .or_default()
+            .accumulate(usage);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.attempts == 0
+    }
+}
+
+/// Sums the usage recorded on every step attempt of one run.
+pub fn run_usage(store: &WorkflowRunStore, run_id: &str) -> Result<UsageRollup, OrchestratorError> {
+    let mut rollup = UsageRollup::default();
+    for attempt in store.load_step_attempts(run_id)? {
+        if let Some(StepUsage {
+            agent_id, tokens, ..
+        }) = &attempt.usage
+        {
+            rollup.record(agent_id.clone(), tokens);
+        }
+    }
+    Ok(rollup)
+}
+
+/// Usage of one workflow across the runs that recorded any in the window.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowUsage {
+    pub runs: u32,
+    pub usage: ProviderUsage,
+}
+
+/// Usage across orchestrators, keyed `<orchestrator_id>/<workflow_id>` and
+/// `<orchestrator_id>/<agent_id>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub since: Option<i64>,
+    pub runs: u32,
+    pub usage: UsageRollup,
+    pub by_workflow: BTreeMap<String, WorkflowUsage>,
+}
+
+/// Collects usage of step attempts that ended at or after `since` from the
+/// run stores of each `(orchestrator_id, store)` pair.
+pub fn collect_usage_report<'a>(
+    stores: impl IntoIterator<Item = (&'a str, &'a WorkflowRunStore)>,
+    since: Option<i64>,
+) -> Result<UsageReport, OrchestratorError> {
+    let mut report = UsageReport {
+        since,
+        ..UsageReport::default()
+    };
+    for (orchestrator_id, store) in stores {
+        for run in store.load_runs()? {
+            let mut run_usage = ProviderUsage::default();
+            let mut recorded = false;
+            for attempt in store.load_step_attempts(&run.run_id)? {
+                if since.is_some_and(|since| attempt.ended_at < since) {
+                    continue;
+                }
+                let Some(usage) = &attempt.usage else {
+                    continue;
+                };
+                report.usage.record(
+                    format!("{orchestrator_id}/{}", usage.agent_id),
+                    &usage.tokens,
+                );
+                run_usage.accumulate(&usage.tokens);
+                recorded = true;
+            }
+            if !recorded {
+                continue;
+            }
+            report.runs = report.runs.saturating_add(1);
+            let workflow = report
+                .by_workflow
+                .entry(format!("{orchestrator_id}/{}", run.workflow_id))
+                .or_default();
+            workflow.runs = workflow.runs.saturating_add(1);
+            workflow.usage.accumulate(&run_usage);
+        }
+    }
+    Ok(report)
+}
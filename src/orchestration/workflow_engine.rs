@@ -1,15 +1,43 @@
-use crate::config::{OrchestratorConfig, WorkflowConfig, WorkflowStepConfig};
+use crate::config::{
+    OrchestratorConfig, RetryErrorClass, WorkflowConfig, WorkflowStepConfig, WorkflowStepType,
+};
+use crate::orchestration::diagnostics::load_provider_usage;
 use crate::orchestration::error::OrchestratorError;
-use crate::orchestration::output_contract::output_validation_errors_for;
+use crate::orchestration::human_review::{
+    clear_human_review_artifacts, find_pending_human_review_run, load_human_review_decision,
+    load_human_review_request, persist_human_review_request, record_human_review_decision,
+    HumanReviewRequest,
+};
+use crate::orchestration::output_contract::{
+    evaluate_step_outputs, materialize_output_files, output_validation_errors_for,
+    resolve_step_output_paths, StepEvaluation,
+};
+use crate::orchestration::retry_policy::{classify_step_error, resolve_step_retry_policy};
+use crate::orchestration::run_events::RunEventKind;
+pub use crate::orchestration::run_store::NextStepPointer;
 use crate::orchestration::run_store::{
-    RunState, StepAttemptRecord, WorkflowRunRecord, WorkflowRunStore,
+    RunState, SelectorStartedRunMetadata, StepAttemptRecord, StepUsage, WorkflowRunRecord,
+    WorkflowRunStore,
 };
+use crate::orchestration::run_worktree::{
+    approve_run_worktree, commit_run_worktree, load_run_worktree, release_run_worktree,
+    reset_run_worktree,
+};
+use crate::orchestration::simulation::Simulation;
 pub use crate::orchestration::step_execution::resolve_runner_binaries;
-use crate::orchestration::step_execution::{execute_step_attempt, StepExecutionContext};
+use crate::orchestration::step_execution::{
+    execute_step_attempt, prepare_run_worktree, render_foreach_items, render_human_review_message,
+    render_workflow_call_inputs, ForeachItem, StepExecutionContext,
+};
 use crate::orchestration::workspace_access::WorkspaceAccessContext;
 use crate::provider::RunnerBinaries;
-use serde_json::Map;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
 use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Instant;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -31,12 +59,6 @@ impl Default for ExecutionSafetyLimits {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct NextStepPointer {
-    pub step_id: String,
-    pub attempt: u32,
-}
-
 pub fn resolve_execution_safety_limits(
     orchestrator: &OrchestratorConfig,
     workflow: &WorkflowConfig,
@@ -66,11 +88,7 @@ pub fn resolve_execution_safety_limits(
             .or_else(|| orchestration.and_then(|v| v.default_run_timeout_seconds))
             .unwrap_or(defaults.run_timeout_seconds),
         step_timeout_seconds,
-        max_retries: step
-            .limits
-            .as_ref()
-            .and_then(|v| v.max_retries)
-            .unwrap_or(defaults.max_retries),
+        max_retries: resolve_step_retry_policy(orchestrator, step).retry_ceiling(),
     }
 }
 
@@ -117,6 +135,28 @@ pub fn resolve_next_step_pointer(
     }
 }
 
+/// Returns the branch pointers a `parallel` step still has to execute. A run
+/// resuming the same parallel attempt continues from its persisted
+/// `active_steps`; a fresh visit starts every branch at attempt 1.
+pub fn resolve_parallel_branch_pointers(
+    run: &WorkflowRunRecord,
+    step: &WorkflowStepConfig,
+    attempt: u32,
+) -> Vec<NextStepPointer> {
+    let resuming = run.current_step_id.as_deref() == Some(step.id.as_str())
+        && run.current_attempt == Some(attempt);
+    if resuming {
+        return run.active_steps.clone();
+    }
+    step.branches
+        .iter()
+        .map(|step_id| NextStepPointer {
+            step_id: step_id.clone(),
+            attempt: 1,
+        })
+        .collect()
+}
+
 pub fn enforce_execution_safety(
     run: &WorkflowRunRecord,
     limits: ExecutionSafetyLimits,
@@ -130,7 +170,10 @@ pub fn enforce_execution_safety(
         });
     }
 
-    if now.saturating_sub(run.started_at) > limits.run_timeout_seconds as i64 {
+    let active_seconds = now
+        .saturating_sub(run.started_at)
+        .saturating_sub(run.waiting_seconds as i64);
+    if active_seconds > limits.run_timeout_seconds as i64 {
         return Err(OrchestratorError::RunTimeout {
             run_timeout_seconds: limits.run_timeout_seconds,
         });
@@ -152,14 +195,20 @@ pub fn enforce_execution_safety(
     Ok(())
 }
 
+/// Attempt record state for a failed attempt; canceled attempts keep their
+/// own state so they are not mistaken for provider failures.
+fn failed_attempt_state(error: &OrchestratorError, can_retry: bool) -> String {
+    if matches!(error, OrchestratorError::StepCanceled { .. }) {
+        "canceled".to_string()
+    } else if can_retry {
+        "failed_retryable".to_string()
+    } else {
+        "failed".to_string()
+    }
+}
+
 pub fn is_retryable_step_error(error: &OrchestratorError) -> bool {
-    matches!(
-        error,
-        OrchestratorError::StepExecution { .. }
-            | OrchestratorError::WorkflowEnvelope(_)
-            | OrchestratorError::InvalidReviewDecision(_)
-            | OrchestratorError::OutputContractValidation { .. }
-    )
+    classify_step_error(error).is_some()
 }
 
 #[derive(Debug, Clone)]
@@ -167,6 +216,7 @@ pub struct WorkflowEngine {
     run_store: WorkflowRunStore,
     orchestrator: OrchestratorConfig,
     runner_binaries: RunnerBinaries,
+    simulation: Option<Arc<Simulation>>,
     workspace_access_context: Option<WorkspaceAccessContext>,
     memory_enabled: bool,
 }
@@ -177,6 +227,7 @@ impl WorkflowEngine {
             run_store,
             orchestrator,
             runner_binaries: resolve_runner_binaries(),
+            simulation: None,
             workspace_access_context: None,
             memory_enabled: false,
         }
@@ -187,6 +238,12 @@ impl WorkflowEngine {
         self
     }
 
+    /// Answers agent steps from `simulation` instead of provider CLIs.
+    pub fn with_simulation(mut self, simulation: Arc<Simulation>) -> Self {
+        self.simulation = Some(simulation);
+        self
+    }
+
     pub fn with_workspace_access_context(
         mut self,
         workspace_access_context: WorkspaceAccessContext,
@@ -222,6 +279,16 @@ impl WorkflowEngine {
         if run.state.clone().is_terminal() {
             return Ok(run);
         }
+        // A run backing off before a retry stays parked until `retry_at`,
+        // whatever woke it.
+        if run.retry_at.is_some_and(|retry_at| now < retry_at) {
+            return Ok(run);
+        }
+        if run.retry_at.take().is_some() {
+            self.run_store.clear_retry_due(&run.run_id)?;
+            let waited = now.saturating_sub(run.updated_at).max(0) as u64;
+            run.waiting_seconds = run.waiting_seconds.saturating_add(waited);
+        }
         if run.state == RunState::Queued || run.state == RunState::Waiting {
             let workflow_id = run.workflow_id.clone();
             self.run_store.transition_state(
@@ -237,10 +304,29 @@ impl WorkflowEngine {
         self.run_store.load_run(run_id)
     }
 
+    /// Executes the run until it leaves `running`, then releases its worktree
+    /// when it finished or was canceled, whether or not execution errored.
     fn run_until_non_running(
         &self,
         run: &mut WorkflowRunRecord,
         start_now: i64,
+    ) -> Result<(), OrchestratorError> {
+        let run_clock_started = Instant::now();
+        let result = self.execute_until_non_running(run, start_now);
+        if let Ok(stopped) = self.run_store.load_run(&run.run_id) {
+            release_run_worktree(
+                &self.run_store,
+                &stopped,
+                elapsed_now(start_now, run_clock_started),
+            )?;
+        }
+        result
+    }
+
+    fn execute_until_non_running(
+        &self,
+        run: &mut WorkflowRunRecord,
+        start_now: i64,
     ) -> Result<(), OrchestratorError> {
         // Guard against accidental infinite loops in malformed workflows.
         let max_cycles = 10_000u32;
@@ -273,6 +359,13 @@ impl WorkflowEngine {
         run: &mut WorkflowRunRecord,
         now: i64,
     ) -> Result<(), OrchestratorError> {
+        if self.run_store.cancellation_token(&run.run_id).is_canceled() {
+            // Canceled from another process between steps; do not overwrite it.
+            *run = self.run_store.load_run(&run.run_id)?;
+            if run.state.clone().is_terminal() {
+                return Ok(());
+            }
+        }
         let workflow = self.workflow_for_run(run)?;
         let Some(pointer) = resolve_next_step_pointer(&self.run_store, run, workflow)? else {
             run.current_step_id = None;
@@ -287,16 +380,7 @@ impl WorkflowEngine {
             );
         };
 
-        let step = workflow
-            .steps
-            .iter()
-            .find(|step| step.id == pointer.step_id)
-            .ok_or_else(|| {
-                OrchestratorError::SelectorValidation(format!(
-                    "workflow `{}` missing step `{}`",
-                    workflow.id, pointer.step_id
-                ))
-            })?;
+        let step = workflow_step(workflow, &pointer.step_id)?;
 
         let limits = resolve_execution_safety_limits(&self.orchestrator, workflow, step);
         let step_context = StepExecutionContext {
@@ -304,9 +388,49 @@ impl WorkflowEngine {
             orchestrator: &self.orchestrator,
             workspace_access_context: self.workspace_access_context.as_ref(),
             runner_binaries: &self.runner_binaries,
+            simulation: self.simulation.as_deref(),
             step_timeout_seconds: limits.step_timeout_seconds,
             memory_enabled: self.memory_enabled,
+            foreach_item: None,
+            session_branch: None,
         };
+        prepare_run_worktree(&step_context, run, workflow)?;
+        if step.step_type == WorkflowStepType::Parallel {
+            return self.execute_parallel(run, workflow, step, pointer.attempt, now, limits);
+        }
+        if step.step_type == WorkflowStepType::Foreach {
+            return self.execute_foreach(
+                run,
+                workflow,
+                step,
+                pointer.attempt,
+                now,
+                limits,
+                &step_context,
+            );
+        }
+        if step.step_type == WorkflowStepType::WorkflowCall {
+            return self.execute_workflow_call(
+                run,
+                workflow,
+                step,
+                pointer.attempt,
+                now,
+                limits,
+                &step_context,
+            );
+        }
+        if step.step_type == WorkflowStepType::HumanReview {
+            return self.execute_human_review(
+                run,
+                workflow,
+                step,
+                pointer.attempt,
+                now,
+                limits,
+                &step_context,
+            );
+        }
         let mut attempt = pointer.attempt;
         let step_clock_started = Instant::now();
 
@@ -334,79 +458,28 @@ impl WorkflowEngine {
             ) {
                 Ok(evaluation) => {
                     let attempt_ended_at = elapsed_now(now, step_clock_started);
-                    self.run_store.persist_step_attempt(&StepAttemptRecord {
-                        run_id: run.run_id.clone(),
-                        step_id: step.id.clone(),
+                    return self.complete_step_attempt(
+                        run,
+                        step,
                         attempt,
-                        started_at: attempt_started_at,
-                        ended_at: attempt_ended_at,
-                        state: "succeeded".to_string(),
-                        outputs: evaluation.outputs.clone(),
-                        output_files: evaluation.output_files.clone(),
-                        final_output_priority: step
-                            .final_output_priority
-                            .iter()
-                            .map(|key| key.as_str().to_string())
-                            .collect(),
-                        next_step_id: evaluation.next_step_id.clone(),
-                        error: None,
-                        output_validation_errors: BTreeMap::new(),
-                    })?;
-                    *run = self.run_store.load_run(&run.run_id)?;
-
-                    self.run_store.append_engine_log(
-                        &run.run_id,
+                        attempt_started_at,
                         attempt_ended_at,
-                        format!(
-                            "run_id={} step_id={} attempt={} transition=succeeded next={}",
-                            run.run_id,
-                            step.id,
-                            attempt,
-                            evaluation
-                                .next_step_id
-                                .clone()
-                                .unwrap_or_else(|| "terminal".to_string())
-                        ),
-                    )?;
-                    enforce_execution_safety(
-                        run,
+                        evaluation,
                         limits,
-                        attempt_ended_at,
-                        attempt_started_at,
-                        attempt,
-                    )?;
-
-                    if let Some(next) = evaluation.next_step_id {
-                        run.current_step_id = Some(next.clone());
-                        run.current_attempt = None;
-                        self.run_store.checkpoint(
-                            run,
-                            attempt_ended_at,
-                            format!(
-                                "step {} attempt {} finished; next {}",
-                                step.id, attempt, next
-                            ),
-                            false,
-                            format!("execute step {next}"),
-                        )?;
-                    } else {
-                        run.current_step_id = None;
-                        run.current_attempt = None;
-                        self.run_store.transition_state(
-                            run,
-                            RunState::Succeeded,
-                            attempt_ended_at,
-                            format!("step {} attempt {} finished", step.id, attempt),
-                            false,
-                            "none",
-                        )?;
-                    }
-                    return Ok(());
+                    );
                 }
                 Err(err) => {
                     let attempt_ended_at = elapsed_now(now, step_clock_started);
-                    let retryable = is_retryable_step_error(&err);
-                    let can_retry = retryable && attempt <= limits.max_retries;
+                    let retry_policy = resolve_step_retry_policy(&self.orchestrator, step);
+                    let error_class = classify_step_error(&err);
+                    let can_retry = match error_class {
+                        Some(class) => retry_policy.allows_retry(
+                            class,
+                            self.prior_class_failures(&run.run_id, &step.id, 1..attempt, class),
+                            attempt,
+                        ),
+                        None => false,
+                    };
                     let output_validation_errors = output_validation_errors_for(&err);
                     self.run_store.persist_step_attempt(&StepAttemptRecord {
                         run_id: run.run_id.clone(),
@@ -414,11 +487,7 @@ impl WorkflowEngine {
                         attempt,
                         started_at: attempt_started_at,
                         ended_at: attempt_ended_at,
-                        state: if can_retry {
-                            "failed_retryable".to_string()
-                        } else {
-                            "failed".to_string()
-                        },
+                        state: failed_attempt_state(&err, can_retry),
                         outputs: Map::new(),
                         output_files: BTreeMap::new(),
                         final_output_priority: step
@@ -429,16 +498,29 @@ impl WorkflowEngine {
                         next_step_id: None,
                         error: Some(err.to_string()),
                         output_validation_errors,
+                        parallel_step_id: None,
+                        foreach_step_id: None,
+                        item_index: None,
+                        error_class,
+                        usage: self.attempt_usage(&run.run_id, &step.id, attempt),
                     })?;
                     *run = self.run_store.load_run(&run.run_id)?;
                     self.run_store.append_engine_log(
                         &run.run_id,
                         attempt_ended_at,
                         format!(
-                            "run_id={} step_id={} attempt={} transition=failed retryable={} error={}",
-                            run.run_id, step.id, attempt, can_retry, err
+                            "run_id={} step_id={} attempt={} transition=failed retryable={} error_class={} error={}",
+                            run.run_id,
+                            step.id,
+                            attempt,
+                            can_retry,
+                            error_class.map_or("none", RetryErrorClass::as_str),
+                            err
                         ),
                     )?;
+                    if matches!(err, OrchestratorError::StepCanceled { .. }) {
+                        return Err(err);
+                    }
                     enforce_execution_safety(
                         run,
                         limits,
@@ -447,7 +529,28 @@ impl WorkflowEngine {
                         attempt,
                     )?;
                     if can_retry {
+                        let delay = retry_policy.jittered_backoff_seconds(attempt);
                         attempt = attempt.saturating_add(1);
+                        self.run_store.append_run_event(
+                            &run.run_id,
+                            attempt_ended_at,
+                            RunEventKind::RetryScheduled {
+                                step_id: step.id.clone(),
+                                attempt,
+                                delay_seconds: delay,
+                                retry_at: attempt_ended_at.saturating_add(delay as i64),
+                                error_class,
+                            },
+                        )?;
+                        if delay > 0 {
+                            return self.park_for_retry(
+                                run,
+                                step,
+                                attempt,
+                                attempt_ended_at,
+                                delay,
+                            );
+                        }
                         continue;
                     }
                     return Err(err);
@@ -456,6 +559,1095 @@ impl WorkflowEngine {
         }
     }
 
+    /// Parks the run in `waiting` until `retry_at` instead of sleeping through
+    /// the retry backoff, so no worker thread is held. The runtime resumes it
+    /// once the delay has passed.
+    fn park_for_retry(
+        &self,
+        run: &mut WorkflowRunRecord,
+        step: &WorkflowStepConfig,
+        next_attempt: u32,
+        now: i64,
+        delay_seconds: u64,
+    ) -> Result<(), OrchestratorError> {
+        let retry_at = now.saturating_add(delay_seconds as i64);
+        run.current_step_id = Some(step.id.clone());
+        run.current_attempt = Some(next_attempt);
+        run.retry_at = Some(retry_at);
+        self.run_store.record_retry_due(&run.run_id, retry_at)?;
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} decision=retry_backoff step_id={} attempt={} delay_seconds={} retry_at={}",
+                run.run_id, step.id, next_attempt, delay_seconds, retry_at
+            ),
+        )?;
+        self.run_store.transition_state(
+            run,
+            RunState::Waiting,
+            now,
+            format!(
+                "step {} attempt {} waiting {delay_seconds}s before retry",
+                step.id, next_attempt
+            ),
+            false,
+            format!("retry step {} at {retry_at}", step.id),
+        )
+    }
+
+    /// Journals a `retry_scheduled` event for each branch or item attempt a
+    /// fan-out round retries and returns their shared delay: the longest
+    /// backoff among them, so the whole round waits once.
+    fn schedule_fan_out_retries(
+        &self,
+        run: &WorkflowRunRecord,
+        retries: &[FanOutRetry],
+        now: i64,
+    ) -> Result<u64, OrchestratorError> {
+        let delay = retries
+            .iter()
+            .map(|retry| retry.delay_seconds)
+            .max()
+            .unwrap_or(0);
+        for retry in retries {
+            self.run_store.append_run_event(
+                &run.run_id,
+                now,
+                RunEventKind::RetryScheduled {
+                    step_id: retry.step_id.clone(),
+                    attempt: retry.attempt,
+                    delay_seconds: delay,
+                    retry_at: now.saturating_add(delay as i64),
+                    error_class: retry.error_class,
+                },
+            )?;
+        }
+        Ok(delay)
+    }
+
+    /// Counts attempts in `attempts` of `step_id` that failed with `class`.
+    fn prior_class_failures(
+        &self,
+        run_id: &str,
+        step_id: &str,
+        attempts: std::ops::Range<u32>,
+        class: RetryErrorClass,
+    ) -> u32 {
+        attempts
+            .filter(|attempt| {
+                self.run_store
+                    .load_step_attempt(run_id, step_id, *attempt)
+                    .is_ok_and(|record| record.error_class == Some(class))
+            })
+            .count() as u32
+    }
+
+    /// Provider usage logged by the agent invocation of one step attempt.
+    fn attempt_usage(&self, run_id: &str, step_id: &str, attempt: u32) -> Option<StepUsage> {
+        load_provider_usage(&self.run_store.step_attempt_dir(run_id, step_id, attempt))
+    }
+
+    /// Records a reviewer decision for a run parked at a `human_review` step
+    /// and resumes it along `on_approve` / `on_reject`. When the review is
+    /// pending in a child run, the decision is recorded there and the run tree
+    /// is resumed from its root so waiting parents pick up the child result.
+    pub fn submit_human_review(
+        &self,
+        run_id: &str,
+        approved: bool,
+        comment: Option<String>,
+        decided_by: Option<String>,
+        now: i64,
+    ) -> Result<WorkflowRunRecord, OrchestratorError> {
+        let review_run_id = find_pending_human_review_run(&self.run_store, run_id)?
+            .unwrap_or_else(|| run_id.to_string());
+        record_human_review_decision(
+            &self.run_store,
+            &review_run_id,
+            approved,
+            comment,
+            decided_by,
+            now,
+        )?;
+        let mut root_run_id = review_run_id;
+        while let Some(parent_run_id) = self.run_store.load_run(&root_run_id)?.parent_run_id {
+            root_run_id = parent_run_id;
+        }
+        self.resume(&root_run_id, now)?;
+        self.run_store.load_run(run_id)
+    }
+
+    /// Starts (or resumes) the child run of a `workflow_call` step inline and
+    /// exposes the child's final outputs as this step's outputs. The parent
+    /// parks in `waiting` while the child waits on a human.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_workflow_call(
+        &self,
+        run: &mut WorkflowRunRecord,
+        workflow: &WorkflowConfig,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        now: i64,
+        limits: ExecutionSafetyLimits,
+        step_context: &StepExecutionContext<'_>,
+    ) -> Result<(), OrchestratorError> {
+        let target_workflow = step.workflow.clone().ok_or_else(|| {
+            OrchestratorError::SelectorValidation(format!(
+                "workflow_call step `{}` is missing `workflow`",
+                step.id
+            ))
+        })?;
+        let resuming = run.current_step_id.as_deref() == Some(step.id.as_str())
+            && run.current_attempt == Some(attempt);
+        let child_prefix = format!("{}-{}-", run.run_id, step.id);
+        let child_run_id = match run.child_run_ids.last() {
+            Some(last) if resuming && last.starts_with(&child_prefix) => last.clone(),
+            _ => {
+                let child_run_id = format!("{child_prefix}{}", run.child_run_ids.len() + 1);
+                run.child_run_ids.push(child_run_id.clone());
+                child_run_id
+            }
+        };
+
+        self.run_store
+            .mark_step_attempt_started(run, &step.id, attempt, now)?;
+        enforce_execution_safety(run, limits, now, now, attempt)?;
+
+        if !self.run_store.run_exists(&child_run_id) {
+            let inputs = render_workflow_call_inputs(step_context, run, workflow, step, attempt)?;
+            let child_workflow = self
+                .orchestrator
+                .workflows
+                .iter()
+                .find(|candidate| candidate.id == target_workflow)
+                .ok_or_else(|| {
+                    OrchestratorError::SelectorValidation(format!(
+                        "workflow `{target_workflow}` is not declared in orchestrator"
+                    ))
+                })?;
+            let mut child = self.run_store.create_run_with_metadata(
+                child_run_id.clone(),
+                child_workflow,
+                SelectorStartedRunMetadata {
+                    channel_profile_id: run.channel_profile_id.clone(),
+                    status_conversation_id: run.status_conversation_id.clone(),
+                    memory_context: run.memory_context.clone(),
+                    ..SelectorStartedRunMetadata::default()
+                },
+                inputs,
+                now,
+            )?;
+            child.parent_run_id = Some(run.run_id.clone());
+            self.run_store.persist_run(&child)?;
+        }
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} decision=workflow_call step_id={} attempt={} child_run_id={} workflow={}",
+                run.run_id, step.id, attempt, child_run_id, target_workflow
+            ),
+        )?;
+
+        let child_state = self.run_store.load_run(&child_run_id)?.state;
+        let outcome = if child_state == RunState::Queued {
+            self.start(&child_run_id, now)
+        } else {
+            self.resume(&child_run_id, now)
+        };
+        // A failing child already records its terminal state; surface it below.
+        let child = match outcome {
+            Ok(child) => child,
+            Err(_) => self.run_store.load_run(&child_run_id)?,
+        };
+        *run = self.run_store.load_run(&run.run_id)?;
+
+        match child.state {
+            RunState::Succeeded => {
+                let outputs = self.child_run_outputs(&child, step)?;
+                let output_paths = resolve_step_output_paths(
+                    self.run_store.state_root(),
+                    &run.run_id,
+                    step,
+                    attempt,
+                )?;
+                let mut evaluation = evaluate_step_outputs(workflow, step, outputs)?;
+                evaluation.output_files =
+                    materialize_output_files(step, &evaluation.outputs, &output_paths)?;
+                self.complete_step_attempt(run, step, attempt, now, now, evaluation, limits)
+            }
+            RunState::Waiting => {
+                let pending_human_input = self
+                    .run_store
+                    .load_progress(&child_run_id)
+                    .map(|progress| progress.pending_human_input)
+                    .unwrap_or(true);
+                self.run_store.transition_state(
+                    run,
+                    RunState::Waiting,
+                    now,
+                    format!("step {} waiting on child run {child_run_id}", step.id),
+                    pending_human_input,
+                    format!("resume child run {child_run_id}"),
+                )
+            }
+            state => {
+                let reason = format!(
+                    "child run `{child_run_id}` ended {state}: {}",
+                    child
+                        .terminal_reason
+                        .as_deref()
+                        .unwrap_or("no reason recorded")
+                );
+                self.run_store.persist_step_attempt(&StepAttemptRecord {
+                    run_id: run.run_id.clone(),
+                    step_id: step.id.clone(),
+                    attempt,
+                    started_at: now,
+                    ended_at: now,
+                    state: "failed".to_string(),
+                    outputs: Map::new(),
+                    output_files: BTreeMap::new(),
+                    final_output_priority: step
+                        .final_output_priority
+                        .iter()
+                        .map(|key| key.as_str().to_string())
+                        .collect(),
+                    next_step_id: None,
+                    error: Some(reason.clone()),
+                    output_validation_errors: BTreeMap::new(),
+                    parallel_step_id: None,
+                    foreach_step_id: None,
+                    item_index: None,
+                    error_class: None,
+                    usage: None,
+                })?;
+                *run = self.run_store.load_run(&run.run_id)?;
+                Err(OrchestratorError::StepExecution {
+                    step_id: step.id.clone(),
+                    reason,
+                })
+            }
+        }
+    }
+
+    /// Merges the outputs of a succeeded child run's succeeded attempts in
+    /// completion order, letting the terminal step win, and keeps only keys
+    /// the calling step declares.
+    fn child_run_outputs(
+        &self,
+        child: &WorkflowRunRecord,
+        step: &WorkflowStepConfig,
+    ) -> Result<Map<String, Value>, OrchestratorError> {
+        let (terminal, intermediate): (Vec<_>, Vec<_>) = self
+            .run_store
+            .load_step_attempts(&child.run_id)?
+            .into_iter()
+            .filter(|record| record.state == "succeeded")
+            .partition(|record| record.next_step_id.is_none());
+        let mut merged = Map::new();
+        for record in intermediate.into_iter().chain(terminal) {
+            merged.extend(record.outputs);
+        }
+        Ok(step
+            .outputs
+            .iter()
+            .filter_map(|key| {
+                merged
+                    .get(&key.name)
+                    .map(|value| (key.name.clone(), value.clone()))
+            })
+            .collect())
+    }
+
+    /// Fans out every branch of a `parallel` step, runs the pending branch
+    /// attempts concurrently in rounds, and routes to the join step once all
+    /// branches have succeeded.
+    fn execute_parallel(
+        &self,
+        run: &mut WorkflowRunRecord,
+        workflow: &WorkflowConfig,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        now: i64,
+        limits: ExecutionSafetyLimits,
+    ) -> Result<(), OrchestratorError> {
+        let mut pending = resolve_parallel_branch_pointers(run, step, attempt);
+        run.active_steps = pending.clone();
+        self.run_store
+            .mark_step_attempt_started(run, &step.id, attempt, now)?;
+        enforce_execution_safety(run, limits, now, now, attempt)?;
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} decision=fan_out step_id={} branches={}",
+                run.run_id,
+                step.id,
+                pending
+                    .iter()
+                    .map(|pointer| pointer.step_id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        )?;
+
+        let step_clock_started = Instant::now();
+        while !pending.is_empty() {
+            let round_started_at = elapsed_now(now, step_clock_started);
+            if run.total_iterations.saturating_add(pending.len() as u32)
+                > limits.max_total_iterations
+            {
+                return Err(OrchestratorError::MaxIterationsExceeded {
+                    max_total_iterations: limits.max_total_iterations,
+                });
+            }
+            let mut branches = Vec::with_capacity(pending.len());
+            for pointer in &pending {
+                let branch = workflow_step(workflow, &pointer.step_id)?;
+                let branch_limits =
+                    resolve_execution_safety_limits(&self.orchestrator, workflow, branch);
+                self.run_store.append_engine_log(
+                    &run.run_id,
+                    round_started_at,
+                    format!(
+                        "run_id={} decision=execute_branch parallel_step_id={} step_id={} attempt={}",
+                        run.run_id, step.id, branch.id, pointer.attempt
+                    ),
+                )?;
+                enforce_execution_safety(
+                    run,
+                    branch_limits,
+                    round_started_at,
+                    round_started_at,
+                    pointer.attempt,
+                )?;
+                branches.push((branch, pointer.attempt, branch_limits));
+            }
+
+            let jobs = branches
+                .iter()
+                .map(
+                    |(branch, branch_attempt, branch_limits)| ConcurrentAttempt {
+                        step: branch,
+                        attempt: *branch_attempt,
+                        limits: *branch_limits,
+                        foreach_item: None,
+                        session_branch: branch.id.clone(),
+                    },
+                )
+                .collect::<Vec<_>>();
+            let results =
+                self.execute_attempts_concurrently(run, workflow, &jobs, round_started_at);
+            let round_ended_at = elapsed_now(now, step_clock_started);
+
+            let mut next_pending = Vec::new();
+            let mut retries = Vec::new();
+            let mut failure = None;
+            for ((branch, branch_attempt, _), result) in branches.iter().zip(results) {
+                let mut error_class = None;
+                let (state, outputs, output_files, next_step_id, error, output_validation_errors) =
+                    match result {
+                        Ok(evaluation) => (
+                            "succeeded".to_string(),
+                            evaluation.outputs,
+                            evaluation.output_files,
+                            evaluation.next_step_id,
+                            None,
+                            BTreeMap::new(),
+                        ),
+                        Err(err) => {
+                            error_class = classify_step_error(&err);
+                            let retry_policy =
+                                resolve_step_retry_policy(&self.orchestrator, branch);
+                            let can_retry = error_class.is_some_and(|class| {
+                                retry_policy.allows_retry(
+                                    class,
+                                    self.prior_class_failures(
+                                        &run.run_id,
+                                        &branch.id,
+                                        1..*branch_attempt,
+                                        class,
+                                    ),
+                                    *branch_attempt,
+                                )
+                            });
+                            let output_validation_errors = output_validation_errors_for(&err);
+                            let error = err.to_string();
+                            let state = failed_attempt_state(&err, can_retry);
+                            if can_retry {
+                                next_pending.push(NextStepPointer {
+                                    step_id: branch.id.clone(),
+                                    attempt: branch_attempt.saturating_add(1),
+                                });
+                                retries.push(FanOutRetry {
+                                    step_id: branch.id.clone(),
+                                    attempt: branch_attempt.saturating_add(1),
+                                    error_class,
+                                    delay_seconds: retry_policy
+                                        .jittered_backoff_seconds(*branch_attempt),
+                                });
+                            } else if failure.is_none() {
+                                failure = Some(err);
+                            }
+                            (
+                                state,
+                                Map::new(),
+                                BTreeMap::new(),
+                                None,
+                                Some(error),
+                                output_validation_errors,
+                            )
+                        }
+                    };
+                self.run_store.append_engine_log(
+                    &run.run_id,
+                    round_ended_at,
+                    format!(
+                        "run_id={} parallel_step_id={} step_id={} attempt={} transition={}",
+                        run.run_id, step.id, branch.id, branch_attempt, state
+                    ),
+                )?;
+                self.run_store.persist_step_attempt(&StepAttemptRecord {
+                    run_id: run.run_id.clone(),
+                    step_id: branch.id.clone(),
+                    attempt: *branch_attempt,
+                    started_at: round_started_at,
+                    ended_at: round_ended_at,
+                    state,
+                    outputs,
+                    output_files,
+                    final_output_priority: branch
+                        .final_output_priority
+                        .iter()
+                        .map(|key| key.as_str().to_string())
+                        .collect(),
+                    next_step_id,
+                    error,
+                    output_validation_errors,
+                    parallel_step_id: Some(step.id.clone()),
+                    foreach_step_id: None,
+                    item_index: None,
+                    error_class,
+                    usage: self.attempt_usage(&run.run_id, &branch.id, *branch_attempt),
+                })?;
+            }
+
+            *run = self.run_store.load_run(&run.run_id)?;
+            run.active_steps = next_pending.clone();
+            self.run_store.persist_run(run)?;
+            if let Some(err) = failure {
+                return Err(err);
+            }
+            let delay = self.schedule_fan_out_retries(run, &retries, round_ended_at)?;
+            if delay > 0 {
+                return self.park_for_retry(run, step, attempt, round_ended_at, delay);
+            }
+            pending = next_pending;
+        }
+
+        let joined_at = elapsed_now(now, step_clock_started);
+        let mut outputs = Map::new();
+        outputs.insert(
+            "summary".to_string(),
+            serde_json::Value::String(format!(
+                "parallel branches completed: {}",
+                step.branches.join(", ")
+            )),
+        );
+        let output_paths =
+            resolve_step_output_paths(self.run_store.state_root(), &run.run_id, step, attempt)?;
+        let mut evaluation = evaluate_step_outputs(workflow, step, outputs)?;
+        evaluation.output_files =
+            materialize_output_files(step, &evaluation.outputs, &output_paths)?;
+        self.complete_step_attempt(run, step, attempt, joined_at, joined_at, evaluation, limits)
+    }
+
+    /// Executes step attempts against a snapshot of the run, one scoped thread
+    /// per attempt, and returns their results in input order. Recording the
+    /// results is left to the caller so run bookkeeping stays sequential.
+    fn execute_attempts_concurrently(
+        &self,
+        run: &WorkflowRunRecord,
+        workflow: &WorkflowConfig,
+        jobs: &[ConcurrentAttempt<'_>],
+        started_at: i64,
+    ) -> Vec<Result<StepEvaluation, OrchestratorError>> {
+        std::thread::scope(|scope| {
+            let handles = jobs
+                .iter()
+                .map(|job| {
+                    scope.spawn(move || {
+                        let job_context = StepExecutionContext {
+                            run_store: &self.run_store,
+                            orchestrator: &self.orchestrator,
+                            workspace_access_context: self.workspace_access_context.as_ref(),
+                            runner_binaries: &self.runner_binaries,
+                            simulation: self.simulation.as_deref(),
+                            step_timeout_seconds: job.limits.step_timeout_seconds,
+                            memory_enabled: self.memory_enabled,
+                            foreach_item: job.foreach_item.clone(),
+                            session_branch: Some(job.session_branch.clone()),
+                        };
+                        execute_step_attempt(
+                            &job_context,
+                            run,
+                            workflow,
+                            job.step,
+                            job.attempt,
+                            started_at,
+                        )
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .zip(jobs)
+                .map(|(handle, job)| {
+                    handle.join().unwrap_or_else(|_| {
+                        Err(OrchestratorError::StepExecution {
+                            step_id: job.step.id.clone(),
+                            reason: "concurrent step attempt thread panicked".to_string(),
+                        })
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Runs a `foreach` step's `sequence` once per element of its rendered
+    /// `items`, at most `max_concurrency` elements at a time, and routes to
+    /// `next` with the per-item results once every element has succeeded.
+    #[allow(clippy::too_many_arguments)]
+    fn execute_foreach(
+        &self,
+        run: &mut WorkflowRunRecord,
+        workflow: &WorkflowConfig,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        now: i64,
+        limits: ExecutionSafetyLimits,
+        step_context: &StepExecutionContext<'_>,
+    ) -> Result<(), OrchestratorError> {
+        let resuming = run.current_step_id.as_deref() == Some(step.id.as_str())
+            && run.current_attempt == Some(attempt);
+        let attempt_dir = self
+            .run_store
+            .step_attempt_dir(&run.run_id, &step.id, attempt);
+        let items_path = attempt_dir.join(FOREACH_ITEMS_FILE);
+        let strides_path = attempt_dir.join(FOREACH_STRIDES_FILE);
+        let sequence = step
+            .sequence
+            .iter()
+            .map(|step_id| {
+                let body = workflow_step(workflow, step_id)?;
+                Ok((
+                    body,
+                    resolve_execution_safety_limits(&self.orchestrator, workflow, body),
+                ))
+            })
+            .collect::<Result<Vec<_>, OrchestratorError>>()?;
+        let items = match read_foreach_file::<Vec<Value>>(&items_path)? {
+            Some(items) if resuming => items,
+            _ => {
+                self.clear_foreach_attempts(run, step)?;
+                let items = render_foreach_items(step_context, run, workflow, step, attempt)?;
+                write_foreach_file(&items_path, &items)?;
+                fs::remove_file(&strides_path).or_else(|source| match source.kind() {
+                    std::io::ErrorKind::NotFound => Ok(()),
+                    _ => Err(OrchestratorError::Io {
+                        path: strides_path.display().to_string(),
+                        source,
+                    }),
+                })?;
+                items
+            }
+        };
+        // Attempt numbers are laid out per item from the retry budgets seen
+        // when the items were rendered, so a retry config changed while the
+        // run is parked cannot shift them onto another item's attempts.
+        let mut strides =
+            read_foreach_file::<BTreeMap<String, u32>>(&strides_path)?.unwrap_or_default();
+        if sequence
+            .iter()
+            .any(|(body, _)| !strides.contains_key(&body.id))
+        {
+            for (body, body_limits) in &sequence {
+                strides
+                    .entry(body.id.clone())
+                    .or_insert_with(|| body_limits.max_retries.saturating_add(1));
+            }
+            write_foreach_file(&strides_path, &strides)?;
+        }
+        let sequence = sequence
+            .into_iter()
+            .map(|(body, body_limits)| (body, body_limits, strides[&body.id]))
+            .collect::<Vec<_>>();
+
+        self.run_store
+            .mark_step_attempt_started(run, &step.id, attempt, now)?;
+        enforce_execution_safety(run, limits, now, now, attempt)?;
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} decision=foreach step_id={} items={} sequence={}",
+                run.run_id,
+                step.id,
+                items.len(),
+                step.sequence.join(",")
+            ),
+        )?;
+
+        let max_concurrency = step.max_concurrency.unwrap_or(1).max(1) as usize;
+        let step_clock_started = Instant::now();
+        let mut budget_checked = false;
+        let item_outputs = loop {
+            let records = self
+                .run_store
+                .load_step_attempts(&run.run_id)?
+                .into_iter()
+                .filter(|record| record.foreach_step_id.as_deref() == Some(step.id.as_str()))
+                .collect::<Vec<_>>();
+            let progress = items
+                .iter()
+                .enumerate()
+                .map(|(index, value)| foreach_item_progress(&records, &sequence, index, value))
+                .collect::<Vec<_>>();
+            if !budget_checked {
+                let remaining = progress
+                    .iter()
+                    .map(|item| item.remaining_steps)
+                    .sum::<usize>();
+                if run.total_iterations as usize + remaining > limits.max_total_iterations as usize
+                {
+                    return Err(OrchestratorError::MaxIterationsExceeded {
+                        max_total_iterations: limits.max_total_iterations,
+                    });
+                }
+                budget_checked = true;
+            }
+            let pending = progress
+                .iter()
+                .filter_map(|item| item.pending.clone())
+                .take(max_concurrency)
+                .collect::<Vec<_>>();
+            if pending.is_empty() {
+                break progress
+                    .into_iter()
+                    .map(|item| item.outputs)
+                    .collect::<Vec<_>>();
+            }
+
+            let round_started_at = elapsed_now(now, step_clock_started);
+            if run.total_iterations.saturating_add(pending.len() as u32)
+                > limits.max_total_iterations
+            {
+                return Err(OrchestratorError::MaxIterationsExceeded {
+                    max_total_iterations: limits.max_total_iterations,
+                });
+            }
+            let mut jobs = Vec::with_capacity(pending.len());
+            for pointer in &pending {
+                let (body, body_limits, _) = sequence[pointer.sequence_index];
+                self.run_store.append_engine_log(
+                    &run.run_id,
+                    round_started_at,
+                    format!(
+                        "run_id={} decision=execute_item foreach_step_id={} item_index={} step_id={} attempt={}",
+                        run.run_id, step.id, pointer.item.index, body.id, pointer.attempt
+                    ),
+                )?;
+                enforce_execution_safety(
+                    run,
+                    body_limits,
+                    round_started_at,
+                    round_started_at,
+                    pointer.retry,
+                )?;
+                jobs.push(ConcurrentAttempt {
+                    step: body,
+                    attempt: pointer.attempt,
+                    limits: body_limits,
+                    foreach_item: Some(pointer.item.clone()),
+                    session_branch: format!("{}[{}]", step.id, pointer.item.index),
+                });
+            }
+            run.active_steps = jobs
+                .iter()
+                .map(|job| NextStepPointer {
+                    step_id: job.step.id.clone(),
+                    attempt: job.attempt,
+                })
+                .collect();
+            self.run_store.persist_run(run)?;
+            let results =
+                self.execute_attempts_concurrently(run, workflow, &jobs, round_started_at);
+            let round_ended_at = elapsed_now(now, step_clock_started);
+
+            let mut retries = Vec::new();
+            let mut failure = None;
+            for ((pointer, job), result) in pending.iter().zip(&jobs).zip(results) {
+                let mut error_class = None;
+                let (state, outputs, output_files, error, output_validation_errors) = match result {
+                    Ok(evaluation) => (
+                        "succeeded".to_string(),
+                        evaluation.outputs,
+                        evaluation.output_files,
+                        None,
+                        BTreeMap::new(),
+                    ),
+                    Err(err) => {
+                        error_class = classify_step_error(&err);
+                        let retry_policy = resolve_step_retry_policy(&self.orchestrator, job.step);
+                        let stride = sequence[pointer.sequence_index].2;
+                        let can_retry = pointer.retry < stride
+                            && error_class.is_some_and(|class| {
+                                retry_policy.allows_retry(
+                                    class,
+                                    pointer.class_failures.get(&class).copied().unwrap_or(0),
+                                    pointer.retry,
+                                )
+                            });
+                        let output_validation_errors = output_validation_errors_for(&err);
+                        let error = err.to_string();
+                        let state = failed_attempt_state(&err, can_retry);
+                        if can_retry {
+                            retries.push(FanOutRetry {
+                                step_id: job.step.id.clone(),
+                                attempt: job.attempt.saturating_add(1),
+                                error_class,
+                                delay_seconds: retry_policy.jittered_backoff_seconds(pointer.retry),
+                            });
+                        } else if failure.is_none() {
+                            failure = Some(err);
+                        }
+                        (
+                            state,
+                            Map::new(),
+                            BTreeMap::new(),
+                            Some(error),
+                            output_validation_errors,
+                        )
+                    }
+                };
+                self.run_store.append_engine_log(
+                    &run.run_id,
+                    round_ended_at,
+                    format!(
+                        "run_id={} foreach_step_id={} item_index={} step_id={} attempt={} transition={}",
+                        run.run_id, step.id, pointer.item.index, job.step.id, job.attempt, state
+                    ),
+                )?;
+                self.run_store.persist_step_attempt(&StepAttemptRecord {
+                    run_id: run.run_id.clone(),
+                    step_id: job.step.id.clone(),
+                    attempt: job.attempt,
+                    started_at: round_started_at,
+                    ended_at: round_ended_at,
+                    state,
+                    outputs,
+                    output_files,
+                    final_output_priority: job
+                        .step
+                        .final_output_priority
+                        .iter()
+                        .map(|key| key.as_str().to_string())
+                        .collect(),
+                    next_step_id: None,
+                    error,
+                    output_validation_errors,
+                    parallel_step_id: None,
+                    foreach_step_id: Some(step.id.clone()),
+                    item_index: Some(pointer.item.index),
+                    error_class,
+                    usage: self.attempt_usage(&run.run_id, &job.step.id, job.attempt),
+                })?;
+            }
+            *run = self.run_store.load_run(&run.run_id)?;
+            run.active_steps.clear();
+            self.run_store.persist_run(run)?;
+            if let Some(err) = failure {
+                return Err(err);
+            }
+            let delay = self.schedule_fan_out_retries(run, &retries, round_ended_at)?;
+            if delay > 0 {
+                return self.park_for_retry(run, step, attempt, round_ended_at, delay);
+            }
+        };
+
+        let completed_at = elapsed_now(now, step_clock_started);
+        let results = items
+            .iter()
+            .zip(item_outputs)
+            .enumerate()
+            .map(|(index, (item, outputs))| {
+                Value::Object(Map::from_iter([
+                    ("item_index".to_string(), Value::from(index)),
+                    ("item".to_string(), item.clone()),
+                    ("outputs".to_string(), Value::Object(outputs)),
+                ]))
+            })
+            .collect::<Vec<_>>();
+        let mut outputs = Map::new();
+        outputs.insert(
+            "summary".to_string(),
+            Value::String(format!(
+                "foreach completed {} items through: {}",
+                items.len(),
+                step.sequence.join(", ")
+            )),
+        );
+        if step.outputs.iter().any(|key| key.as_str() == "results") {
+            outputs.insert("results".to_string(), Value::Array(results));
+        }
+        let output_paths =
+            resolve_step_output_paths(self.run_store.state_root(), &run.run_id, step, attempt)?;
+        let mut evaluation = evaluate_step_outputs(workflow, step, outputs)?;
+        evaluation.output_files =
+            materialize_output_files(step, &evaluation.outputs, &output_paths)?;
+        self.complete_step_attempt(
+            run,
+            step,
+            attempt,
+            completed_at,
+            completed_at,
+            evaluation,
+            limits,
+        )
+    }
+
+    /// Removes sequence attempts left by an earlier visit of a `foreach`
+    /// step so a revisit iterates its items from the start.
+    fn clear_foreach_attempts(
+        &self,
+        run: &WorkflowRunRecord,
+        step: &WorkflowStepConfig,
+    ) -> Result<(), OrchestratorError> {
+        for record in self.run_store.load_step_attempts(&run.run_id)? {
+            if record.foreach_step_id.as_deref() != Some(step.id.as_str()) {
+                continue;
+            }
+            let dir = self
+                .run_store
+                .step_attempt_dir(&run.run_id, &record.step_id, record.attempt);
+            fs::remove_dir_all(&dir).map_err(|source| OrchestratorError::Io {
+                path: dir.display().to_string(),
+                source,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Commits a succeeded step's changes to the run worktree. An approving
+    /// review marks the result as the last approved commit; a rejecting review
+    /// resets the worktree to it so the reject loop restarts from approved work.
+    fn sync_run_worktree(
+        &self,
+        run: &WorkflowRunRecord,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        evaluation: &StepEvaluation,
+        now: i64,
+    ) -> Result<(), OrchestratorError> {
+        let Some(mut worktree) = load_run_worktree(&self.run_store, &run.run_id)? else {
+            return Ok(());
+        };
+        if worktree.removed {
+            return Ok(());
+        }
+        let decision = if step.routes_on_decision() {
+            evaluation.outputs.get("decision").and_then(Value::as_str)
+        } else {
+            None
+        };
+        if decision == Some("reject") {
+            reset_run_worktree(&worktree)?;
+            return self.run_store.append_engine_log(
+                &run.run_id,
+                now,
+                format!(
+                    "run_id={} step_id={} attempt={} worktree_reset={}",
+                    run.run_id, step.id, attempt, worktree.approved_commit
+                ),
+            );
+        }
+        let commit = commit_run_worktree(
+            &worktree,
+            &format!("{} attempt {attempt} of run {}", step.id, run.run_id),
+        )?;
+        if decision == Some("approve") {
+            approve_run_worktree(&self.run_store, &mut worktree)?;
+        }
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} step_id={} attempt={} worktree_commit={}",
+                run.run_id,
+                step.id,
+                attempt,
+                commit.as_deref().unwrap_or("unchanged")
+            ),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_human_review(
+        &self,
+        run: &mut WorkflowRunRecord,
+        workflow: &WorkflowConfig,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        now: i64,
+        limits: ExecutionSafetyLimits,
+        step_context: &StepExecutionContext<'_>,
+    ) -> Result<(), OrchestratorError> {
+        let resuming = run.current_step_id.as_deref() == Some(step.id.as_str())
+            && run.current_attempt == Some(attempt);
+        if !resuming {
+            clear_human_review_artifacts(&self.run_store, &run.run_id, &step.id, attempt)?;
+        }
+
+        let decision = if resuming {
+            load_human_review_decision(&self.run_store, &run.run_id, &step.id, attempt)?
+        } else {
+            None
+        };
+        if let Some(decision) = decision {
+            if let Some(request) =
+                load_human_review_request(&self.run_store, &run.run_id, &step.id, attempt)?
+            {
+                let waited = now.saturating_sub(request.requested_at).max(0) as u64;
+                run.waiting_seconds = run.waiting_seconds.saturating_add(waited);
+                self.run_store.persist_run(run)?;
+            }
+            let output_paths =
+                resolve_step_output_paths(self.run_store.state_root(), &run.run_id, step, attempt)?;
+            let mut evaluation =
+                evaluate_step_outputs(workflow, step, decision.step_outputs(step))?;
+            evaluation.output_files =
+                materialize_output_files(step, &evaluation.outputs, &output_paths)?;
+            return self.complete_step_attempt(run, step, attempt, now, now, evaluation, limits);
+        }
+
+        self.run_store
+            .mark_step_attempt_started(run, &step.id, attempt, now)?;
+        enforce_execution_safety(run, limits, now, now, attempt)?;
+        if load_human_review_request(&self.run_store, &run.run_id, &step.id, attempt)?.is_none() {
+            let message = render_human_review_message(step_context, run, workflow, step, attempt)?;
+            persist_human_review_request(
+                &self.run_store,
+                &HumanReviewRequest {
+                    run_id: run.run_id.clone(),
+                    step_id: step.id.clone(),
+                    attempt,
+                    requested_at: now,
+                    message,
+                },
+            )?;
+        }
+        self.run_store.append_engine_log(
+            &run.run_id,
+            now,
+            format!(
+                "run_id={} decision=await_human_review step_id={} attempt={}",
+                run.run_id, step.id, attempt
+            ),
+        )?;
+        let run_id = run.run_id.clone();
+        self.run_store.transition_state(
+            run,
+            RunState::Waiting,
+            now,
+            format!("step {} awaiting human review", step.id),
+            true,
+            format!("approve or reject run {run_id}"),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn complete_step_attempt(
+        &self,
+        run: &mut WorkflowRunRecord,
+        step: &WorkflowStepConfig,
+        attempt: u32,
+        attempt_started_at: i64,
+        attempt_ended_at: i64,
+        evaluation: StepEvaluation,
+        limits: ExecutionSafetyLimits,
+    ) -> Result<(), OrchestratorError> {
+        self.run_store.persist_step_attempt(&StepAttemptRecord {
+            run_id: run.run_id.clone(),
+            step_id: step.id.clone(),
+            attempt,
+            started_at: attempt_started_at,
+            ended_at: attempt_ended_at,
+            state: "succeeded".to_string(),
+            outputs: evaluation.outputs.clone(),
+            output_files: evaluation.output_files.clone(),
+            final_output_priority: step
+                .final_output_priority
+                .iter()
+                .map(|key| key.as_str().to_string())
+                .collect(),
+            next_step_id: evaluation.next_step_id.clone(),
+            error: None,
+            output_validation_errors: BTreeMap::new(),
+            parallel_step_id: None,
+            foreach_step_id: None,
+            item_index: None,
+            error_class: None,
+            usage: self.attempt_usage(&run.run_id, &step.id, attempt),
+        })?;
+        *run = self.run_store.load_run(&run.run_id)?;
+
+        self.run_store.append_engine_log(
+            &run.run_id,
+            attempt_ended_at,
+            format!(
+                "run_id={} step_id={} attempt={} transition=succeeded next={}",
+                run.run_id,
+                step.id,
+                attempt,
+                evaluation
+                    .next_step_id
+                    .clone()
+                    .unwrap_or_else(|| "terminal".to_string())
+            ),
+        )?;
+        self.sync_run_worktree(run, step, attempt, &evaluation, attempt_ended_at)?;
+        enforce_execution_safety(run, limits, attempt_ended_at, attempt_started_at, attempt)?;
+
+        if let Some(next) = evaluation.next_step_id {
+            run.current_step_id = Some(next.clone());
+            run.current_attempt = None;
+            self.run_store.checkpoint(
+                run,
+                attempt_ended_at,
+                format!(
+                    "step {} attempt {} finished; next {}",
+                    step.id, attempt, next
+                ),
+                false,
+                format!("execute step {next}"),
+            )?;
+        } else {
+            run.current_step_id = None;
+            run.current_attempt = None;
+            self.run_store.transition_state(
+                run,
+                RunState::Succeeded,
+                attempt_ended_at,
+                format!("step {} attempt {} finished", step.id, attempt),
+                false,
+                "none",
+            )?;
+        }
+        Ok(())
+    }
+
     fn execute_or_fail(
         &self,
         run: &mut WorkflowRunRecord,
@@ -463,6 +1655,32 @@ impl WorkflowEngine {
     ) -> Result<(), OrchestratorError> {
         match self.execute_next(run, now) {
             Ok(()) => Ok(()),
+            Err(OrchestratorError::StepCanceled { step_id, attempt }) => {
+                *run = self.run_store.load_run(&run.run_id)?;
+                let detail =
+                    format!("step {step_id} attempt {attempt} canceled; in-flight process killed");
+                self.run_store.append_engine_log(
+                    &run.run_id,
+                    now,
+                    format!("run_id={} transition=canceled reason={detail}", run.run_id),
+                )?;
+                if run.state.clone().is_terminal() {
+                    run.terminal_reason = Some(match run.terminal_reason.take() {
+                        Some(reason) => format!("{reason}; {detail}"),
+                        None => detail,
+                    });
+                    self.run_store.persist_run(run)
+                } else {
+                    self.run_store.transition_state(
+                        run,
+                        RunState::Canceled,
+                        now,
+                        detail,
+                        false,
+                        "none",
+                    )
+                }
+            }
             Err(err) => {
                 let reason = format!("engine start failed: {err}");
                 self.run_store.append_engine_log(
@@ -502,6 +1720,150 @@ impl WorkflowEngine {
     }
 }
 
+fn workflow_step<'a>(
+    workflow: &'a WorkflowConfig,
+    step_id: &str,
+) -> Result<&'a WorkflowStepConfig, OrchestratorError> {
+    workflow
+        .steps
+        .iter()
+        .find(|step| step.id == step_id)
+        .ok_or_else(|| {
+            OrchestratorError::SelectorValidation(format!(
+                "workflow `{}` missing step `{step_id}`",
+                workflow.id
+            ))
+        })
+}
+
+struct ConcurrentAttempt<'a> {
+    step: &'a WorkflowStepConfig,
+    attempt: u32,
+    limits: ExecutionSafetyLimits,
+    foreach_item: Option<ForeachItem>,
+    /// Branch the attempt's agent sessions are keyed by: the branch step id,
+    /// or `<foreach_step_id>[<index>]` for a `foreach` element.
+    session_branch: String,
+}
+
+const FOREACH_ITEMS_FILE: &str = "foreach_items.json";
+const FOREACH_STRIDES_FILE: &str = "foreach_strides.json";
+
+/// A failed branch or item attempt a fan-out round will retry.
+struct FanOutRetry {
+    step_id: String,
+    attempt: u32,
+    error_class: Option<RetryErrorClass>,
+    delay_seconds: u64,
+}
+
+#[derive(Debug, Clone)]
+struct ForeachPointer {
+    item: ForeachItem,
+    sequence_index: usize,
+    /// One-based try of the sequence step for this item.
+    retry: u32,
+    attempt: u32,
+    /// Earlier failed tries of the sequence step for this item, by class.
+    class_failures: BTreeMap<RetryErrorClass, u32>,
+}
+
+struct ForeachItemProgress {
+    pending: Option<ForeachPointer>,
+    remaining_steps: usize,
+    outputs: Map<String, Value>,
+}
+
+/// Works out where one `foreach` item stands from its persisted sequence
+/// attempts. Item `i` of a sequence step with stride `s` (its persisted
+/// `max_retries + 1`) uses attempt numbers `i * s + 1 ..= (i + 1) * s`, so
+/// every item and retry gets its own attempt directory.
+fn foreach_item_progress(
+    records: &[StepAttemptRecord],
+    sequence: &[(&WorkflowStepConfig, ExecutionSafetyLimits, u32)],
+    index: usize,
+    value: &Value,
+) -> ForeachItemProgress {
+    let mut sequence_outputs = BTreeMap::new();
+    let mut merged = Map::new();
+    for (sequence_index, (body, _, stride)) in sequence.iter().enumerate() {
+        let item_records = records
+            .iter()
+            .filter(|record| record.step_id == body.id && record.item_index == Some(index))
+            .collect::<Vec<_>>();
+        if let Some(done) = item_records
+            .iter()
+            .find(|record| record.state == "succeeded")
+        {
+            merged.extend(done.outputs.clone());
+            sequence_outputs.insert(body.id.clone(), done.outputs.clone());
+            continue;
+        }
+        let retry = item_records.len() as u32 + 1;
+        let mut class_failures = BTreeMap::new();
+        for class in item_records.iter().filter_map(|record| record.error_class) {
+            *class_failures.entry(class).or_insert(0) += 1;
+        }
+        return ForeachItemProgress {
+            pending: Some(ForeachPointer {
+                item: ForeachItem {
+                    index,
+                    value: value.clone(),
+                    sequence_outputs,
+                },
+                sequence_index,
+                retry,
+                attempt: (index as u32).saturating_mul(*stride).saturating_add(retry),
+                class_failures,
+            }),
+            remaining_steps: sequence.len() - sequence_index,
+            outputs: merged,
+        };
+    }
+    ForeachItemProgress {
+        pending: None,
+        remaining_steps: 0,
+        outputs: merged,
+    }
+}
+
+fn read_foreach_file<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, OrchestratorError> {
+    let raw = match fs::read_to_string(path) {
+        Ok(raw) => raw,
+        Err(source) if source.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => {
+            return Err(OrchestratorError::Io {
+                path: path.display().to_string(),
+                source,
+            })
+        }
+    };
+    serde_json::from_str(&raw)
+        .map(Some)
+        .map_err(|source| OrchestratorError::Json {
+            path: path.display().to_string(),
+            source,
+        })
+}
+
+fn write_foreach_file<T: Serialize + ?Sized>(
+    path: &Path,
+    value: &T,
+) -> Result<(), OrchestratorError> {
+    let io_error = |source| OrchestratorError::Io {
+        path: path.display().to_string(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let body = serde_json::to_vec_pretty(value).map_err(|source| OrchestratorError::Json {
+        path: path.display().to_string(),
+        source,
+    })?;
+    fs::write(path, body).map_err(io_error)
+}
+
 fn elapsed_now(base_now: i64, started_at: Instant) -> i64 {
     base_now.saturating_add(started_at.elapsed().as_secs() as i64)
 }
@@ -0,0 +1,525 @@
+use crate::config::{WorkflowConfig, WorkflowStepConfig, WorkflowStepTransition, WorkflowStepType};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+const START_NODE: &str = "wf_start";
+const END_NODE: &str = "wf_end";
+
+/// Output format of `workflow graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Mermaid,
+    Dot,
+}
+
+impl GraphFormat {
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "mermaid" => Ok(Self::Mermaid),
+            "dot" | "graphviz" => Ok(Self::Dot),
+            other => Err(format!(
+                "unsupported graph format `{other}`; expected mermaid or dot"
+            )),
+        }
+    }
+}
+
+/// Why one step hands control to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphEdgeKind {
+    /// `next`, or the following step when a step declares no route.
+    Next,
+    Approve,
+    Reject,
+    When(String),
+    Default,
+    /// `parallel` step to one of its `branches`.
+    Branch,
+    /// `parallel` step to the join step named by its `next`.
+    Join,
+    /// `foreach` step to the first step of its `sequence`.
+    EachItem,
+    /// One `sequence` step to the following one.
+    Then,
+}
+
+impl GraphEdgeKind {
+    pub fn label(&self) -> String {
+        match self {
+            Self::Next => "next".to_string(),
+            Self::Approve => "approve".to_string(),
+            Self::Reject => "reject".to_string(),
+            Self::When(expression) => format!("when {expression}"),
+            Self::Default => "default".to_string(),
+            Self::Branch => "branch".to_string(),
+            Self::Join => "join".to_string(),
+            Self::EachItem => "each item".to_string(),
+            Self::Then => "then".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: GraphEdgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub step_id: String,
+    /// Step type, agent, workspace mode, and limits, one per line.
+    pub annotations: Vec<String>,
+    /// Whether the run ends after this step.
+    pub terminal: bool,
+}
+
+/// Structural problems found while building a workflow graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphWarning {
+    Unreachable {
+        step_id: String,
+    },
+    /// Steps that keep routing among themselves with no path to the end.
+    LoopWithoutExit {
+        step_ids: Vec<String>,
+    },
+    UnknownTarget {
+        step_id: String,
+        target: String,
+    },
+}
+
+impl GraphWarning {
+    fn step_ids(&self) -> Vec<&str> {
+        match self {
+            Self::Unreachable { step_id } | Self::UnknownTarget { step_id, .. } => {
+                vec![step_id.as_str()]
+            }
+            Self::LoopWithoutExit { step_ids } => step_ids.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl fmt::Display for GraphWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreachable { step_id } => {
+                write!(f, "step `{step_id}` is unreachable from the entry step")
+            }
+            Self::LoopWithoutExit { step_ids } => write!(
+                f,
+                "steps {} loop with no path to the end of the workflow",
+                step_ids
+                    .iter()
+                    .map(|id| format!("`{id}`"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::UnknownTarget { step_id, target } => {
+                write!(f, "step `{step_id}` routes to unknown step `{target}`")
+            }
+        }
+    }
+}
+
+/// Steps and routes of one workflow, with reachability warnings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkflowGraph {
+    pub workflow_id: String,
+    pub annotations: Vec<String>,
+    pub entry: Option<String>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub warnings: Vec<GraphWarning>,
+}
+
+pub fn workflow_graph(workflow: &WorkflowConfig) -> WorkflowGraph {
+    let step_ids = workflow
+        .steps
+        .iter()
+        .map(|step| step.id.as_str())
+        .collect::<BTreeSet<_>>();
+    // Branch and sequence steps hand control back to their parent step.
+    let mut parents = BTreeMap::new();
+    for step in &workflow.steps {
+        let members = match step.step_type {
+            WorkflowStepType::Parallel => &step.branches,
+            WorkflowStepType::Foreach => &step.sequence,
+            _ => continue,
+        };
+        for member in members {
+            parents.insert(member.as_str(), step.id.as_str());
+        }
+    }
+
+    let mut edges = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, step) in workflow.steps.iter().enumerate() {
+        let following = workflow.steps.get(index + 1).map(|next| next.id.clone());
+        for (to, kind) in step_routes(step, following, parents.contains_key(step.id.as_str())) {
+            if step_ids.contains(to.as_str()) {
+                edges.push(GraphEdge {
+                    from: step.id.clone(),
+                    to,
+                    kind,
+                });
+            } else {
+                warnings.push(GraphWarning::UnknownTarget {
+                    step_id: step.id.clone(),
+                    target: to,
+                });
+            }
+        }
+    }
+
+    let nodes = workflow
+        .steps
+        .iter()
+        .map(|step| GraphNode {
+            step_id: step.id.clone(),
+            annotations: step_annotations(step),
+            terminal: !parents.contains_key(step.id.as_str())
+                && !edges.iter().any(|edge| edge.from == step.id),
+        })
+        .collect::<Vec<_>>();
+
+    let mut successors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for edge in &edges {
+        successors
+            .entry(edge.from.as_str())
+            .or_default()
+            .insert(edge.to.as_str());
+    }
+    for (member, parent) in &parents {
+        successors.entry(member).or_default().insert(parent);
+    }
+    let entry = workflow.steps.first().map(|step| step.id.clone());
+    let reachable = reachable_from(entry.as_deref(), &successors);
+    for step in &workflow.steps {
+        if !reachable.contains(step.id.as_str()) {
+            warnings.push(GraphWarning::Unreachable {
+                step_id: step.id.clone(),
+            });
+        }
+    }
+    warnings.extend(loops_without_exit(
+        workflow,
+        &nodes,
+        &successors,
+        &reachable,
+    ));
+
+    let mut annotations = vec![format!("workflow {} v{}", workflow.id, workflow.version)];
+    if let Some(limits) = &workflow.limits {
+        if let Some(value) = limits.max_total_iterations {
+            annotations.push(format!("max_total_iterations={value}"));
+        }
+        if let Some(value) = limits.run_timeout_seconds {
+            annotations.push(format!("run_timeout_seconds={value}"));
+        }
+    }
+
+    WorkflowGraph {
+        workflow_id: workflow.id.clone(),
+        annotations,
+        entry,
+        nodes,
+        edges,
+        warnings,
+    }
+}
+
+/// Routes out of `step` in the order the engine considers them.
+fn step_routes(
+    step: &WorkflowStepConfig,
+    following: Option<String>,
+    is_member: bool,
+) -> Vec<(String, GraphEdgeKind)> {
+    let mut routes = Vec::new();
+    match step.step_type {
+        WorkflowStepType::Parallel => {
+            routes.extend(
+                step.branches
+                    .iter()
+                    .map(|branch| (branch.clone(), GraphEdgeKind::Branch)),
+            );
+            routes.extend(step.next.clone().map(|next| (next, GraphEdgeKind::Join)));
+            return routes;
+        }
+        WorkflowStepType::Foreach => {
+            routes.extend(
+                step.sequence
+                    .first()
+                    .map(|first| (first.clone(), GraphEdgeKind::EachItem)),
+            );
+            routes.extend(
+                step.sequence
+                    .windows(2)
+                    .map(|pair| (pair[1].clone(), GraphEdgeKind::Then)),
+            );
+            routes.extend(step.next.clone().map(|next| (next, GraphEdgeKind::Next)));
+            return routes;
+        }
+        _ => {}
+    }
+    if step.routes_on_decision() {
+        routes.extend(
+            step.on_approve
+                .clone()
+                .map(|target| (target, GraphEdgeKind::Approve)),
+        );
+        routes.extend(
+            step.on_reject
+                .clone()
+                .map(|target| (target, GraphEdgeKind::Reject)),
+        );
+    } else if !step.transitions.is_empty() {
+        routes.extend(step.transitions.iter().map(|transition| match transition {
+            WorkflowStepTransition::When { when, next } => {
+                (next.clone(), GraphEdgeKind::When(when.clone()))
+            }
+            WorkflowStepTransition::Default { default } => {
+                (default.clone(), GraphEdgeKind::Default)
+            }
+        }));
+    } else if let Some(next) = &step.next {
+        routes.push((next.clone(), GraphEdgeKind::Next));
+    } else if !is_member {
+        routes.extend(following.map(|next| (next, GraphEdgeKind::Next)));
+    }
+    routes
+}
+
+fn step_annotations(step: &WorkflowStepConfig) -> Vec<String> {
+    let mut annotations = vec![step.step_type.as_str().to_string()];
+    if !step.agent.is_empty() {
+        annotations.push(format!("agent={}", step.agent));
+    }
+    if let Some(workflow) = &step.workflow {
+        annotations.push(format!("calls={workflow}"));
+    }
+    annotations.push(format!("workspace={}", step.workspace_mode.as_str()));
+    if let Some(max_retries) = step.limits.as_ref().and_then(|limits| limits.max_retries) {
+        annotations.push(format!("max_retries={max_retries}"));
+    }
+    if let Some(max_concurrency) = step.max_concurrency {
+        annotations.push(format!("max_concurrency={max_concurrency}"));
+    }
+    annotations
+}
+
+fn reachable_from<'a>(
+    start: Option<&'a str>,
+    successors: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+) -> BTreeSet<&'a str> {
+    let mut seen = BTreeSet::new();
+    let mut queue = start.into_iter().collect::<VecDeque<_>>();
+    while let Some(step_id) = queue.pop_front() {
+        if !seen.insert(step_id) {
+            continue;
+        }
+        if let Some(next) = successors.get(step_id) {
+            queue.extend(next.iter().copied());
+        }
+    }
+    seen
+}
+
+/// Groups reachable steps that cannot reach a terminal step into the cycles
+/// that trap them.
+fn loops_without_exit(
+    workflow: &WorkflowConfig,
+    nodes: &[GraphNode],
+    successors: &BTreeMap<&str, BTreeSet<&str>>,
+    reachable: &BTreeSet<&str>,
+) -> Vec<GraphWarning> {
+    let mut predecessors: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (from, targets) in successors {
+        for to in targets {
+            predecessors.entry(to).or_default().insert(from);
+        }
+    }
+    let mut can_exit = BTreeSet::new();
+    for node in nodes.iter().filter(|node| node.terminal) {
+        can_exit.extend(reachable_from(Some(node.step_id.as_str()), &predecessors));
+    }
+
+    let trapped = workflow
+        .steps
+        .iter()
+        .map(|step| step.id.as_str())
+        .filter(|id| reachable.contains(id) && !can_exit.contains(id))
+        .collect::<Vec<_>>();
+    let mut grouped = BTreeSet::new();
+    let mut warnings = Vec::new();
+    for step_id in &trapped {
+        if grouped.contains(step_id) {
+            continue;
+        }
+        let forward = successors
+            .get(step_id)
+            .map(|next| {
+                next.iter()
+                    .flat_map(|id| reachable_from(Some(id), successors))
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+        if !forward.contains(step_id) {
+            continue;
+        }
+        let cycle = trapped
+            .iter()
+            .copied()
+            .filter(|id| {
+                forward.contains(id) && reachable_from(Some(id), successors).contains(step_id)
+            })
+            .collect::<Vec<_>>();
+        grouped.extend(cycle.iter().copied());
+        warnings.push(GraphWarning::LoopWithoutExit {
+            step_ids: cycle.into_iter().map(str::to_string).collect(),
+        });
+    }
+    warnings
+}
+
+impl WorkflowGraph {
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Mermaid => self.to_mermaid(),
+            GraphFormat::Dot => self.to_dot(),
+        }
+    }
+
+    fn flagged_steps(&self) -> BTreeSet<&str> {
+        self.warnings
+            .iter()
+            .flat_map(GraphWarning::step_ids)
+            .collect()
+    }
+
+    fn node_ids(&self) -> BTreeMap<&str, String> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.step_id.as_str(), format!("s{index}")))
+            .collect()
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let ids = self.node_ids();
+        let mut lines = vec!["flowchart TD".to_string()];
+        for annotation in &self.annotations {
+            lines.push(format!("    %% {annotation}"));
+        }
+        for warning in &self.warnings {
+            lines.push(format!("    %% warning: {warning}"));
+        }
+        lines.push(format!("    {START_NODE}([start])"));
+        for node in &self.nodes {
+            let label = std::iter::once(&node.step_id)
+                .chain(&node.annotations)
+                .map(|line| mermaid_escape(line))
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "    {}[\"{}\"]",
+                ids[node.step_id.as_str()],
+                label.join("<br/>")
+            ));
+        }
+        if self.nodes.iter().any(|node| node.terminal) {
+            lines.push(format!("    {END_NODE}([end])"));
+        }
+        if let Some(entry) = &self.entry {
+            lines.push(format!("    {START_NODE} --> {}", ids[entry.as_str()]));
+        }
+        for edge in &self.edges {
+            lines.push(format!(
+                "    {} -->|\"{}\"| {}",
+                ids[edge.from.as_str()],
+                mermaid_escape(&edge.kind.label()),
+                ids[edge.to.as_str()]
+            ));
+        }
+        for node in self.nodes.iter().filter(|node| node.terminal) {
+            lines.push(format!("    {} --> {END_NODE}", ids[node.step_id.as_str()]));
+        }
+        let flagged = self.flagged_steps();
+        if !flagged.is_empty() {
+            lines.push("    classDef warning stroke:#d93025,stroke-width:3px".to_string());
+            lines.push(format!(
+                "    class {} warning",
+                self.nodes
+                    .iter()
+                    .filter(|node| flagged.contains(node.step_id.as_str()))
+                    .map(|node| ids[node.step_id.as_str()].as_str())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ));
+        }
+        lines.join("\n")
+    }
+
+    pub fn to_dot(&self) -> String {
+        let ids = self.node_ids();
+        let flagged = self.flagged_steps();
+        let mut lines = Vec::new();
+        for warning in &self.warnings {
+            lines.push(format!("// warning: {warning}"));
+        }
+        lines.push(format!("digraph \"{}\" {{", dot_escape(&self.workflow_id)));
+        lines.push(format!(
+            "    label=\"{}\";",
+            dot_escape(&self.annotations.join("\n"))
+        ));
+        lines.push("    labelloc=t;".to_string());
+        lines.push("    node [shape=box];".to_string());
+        lines.push(format!("    {START_NODE} [label=\"start\", shape=oval];"));
+        for node in &self.nodes {
+            let mut label = vec![node.step_id.clone()];
+            label.extend(node.annotations.iter().cloned());
+            let style = if flagged.contains(node.step_id.as_str()) {
+                ", color=\"#d93025\", penwidth=3"
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "    {} [label=\"{}\"{style}];",
+                ids[node.step_id.as_str()],
+                dot_escape(&label.join("\n"))
+            ));
+        }
+        if self.nodes.iter().any(|node| node.terminal) {
+            lines.push(format!("    {END_NODE} [label=\"end\", shape=oval];"));
+        }
+        if let Some(entry) = &self.entry {
+            lines.push(format!("    {START_NODE} -> {};", ids[entry.as_str()]));
+        }
+        for edge in &self.edges {
+            lines.push(format!(
+                "    {} -> {} [label=\"{}\"];",
+                ids[edge.from.as_str()],
+                ids[edge.to.as_str()],
+                dot_escape(&edge.kind.label())
+            ));
+        }
+        for node in self.nodes.iter().filter(|node| node.terminal) {
+            lines.push(format!("    {} -> {END_NODE};", ids[node.step_id.as_str()]));
+        }
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+fn mermaid_escape(raw: &str) -> String {
+    raw.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+fn dot_escape(raw: &str) -> String {
+    raw.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -1,4 +1,5 @@
 use crate::config::{ConfigError, OrchestratorConfig, WorkflowStepType};
+use serde_json::Value;
 use std::fs;
 use std::path::{Component, Path, PathBuf};
 
@@ -13,6 +14,8 @@ const DEFAULT_TASK_PROMPT_TEMPLATE: &str =
     include_str!("assets/workflow_steps/agent_task.prompt.md");
 const DEFAULT_REVIEW_PROMPT_TEMPLATE: &str =
     include_str!("assets/workflow_steps/agent_review.prompt.md");
+const DEFAULT_HUMAN_REVIEW_PROMPT_TEMPLATE: &str =
+    include_str!("assets/workflow_steps/human_review.prompt.md");
 const DEFAULT_CONTEXT_TEMPLATE: &str = include_str!("assets/workflow_steps/default.context.md");
 
 const MINIMAL_DEFAULT_STEP_1_PROMPT: &str =
@@ -85,6 +88,15 @@ pub fn default_step_prompt(step_type: WorkflowStepType) -> &'static str {
     match step_type {
         WorkflowStepType::AgentTask => DEFAULT_TASK_PROMPT_TEMPLATE,
         WorkflowStepType::AgentReview => DEFAULT_REVIEW_PROMPT_TEMPLATE,
+        WorkflowStepType::HumanReview => DEFAULT_HUMAN_REVIEW_PROMPT_TEMPLATE,
+        // Parallel steps only fan out to their branches and never render a prompt.
+        WorkflowStepType::Parallel => "",
+        // Workflow calls start a child run from mapped inputs instead of a prompt.
+        WorkflowStepType::WorkflowCall => "",
+        // Command steps run a local program and never render a prompt.
+        WorkflowStepType::Command => "",
+        // Foreach steps run their `sequence` per item and never render a prompt.
+        WorkflowStepType::Foreach => "",
     }
 }
 
@@ -273,6 +285,46 @@ where
     Ok(rendered)
 }
 
+/// Resolves the `{{item}}`, `{{item.<path>}}` and `{{item_index}}`
+/// placeholders of a `foreach` iteration and leaves every other placeholder
+/// in place for step rendering.
+pub fn render_foreach_item_placeholders(
+    template: &str,
+    item: &Value,
+    item_index: usize,
+) -> Result<String, String> {
+    render_template_with_placeholders(template, |token| {
+        if token == "item_index" {
+            return Ok(item_index.to_string());
+        }
+        let path = if token == "item" {
+            Vec::new()
+        } else if let Some(path) = token.strip_prefix("item.") {
+            path.split('.').collect()
+        } else {
+            return Ok(format!("{{{{{token}}}}}"));
+        };
+        let mut value = item;
+        for segment in path {
+            value = match value {
+                Value::Object(map) => map.get(segment),
+                Value::Array(values) => segment
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| values.get(index)),
+                _ => None,
+            }
+            .ok_or_else(|| {
+                format!("missing foreach item field for placeholder `{{{{{token}}}}}`")
+            })?;
+        }
+        Ok(match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        })
+    })
+}
+
 fn has_non_empty_path_segments(raw: &str) -> bool {
     let segments = raw
         .split('.')
@@ -291,6 +343,12 @@ fn is_supported_step_placeholder(token: &str) -> bool {
     if let Some(path) = token.strip_prefix("state.") {
         return has_non_empty_path_segments(path);
     }
+    if token == "item" || token == "item_index" {
+        return true;
+    }
+    if let Some(path) = token.strip_prefix("item.") {
+        return has_non_empty_path_segments(path);
+    }
     if let Some(path) = token.strip_prefix("steps.") {
         let segments = path.split('.').collect::<Vec<_>>();
         if segments.len() < 3 || segments[1] != "outputs" || segments[0].trim().is_empty() {
@@ -397,6 +455,9 @@ pub fn validate_orchestrator_prompt_templates(
 
     for workflow in &orchestrator.workflows {
         for step in &workflow.steps {
+            if !step.step_type.renders_prompt() {
+                continue;
+            }
             if !is_prompt_template_reference(&step.prompt) {
                 issues.push(format!(
                     "workflow `{}` step `{}` prompt is inline; expected relative markdown path under prompts/",
@@ -0,0 +1,78 @@
+use crate::provider::backend::ProviderBackend;
+use crate::provider::output_parse::parse_anthropic_output;
+use crate::provider::{
+    parse_anthropic_error, parse_anthropic_session_id, parse_anthropic_usage,
+    resolve_anthropic_model, ProviderError, ProviderRequest, ProviderUsage,
+};
+
+pub const ANTHROPIC_BINARY_ENV_VAR: &str = "DIRECLAW_PROVIDER_BIN_ANTHROPIC";
+
+/// Claude Code CLI run headless with `-p` and json output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnthropicBackend;
+
+impl ProviderBackend for AnthropicBackend {
+    fn id(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn default_binary(&self) -> Option<&'static str> {
+        Some("claude")
+    }
+
+    fn binary_env_var(&self) -> Option<&'static str> {
+        Some(ANTHROPIC_BINARY_ENV_VAR)
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "install the Anthropic CLI"
+    }
+
+    fn model_options(&self) -> &'static [&'static str] {
+        &["sonnet", "opus", "haiku"]
+    }
+
+    fn resolve_model(&self, model: &str) -> Result<String, ProviderError> {
+        resolve_anthropic_model(model)
+    }
+
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        model: &str,
+        resume_session: Option<&str>,
+    ) -> Vec<String> {
+        let mut args = vec![
+            "--dangerously-skip-permissions".to_string(),
+            "--model".to_string(),
+            model.to_string(),
+        ];
+        if let Some(session_id) = resume_session {
+            args.push("--resume".to_string());
+            args.push(session_id.to_string());
+        }
+        args.extend([
+            "--output-format".to_string(),
+            "json".to_string(),
+            "-p".to_string(),
+            request.message.clone(),
+        ]);
+        args
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError> {
+        parse_anthropic_output(stdout)
+    }
+
+    fn parse_usage(&self, stdout: &str) -> Option<ProviderUsage> {
+        parse_anthropic_usage(stdout)
+    }
+
+    fn parse_error(&self, stdout: &str) -> Option<String> {
+        parse_anthropic_error(stdout)
+    }
+
+    fn parse_session_id(&self, stdout: &str) -> Option<String> {
+        parse_anthropic_session_id(stdout)
+    }
+}
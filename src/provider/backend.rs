@@ -0,0 +1,243 @@
+use crate::provider::anthropic::AnthropicBackend;
+use crate::provider::gemini::GeminiBackend;
+use crate::provider::openai::OpenAiBackend;
+use crate::provider::openai_compatible::OpenAiCompatibleBackend;
+use crate::provider::runner::run_cli;
+use crate::provider::{
+    InvocationSpec, ProviderEndpoint, ProviderError, ProviderKind, ProviderRequest, ProviderResult,
+    ProviderUsage, RunnerBinaries,
+};
+use std::collections::BTreeMap;
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+/// One agent CLI direclaw can drive. Backends are looked up by the
+/// `provider:` value of an agent in a [`ProviderRegistry`].
+pub trait ProviderBackend: Send + Sync {
+    /// `provider:` value that selects this backend.
+    fn id(&self) -> &'static str;
+
+    /// CLI binary run when `RunnerBinaries` sets none; `None` for backends
+    /// that do not spawn a process.
+    fn default_binary(&self) -> Option<&'static str>;
+
+    /// Environment variable that overrides the CLI binary.
+    fn binary_env_var(&self) -> Option<&'static str>;
+
+    /// Doctor remediation for a missing binary, completed by the override
+    /// variable.
+    fn install_hint(&self) -> &'static str;
+
+    /// Whether requests go to the agent's `endpoint` over HTTP instead of a
+    /// CLI on this host.
+    fn uses_endpoint(&self) -> bool {
+        false
+    }
+
+    /// Models offered when picking this provider during setup, the first
+    /// being the default.
+    fn model_options(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Binary this backend runs: the override in `binaries`, else its
+    /// default.
+    fn binary<'a>(&self, binaries: &'a RunnerBinaries) -> &'a str {
+        binaries
+            .get(self.id())
+            .or(self.default_binary())
+            .unwrap_or_default()
+    }
+
+    /// Maps a configured model name to the one passed to the CLI.
+    fn resolve_model(&self, model: &str) -> Result<String, ProviderError> {
+        Ok(model.trim().to_string())
+    }
+
+    /// Whether the invocation may resume the request's `session_id` rather
+    /// than starting a fresh session.
+    fn continues_session(&self, request: &ProviderRequest) -> bool {
+        !request.reset_requested && !request.fresh_on_failure
+    }
+
+    /// CLI arguments for one headless invocation, resuming `resume_session`
+    /// when given.
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        model: &str,
+        resume_session: Option<&str>,
+    ) -> Vec<String>;
+
+    fn build_invocation(
+        &self,
+        request: &ProviderRequest,
+        binaries: &RunnerBinaries,
+    ) -> Result<InvocationSpec, ProviderError> {
+        let model = self.resolve_model(&request.model)?;
+        let resume_session = request
+            .session_id
+            .as_deref()
+            .filter(|_| self.continues_session(request));
+        Ok(InvocationSpec {
+            binary: self.binary(binaries).to_string(),
+            args: self.invocation_args(request, &model, resume_session),
+            resolved_model: model,
+        })
+    }
+
+    /// Extracts the agent's final reply from captured stdout.
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError>;
+
+    /// Reads token usage from captured stdout, when the CLI reports any.
+    fn parse_usage(&self, stdout: &str) -> Option<ProviderUsage>;
+
+    /// Reads the error a CLI reported in its structured stdout. A reported
+    /// error fails the invocation whatever the exit status.
+    fn parse_error(&self, _stdout: &str) -> Option<String> {
+        None
+    }
+
+    /// Reads the id of the session the CLI ran in, for later attempts to
+    /// resume.
+    fn parse_session_id(&self, _stdout: &str) -> Option<String> {
+        None
+    }
+
+    /// Runs one invocation of `request`. The default spawns the CLI from
+    /// [`ProviderBackend::build_invocation`] and parses its stdout.
+    fn run(
+        &self,
+        request: &ProviderRequest,
+        binaries: &RunnerBinaries,
+    ) -> Result<ProviderResult, ProviderError> {
+        run_cli(self, request, binaries)
+    }
+
+    /// Checks the backend can be invoked on this host.
+    fn health_check(&self, binaries: &RunnerBinaries) -> ProviderHealth {
+        let binary = self.binary(binaries);
+        ProviderHealth {
+            ok: is_binary_available(binary),
+            detail: format!("binary={binary}"),
+            remediation: match self.binary_env_var() {
+                Some(env_var) => format!("{} or set {env_var}", self.install_hint()),
+                None => self.install_hint().to_string(),
+            },
+        }
+    }
+
+    /// Checks an agent `endpoint` the backend would call. `None` for
+    /// backends that are not called over HTTP.
+    fn endpoint_health_check(&self, _endpoint: &ProviderEndpoint) -> Option<ProviderHealth> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    pub ok: bool,
+    pub detail: String,
+    pub remediation: String,
+}
+
+/// Provider backends keyed by their `provider:` id.
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    backends: BTreeMap<&'static str, Arc<dyn ProviderBackend>>,
+}
+
+impl ProviderRegistry {
+    /// Registry of the backends shipped with direclaw.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(Arc::new(AnthropicBackend));
+        registry.register(Arc::new(OpenAiBackend));
+        registry.register(Arc::new(GeminiBackend));
+        registry.register(Arc::new(OpenAiCompatibleBackend));
+        registry
+    }
+
+    /// Adds `backend`, returning the backend it replaced under the same id.
+    pub fn register(
+        &mut self,
+        backend: Arc<dyn ProviderBackend>,
+    ) -> Option<Arc<dyn ProviderBackend>> {
+        self.backends.insert(backend.id(), backend)
+    }
+
+    pub fn get(&self, provider: &str) -> Result<&dyn ProviderBackend, ProviderError> {
+        let provider = provider.trim().to_ascii_lowercase();
+        self.backends
+            .get(provider.as_str())
+            .map(|backend| backend.as_ref())
+            .ok_or(ProviderError::UnknownProvider(provider))
+    }
+
+    pub fn backend_for(&self, kind: &ProviderKind) -> Result<&dyn ProviderBackend, ProviderError> {
+        self.get(&kind.to_string())
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.backends.keys().copied()
+    }
+
+    pub fn backends(&self) -> impl Iterator<Item = &dyn ProviderBackend> {
+        self.backends.values().map(|backend| backend.as_ref())
+    }
+}
+
+/// Shared registry of the built-in backends.
+pub fn builtin_registry() -> &'static ProviderRegistry {
+    static REGISTRY: OnceLock<ProviderRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ProviderRegistry::builtin)
+}
+
+/// Whether `binary` names an executable file, either as a path or by lookup
+/// on `PATH`.
+pub fn is_binary_available(binary: &str) -> bool {
+    if binary.trim().is_empty() {
+        return false;
+    }
+    let explicit = Path::new(binary);
+    if explicit.components().count() > 1 || explicit.is_absolute() {
+        return is_executable_file(explicit);
+    }
+
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(binary);
+        if is_executable_file(&candidate) {
+            return true;
+        }
+        #[cfg(windows)]
+        {
+            if is_executable_file(&dir.join(format!("{binary}.exe"))) {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+fn is_executable_file(path: &Path) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    if !metadata.is_file() {
+        return false;
+    }
+    #[cfg(unix)]
+    {
+        metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        true
+    }
+}
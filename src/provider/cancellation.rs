@@ -0,0 +1,44 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Cooperative cancellation signal polled by the provider and command
+/// runners. A token bound to a marker file also fires once any process
+/// creates that file, so `workflow cancel` can reach a supervisor-owned run.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    canceled: Arc<AtomicBool>,
+    marker: Option<PathBuf>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_marker(marker: impl Into<PathBuf>) -> Self {
+        Self {
+            canceled: Arc::new(AtomicBool::new(false)),
+            marker: Some(marker.into()),
+        }
+    }
+
+    pub fn marker(&self) -> Option<&Path> {
+        self.marker.as_deref()
+    }
+
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        if self.canceled.load(Ordering::Relaxed) {
+            return true;
+        }
+        if self.marker.as_deref().is_some_and(Path::exists) {
+            self.canceled.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+}
@@ -0,0 +1,73 @@
+use crate::provider::backend::ProviderBackend;
+use crate::provider::{
+    parse_gemini_error, parse_gemini_output, parse_gemini_session_id, parse_gemini_usage,
+    ProviderError, ProviderRequest, ProviderUsage,
+};
+
+pub const GEMINI_BINARY_ENV_VAR: &str = "DIRECLAW_PROVIDER_BIN_GEMINI";
+
+/// Gemini CLI run headless with `--prompt` and json output.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeminiBackend;
+
+impl ProviderBackend for GeminiBackend {
+    fn id(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn default_binary(&self) -> Option<&'static str> {
+        Some("gemini")
+    }
+
+    fn binary_env_var(&self) -> Option<&'static str> {
+        Some(GEMINI_BINARY_ENV_VAR)
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "install the Gemini CLI"
+    }
+
+    fn model_options(&self) -> &'static [&'static str] {
+        &["gemini-2.5-pro", "gemini-2.5-flash"]
+    }
+
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        model: &str,
+        resume_session: Option<&str>,
+    ) -> Vec<String> {
+        let mut args = vec![
+            "--yolo".to_string(),
+            "--model".to_string(),
+            model.to_string(),
+        ];
+        if let Some(session_id) = resume_session {
+            args.push("--resume".to_string());
+            args.push(session_id.to_string());
+        }
+        args.extend([
+            "--output-format".to_string(),
+            "json".to_string(),
+            "--prompt".to_string(),
+            request.message.clone(),
+        ]);
+        args
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError> {
+        parse_gemini_output(stdout)
+    }
+
+    fn parse_usage(&self, stdout: &str) -> Option<ProviderUsage> {
+        parse_gemini_usage(stdout)
+    }
+
+    fn parse_error(&self, stdout: &str) -> Option<String> {
+        parse_gemini_error(stdout)
+    }
+
+    fn parse_session_id(&self, stdout: &str) -> Option<String> {
+        parse_gemini_session_id(stdout)
+    }
+}
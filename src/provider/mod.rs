@@ -1,21 +1,34 @@
+pub mod anthropic;
+pub mod backend;
+pub mod cancellation;
+pub mod gemini;
 pub mod invocation;
 pub mod model_map;
+pub mod openai;
+pub mod openai_compatible;
 pub mod output_parse;
 pub mod prompt_files;
 pub mod runner;
 pub mod types;
 
+pub use backend::{builtin_registry, ProviderBackend, ProviderHealth, ProviderRegistry};
+pub use cancellation::CancellationToken;
 pub use invocation::build_invocation;
 pub use model_map::resolve_anthropic_model;
-pub use output_parse::parse_openai_jsonl;
+pub use output_parse::{
+    parse_anthropic_error, parse_anthropic_session_id, parse_anthropic_usage,
+    parse_chat_completion_output, parse_chat_completion_usage, parse_gemini_error,
+    parse_gemini_output, parse_gemini_session_id, parse_gemini_usage, parse_openai_error,
+    parse_openai_jsonl, parse_openai_session_id, parse_openai_usage,
+};
 pub use prompt_files::{
     consume_reset_flag, read_to_string, write_file_backed_prompt, ResetResolution,
 };
-pub use runner::{run_provider, RunnerBinaries};
+pub use runner::{run_captured, run_provider, CapturedProcess, RunnerBinaries};
 pub(crate) use types::io_error;
 pub use types::{
-    InvocationLog, InvocationSpec, PromptArtifacts, ProviderError, ProviderKind, ProviderRequest,
-    ProviderResult,
+    InvocationLog, InvocationSpec, PromptArtifacts, ProviderAttempt, ProviderEndpoint,
+    ProviderError, ProviderFallback, ProviderKind, ProviderRequest, ProviderResult, ProviderUsage,
 };
 
 #[cfg(test)]
@@ -43,6 +56,10 @@ mod tests {
             reset_requested: false,
             fresh_on_failure: false,
             env_overrides: BTreeMap::new(),
+            cancellation: CancellationToken::new(),
+            endpoint: None,
+            session_id: None,
+            fallbacks: Vec::new(),
         }
     }
 
@@ -65,26 +82,64 @@ mod tests {
     #[test]
     fn invocation_builds_expected_anthropic_args() {
         let dir = tempdir().expect("tempdir");
-        let req = sample_request(ProviderKind::Anthropic, dir.path());
+        let mut req = sample_request(ProviderKind::ANTHROPIC, dir.path());
+        req.session_id = Some("sess-1".to_string());
         let spec = build_invocation(&req, &RunnerBinaries::default()).expect("build");
         assert_eq!(spec.binary, "claude");
         assert!(spec
             .args
             .contains(&"--dangerously-skip-permissions".to_string()));
-        assert!(spec.args.contains(&"-c".to_string()));
+        assert!(spec
+            .args
+            .windows(2)
+            .any(|pair| pair[0] == "--resume" && pair[1] == "sess-1"));
         assert!(spec.args.contains(&"-p".to_string()));
+        assert!(spec
+            .args
+            .windows(2)
+            .any(|pair| pair[0] == "--output-format" && pair[1] == "json"));
+    }
+
+    #[test]
+    fn anthropic_output_reads_json_result_text_and_falls_back_to_plain_text() {
+        let json = r#"{"type":"result","is_error":false,"duration_ms":1200,"result":" done \n","total_cost_usd":0.0125,"usage":{"input_tokens":3,"output_tokens":7,"cache_read_input_tokens":11,"cache_creation_input_tokens":5}}"#;
+        assert_eq!(
+            output_parse::parse_anthropic_output(json).expect("json"),
+            "done"
+        );
+        assert_eq!(
+            output_parse::parse_anthropic_output("  plain reply\n").expect("plain"),
+            "plain reply"
+        );
+        assert!(output_parse::parse_anthropic_output(r#"{"type":"result","result":""}"#).is_err());
+
+        let usage = parse_anthropic_usage(json).expect("usage");
+        assert_eq!(
+            usage,
+            ProviderUsage {
+                input_tokens: 3,
+                output_tokens: 7,
+                cache_read_tokens: 11,
+                cache_creation_tokens: 5,
+                duration_ms: 1200,
+                cost_usd_micros: 12_500,
+            }
+        );
+        assert_eq!(usage.cost_usd(), "0.012500");
+        assert_eq!(parse_anthropic_usage("plain reply"), None);
     }
 
     #[test]
     fn invocation_builds_expected_openai_args_and_resume_behavior() {
         let dir = tempdir().expect("tempdir");
-        let mut req = sample_request(ProviderKind::OpenAi, dir.path());
+        let mut req = sample_request(ProviderKind::OPENAI, dir.path());
         req.model = "gpt-5.3-codex-spark".to_string();
+        req.session_id = Some("thread-1".to_string());
 
         let spec = build_invocation(&req, &RunnerBinaries::default()).expect("build");
         assert_eq!(spec.binary, "codex");
-        assert_eq!(&spec.args[0], "exec");
-        assert!(spec.args.contains(&"resume".to_string()));
+        assert_eq!(&spec.args[..3], ["exec", "resume", "thread-1"]);
+        assert!(!spec.args.contains(&"--last".to_string()));
         assert!(spec.args.contains(&"--json".to_string()));
         assert!(spec.args.contains(&"--full-auto".to_string()));
         assert!(!spec
@@ -0,0 +1,71 @@
+use crate::provider::backend::ProviderBackend;
+use crate::provider::{
+    parse_openai_error, parse_openai_jsonl, parse_openai_session_id, parse_openai_usage,
+    ProviderError, ProviderRequest, ProviderUsage,
+};
+
+pub const OPENAI_BINARY_ENV_VAR: &str = "DIRECLAW_PROVIDER_BIN_OPENAI";
+
+/// Codex CLI run through `codex exec --json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenAiBackend;
+
+impl ProviderBackend for OpenAiBackend {
+    fn id(&self) -> &'static str {
+        "openai"
+    }
+
+    fn default_binary(&self) -> Option<&'static str> {
+        Some("codex")
+    }
+
+    fn binary_env_var(&self) -> Option<&'static str> {
+        Some(OPENAI_BINARY_ENV_VAR)
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "install the OpenAI Codex CLI"
+    }
+
+    fn model_options(&self) -> &'static [&'static str] {
+        &["gpt-5.3-codex", "gpt-5.3-codex-spark"]
+    }
+
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        model: &str,
+        resume_session: Option<&str>,
+    ) -> Vec<String> {
+        let mut args = vec!["exec".to_string()];
+        if let Some(session_id) = resume_session {
+            args.push("resume".to_string());
+            args.push(session_id.to_string());
+        }
+        args.extend([
+            "--model".to_string(),
+            model.to_string(),
+            "--skip-git-repo-check".to_string(),
+            "--full-auto".to_string(),
+            "--json".to_string(),
+            request.message.clone(),
+        ]);
+        args
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError> {
+        parse_openai_jsonl(stdout)
+    }
+
+    fn parse_usage(&self, stdout: &str) -> Option<ProviderUsage> {
+        parse_openai_usage(stdout)
+    }
+
+    fn parse_error(&self, stdout: &str) -> Option<String> {
+        parse_openai_error(stdout)
+    }
+
+    fn parse_session_id(&self, stdout: &str) -> Option<String> {
+        parse_openai_session_id(stdout)
+    }
+}
@@ -0,0 +1,304 @@
+use crate::provider::backend::{ProviderBackend, ProviderHealth};
+use crate::provider::{
+    io_error, parse_chat_completion_output, parse_chat_completion_usage, read_to_string,
+    InvocationLog, InvocationSpec, PromptArtifacts, ProviderEndpoint, ProviderError, ProviderKind,
+    ProviderRequest, ProviderResult, ProviderUsage, RunnerBinaries,
+};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+/// Bound on the `GET /models` request `direclaw doctor` sends.
+const ENDPOINT_HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Any server exposing OpenAI's `/chat/completions` (vLLM, llama-server,
+/// hosted gateways), called over HTTP instead of spawning a CLI. The prompt
+/// and context files are sent as chat messages, so the model answers from
+/// their text alone and has no workspace tools.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OpenAiCompatibleBackend;
+
+impl ProviderBackend for OpenAiCompatibleBackend {
+    fn id(&self) -> &'static str {
+        "openai_compatible"
+    }
+
+    // No CLI binary: requests go to the agent's endpoint.
+    fn default_binary(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn binary_env_var(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "set `endpoint.base_url` on the agent"
+    }
+
+    fn uses_endpoint(&self) -> bool {
+        true
+    }
+
+    /// Chat completions keep no server-side session to continue.
+    fn continues_session(&self, _request: &ProviderRequest) -> bool {
+        false
+    }
+
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        _model: &str,
+        _resume_session: Option<&str>,
+    ) -> Vec<String> {
+        request
+            .endpoint
+            .as_ref()
+            .map(|endpoint| vec![chat_completions_url(endpoint)])
+            .unwrap_or_default()
+    }
+
+    fn build_invocation(
+        &self,
+        request: &ProviderRequest,
+        _binaries: &RunnerBinaries,
+    ) -> Result<InvocationSpec, ProviderError> {
+        let endpoint = request_endpoint(request)?;
+        Ok(InvocationSpec {
+            binary: "POST".to_string(),
+            args: vec![chat_completions_url(endpoint)],
+            resolved_model: self.resolve_model(&request.model)?,
+        })
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError> {
+        parse_chat_completion_output(stdout)
+    }
+
+    fn parse_usage(&self, stdout: &str) -> Option<ProviderUsage> {
+        parse_chat_completion_usage(stdout)
+    }
+
+    /// Nothing to install; each agent's endpoint is checked on its own.
+    fn health_check(&self, _binaries: &RunnerBinaries) -> ProviderHealth {
+        ProviderHealth {
+            ok: true,
+            detail: "transport=http".to_string(),
+            remediation: "none".to_string(),
+        }
+    }
+
+    /// Lists the endpoint's models, which fails fast on a wrong `base_url`,
+    /// an unreachable server or a rejected key without spending tokens.
+    fn endpoint_health_check(&self, endpoint: &ProviderEndpoint) -> Option<ProviderHealth> {
+        let failed = |detail: String, remediation: String| ProviderHealth {
+            ok: false,
+            detail,
+            remediation,
+        };
+        let base_url = endpoint.base_url.trim().trim_end_matches('/');
+        if base_url.is_empty() {
+            return Some(failed(
+                "endpoint.base_url is empty".to_string(),
+                self.install_hint().to_string(),
+            ));
+        }
+        let api_key = match endpoint.api_key_env.as_deref() {
+            None => None,
+            Some(env_var) => match std::env::var(env_var)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+            {
+                Some(api_key) => Some(api_key),
+                None => {
+                    return Some(failed(
+                        format!("api_key_env={env_var} is not set"),
+                        format!("set {env_var} to the endpoint's API key"),
+                    ))
+                }
+            },
+        };
+
+        let url = format!("{base_url}/models");
+        let agent = ureq::AgentBuilder::new()
+            .timeout(ENDPOINT_HEALTH_CHECK_TIMEOUT)
+            .build();
+        let mut call = agent.get(&url);
+        if let Some(api_key) = &api_key {
+            call = call.set("Authorization", &format!("Bearer {api_key}"));
+        }
+        Some(match call.call() {
+            Ok(response) => ProviderHealth {
+                ok: true,
+                detail: format!("endpoint={url} status={}", response.status()),
+                remediation: "none".to_string(),
+            },
+            Err(ureq::Error::Status(status, _)) => failed(
+                format!("endpoint={url} status={status}"),
+                "check `endpoint.base_url` and the API key".to_string(),
+            ),
+            Err(ureq::Error::Transport(err)) => failed(
+                format!("endpoint={url} error={err}"),
+                "start the server or fix `endpoint.base_url`".to_string(),
+            ),
+        })
+    }
+
+    /// Posts the request and maps HTTP statuses onto the process outcomes:
+    /// the status is recorded as the exit code and any non-2xx status fails
+    /// like a non-zero exit. Cancellation is only observed before and after
+    /// the request.
+    fn run(
+        &self,
+        request: &ProviderRequest,
+        binaries: &RunnerBinaries,
+    ) -> Result<ProviderResult, ProviderError> {
+        let spec = self.build_invocation(request, binaries)?;
+        let url = &spec.args[0];
+        let mut log = InvocationLog {
+            agent_id: request.agent_id.clone(),
+            provider: request.provider.clone(),
+            model: spec.resolved_model.clone(),
+            command_form: format!("{} {url}", spec.binary),
+            working_directory: request.cwd.clone(),
+            prompt_file: request.prompt_artifacts.prompt_file.clone(),
+            context_files: request.prompt_artifacts.context_files.clone(),
+            exit_code: None,
+            timed_out: false,
+            usage: None,
+            session_id: None,
+            prior_attempts: Vec::new(),
+        };
+        let api_key = resolve_api_key(request, request_endpoint(request)?)?;
+        let body = json!({
+            "model": spec.resolved_model,
+            "messages": chat_messages(&request.prompt_artifacts)?,
+        });
+        if request.cancellation.is_canceled() {
+            return Err(ProviderError::Canceled {
+                provider: request.provider.clone(),
+                log: Box::new(log),
+            });
+        }
+
+        let agent = ureq::AgentBuilder::new().timeout(request.timeout).build();
+        let mut call = agent.post(url).set("Content-Type", "application/json");
+        if let Some(api_key) = &api_key {
+            call = call.set("Authorization", &format!("Bearer {api_key}"));
+        }
+        let started = Instant::now();
+        let response = call.send_string(&body.to_string());
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let usage = |body: &str| {
+            parse_chat_completion_usage(body).map(|mut usage| {
+                usage.duration_ms = elapsed_ms;
+                usage
+            })
+        };
+
+        match response {
+            Ok(response) => {
+                log.exit_code = Some(i32::from(response.status()));
+                let body = response
+                    .into_string()
+                    .map_err(|err| io_error(&request.cwd, err))?;
+                log.usage = usage(&body);
+                if request.cancellation.is_canceled() {
+                    return Err(ProviderError::Canceled {
+                        provider: request.provider.clone(),
+                        log: Box::new(log),
+                    });
+                }
+                let message = parse_chat_completion_output(&body).map_err(|err| match err {
+                    ProviderError::ParseFailure {
+                        provider, reason, ..
+                    } => ProviderError::ParseFailure {
+                        provider,
+                        reason,
+                        log: Some(Box::new(log.clone())),
+                    },
+                    other => other,
+                })?;
+                Ok(ProviderResult { message, log })
+            }
+            Err(ureq::Error::Status(status, response)) => {
+                let body = response.into_string().unwrap_or_default();
+                log.exit_code = Some(i32::from(status));
+                log.usage = usage(&body);
+                Err(ProviderError::NonZeroExit {
+                    provider: request.provider.clone(),
+                    exit_code: i32::from(status),
+                    stderr: format!("http status {status}"),
+                    stdout: body.trim().to_string(),
+                    log: Box::new(log),
+                })
+            }
+            Err(ureq::Error::Transport(_)) if started.elapsed() >= request.timeout => {
+                log.timed_out = true;
+                Err(ProviderError::Timeout {
+                    provider: request.provider.clone(),
+                    timeout_ms: request.timeout.as_millis() as u64,
+                    log: Box::new(log),
+                })
+            }
+            Err(ureq::Error::Transport(err)) => Err(ProviderError::NonZeroExit {
+                provider: request.provider.clone(),
+                exit_code: -1,
+                stderr: format!("request to {url} failed: {err}"),
+                stdout: String::new(),
+                log: Box::new(log),
+            }),
+        }
+    }
+}
+
+fn request_endpoint(request: &ProviderRequest) -> Result<&ProviderEndpoint, ProviderError> {
+    request
+        .endpoint
+        .as_ref()
+        .filter(|endpoint| !endpoint.base_url.trim().is_empty())
+        .ok_or(ProviderError::MissingEndpoint {
+            provider: ProviderKind::OPENAI_COMPATIBLE,
+        })
+}
+
+fn chat_completions_url(endpoint: &ProviderEndpoint) -> String {
+    format!(
+        "{}/chat/completions",
+        endpoint.base_url.trim().trim_end_matches('/')
+    )
+}
+
+/// Bearer token from `api_key_env`, preferring the request's env overrides.
+fn resolve_api_key(
+    request: &ProviderRequest,
+    endpoint: &ProviderEndpoint,
+) -> Result<Option<String>, ProviderError> {
+    let Some(env_var) = endpoint.api_key_env.as_deref() else {
+        return Ok(None);
+    };
+    request
+        .env_overrides
+        .get(env_var)
+        .cloned()
+        .or_else(|| std::env::var(env_var).ok())
+        .filter(|value| !value.trim().is_empty())
+        .map(Some)
+        .ok_or_else(|| ProviderError::MissingApiKey {
+            provider: ProviderKind::OPENAI_COMPATIBLE,
+            env_var: env_var.to_string(),
+        })
+}
+
+/// Context files become system messages, followed by the prompt as the user
+/// message.
+fn chat_messages(artifacts: &PromptArtifacts) -> Result<Vec<Value>, ProviderError> {
+    let mut messages = Vec::with_capacity(artifacts.context_files.len() + 1);
+    for path in &artifacts.context_files {
+        messages.push(json!({"role": "system", "content": read_to_string(path)?}));
+    }
+    messages.push(json!({
+        "role": "user",
+        "content": read_to_string(&artifacts.prompt_file)?,
+    }));
+    Ok(messages)
+}
@@ -1,16 +1,92 @@
-use crate::provider::{ProviderError, ProviderKind};
+use crate::provider::{ProviderError, ProviderKind, ProviderUsage};
 use serde_json::Value;
 
+/// Returns the `--output-format json` result object printed by `claude`, or
+/// `None` when stdout is plain text.
+fn anthropic_result_object(stdout: &str) -> Option<Value> {
+    let value: Value = serde_json::from_str(stdout.trim()).ok()?;
+    (value.get("type").and_then(Value::as_str) == Some("result")).then_some(value)
+}
+
 pub(crate) fn parse_anthropic_output(stdout: &str) -> Result<String, ProviderError> {
+    let empty = || ProviderError::ParseFailure {
+        provider: ProviderKind::ANTHROPIC,
+        reason: "stdout was empty".to_string(),
+        log: None,
+    };
     let trimmed = stdout.trim();
     if trimmed.is_empty() {
+        return Err(empty());
+    }
+    let Some(result) = anthropic_result_object(trimmed) else {
+        return Ok(trimmed.to_string());
+    };
+    let message = result
+        .get("result")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if message.is_empty() {
         return Err(ProviderError::ParseFailure {
-            provider: ProviderKind::Anthropic,
-            reason: "stdout was empty".to_string(),
+            provider: ProviderKind::ANTHROPIC,
+            reason: "json result object carried no `result` text".to_string(),
             log: None,
         });
     }
-    Ok(trimmed.to_string())
+    Ok(message.to_string())
+}
+
+/// Returns the `result` text of a `claude` json result object flagged
+/// `is_error`, which the CLI can print even when it exits with status 0.
+pub fn parse_anthropic_error(stdout: &str) -> Option<String> {
+    let result = anthropic_result_object(stdout)?;
+    if result.get("is_error").and_then(Value::as_bool) != Some(true) {
+        return None;
+    }
+    let text = result
+        .get("result")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .unwrap_or("claude reported an error result");
+    Some(text.to_string())
+}
+
+fn session_id_field(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+}
+
+/// Reads `session_id` from the `claude` json result object.
+pub fn parse_anthropic_session_id(stdout: &str) -> Option<String> {
+    session_id_field(&anthropic_result_object(stdout)?, "session_id")
+}
+
+fn usage_counter(usage: &Value, key: &str) -> u64 {
+    usage.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+/// Reads token usage, duration and cost from the `claude` json result object.
+pub fn parse_anthropic_usage(stdout: &str) -> Option<ProviderUsage> {
+    let result = anthropic_result_object(stdout)?;
+    let usage = result.get("usage")?;
+    Some(ProviderUsage {
+        input_tokens: usage_counter(usage, "input_tokens"),
+        output_tokens: usage_counter(usage, "output_tokens"),
+        cache_read_tokens: usage_counter(usage, "cache_read_input_tokens"),
+        cache_creation_tokens: usage_counter(usage, "cache_creation_input_tokens"),
+        duration_ms: usage_counter(&result, "duration_ms"),
+        cost_usd_micros: result
+            .get("total_cost_usd")
+            .and_then(Value::as_f64)
+            .filter(|cost| cost.is_finite() && *cost > 0.0)
+            .map(|cost| (cost * 1_000_000.0).round() as u64)
+            .unwrap_or(0),
+    })
 }
 
 fn extract_agent_message(item: &Value) -> Option<String> {
@@ -66,7 +142,7 @@ pub fn parse_openai_jsonl(stdout: &str) -> Result<String, ProviderError> {
 
         let value: Value =
             serde_json::from_str(line).map_err(|err| ProviderError::ParseFailure {
-                provider: ProviderKind::OpenAi,
+                provider: ProviderKind::OPENAI,
                 reason: format!("invalid jsonl event: {err}"),
                 log: None,
             })?;
@@ -88,8 +164,241 @@ pub fn parse_openai_jsonl(stdout: &str) -> Result<String, ProviderError> {
     }
 
     last_message.ok_or_else(|| ProviderError::ParseFailure {
-        provider: ProviderKind::OpenAi,
+        provider: ProviderKind::OPENAI,
         reason: "missing terminal agent_message item.completed event".to_string(),
         log: None,
     })
 }
+
+/// Sums the `usage` of every `turn.completed` event in a `codex exec --json`
+/// stream. Codex reports no duration or cost, so both stay zero.
+pub fn parse_openai_usage(stdout: &str) -> Option<ProviderUsage> {
+    let mut total: Option<ProviderUsage> = None;
+    for line in stdout.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        if value.get("type").and_then(Value::as_str) != Some("turn.completed") {
+            continue;
+        }
+        let Some(usage) = value.get("usage") else {
+            continue;
+        };
+        total
+            .get_or_insert_with(ProviderUsage::default)
+            .accumulate(&ProviderUsage {
+                input_tokens: usage_counter(usage, "input_tokens"),
+                output_tokens: usage_counter(usage, "output_tokens"),
+                cache_read_tokens: usage_counter(usage, "cached_input_tokens"),
+                ..ProviderUsage::default()
+            });
+    }
+    total
+}
+
+/// Reads the `thread_id` of the `thread.started` event in a `codex exec
+/// --json` stream.
+pub fn parse_openai_session_id(stdout: &str) -> Option<String> {
+    stdout.lines().find_map(|line| {
+        let value = serde_json::from_str::<Value>(line.trim()).ok()?;
+        if value.get("type").and_then(Value::as_str) != Some("thread.started") {
+            return None;
+        }
+        session_id_field(&value, "thread_id")
+    })
+}
+
+/// Reads the failure a `codex exec --json` stream reported: the message of an
+/// `error` or `turn.failed` event not followed by a completed turn. Codex
+/// reports rate-limit and quota failures this way rather than on stderr.
+pub fn parse_openai_error(stdout: &str) -> Option<String> {
+    let mut reported = None;
+    for line in stdout.lines() {
+        let Ok(value) = serde_json::from_str::<Value>(line.trim()) else {
+            continue;
+        };
+        match value.get("type").and_then(Value::as_str) {
+            Some("error") => {
+                reported = Some(error_message(&value, "codex reported an error"));
+            }
+            Some("turn.failed") => {
+                let error = value.get("error").unwrap_or(&Value::Null);
+                reported = Some(error_message(error, "codex turn failed"));
+            }
+            Some("turn.completed") => reported = None,
+            _ => {}
+        }
+    }
+    reported
+}
+
+/// Text of an error object's `message`, or `fallback` when it has none.
+fn error_message(error: &Value, fallback: &str) -> String {
+    error
+        .get("message")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|message| !message.is_empty())
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+/// Returns the `--output-format json` object printed by `gemini`, skipping
+/// any notice lines the CLI prints before it.
+fn gemini_result_object(stdout: &str) -> Option<Value> {
+    // `split_inclusive` keeps each line's terminator, `\r\n` included, so
+    // the summed lengths stay on char boundaries of `stdout`.
+    let start = stdout
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let line_start = *offset;
+            *offset += line.len();
+            Some((line_start, line))
+        })
+        .find(|(_, line)| line.trim_start().starts_with('{'))
+        .map(|(offset, _)| offset)?;
+    let value: Value = serde_json::from_str(stdout[start..].trim()).ok()?;
+    value.is_object().then_some(value)
+}
+
+pub fn parse_gemini_output(stdout: &str) -> Result<String, ProviderError> {
+    let failure = |reason: String| ProviderError::ParseFailure {
+        provider: ProviderKind::GEMINI,
+        reason,
+        log: None,
+    };
+    let trimmed = stdout.trim();
+    if trimmed.is_empty() {
+        return Err(failure("stdout was empty".to_string()));
+    }
+    let Some(result) = gemini_result_object(trimmed) else {
+        return Ok(trimmed.to_string());
+    };
+    if let Some(error) = result.get("error").filter(|error| !error.is_null()) {
+        let message = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+        return Err(failure(format!("json output reported an error: {message}")));
+    }
+    let message = result
+        .get("response")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .unwrap_or_default();
+    if message.is_empty() {
+        return Err(failure(
+            "json output carried no `response` text".to_string(),
+        ));
+    }
+    Ok(message.to_string())
+}
+
+/// Reads the `error` object of `gemini --output-format json` output. Its
+/// `type` and `code` follow the message so quota errors (`code 429`) are
+/// classified like rate-limit text on stderr.
+pub fn parse_gemini_error(stdout: &str) -> Option<String> {
+    let result = gemini_result_object(stdout.trim())?;
+    let error = result.get("error").filter(|error| !error.is_null())?;
+    let mut text = error_message(error, "gemini reported an error");
+    let kind = error.get("type").and_then(Value::as_str);
+    let code = error
+        .get("code")
+        .filter(|code| !code.is_null())
+        .map(|code| match code {
+            Value::String(code) => code.clone(),
+            other => other.to_string(),
+        });
+    let details = [
+        kind.map(|kind| format!("type {kind}")),
+        code.map(|code| format!("code {code}")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !details.is_empty() {
+        text.push_str(&format!(" ({})", details.join(", ")));
+    }
+    Some(text)
+}
+
+/// Reads `session_id` from the `gemini --output-format json` object.
+pub fn parse_gemini_session_id(stdout: &str) -> Option<String> {
+    session_id_field(&gemini_result_object(stdout)?, "session_id")
+}
+
+/// Sums the per-model token counters and API latency of the `stats` block in
+/// `gemini --output-format json` output. Gemini reports no cost.
+pub fn parse_gemini_usage(stdout: &str) -> Option<ProviderUsage> {
+    let result = gemini_result_object(stdout)?;
+    let models = result.get("stats")?.get("models")?.as_object()?;
+    let mut total = ProviderUsage::default();
+    for stats in models.values() {
+        let tokens = stats.get("tokens").unwrap_or(&Value::Null);
+        let cached = usage_counter(tokens, "cached");
+        total.accumulate(&ProviderUsage {
+            input_tokens: usage_counter(tokens, "prompt").saturating_sub(cached),
+            output_tokens: usage_counter(tokens, "candidates"),
+            cache_read_tokens: cached,
+            duration_ms: stats
+                .get("api")
+                .map(|api| usage_counter(api, "totalLatencyMs"))
+                .unwrap_or(0),
+            ..ProviderUsage::default()
+        });
+    }
+    Some(total)
+}
+
+/// Reads the first choice's assistant message from a `/chat/completions`
+/// response body. Content may be a string or a list of text parts.
+pub fn parse_chat_completion_output(body: &str) -> Result<String, ProviderError> {
+    let failure = |reason: String| ProviderError::ParseFailure {
+        provider: ProviderKind::OPENAI_COMPATIBLE,
+        reason,
+        log: None,
+    };
+    let response: Value = serde_json::from_str(body.trim())
+        .map_err(|err| failure(format!("response body was not json: {err}")))?;
+    let content = response
+        .get("choices")
+        .and_then(Value::as_array)
+        .and_then(|choices| choices.first())
+        .and_then(|choice| choice.get("message"))
+        .and_then(|message| message.get("content"));
+    let message = match content {
+        Some(Value::String(text)) => text.trim().to_string(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("")
+            .trim()
+            .to_string(),
+        _ => String::new(),
+    };
+    if message.is_empty() {
+        return Err(failure(
+            "response carried no `choices[0].message.content` text".to_string(),
+        ));
+    }
+    Ok(message)
+}
+
+/// Reads `usage` from a `/chat/completions` response body. Cached prompt
+/// tokens are split out of `prompt_tokens`.
+pub fn parse_chat_completion_usage(body: &str) -> Option<ProviderUsage> {
+    let response: Value = serde_json::from_str(body.trim()).ok()?;
+    let usage = response.get("usage")?;
+    let cached = usage
+        .get("prompt_tokens_details")
+        .map(|details| usage_counter(details, "cached_tokens"))
+        .unwrap_or(0);
+    Some(ProviderUsage {
+        input_tokens: usage_counter(usage, "prompt_tokens").saturating_sub(cached),
+        output_tokens: usage_counter(usage, "completion_tokens"),
+        cache_read_tokens: cached,
+        ..ProviderUsage::default()
+    })
+}
@@ -1,38 +1,106 @@
-use crate::provider::invocation::build_invocation;
-use crate::provider::output_parse::parse_anthropic_output;
+use crate::provider::backend::{builtin_registry, ProviderBackend};
 use crate::provider::{
-    io_error, parse_openai_jsonl, InvocationLog, ProviderError, ProviderKind, ProviderRequest,
+    io_error, CancellationToken, InvocationLog, ProviderAttempt, ProviderError, ProviderRequest,
     ProviderResult,
 };
+use std::collections::BTreeMap;
 use std::io::BufReader;
 use std::io::Read;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
 const EXECUTABLE_BUSY_RETRY_DELAY_MS: u64 = 10;
 const EXECUTABLE_BUSY_MAX_ATTEMPTS: usize = 50;
 
-#[derive(Debug, Clone)]
+/// CLI binaries to run, keyed by provider id. Providers without an entry
+/// run their backend's [`ProviderBackend::default_binary`].
+#[derive(Debug, Clone, Default)]
 pub struct RunnerBinaries {
-    pub anthropic: String,
-    pub openai: String,
+    binaries: BTreeMap<String, String>,
 }
 
-impl Default for RunnerBinaries {
-    fn default() -> Self {
-        Self {
-            anthropic: "claude".to_string(),
-            openai: "codex".to_string(),
+impl RunnerBinaries {
+    /// Binaries named by each registered backend's
+    /// [`ProviderBackend::binary_env_var`], e.g. `DIRECLAW_PROVIDER_BIN_ANTHROPIC`.
+    pub fn from_env() -> Self {
+        let mut binaries = Self::default();
+        for backend in builtin_registry().backends() {
+            let Some(binary) = backend
+                .binary_env_var()
+                .and_then(|var| std::env::var(var).ok())
+                .filter(|value| !value.trim().is_empty())
+            else {
+                continue;
+            };
+            binaries.set(backend.id(), binary);
         }
+        binaries
+    }
+
+    /// Returns these binaries with `provider` running `binary`.
+    pub fn with(mut self, provider: &str, binary: impl Into<String>) -> Self {
+        self.set(provider, binary);
+        self
+    }
+
+    pub fn set(&mut self, provider: &str, binary: impl Into<String>) {
+        self.binaries.insert(provider.to_string(), binary.into());
+    }
+
+    /// Binary set for `provider`, if any.
+    pub fn get(&self, provider: &str) -> Option<&str> {
+        self.binaries.get(provider).map(String::as_str)
     }
 }
 
+/// Runs `request` on its provider, moving down `request.fallbacks` while the
+/// failure [`ProviderError::allows_fallback`]. The returned log, or the last
+/// error's log, lists the entries that failed first as `prior_attempts`.
 pub fn run_provider(
     request: &ProviderRequest,
     binaries: &RunnerBinaries,
 ) -> Result<ProviderResult, ProviderError> {
-    let spec = build_invocation(request, binaries)?;
+    let mut prior_attempts = Vec::new();
+    let mut fallbacks = request.fallbacks.iter();
+    let mut current = request.clone();
+    loop {
+        let outcome = builtin_registry()
+            .backend_for(&current.provider)
+            .and_then(|backend| backend.run(&current, binaries));
+        let mut err = match outcome {
+            Ok(mut result) => {
+                result.log.prior_attempts = prior_attempts;
+                return Ok(result);
+            }
+            Err(err) => err,
+        };
+        match fallbacks.next().filter(|_| err.allows_fallback()) {
+            Some(fallback) => {
+                prior_attempts.push(ProviderAttempt {
+                    provider: current.provider.clone(),
+                    model: current.model.clone(),
+                    error: err.to_string(),
+                });
+                current = request.for_fallback(fallback);
+            }
+            None => {
+                if let Some(log) = err.log_mut() {
+                    log.prior_attempts = prior_attempts;
+                }
+                return Err(err);
+            }
+        }
+    }
+}
+
+/// Runs `backend`'s CLI for `request` and parses its captured stdout.
+pub(crate) fn run_cli<B: ProviderBackend + ?Sized>(
+    backend: &B,
+    request: &ProviderRequest,
+    binaries: &RunnerBinaries,
+) -> Result<ProviderResult, ProviderError> {
+    let spec = backend.build_invocation(request, binaries)?;
 
     let command_form = format!("{} {}", spec.binary, spec.args.join(" "));
     let base_log = InvocationLog {
@@ -45,49 +113,155 @@ pub fn run_provider(
         context_files: request.prompt_artifacts.context_files.clone(),
         exit_code: None,
         timed_out: false,
+        usage: None,
+        session_id: None,
+        prior_attempts: Vec::new(),
     };
 
     let mut command = Command::new(&spec.binary);
-    command
-        .current_dir(&request.cwd)
-        .args(&spec.args)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    command.current_dir(&request.cwd).args(&spec.args);
 
     for (k, v) in &request.env_overrides {
         command.env(k, v);
     }
 
+    let started = Instant::now();
+    let captured = match run_captured(&mut command, request.timeout, &request.cancellation) {
+        Ok(captured) => captured,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ProviderError::MissingBinary {
+                provider: request.provider.clone(),
+                binary: spec.binary,
+                log: Box::new(base_log),
+            })
+        }
+        Err(err) => return Err(io_error(&request.cwd, err)),
+    };
+    if captured.canceled {
+        let mut log = base_log.clone();
+        log.exit_code = captured.exit_status.code();
+        return Err(ProviderError::Canceled {
+            provider: request.provider.clone(),
+            log: Box::new(log),
+        });
+    }
+    if captured.timed_out {
+        let mut log = base_log.clone();
+        log.timed_out = true;
+        log.exit_code = captured.exit_status.code();
+        return Err(ProviderError::Timeout {
+            provider: request.provider.clone(),
+            timeout_ms: request.timeout.as_millis() as u64,
+            log: Box::new(log),
+        });
+    }
+    let CapturedProcess {
+        exit_status,
+        stdout,
+        stderr,
+        ..
+    } = captured;
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let usage = backend.parse_usage(&stdout).map(|mut usage| {
+        if usage.duration_ms == 0 {
+            usage.duration_ms = elapsed_ms;
+        }
+        usage
+    });
+    let session_id = backend.parse_session_id(&stdout);
+    let reported_error = backend.parse_error(&stdout);
+
+    if !exit_status.success() || reported_error.is_some() {
+        let mut log = base_log.clone();
+        log.exit_code = exit_status.code();
+        log.usage = usage;
+        log.session_id = session_id;
+        // The reported error joins stderr so rate-limit and auth text in it
+        // is classified for fallback like any stderr.
+        let stderr = [stderr.trim(), reported_error.as_deref().unwrap_or_default()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(ProviderError::NonZeroExit {
+            provider: request.provider.clone(),
+            exit_code: exit_status.code().unwrap_or(-1),
+            stderr,
+            stdout: stdout.trim().to_string(),
+            log: Box::new(log),
+        });
+    }
+
+    let mut parse_log = base_log.clone();
+    parse_log.exit_code = exit_status.code();
+    parse_log.usage = usage;
+    parse_log.session_id = session_id;
+    let message = backend.parse_output(&stdout).map_err(|err| match err {
+        ProviderError::ParseFailure {
+            provider, reason, ..
+        } => ProviderError::ParseFailure {
+            provider,
+            reason,
+            log: Some(Box::new(parse_log.clone())),
+        },
+        other => other,
+    })?;
+
+    Ok(ProviderResult {
+        message,
+        log: parse_log,
+    })
+}
+
+/// Output of a child process run to completion (or killed on timeout or
+/// cancellation) with stdout and stderr captured.
+#[derive(Debug, Clone)]
+pub struct CapturedProcess {
+    pub exit_status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    pub canceled: bool,
+}
+
+/// Spawns `command` in its own process group with piped stdout/stderr,
+/// retrying briefly while the executable is busy. The whole group is killed
+/// once `timeout` elapses or `cancellation` fires.
+pub fn run_captured(
+    command: &mut Command,
+    timeout: Duration,
+    cancellation: &CancellationToken,
+) -> Result<CapturedProcess, std::io::Error> {
+    command.stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
     let mut busy_attempts = 0usize;
     let mut child = loop {
         match command.spawn() {
             Ok(child) => break child,
-            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
-                return Err(ProviderError::MissingBinary {
-                    provider: request.provider.clone(),
-                    binary: spec.binary,
-                    log: Box::new(base_log),
-                })
-            }
             Err(err) if err.kind() == std::io::ErrorKind::ExecutableFileBusy => {
                 if busy_attempts + 1 >= EXECUTABLE_BUSY_MAX_ATTEMPTS {
-                    return Err(io_error(&request.cwd, err));
+                    return Err(err);
                 }
                 busy_attempts += 1;
                 thread::sleep(Duration::from_millis(EXECUTABLE_BUSY_RETRY_DELAY_MS));
             }
-            Err(err) => return Err(io_error(&request.cwd, err)),
+            Err(err) => return Err(err),
         }
     };
 
     let stdout = child
         .stdout
         .take()
-        .ok_or_else(|| io_error(&request.cwd, std::io::Error::other("missing stdout pipe")))?;
+        .ok_or_else(|| std::io::Error::other("missing stdout pipe"))?;
     let stderr = child
         .stderr
         .take()
-        .ok_or_else(|| io_error(&request.cwd, std::io::Error::other("missing stderr pipe")))?;
+        .ok_or_else(|| std::io::Error::other("missing stderr pipe"))?;
 
     let stdout_reader = thread::spawn(move || {
         let mut buf = String::new();
@@ -103,76 +277,64 @@ pub fn run_provider(
     });
 
     let start = Instant::now();
-    let exit_status = loop {
-        match child.try_wait() {
-            Ok(Some(status)) => break status,
-            Ok(None) => {
-                if start.elapsed() > request.timeout {
-                    let _ = child.kill();
-                    let status = child.wait().map_err(|e| io_error(&request.cwd, e))?;
-                    let _stdout = stdout_reader.join().unwrap_or_default();
-                    let _stderr = stderr_reader.join().unwrap_or_default();
-                    let mut log = base_log.clone();
-                    log.timed_out = true;
-                    log.exit_code = status.code();
-                    return Err(ProviderError::Timeout {
-                        provider: request.provider.clone(),
-                        timeout_ms: request.timeout.as_millis() as u64,
-                        log: Box::new(log),
-                    });
+    let (exit_status, timed_out, canceled) = loop {
+        match child.try_wait()? {
+            Some(status) => break (status, false, false),
+            None => {
+                if cancellation.is_canceled() {
+                    let killed = kill_process_group(&mut child);
+                    let status = child.wait()?;
+                    killed?;
+                    break (status, false, true);
+                }
+                if start.elapsed() > timeout {
+                    let killed = kill_process_group(&mut child);
+                    let status = child.wait()?;
+                    killed?;
+                    break (status, true, false);
                 }
                 thread::sleep(Duration::from_millis(10));
             }
-            Err(err) => return Err(io_error(&request.cwd, err)),
         }
     };
 
-    let stdout = stdout_reader.join().unwrap_or_default();
-    let stderr = stderr_reader.join().unwrap_or_default();
-
-    if !exit_status.success() {
-        let failure_detail = format_failure_detail(&stdout, &stderr);
-        let mut log = base_log.clone();
-        log.exit_code = exit_status.code();
-        return Err(ProviderError::NonZeroExit {
-            provider: request.provider.clone(),
-            exit_code: exit_status.code().unwrap_or(-1),
-            stderr: failure_detail,
-            log: Box::new(log),
-        });
-    }
-
-    let mut parse_log = base_log.clone();
-    parse_log.exit_code = exit_status.code();
-    let message_result = match request.provider {
-        ProviderKind::Anthropic => parse_anthropic_output(&stdout),
-        ProviderKind::OpenAi => parse_openai_jsonl(&stdout),
-    };
-    let message = message_result.map_err(|err| match err {
-        ProviderError::ParseFailure {
-            provider, reason, ..
-        } => ProviderError::ParseFailure {
-            provider,
-            reason,
-            log: Some(Box::new(parse_log.clone())),
-        },
-        other => other,
-    })?;
-
-    Ok(ProviderResult {
-        message,
-        log: parse_log,
+    Ok(CapturedProcess {
+        exit_status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+        timed_out,
+        canceled,
     })
 }
 
-fn format_failure_detail(stdout: &str, stderr: &str) -> String {
-    let stdout = stdout.trim();
-    let stderr = stderr.trim();
-
-    match (stdout.is_empty(), stderr.is_empty()) {
-        (true, true) => String::new(),
-        (true, false) => stderr.to_string(),
-        (false, true) => stdout.to_string(),
-        (false, false) => format!("stderr:\n{stderr}\n\nstdout:\n{stdout}"),
-    }
+/// Kills the child's process group so tools it spawned die with it, then the
+/// child itself in case the group signal could not be delivered. A group
+/// that has already exited is not an error.
+fn kill_process_group(child: &mut Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    let group = {
+        // The child leads its own group (`process_group(0)` at spawn).
+        let pgid = child.id() as libc::pid_t;
+        // SAFETY: `killpg` only sends a signal; it does not touch memory.
+        if unsafe { libc::killpg(pgid, libc::SIGKILL) } == 0 {
+            Ok(())
+        } else {
+            let err = std::io::Error::last_os_error();
+            if err.raw_os_error() == Some(libc::ESRCH) {
+                Ok(())
+            } else {
+                Err(std::io::Error::new(
+                    err.kind(),
+                    format!("failed to kill process group {pgid}: {err}"),
+                ))
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let group = Ok(());
+    let child_killed = match child.kill() {
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidInput => Ok(()),
+        other => other,
+    };
+    group.and(child_killed)
 }
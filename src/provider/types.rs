@@ -1,3 +1,7 @@
+use crate::provider::backend::{builtin_registry, ProviderBackend};
+use crate::provider::CancellationToken;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -8,17 +12,31 @@ pub enum ProviderError {
     UnknownProvider(String),
     #[error("unsupported anthropic model `{0}`")]
     UnsupportedAnthropicModel(String),
+    #[error("provider {provider} requires an endpoint base url")]
+    MissingEndpoint { provider: ProviderKind },
+    #[error("provider {provider} api key variable `{env_var}` is not set")]
+    MissingApiKey {
+        provider: ProviderKind,
+        env_var: String,
+    },
     #[error("provider binary missing for {provider}: {binary}")]
     MissingBinary {
         provider: ProviderKind,
         binary: String,
         log: Box<InvocationLog>,
     },
-    #[error("provider process failed for {provider} with exit code {exit_code}: {stderr}")]
+    #[error(
+        "provider process failed for {provider} with exit code {exit_code}: {}",
+        failure_detail(.stdout, .stderr)
+    )]
     NonZeroExit {
         provider: ProviderKind,
         exit_code: i32,
+        /// What the provider reported as its error; the only text failures
+        /// are classified on.
         stderr: String,
+        /// Output captured alongside the failure, kept for the message.
+        stdout: String,
         log: Box<InvocationLog>,
     },
     #[error("provider process timed out for {provider} after {timeout_ms}ms")]
@@ -27,6 +45,11 @@ pub enum ProviderError {
         timeout_ms: u64,
         log: Box<InvocationLog>,
     },
+    #[error("provider process canceled for {provider}")]
+    Canceled {
+        provider: ProviderKind,
+        log: Box<InvocationLog>,
+    },
     #[error("provider output parse failure for {provider}: {reason}")]
     ParseFailure {
         provider: ProviderKind,
@@ -41,18 +64,166 @@ pub enum ProviderError {
     },
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ProviderKind {
-    Anthropic,
-    OpenAi,
+/// Lower-cased stderr phrases that mark a provider exit as a rate-limit or
+/// quota failure rather than an ordinary error. Status codes only count in
+/// status context, so incidental digits (ids, timestamps, token counts) do
+/// not match.
+const RATE_LIMIT_MARKERS: &[&str] = &[
+    "rate limit",
+    "rate-limit",
+    "rate_limit",
+    "ratelimit",
+    "too many requests",
+    "usage limit",
+    "quota exceeded",
+    "exceeded your current quota",
+    "insufficient_quota",
+    "resource_exhausted",
+    "overloaded",
+    "status 429",
+    "status: 429",
+    "status code 429",
+    "http 429",
+    "error 429",
+    "code 429",
+];
+
+/// Lower-cased stderr phrases that mark a provider exit as rejected
+/// credentials or a missing login.
+const AUTH_FAILURE_MARKERS: &[&str] = &[
+    "unauthorized",
+    "authentication failed",
+    "authentication_error",
+    "invalid api key",
+    "invalid_api_key",
+    "api key not valid",
+    "not logged in",
+    "please run /login",
+    "status 401",
+    "status: 401",
+    "status code 401",
+    "http 401",
+    "error 401",
+    "code 401",
+];
+
+impl ProviderError {
+    /// Whether a non-zero provider exit reported a rate-limit or quota error
+    /// on stderr.
+    pub fn is_rate_limited(&self) -> bool {
+        self.stderr_contains_any(RATE_LIMIT_MARKERS)
+    }
+
+    /// Whether the provider rejected its credentials: an unset API key
+    /// variable, or a non-zero exit reporting an auth failure on stderr.
+    pub fn is_auth_failure(&self) -> bool {
+        matches!(self, Self::MissingApiKey { .. }) || self.stderr_contains_any(AUTH_FAILURE_MARKERS)
+    }
+
+    /// Whether the next entry of the agent's fallback chain should be tried:
+    /// the provider is unavailable (missing binary), rate-limited or out of
+    /// quota, or rejected its credentials.
+    pub fn allows_fallback(&self) -> bool {
+        matches!(self, Self::MissingBinary { .. })
+            || self.is_rate_limited()
+            || self.is_auth_failure()
+    }
+
+    pub(crate) fn log_mut(&mut self) -> Option<&mut InvocationLog> {
+        match self {
+            Self::MissingBinary { log, .. }
+            | Self::NonZeroExit { log, .. }
+            | Self::Timeout { log, .. }
+            | Self::Canceled { log, .. } => Some(log),
+            Self::ParseFailure { log, .. } => log.as_deref_mut(),
+            Self::UnknownProvider(_)
+            | Self::UnsupportedAnthropicModel(_)
+            | Self::MissingEndpoint { .. }
+            | Self::MissingApiKey { .. }
+            | Self::Io { .. } => None,
+        }
+    }
+
+    fn stderr_contains_any(&self, markers: &[&str]) -> bool {
+        match self {
+            Self::NonZeroExit { stderr, .. } => {
+                let stderr = stderr.to_ascii_lowercase();
+                markers
+                    .iter()
+                    .any(|marker| contains_phrase(&stderr, marker))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether `phrase` occurs in `text` starting at a word boundary. A phrase
+/// ending in a digit must also end at one, so `status 429` does not match
+/// `status 4290`.
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let needs_end_boundary = phrase.ends_with(|c: char| c.is_ascii_digit());
+    text.match_indices(phrase).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + phrase.len()..].chars().next();
+        let starts_word = !before.is_some_and(is_word);
+        let ends_word = !needs_end_boundary || !after.is_some_and(is_word);
+        starts_word && ends_word
+    })
+}
+
+/// Failure text of a non-zero exit: stderr, stdout, or both labelled.
+fn failure_detail(stdout: &str, stderr: &str) -> String {
+    match (stdout.is_empty(), stderr.is_empty()) {
+        (true, true) => String::new(),
+        (true, false) => stderr.to_string(),
+        (false, true) => stdout.to_string(),
+        (false, false) => format!("stderr:\n{stderr}\n\nstdout:\n{stdout}"),
+    }
+}
+
+/// Id of a provider backend: the `provider:` value of an agent and the key
+/// it is registered under in a [`ProviderRegistry`](crate::provider::ProviderRegistry).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProviderKind(Cow<'static, str>);
+
+impl ProviderKind {
+    pub const ANTHROPIC: Self = Self(Cow::Borrowed("anthropic"));
+    pub const OPENAI: Self = Self(Cow::Borrowed("openai"));
+    pub const GEMINI: Self = Self(Cow::Borrowed("gemini"));
+    pub const OPENAI_COMPATIBLE: Self = Self(Cow::Borrowed("openai_compatible"));
+
+    /// Id of `backend`, without checking it is registered anywhere.
+    pub fn of(backend: &dyn ProviderBackend) -> Self {
+        Self(Cow::Borrowed(backend.id()))
+    }
+
+    /// Parses a configured `provider:` value against the built-in registry.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        builtin_registry().get(raw).map(Self::of).map_err(|_| {
+            format!(
+                "provider must be one of: {}",
+                builtin_registry().ids().collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the backend is called over HTTP at an agent `endpoint` rather
+    /// than as a CLI on this host.
+    pub fn uses_endpoint(&self) -> bool {
+        builtin_registry()
+            .get(self.as_str())
+            .is_ok_and(|backend| backend.uses_endpoint())
+    }
 }
 
 impl std::fmt::Display for ProviderKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ProviderKind::Anthropic => write!(f, "anthropic"),
-            ProviderKind::OpenAi => write!(f, "openai"),
-        }
+        f.write_str(self.as_str())
     }
 }
 
@@ -60,11 +231,20 @@ impl TryFrom<&str> for ProviderKind {
     type Error = ProviderError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.trim().to_ascii_lowercase().as_str() {
-            "anthropic" => Ok(Self::Anthropic),
-            "openai" => Ok(Self::OpenAi),
-            other => Err(ProviderError::UnknownProvider(other.to_string())),
-        }
+        builtin_registry().get(value).map(Self::of)
+    }
+}
+
+impl Serialize for ProviderKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProviderKind {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        Self::parse(&raw).map_err(serde::de::Error::custom)
     }
 }
 
@@ -86,6 +266,47 @@ pub struct ProviderRequest {
     pub reset_requested: bool,
     pub fresh_on_failure: bool,
     pub env_overrides: BTreeMap<String, String>,
+    pub cancellation: CancellationToken,
+    /// HTTP endpoint for providers that are not local CLIs.
+    pub endpoint: Option<ProviderEndpoint>,
+    /// Provider session to resume. Without one the invocation starts a
+    /// fresh session.
+    pub session_id: Option<String>,
+    /// Providers tried in order when this one fails with an error that
+    /// [`ProviderError::allows_fallback`].
+    pub fallbacks: Vec<ProviderFallback>,
+}
+
+impl ProviderRequest {
+    /// This request redirected to `fallback`. The session belongs to the
+    /// original provider, so the fallback starts fresh.
+    pub fn for_fallback(&self, fallback: &ProviderFallback) -> Self {
+        Self {
+            provider: fallback.provider.clone(),
+            model: fallback.model.clone(),
+            endpoint: fallback.endpoint.clone(),
+            session_id: None,
+            fallbacks: Vec::new(),
+            ..self.clone()
+        }
+    }
+}
+
+/// One entry of an agent's fallback chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderFallback {
+    pub provider: ProviderKind,
+    pub model: String,
+    pub endpoint: Option<ProviderEndpoint>,
+}
+
+/// OpenAI-compatible endpoint a request is sent to instead of a CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderEndpoint {
+    pub base_url: String,
+    /// Variable holding the bearer token, read from the request's
+    /// `env_overrides` before the process environment.
+    pub api_key_env: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,6 +327,83 @@ pub struct InvocationLog {
     pub context_files: Vec<PathBuf>,
     pub exit_code: Option<i32>,
     pub timed_out: bool,
+    /// Token usage the provider reported, when its output carried any.
+    pub usage: Option<ProviderUsage>,
+    /// Session the provider reported running in, when its output carried
+    /// one.
+    pub session_id: Option<String>,
+    /// Providers of the fallback chain that failed before this invocation,
+    /// in the order they were tried.
+    pub prior_attempts: Vec<ProviderAttempt>,
+}
+
+/// A fallback-chain entry that failed before the provider that answered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderAttempt {
+    pub provider: ProviderKind,
+    pub model: String,
+    pub error: String,
+}
+
+/// Token usage and timing a provider CLI reported for one invocation.
+///
+/// Counters are recorded as the provider reports them; `cost_usd_micros` is
+/// only filled by providers that report a cost.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProviderUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub duration_ms: u64,
+    pub cost_usd_micros: u64,
+}
+
+impl ProviderUsage {
+    pub fn accumulate(&mut self, other: &ProviderUsage) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cache_read_tokens = self
+            .cache_read_tokens
+            .saturating_add(other.cache_read_tokens);
+        self.cache_creation_tokens = self
+            .cache_creation_tokens
+            .saturating_add(other.cache_creation_tokens);
+        self.duration_ms = self.duration_ms.saturating_add(other.duration_ms);
+        self.cost_usd_micros = self.cost_usd_micros.saturating_add(other.cost_usd_micros);
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens
+            .saturating_add(self.output_tokens)
+            .saturating_add(self.cache_read_tokens)
+            .saturating_add(self.cache_creation_tokens)
+    }
+
+    /// Reported cost formatted as decimal US dollars.
+    pub fn cost_usd(&self) -> String {
+        format!(
+            "{}.{:06}",
+            self.cost_usd_micros / 1_000_000,
+            self.cost_usd_micros % 1_000_000
+        )
+    }
+}
+
+impl std::fmt::Display for ProviderUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "input_tokens={} output_tokens={} cache_read_tokens={} cache_creation_tokens={} duration_ms={} cost_usd={}",
+            self.input_tokens,
+            self.output_tokens,
+            self.cache_read_tokens,
+            self.cache_creation_tokens,
+            self.duration_ms,
+            self.cost_usd()
+        )
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1,10 +1,13 @@
 use super::{
-    heartbeat_worker, memory_worker, now_secs, queue_worker, scheduler_worker, WorkerEvent,
+    append_runtime_log, heartbeat_worker, memory_worker, now_secs, queue_worker, retry_worker,
+    scheduler_worker, StatePaths, WorkerEvent,
 };
-use crate::channels::slack;
+use crate::channels::{slack, webhook};
 use crate::config::{Settings, SlackInboundMode};
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
 use std::sync::Arc;
 use std::thread;
@@ -33,6 +36,7 @@ pub(crate) enum WorkerRuntime {
     Scheduler,
     SlackSocket,
     SlackBackfill,
+    Webhook,
     Heartbeat,
 }
 
@@ -126,6 +130,139 @@ fn run_slack_socket_worker_until_stop(
     });
 }
 
+fn run_webhook_worker_until_stop(
+    spec: &WorkerSpec,
+    state_root: &Path,
+    settings: &Settings,
+    stop: &Arc<AtomicBool>,
+    events: &Sender<WorkerEvent>,
+) {
+    let paths = StatePaths::new(state_root);
+    let listener = settings
+        .webhooks
+        .bind_addr()
+        .and_then(|addr| {
+            TcpListener::bind(addr).map_err(|err| format!("failed to bind {addr}: {err}"))
+        })
+        .and_then(|listener| {
+            listener
+                .set_nonblocking(true)
+                .map(|_| listener)
+                .map_err(|err| format!("failed to configure webhook listener: {err}"))
+        });
+    match listener {
+        Ok(listener) => {
+            append_runtime_log(
+                &paths,
+                "info",
+                "webhook.listening",
+                &format!("bind={}", settings.webhooks.bind),
+            );
+            let ingress = webhook::WebhookIngress::new(settings.clone());
+            let open_connections = Arc::new(AtomicUsize::new(0));
+            let mut last_heartbeat = std::time::Instant::now();
+            while !stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        if open_connections.fetch_add(1, Ordering::AcqRel)
+                            >= MAX_WEBHOOK_CONNECTIONS
+                        {
+                            open_connections.fetch_sub(1, Ordering::AcqRel);
+                            reject_busy_webhook_connection(&paths, stream);
+                        } else {
+                            let paths = paths.clone();
+                            let ingress = ingress.clone();
+                            let open_connections = open_connections.clone();
+                            let max_body_bytes = settings.webhooks.max_body_bytes;
+                            thread::spawn(move || {
+                                serve_webhook_connection(&paths, &ingress, stream, max_body_bytes);
+                                open_connections.fetch_sub(1, Ordering::AcqRel);
+                            });
+                        }
+                    }
+                    Err(err) if err.kind() == ErrorKind::WouldBlock => {
+                        thread::sleep(Duration::from_millis(25));
+                    }
+                    Err(err) => {
+                        let _ = events.send(WorkerEvent::Error {
+                            worker_id: spec.id.clone(),
+                            at: now_secs(),
+                            message: format!("webhook accept failed: {err}"),
+                            fatal: false,
+                        });
+                    }
+                }
+                if last_heartbeat.elapsed() >= spec.interval {
+                    last_heartbeat = std::time::Instant::now();
+                    let _ = events.send(WorkerEvent::Heartbeat {
+                        worker_id: spec.id.clone(),
+                        at: now_secs(),
+                    });
+                }
+            }
+        }
+        Err(message) => {
+            let _ = events.send(WorkerEvent::Error {
+                worker_id: spec.id.clone(),
+                at: now_secs(),
+                message,
+                fatal: true,
+            });
+        }
+    }
+    let _ = events.send(WorkerEvent::Stopped {
+        worker_id: spec.id.clone(),
+        at: now_secs(),
+    });
+}
+
+/// Connections served at once; a slow client holds its thread for up to the
+/// read timeout, so later ones are turned away rather than queued behind it.
+const MAX_WEBHOOK_CONNECTIONS: usize = 16;
+
+fn reject_busy_webhook_connection(paths: &StatePaths, stream: TcpStream) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+    append_runtime_log(
+        paths,
+        "warn",
+        "webhook.busy",
+        &format!("open_connections={MAX_WEBHOOK_CONNECTIONS}"),
+    );
+    let response = webhook::http::error_response(503, "webhook server is busy, retry later");
+    let _ = webhook::http::write_response(&stream, &response);
+}
+
+fn serve_webhook_connection(
+    paths: &StatePaths,
+    ingress: &webhook::WebhookIngress,
+    stream: TcpStream,
+    max_body_bytes: usize,
+) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let _ = stream.set_write_timeout(Some(Duration::from_secs(5)));
+    let (path, response) = match webhook::http::read_request(&stream, max_body_bytes) {
+        Ok(request) => (request.path.clone(), ingress.handle(&request, now_secs())),
+        Err(response) => (String::new(), response),
+    };
+    let level = if response.status < 400 {
+        "info"
+    } else {
+        "warn"
+    };
+    append_runtime_log(
+        paths,
+        level,
+        "webhook.request",
+        &format!(
+            "path={path} status={} body={}",
+            response.status, response.body
+        ),
+    );
+    let _ = webhook::http::write_response(&stream, &response);
+}
+
 pub fn tick_slack_worker(state_root: &Path, settings: &Settings) -> Result<(), String> {
     tick_slack_socket_worker(state_root, settings)
 }
@@ -229,6 +366,14 @@ pub(crate) fn build_worker_specs(settings: &Settings) -> Vec<WorkerSpec> {
         }
     }
 
+    if settings.webhooks.enabled {
+        specs.push(WorkerSpec {
+            id: "channel:webhook".to_string(),
+            runtime: WorkerRuntime::Webhook,
+            interval: Duration::from_secs(2),
+        });
+    }
+
     specs
 }
 
@@ -322,6 +467,11 @@ pub(crate) fn run_worker(spec: WorkerSpec, context: WorkerRunContext) {
         return;
     }
 
+    if matches!(spec.runtime, WorkerRuntime::Webhook) {
+        run_webhook_worker_until_stop(&spec, &state_root, &settings, &stop, &events);
+        return;
+    }
+
     loop {
         if stop.load(Ordering::Relaxed) {
             if slow_shutdown {
@@ -332,13 +482,16 @@ pub(crate) fn run_worker(spec: WorkerSpec, context: WorkerRunContext) {
 
         let tick = match spec.runtime {
             WorkerRuntime::QueueProcessor => Ok(()),
-            WorkerRuntime::OrchestratorDispatcher => Ok(()),
+            WorkerRuntime::OrchestratorDispatcher => {
+                retry_worker::tick_retry_worker(&state_root, &settings)
+            }
             WorkerRuntime::Memory => memory_worker::tick_memory_worker(&settings),
             WorkerRuntime::Scheduler => {
                 scheduler_worker::tick_scheduler_worker(&state_root, &settings)
             }
             WorkerRuntime::SlackSocket => tick_slack_socket_worker(&state_root, &settings),
             WorkerRuntime::SlackBackfill => tick_slack_backfill_worker(&state_root, &settings),
+            WorkerRuntime::Webhook => Ok(()),
             WorkerRuntime::Heartbeat => {
                 heartbeat_worker::tick_heartbeat_worker(&state_root, &settings)
             }
@@ -369,6 +522,9 @@ pub(crate) fn run_worker(spec: WorkerSpec, context: WorkerRunContext) {
         }
     }
 
+    if matches!(spec.runtime, WorkerRuntime::OrchestratorDispatcher) {
+        retry_worker::join_resume_threads();
+    }
     let _ = events.send(WorkerEvent::Stopped {
         worker_id: spec.id,
         at: now_secs(),
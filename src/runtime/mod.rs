@@ -14,6 +14,7 @@ pub mod memory_worker;
 pub mod ownership_lock;
 pub mod queue_worker;
 pub mod recovery;
+pub mod retry_worker;
 pub mod scheduler_worker;
 pub mod state_paths;
 pub mod supervisor;
@@ -33,7 +34,11 @@ pub use ownership_lock::{
 };
 pub use queue_worker::{drain_queue_once, drain_queue_once_with_binaries};
 pub use queue_worker::{queue_polling_defaults, QueuePollingDefaults};
-pub use recovery::recover_processing_queue_entries;
+pub use recovery::{
+    due_retry_workflow_runs, recover_processing_queue_entries, recover_workflow_runs,
+    resume_recovered_workflow_run, InterruptedStepAttempt, RecoverableWorkflowRun,
+    WorkflowRunRecoveryReport,
+};
 pub use state_paths::{
     bootstrap_state_root, default_state_root_path, StatePaths, DEFAULT_STATE_ROOT_DIR,
 };
@@ -245,6 +250,33 @@ memory:
         assert_eq!(memory_worker.interval, Duration::from_secs(7));
     }
 
+    #[test]
+    fn webhook_listener_worker_runs_only_when_enabled() {
+        let mut settings: Settings = serde_yaml::from_str(
+            r#"
+workspaces_path: /tmp
+orchestrators:
+  eng: {}
+webhooks:
+  routes:
+    deploy:
+      path: /hooks/deploy
+      secret_env: DEPLOY_SECRET
+      orchestrator_id: eng
+      workflow_id: deploy
+"#,
+        )
+        .expect("parse settings");
+        let has_webhook_worker = |settings: &Settings| {
+            channel_worker::build_worker_specs(settings)
+                .iter()
+                .any(|spec| spec.id == "channel:webhook")
+        };
+        assert!(!has_webhook_worker(&settings));
+        settings.webhooks.enabled = true;
+        assert!(has_webhook_worker(&settings));
+    }
+
     fn sample_incoming(message_id: &str) -> IncomingMessage {
         IncomingMessage {
             channel: "slack".to_string(),
@@ -360,10 +392,9 @@ channels: {{}}
             let stop = stop.clone();
             let settings = settings.clone();
             let state_root = state_root.clone();
-            let binaries = RunnerBinaries {
-                anthropic: claude.display().to_string(),
-                openai: "unused".to_string(),
-            };
+            let binaries = RunnerBinaries::default()
+                .with("anthropic", claude.display().to_string())
+                .with("openai", "unused");
             let config = queue_worker::QueueProcessorLoopConfig {
                 slow_shutdown: false,
                 max_concurrency: 1,
@@ -436,10 +467,7 @@ channels: {{}}
             queue_worker::QueueProcessorLoopConfig {
                 slow_shutdown: false,
                 max_concurrency: 1,
-                binaries: RunnerBinaries {
-                    anthropic: "claude".to_string(),
-                    openai: "codex".to_string(),
-                },
+                binaries: RunnerBinaries::default(),
             },
         );
 
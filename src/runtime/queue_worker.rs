@@ -4,6 +4,9 @@ use crate::config::Settings;
 use crate::orchestration::conversation_context::append_outbound_turn;
 use crate::orchestration::error::OrchestratorError;
 use crate::orchestration::function_registry::FunctionRegistry;
+use crate::orchestration::human_review::{
+    find_pending_human_review_run, load_pending_human_review,
+};
 use crate::orchestration::routing::process_queued_message_with_runner_binaries_and_hook;
 use crate::orchestration::run_store::{RunState, StepAttemptRecord, WorkflowRunStore};
 use crate::orchestration::scheduler::parse_trigger_envelope;
@@ -680,12 +683,7 @@ fn enqueue_workflow_selection_ack(
 }
 
 fn resolve_runner_binaries() -> RunnerBinaries {
-    RunnerBinaries {
-        anthropic: std::env::var("DIRECLAW_PROVIDER_BIN_ANTHROPIC")
-            .unwrap_or_else(|_| "claude".to_string()),
-        openai: std::env::var("DIRECLAW_PROVIDER_BIN_OPENAI")
-            .unwrap_or_else(|_| "codex".to_string()),
-    }
+    RunnerBinaries::from_env()
 }
 
 fn sleep_with_stop(stop: &AtomicBool, total: Duration) -> bool {
@@ -799,6 +797,7 @@ fn workflow_lifecycle_messages(
             format!("workflow started\nrun_id={run_id}"),
             "orchestrator".to_string(),
         ));
+        messages.extend(human_review_request_message(run_store, run_id));
         return messages;
     }
 
@@ -830,9 +829,22 @@ fn workflow_lifecycle_messages(
 
     let final_message = final_user_message(run_store, run_id, attempts.last());
     messages.push((final_message, "orchestrator".to_string()));
+    messages.extend(human_review_request_message(run_store, run_id));
     messages
 }
 
+fn human_review_request_message(
+    run_store: &WorkflowRunStore,
+    run_id: &str,
+) -> Option<(String, String)> {
+    let review_run_id = find_pending_human_review_run(run_store, run_id).ok()??;
+    let request = load_pending_human_review(run_store, &review_run_id).ok()??;
+    Some((
+        format!("{}\nrun_id={review_run_id}", request.message),
+        "orchestrator".to_string(),
+    ))
+}
+
 fn step_attempts_by_time(state_root: &Path, run_id: &str) -> Vec<StepAttemptRecord> {
     let steps_root = state_root.join("workflows/runs").join(run_id).join("steps");
     let mut attempts = Vec::new();
@@ -1191,6 +1203,11 @@ mod tests {
             next_step_id: None,
             error: None,
             output_validation_errors: Default::default(),
+            parallel_step_id: None,
+            foreach_step_id: None,
+            item_index: None,
+            error_class: None,
+            usage: None,
         }
     }
 
@@ -1306,6 +1323,12 @@ mod tests {
             selected_workflow: None,
             status_conversation_id: None,
             terminal_reason: Some("engine start failed".to_string()),
+            waiting_seconds: 0,
+            active_steps: Vec::new(),
+            parent_run_id: None,
+            child_run_ids: Vec::new(),
+            rerun_of: None,
+            retry_at: None,
         };
         let attempts = vec![
             StepAttemptRecord {
@@ -1321,6 +1344,11 @@ mod tests {
                 next_step_id: None,
                 error: None,
                 output_validation_errors: Default::default(),
+                parallel_step_id: None,
+                foreach_step_id: None,
+                item_index: None,
+                error_class: None,
+                usage: None,
             },
             StepAttemptRecord {
                 run_id: "run-99".to_string(),
@@ -1335,6 +1363,11 @@ mod tests {
                 next_step_id: None,
                 error: Some("provider exited 7".to_string()),
                 output_validation_errors: Default::default(),
+                parallel_step_id: None,
+                foreach_step_id: None,
+                item_index: None,
+                error_class: None,
+                usage: None,
             },
         ];
         let message = render_dead_letter_failure_message(Some(&run), &attempts, 3, "queue failure");
@@ -1,6 +1,14 @@
+use crate::config::{load_orchestrator_config, InterruptedAttemptPolicy, Settings};
+use crate::orchestration::run_store::{
+    RunState, StepAttemptRecord, WorkflowRunRecord, WorkflowRunStore,
+};
+use crate::orchestration::workflow_engine::WorkflowEngine;
+use crate::orchestration::workspace_access::verify_orchestrator_workspace_access;
+use crate::provider::RunnerBinaries;
 use crate::queue::{IncomingMessage, OutgoingMessage, QueuePaths};
+use serde_json::Map;
 use sha2::{Digest, Sha256};
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -127,3 +135,346 @@ fn collect_outgoing_message_keys(
     }
     Ok(keys)
 }
+
+/// A step attempt that was running when the previous process died.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterruptedStepAttempt {
+    pub orchestrator_id: String,
+    pub run_id: String,
+    pub step_id: String,
+    pub attempt: u32,
+    pub policy: InterruptedAttemptPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoverableWorkflowRun {
+    pub orchestrator_id: String,
+    pub run_id: String,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WorkflowRunRecoveryReport {
+    /// Root runs to resume, oldest first.
+    pub resumable: Vec<RecoverableWorkflowRun>,
+    pub interrupted_attempts: Vec<InterruptedStepAttempt>,
+    /// Runs left parked in `waiting` on a human decision.
+    pub parked: Vec<RecoverableWorkflowRun>,
+    /// Runs whose starting message is still queued; routing that message
+    /// again resumes them, so they are not resumed directly.
+    pub requeued: Vec<RecoverableWorkflowRun>,
+    /// Orchestrators whose runs could not be recovered.
+    pub failed: Vec<OrchestratorRecoveryFailure>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrchestratorRecoveryFailure {
+    pub orchestrator_id: String,
+    pub error: String,
+}
+
+/// Scans every orchestrator's `workflows/runs` for non-terminal runs left
+/// behind by a previous process. In-flight attempts are either left to be
+/// retried on resume or failed, per `interrupted_attempt_policy`. Runs are
+/// reported by their root so `workflow_call` children resume through their
+/// parent. An orchestrator whose config or runs cannot be read is reported in
+/// `failed` and does not stop recovery of the others.
+pub fn recover_workflow_runs(
+    settings: &Settings,
+    now: i64,
+) -> Result<WorkflowRunRecoveryReport, String> {
+    let mut report = WorkflowRunRecoveryReport::default();
+    for orchestrator_id in settings.orchestrators.keys() {
+        if let Err(error) = recover_orchestrator_runs(settings, orchestrator_id, now, &mut report) {
+            report.failed.push(OrchestratorRecoveryFailure {
+                orchestrator_id: orchestrator_id.clone(),
+                error,
+            });
+        }
+    }
+    Ok(report)
+}
+
+fn recover_orchestrator_runs(
+    settings: &Settings,
+    orchestrator_id: &str,
+    now: i64,
+    report: &mut WorkflowRunRecoveryReport,
+) -> Result<(), String> {
+    let runtime_root = settings
+        .resolve_orchestrator_runtime_root(orchestrator_id)
+        .map_err(|err| err.to_string())?;
+    let run_store = WorkflowRunStore::new(&runtime_root);
+    let runs = run_store.load_runs().map_err(|err| err.to_string())?;
+    if runs.iter().all(|run| run.state.clone().is_terminal()) {
+        return Ok(());
+    }
+    let policy = load_orchestrator_config(settings, orchestrator_id)
+        .map_err(|err| err.to_string())?
+        .workflow_orchestration
+        .and_then(|value| value.interrupted_attempt_policy)
+        .unwrap_or_default();
+    let parents = runs
+        .iter()
+        .filter_map(|run| {
+            run.parent_run_id
+                .clone()
+                .map(|parent| (run.run_id.clone(), parent))
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let pending = pending_queue_references(&QueuePaths::from_state_root(&runtime_root))?;
+    let mut resumable_roots = BTreeSet::new();
+    for run in &runs {
+        match run.state {
+            RunState::Queued | RunState::Running => {}
+            RunState::Waiting => {
+                if let Some(retry_at) = run.retry_at {
+                    // Runs parked before the retry index existed are
+                    // added to it so the retry worker finds them.
+                    run_store
+                        .record_retry_due(&run.run_id, retry_at)
+                        .map_err(|err| err.to_string())?;
+                } else if run.parent_run_id.is_none() {
+                    report.parked.push(RecoverableWorkflowRun {
+                        orchestrator_id: orchestrator_id.to_string(),
+                        run_id: run.run_id.clone(),
+                    });
+                }
+                continue;
+            }
+            RunState::Succeeded | RunState::Failed | RunState::Canceled => continue,
+        }
+        let interrupted = interrupted_step_attempts(&run_store, run);
+        if !interrupted.is_empty() && policy == InterruptedAttemptPolicy::Fail {
+            fail_interrupted_run(&run_store, run, &interrupted, now)?;
+        }
+        for (step_id, attempt) in interrupted {
+            report.interrupted_attempts.push(InterruptedStepAttempt {
+                orchestrator_id: orchestrator_id.to_string(),
+                run_id: run.run_id.clone(),
+                step_id,
+                attempt,
+                policy,
+            });
+        }
+        let mut root_run_id = run.run_id.clone();
+        while let Some(parent_run_id) = parents.get(&root_run_id) {
+            root_run_id = parent_run_id.clone();
+        }
+        resumable_roots.insert(root_run_id);
+    }
+
+    for run in &runs {
+        if !resumable_roots.contains(&run.run_id) {
+            continue;
+        }
+        let state = run_store
+            .load_run(&run.run_id)
+            .map_err(|err| err.to_string())?
+            .state;
+        if state.is_terminal() {
+            continue;
+        }
+        report.parked.retain(|parked| parked.run_id != run.run_id);
+        let recoverable = RecoverableWorkflowRun {
+            orchestrator_id: orchestrator_id.to_string(),
+            run_id: run.run_id.clone(),
+        };
+        if pending.contains(&run.run_id)
+            || run
+                .source_message_id
+                .as_ref()
+                .is_some_and(|message_id| pending.contains(message_id))
+        {
+            report.requeued.push(recoverable);
+        } else {
+            report.resumable.push(recoverable);
+        }
+    }
+    Ok(())
+}
+
+/// Collects the message ids and `workflow_run_id`s of messages still in
+/// `incoming` or `processing`.
+fn pending_queue_references(queue_paths: &QueuePaths) -> Result<BTreeSet<String>, String> {
+    let mut references = BTreeSet::new();
+    for dir in [&queue_paths.incoming, &queue_paths.processing] {
+        let read_dir = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.to_string()),
+        };
+        for entry in read_dir {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let raw = fs::read_to_string(&path)
+                .map_err(|e| format!("failed to read queue file {}: {}", path.display(), e))?;
+            let Ok(message) = serde_json::from_str::<IncomingMessage>(&raw) else {
+                continue;
+            };
+            references.insert(message.message_id);
+            references.extend(message.workflow_run_id);
+        }
+    }
+    Ok(references)
+}
+
+/// Finds runs parked in `waiting` for retry backoff whose `retryAt` has
+/// passed, reported by their root run like `recover_workflow_runs`. Only the
+/// `workflows/retry_due` index and the runs it names are read; unreadable
+/// entries and runs are skipped, and entries of finished runs are dropped.
+pub fn due_retry_workflow_runs(
+    settings: &Settings,
+    now: i64,
+) -> Result<Vec<RecoverableWorkflowRun>, String> {
+    let mut due = Vec::new();
+    for orchestrator_id in settings.orchestrators.keys() {
+        let Ok(runtime_root) = settings.resolve_orchestrator_runtime_root(orchestrator_id) else {
+            continue;
+        };
+        let run_store = WorkflowRunStore::new(&runtime_root);
+        let Ok(entries) = run_store.due_retry_entries(now) else {
+            continue;
+        };
+        let mut roots = BTreeSet::new();
+        for entry in entries {
+            let Ok(run) = run_store.load_run(&entry.run_id) else {
+                continue;
+            };
+            if run.state.clone().is_terminal()
+                || (run.state == RunState::Waiting && run.retry_at.is_none())
+            {
+                let _ = run_store.clear_retry_due(&run.run_id);
+                continue;
+            }
+            if run.state != RunState::Waiting || run.retry_at.is_none_or(|at| at > now) {
+                continue;
+            }
+            let Some(root_run_id) = root_run_id(&run_store, run) else {
+                continue;
+            };
+            if roots.insert(root_run_id.clone()) {
+                due.push(RecoverableWorkflowRun {
+                    orchestrator_id: orchestrator_id.clone(),
+                    run_id: root_run_id,
+                });
+            }
+        }
+    }
+    Ok(due)
+}
+
+/// Follows `parentRunId` links up to the run that started the tree.
+fn root_run_id(run_store: &WorkflowRunStore, run: WorkflowRunRecord) -> Option<String> {
+    let mut run = run;
+    while let Some(parent_run_id) = run.parent_run_id.clone() {
+        run = run_store.load_run(&parent_run_id).ok()?;
+    }
+    Some(run.run_id)
+}
+
+/// Resumes a recovered run and returns its state once the engine stops
+/// driving it.
+pub fn resume_recovered_workflow_run(
+    settings: &Settings,
+    recoverable: &RecoverableWorkflowRun,
+    runner_binaries: &RunnerBinaries,
+    now: i64,
+) -> Result<RunState, String> {
+    let orchestrator_id = &recoverable.orchestrator_id;
+    let orchestrator =
+        load_orchestrator_config(settings, orchestrator_id).map_err(|err| err.to_string())?;
+    let workspace_context =
+        verify_orchestrator_workspace_access(settings, orchestrator_id, &orchestrator)
+            .map_err(|err| err.to_string())?;
+    let runtime_root = settings
+        .resolve_orchestrator_runtime_root(orchestrator_id)
+        .map_err(|err| err.to_string())?;
+    let engine = WorkflowEngine::new(WorkflowRunStore::new(&runtime_root), orchestrator)
+        .with_runner_binaries(runner_binaries.clone())
+        .with_workspace_access_context(workspace_context)
+        .with_memory_enabled(settings.memory.enabled);
+    engine
+        .resume(&recoverable.run_id, now)
+        .map(|run| run.state)
+        .map_err(|err| err.to_string())
+}
+
+/// Lists the attempts a `running` run had in flight without a persisted
+/// result: the branch or item attempts in `active_steps` while a `parallel` or
+/// `foreach` step fans out, otherwise the current step attempt.
+fn interrupted_step_attempts(
+    run_store: &WorkflowRunStore,
+    run: &WorkflowRunRecord,
+) -> Vec<(String, u32)> {
+    if run.state != RunState::Running {
+        return Vec::new();
+    }
+    if !run.active_steps.is_empty() {
+        return run
+            .active_steps
+            .iter()
+            .filter(|pointer| {
+                !run_store.step_attempt_exists(&run.run_id, &pointer.step_id, pointer.attempt)
+            })
+            .map(|pointer| (pointer.step_id.clone(), pointer.attempt))
+            .collect();
+    }
+    let (Some(step_id), Some(attempt)) = (run.current_step_id.clone(), run.current_attempt) else {
+        return Vec::new();
+    };
+    if run_store.step_attempt_exists(&run.run_id, &step_id, attempt) {
+        return Vec::new();
+    }
+    vec![(step_id, attempt)]
+}
+
+fn fail_interrupted_run(
+    run_store: &WorkflowRunStore,
+    run: &WorkflowRunRecord,
+    interrupted: &[(String, u32)],
+    now: i64,
+) -> Result<(), String> {
+    let mut reasons = Vec::with_capacity(interrupted.len());
+    for (step_id, attempt) in interrupted {
+        let reason = format!("step {step_id} attempt {attempt} interrupted by runtime restart");
+        run_store
+            .persist_step_attempt(&StepAttemptRecord {
+                run_id: run.run_id.clone(),
+                step_id: step_id.clone(),
+                attempt: *attempt,
+                started_at: run.updated_at,
+                ended_at: now,
+                state: "failed".to_string(),
+                outputs: Map::new(),
+                output_files: BTreeMap::new(),
+                final_output_priority: Vec::new(),
+                next_step_id: None,
+                error: Some(reason.clone()),
+                output_validation_errors: BTreeMap::new(),
+                parallel_step_id: None,
+                foreach_step_id: None,
+                item_index: None,
+                error_class: None,
+                usage: None,
+            })
+            .map_err(|err| err.to_string())?;
+        reasons.push(reason);
+    }
+    let mut run = run_store
+        .load_run(&run.run_id)
+        .map_err(|err| err.to_string())?;
+    run.active_steps.clear();
+    run_store
+        .transition_state(
+            &mut run,
+            RunState::Failed,
+            now,
+            reasons.join("; "),
+            false,
+            "none",
+        )
+        .map_err(|err| err.to_string())
+}
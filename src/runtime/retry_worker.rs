@@ -0,0 +1,140 @@
+use crate::config::Settings;
+use crate::orchestration::workflow_engine::resolve_runner_binaries;
+use crate::runtime::queue_worker::QUEUE_MAX_CONCURRENCY;
+use crate::runtime::recovery::{
+    due_retry_workflow_runs, resume_recovered_workflow_run, RecoverableWorkflowRun,
+};
+use crate::runtime::{append_runtime_log, now_secs, StatePaths};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+/// Runs currently being resumed, with the thread resuming each, so a slow
+/// retry is not picked up again by the next tick. At most
+/// `QUEUE_MAX_CONCURRENCY` runs resume at once.
+static RESUMING_RUNS: Mutex<BTreeMap<String, JoinHandle<()>>> = Mutex::new(BTreeMap::new());
+
+/// Runs recovered at startup, keyed by state root, waiting for the next tick.
+static RECOVERED_RUNS: Mutex<Vec<(PathBuf, RecoverableWorkflowRun)>> = Mutex::new(Vec::new());
+
+/// Hands runs found by startup recovery to the retry worker, which resumes
+/// them on its next tick alongside runs whose retry backoff has elapsed.
+pub fn schedule_recovered_runs(state_root: &Path, runs: Vec<RecoverableWorkflowRun>) {
+    if let Ok(mut recovered) = RECOVERED_RUNS.lock() {
+        recovered.extend(runs.into_iter().map(|run| (state_root.to_path_buf(), run)));
+    }
+}
+
+/// Resumes recovered runs and runs whose retry backoff has elapsed, each on
+/// its own thread so a long step attempt does not stall the tick. Runs past
+/// the concurrency cap wait for a later tick.
+pub fn tick_retry_worker(state_root: &Path, settings: &Settings) -> Result<(), String> {
+    let paths = StatePaths::new(state_root);
+    reap_finished_resumes();
+    let recovered = RECOVERED_RUNS
+        .lock()
+        .map(|mut recovered| {
+            let (ours, others) = recovered
+                .drain(..)
+                .partition::<Vec<_>, _>(|(root, _)| root == state_root);
+            *recovered = others;
+            ours
+        })
+        .unwrap_or_default();
+    let mut deferred = Vec::new();
+    for (root, run) in recovered {
+        if !dispatch_resume(&paths, settings, &run, "workflow.recovery") {
+            deferred.push((root, run));
+        }
+    }
+    if !deferred.is_empty() {
+        if let Ok(mut recovered) = RECOVERED_RUNS.lock() {
+            recovered.extend(deferred);
+        }
+        return Ok(());
+    }
+    for run in due_retry_workflow_runs(settings, now_secs())? {
+        if !dispatch_resume(&paths, settings, &run, "workflow.retry") {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Waits for every in-flight resume to finish; called when the worker stops.
+pub fn join_resume_threads() {
+    let handles = RESUMING_RUNS
+        .lock()
+        .map(|mut resuming| std::mem::take(&mut *resuming))
+        .unwrap_or_default();
+    for handle in handles.into_values() {
+        let _ = handle.join();
+    }
+}
+
+fn reap_finished_resumes() {
+    let finished = RESUMING_RUNS
+        .lock()
+        .map(|mut resuming| {
+            let done = resuming
+                .iter()
+                .filter(|(_, handle)| handle.is_finished())
+                .map(|(run_id, _)| run_id.clone())
+                .collect::<Vec<_>>();
+            done.into_iter()
+                .filter_map(|run_id| resuming.remove(&run_id))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    for handle in finished {
+        let _ = handle.join();
+    }
+}
+
+/// Starts resuming `run` unless it is already resuming. Returns `false` when
+/// the concurrency cap is reached and the run must wait for a later tick.
+fn dispatch_resume(
+    paths: &StatePaths,
+    settings: &Settings,
+    run: &RecoverableWorkflowRun,
+    event_prefix: &'static str,
+) -> bool {
+    let Ok(mut resuming) = RESUMING_RUNS.lock() else {
+        return false;
+    };
+    if resuming.contains_key(&run.run_id) {
+        return true;
+    }
+    if resuming.len() >= QUEUE_MAX_CONCURRENCY {
+        return false;
+    }
+    append_runtime_log(
+        paths,
+        "info",
+        &format!("{event_prefix}.resuming"),
+        &format!("orchestrator={} run_id={}", run.orchestrator_id, run.run_id),
+    );
+    let thread_paths = paths.clone();
+    let settings = settings.clone();
+    let thread_run = run.clone();
+    let handle = thread::spawn(move || {
+        let binaries = resolve_runner_binaries();
+        match resume_recovered_workflow_run(&settings, &thread_run, &binaries, now_secs()) {
+            Ok(state) => append_runtime_log(
+                &thread_paths,
+                "info",
+                &format!("{event_prefix}.resumed"),
+                &format!("run_id={} state={state}", thread_run.run_id),
+            ),
+            Err(err) => append_runtime_log(
+                &thread_paths,
+                "error",
+                &format!("{event_prefix}.resume_failed"),
+                &format!("run_id={} error={err}", thread_run.run_id),
+            ),
+        }
+    });
+    resuming.insert(run.run_id.clone(), handle);
+    true
+}
@@ -1,5 +1,5 @@
 use crate::config::Settings;
-use crate::orchestration::scheduler::SchedulerWorker;
+use crate::orchestration::scheduler::{granted_shared_workspaces, SchedulerWorker};
 use crate::runtime::append_runtime_log;
 use crate::runtime::StatePaths;
 
@@ -13,7 +13,8 @@ pub fn tick_scheduler_worker(
         let runtime_root = settings
             .resolve_orchestrator_runtime_root(orchestrator_id)
             .map_err(|err| err.to_string())?;
-        let mut worker = SchedulerWorker::new(&runtime_root);
+        let mut worker = SchedulerWorker::new(&runtime_root)
+            .with_shared_workspaces(granted_shared_workspaces(settings, orchestrator_id));
         let runs = worker.tick(now_secs())?;
         dispatched_total = dispatched_total.saturating_add(runs.len());
     }
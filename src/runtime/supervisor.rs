@@ -1,9 +1,10 @@
 use super::{
     append_runtime_log, atomic_write_file, bootstrap_state_root, channel_worker, now_secs,
-    ownership_lock, queue_worker, RuntimeError, StatePaths, WorkerEvent, WorkerState,
+    ownership_lock, queue_worker, recovery, retry_worker, RuntimeError, StatePaths, WorkerEvent,
+    WorkerState,
 };
 use crate::channels::slack;
-use crate::config::load_orchestrator_config;
+use crate::config::{load_orchestrator_config, InterruptedAttemptPolicy};
 use crate::local_llm::initialize_local_runtime;
 use crate::orchestration::shared_mounts::reconcile_all_orchestrator_shared_mounts;
 use crate::orchestration::skills_mounts::reconcile_all_orchestrator_skill_mounts;
@@ -84,6 +85,9 @@ pub fn run_supervisor(
     let (events_tx, events_rx) = mpsc::channel::<WorkerEvent>();
     let mut handles = Vec::new();
     let mut active = BTreeSet::new();
+    // Recover before the queue worker requeues `processing` entries, so runs
+    // whose message is still queued are left to that message.
+    recover_workflow_runs_on_startup(&paths, &settings);
 
     for spec in specs {
         active.insert(spec.id.clone());
@@ -198,6 +202,65 @@ fn reconcile_shared_mounts_on_startup(
         .map_err(|err| RuntimeError::Startup(err.to_string()))
 }
 
+fn recover_workflow_runs_on_startup(paths: &StatePaths, settings: &crate::config::Settings) {
+    let report = match recovery::recover_workflow_runs(settings, now_secs()) {
+        Ok(report) => report,
+        Err(err) => {
+            append_runtime_log(paths, "error", "workflow.recovery.failed", &err);
+            return;
+        }
+    };
+    for failed in &report.failed {
+        append_runtime_log(
+            paths,
+            "error",
+            "workflow.recovery.failed",
+            &format!("orchestrator={} {}", failed.orchestrator_id, failed.error),
+        );
+    }
+    for interrupted in &report.interrupted_attempts {
+        append_runtime_log(
+            paths,
+            "warn",
+            "workflow.recovery.interrupted_attempt",
+            &format!(
+                "orchestrator={} run_id={} step_id={} attempt={} policy={}",
+                interrupted.orchestrator_id,
+                interrupted.run_id,
+                interrupted.step_id,
+                interrupted.attempt,
+                match interrupted.policy {
+                    InterruptedAttemptPolicy::Retry => "retry",
+                    InterruptedAttemptPolicy::Fail => "fail",
+                }
+            ),
+        );
+    }
+    for parked in &report.parked {
+        append_runtime_log(
+            paths,
+            "info",
+            "workflow.recovery.parked",
+            &format!(
+                "orchestrator={} run_id={} awaiting human input",
+                parked.orchestrator_id, parked.run_id
+            ),
+        );
+    }
+    for requeued in &report.requeued {
+        append_runtime_log(
+            paths,
+            "info",
+            "workflow.recovery.requeued",
+            &format!(
+                "orchestrator={} run_id={} resumes with its queued message",
+                requeued.orchestrator_id, requeued.run_id
+            ),
+        );
+    }
+    retry_worker::schedule_recovered_runs(&paths.root, report.resumable);
+}
+
 fn log_prompt_template_validation_warnings(paths: &StatePaths, settings: &crate::config::Settings) {
     for orchestrator_id in settings.orchestrators.keys() {
         let private_workspace = match settings.resolve_private_workspace(orchestrator_id) {
@@ -1,8 +1,9 @@
 use crate::app::command_support::{ensure_runtime_root, map_config_err};
 use crate::config::{
-    agent_editable_fields, default_global_config_path, AgentEditableField, ConfigProviderKind,
-    OrchestrationLimitField, OutputKey,
+    agent_editable_fields, default_global_config_path, AgentEditableField, OrchestrationLimitField,
+    OutputKey,
 };
+use crate::provider::ProviderKind;
 use crate::setup::navigation::{
     parse_scripted_setup_keys, setup_action_from_key, setup_screen_item_count, setup_transition,
     NavState, SetupAction, SetupNavEffect, SetupScreen,
@@ -199,13 +200,14 @@ fn draw_active_setup_screen(
 }
 
 fn toggle_default_provider(bootstrap: &mut SetupState) -> String {
-    let next_provider = if bootstrap.provider == "anthropic" {
-        "openai".to_string()
-    } else {
-        "anthropic".to_string()
+    let next_provider = match bootstrap.provider.as_str() {
+        "anthropic" => "openai",
+        "openai" => "gemini",
+        _ => "anthropic",
     };
-    bootstrap.set_default_provider(next_provider);
-    if bootstrap.model == "sonnet" || bootstrap.model == "gpt-5.3-codex" {
+    let previous_default = default_model_for_provider(&bootstrap.provider);
+    bootstrap.set_default_provider(next_provider.to_string());
+    if bootstrap.model == previous_default {
         bootstrap.set_default_model(default_model_for_provider(&bootstrap.provider).to_string());
     }
     format!("provider set to {}", bootstrap.provider)
@@ -2023,19 +2025,20 @@ fn edit_agent_provider_field(
         .orchestrator_configs
         .get(orchestrator_id)
         .and_then(|cfg| cfg.agents.get(agent_id))
-        .map(|a| a.provider)
-        .unwrap_or(ConfigProviderKind::Anthropic);
+        .map(|a| a.provider.clone())
+        .unwrap_or(ProviderKind::ANTHROPIC);
     let current_model = bootstrap
         .orchestrator_configs
         .get(orchestrator_id)
         .and_then(|cfg| cfg.agents.get(agent_id))
         .map(|a| a.model.clone())
         .unwrap_or_else(|| default_model_for_provider(current_provider.as_str()).to_string());
-    let provider_labels: Vec<String> = provider_options()
+    let providers = provider_options();
+    let provider_labels: Vec<String> = providers
         .iter()
         .map(|provider| provider.to_string())
         .collect();
-    let selected_provider_index = provider_options()
+    let selected_provider_index = providers
         .iter()
         .position(|provider| *provider == current_provider)
         .unwrap_or(0);
@@ -2051,7 +2054,7 @@ fn edit_agent_provider_field(
     else {
         return Ok(None);
     };
-    let provider = provider_options()[selected_index];
+    let provider = &providers[selected_index];
     match bootstrap.set_agent_provider(orchestrator_id, agent_id, provider.as_str()) {
         Ok(_) => {
             if !model_options_for_provider(provider).contains(&current_model.as_str()) {
@@ -2081,15 +2084,20 @@ fn edit_agent_model_field(
         .orchestrator_configs
         .get(orchestrator_id)
         .and_then(|cfg| cfg.agents.get(agent_id))
-        .map(|a| a.provider)
-        .unwrap_or(ConfigProviderKind::Anthropic);
+        .map(|a| a.provider.clone())
+        .unwrap_or(ProviderKind::ANTHROPIC);
     let current = bootstrap
         .orchestrator_configs
         .get(orchestrator_id)
         .and_then(|cfg| cfg.agents.get(agent_id))
         .map(|a| a.model.clone())
         .unwrap_or_else(|| default_model_for_provider(provider.as_str()).to_string());
-    let model_options = model_options_for_provider(provider);
+    let model_options = model_options_for_provider(&provider);
+    if model_options.is_empty() {
+        return Ok(Some(format!(
+            "{provider} models are set in orchestrator.yaml"
+        )));
+    }
     let model_labels: Vec<String> = model_options
         .iter()
         .map(|model| (*model).to_string())
@@ -2475,16 +2483,28 @@ mod tests {
     fn provider_and_model_options_match_supported_variants() {
         assert_eq!(
             provider_options(),
-            &[ConfigProviderKind::Anthropic, ConfigProviderKind::OpenAi]
+            vec![
+                ProviderKind::ANTHROPIC,
+                ProviderKind::GEMINI,
+                ProviderKind::OPENAI
+            ]
+        );
+        assert_eq!(
+            model_options_for_provider(&ProviderKind::OPENAI_COMPATIBLE),
+            &[] as &[&str]
         );
         assert_eq!(
-            model_options_for_provider(ConfigProviderKind::Anthropic),
+            model_options_for_provider(&ProviderKind::ANTHROPIC),
             &["sonnet", "opus", "haiku"]
         );
         assert_eq!(
-            model_options_for_provider(ConfigProviderKind::OpenAi),
+            model_options_for_provider(&ProviderKind::OPENAI),
             &["gpt-5.3-codex", "gpt-5.3-codex-spark"]
         );
+        assert_eq!(
+            model_options_for_provider(&ProviderKind::GEMINI),
+            &["gemini-2.5-pro", "gemini-2.5-flash"]
+        );
     }
 
     #[test]
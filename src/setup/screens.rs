@@ -1,4 +1,4 @@
-use crate::config::{WorkflowStepConfig, WorkflowStepWorkspaceMode};
+use crate::config::WorkflowStepConfig;
 use crate::setup::navigation::{clamp_selection, NavState, ALL_SETUP_SCREENS};
 use crate::setup::state::output_files_as_csv;
 use ratatui::backend::CrosstermBackend;
@@ -68,10 +68,7 @@ pub fn tail_for_display(value: &str, max_chars: usize) -> String {
 }
 
 pub fn workflow_step_menu_rows(step: &WorkflowStepConfig) -> Vec<SetupFieldRow> {
-    let workspace_mode = match step.workspace_mode {
-        WorkflowStepWorkspaceMode::OrchestratorWorkspace => "orchestrator_workspace",
-        WorkflowStepWorkspaceMode::RunWorkspace => "run_workspace",
-    };
+    let workspace_mode = step.workspace_mode.as_str();
     let outputs = if step.outputs.is_empty() {
         "<none>".to_string()
     } else {
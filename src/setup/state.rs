@@ -1,33 +1,35 @@
 use crate::config::{
-    ConfigProviderKind, OrchestratorConfig, OutputKey, PathTemplate, SetupDraft, WorkflowInputs,
-    WorkflowStepConfig,
+    OrchestratorConfig, OutputKey, PathTemplate, SetupDraft, WorkflowInputs, WorkflowStepConfig,
 };
+use crate::provider::{builtin_registry, ProviderKind};
 use crate::templates::orchestrator_templates::WorkflowTemplate as SetupWorkflowTemplate;
 use std::collections::BTreeMap;
 
 pub(crate) type SetupState = SetupDraft;
 
 pub(crate) fn default_model_for_provider(provider: &str) -> &'static str {
-    if provider == "openai" {
-        "gpt-5.3-codex"
-    } else {
-        "sonnet"
-    }
+    builtin_registry()
+        .get(provider)
+        .ok()
+        .and_then(|backend| backend.model_options().first().copied())
+        .unwrap_or("sonnet")
 }
 
-const PROVIDER_OPTIONS: [ConfigProviderKind; 2] =
-    [ConfigProviderKind::Anthropic, ConfigProviderKind::OpenAi];
-
-pub(crate) fn provider_options() -> &'static [ConfigProviderKind] {
-    &PROVIDER_OPTIONS
+/// Registered providers setup can pick from. Endpoint-backed providers are
+/// left out; their endpoint is set in orchestrator.yaml.
+pub(crate) fn provider_options() -> Vec<ProviderKind> {
+    builtin_registry()
+        .backends()
+        .filter(|backend| !backend.uses_endpoint())
+        .map(ProviderKind::of)
+        .collect()
 }
 
-pub(crate) fn model_options_for_provider(provider: ConfigProviderKind) -> &'static [&'static str] {
-    if provider == ConfigProviderKind::OpenAi {
-        &["gpt-5.3-codex", "gpt-5.3-codex-spark"]
-    } else {
-        &["sonnet", "opus", "haiku"]
-    }
+pub(crate) fn model_options_for_provider(provider: &ProviderKind) -> &'static [&'static str] {
+    builtin_registry()
+        .get(provider.as_str())
+        .map(|backend| backend.model_options())
+        .unwrap_or(&[])
 }
 
 #[cfg(test)]
@@ -83,7 +85,7 @@ pub(crate) fn workflow_inputs_as_csv(inputs: &WorkflowInputs) -> String {
     let parts: Vec<String> = inputs
         .as_slice()
         .iter()
-        .map(|key| key.as_str().to_string())
+        .map(|spec| spec.key.as_str().to_string())
         .collect();
     if parts.is_empty() {
         "<none>".to_string()
@@ -188,10 +190,19 @@ mod tests {
                 next: None,
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: Vec::new(),
                 output_files: BTreeMap::new(),
                 final_output_priority: Vec::new(),
                 limits: None,
+                retry: None,
             },
             WorkflowStepConfig {
                 id: "step_2".to_string(),
@@ -203,10 +214,19 @@ mod tests {
                 next: None,
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: Vec::new(),
                 output_files: BTreeMap::new(),
                 final_output_priority: Vec::new(),
                 limits: None,
+                retry: None,
             },
         ];
 
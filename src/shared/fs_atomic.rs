@@ -1,6 +1,7 @@
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 pub fn canonicalize_existing(path: &Path) -> Result<PathBuf, std::io::Error> {
@@ -8,13 +9,40 @@ pub fn canonicalize_existing(path: &Path) -> Result<PathBuf, std::io::Error> {
 }
 
 pub fn atomic_write_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let (parent, tmp_path) = write_temp_sibling(path, content)?;
+    fs::rename(&tmp_path, path)?;
+    sync_parent_dir(parent)?;
+    Ok(())
+}
+
+/// Creates `path` with `content` unless it already exists, in which case
+/// nothing is written and `false` is returned. The file appears complete or
+/// not at all, so racing creators never observe a partial write.
+pub fn atomic_create_file(path: &Path, content: &[u8]) -> std::io::Result<bool> {
+    let (parent, tmp_path) = write_temp_sibling(path, content)?;
+    let linked = fs::hard_link(&tmp_path, path);
+    let _ = fs::remove_file(&tmp_path);
+    match linked {
+        Ok(()) => {
+            sync_parent_dir(parent)?;
+            Ok(true)
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn write_temp_sibling<'a>(path: &'a Path, content: &[u8]) -> std::io::Result<(&'a Path, PathBuf)> {
     let parent = path
         .parent()
         .ok_or_else(|| std::io::Error::other("path has no parent"))?;
     let tmp_name = format!(
-        ".{}.tmp-{}-{}",
+        ".{}.tmp-{}-{}-{}",
         path.file_name().and_then(|v| v.to_str()).unwrap_or("state"),
         std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed),
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_nanos())
@@ -30,10 +58,7 @@ pub fn atomic_write_file(path: &Path, content: &[u8]) -> std::io::Result<()> {
         file.write_all(content)?;
         file.sync_all()?;
     }
-
-    fs::rename(&tmp_path, path)?;
-    sync_parent_dir(parent)?;
-    Ok(())
+    Ok((parent, tmp_path))
 }
 
 #[cfg(unix)]
@@ -1,9 +1,10 @@
 use crate::config::{
-    AgentConfig, ConfigProviderKind, OrchestratorConfig, OutputKey, PathTemplate, WorkflowConfig,
-    WorkflowInputs, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType,
-    WorkflowStepWorkspaceMode, WorkflowTag,
+    AgentConfig, OrchestratorConfig, OutputKey, PathTemplate, WorkflowConfig, WorkflowInputs,
+    WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType, WorkflowStepWorkspaceMode,
+    WorkflowTag,
 };
 use crate::prompts::default_prompt_rel_path;
+use crate::provider::ProviderKind;
 use crate::templates::workflow_step_defaults::{
     default_step_output_contract, default_step_output_files, default_step_output_priority,
 };
@@ -47,18 +48,29 @@ fn workflow_step(
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: default_step_output_contract(step_type),
         output_files: default_step_output_files(step_type),
         final_output_priority: default_step_output_priority(step_type),
         limits: None,
+        retry: None,
     }
 }
 
 fn agent_config(provider: &str, model: &str, can_orchestrate_workflows: bool) -> AgentConfig {
     AgentConfig {
-        provider: ConfigProviderKind::parse(provider).expect("default provider is valid"),
+        provider: ProviderKind::parse(provider).expect("default provider is valid"),
         model: model.to_string(),
         can_orchestrate_workflows,
+        endpoint: None,
+        fallbacks: Vec::new(),
     }
 }
 
@@ -93,6 +105,7 @@ pub fn initial_orchestrator_config(
                     tags: vec![workflow_tag("default")],
                     inputs: WorkflowInputs::default(),
                     limits: None,
+                    repository: None,
                     steps,
                 }],
             )
@@ -192,6 +205,7 @@ pub fn initial_orchestrator_config(
                         ],
                         inputs: WorkflowInputs::default(),
                         limits: None,
+                        repository: None,
                         steps: vec![
                             {
                                 let mut plan = workflow_step(
@@ -230,6 +244,7 @@ pub fn initial_orchestrator_config(
                         ],
                         inputs: WorkflowInputs::default(),
                         limits: None,
+                        repository: None,
                         steps: vec![workflow_step(
                             "quick_answer",
                             "answer",
@@ -263,6 +278,7 @@ pub fn initial_orchestrator_config(
                         ],
                         inputs: WorkflowInputs::default(),
                         limits: None,
+                        repository: None,
                         steps: vec![
                             {
                                 let mut research = workflow_step(
@@ -296,6 +312,7 @@ pub fn initial_orchestrator_config(
                         ],
                         inputs: WorkflowInputs::default(),
                         limits: None,
+                        repository: None,
                         steps: vec![workflow_step(
                             "release_notes",
                             "compose",
@@ -12,8 +12,24 @@ pub fn default_step_scaffold(step_type: &str) -> String {
     default_step_prompt(step_type)
 }
 
+fn is_review_step_type(step_type: &str) -> bool {
+    matches!(step_type, "agent_review" | "human_review")
+}
+
 pub fn default_step_output_contract(step_type: &str) -> Vec<OutputKey> {
-    if step_type == "agent_review" {
+    if step_type == "foreach" {
+        vec![
+            OutputKey::parse("summary").expect("default output key is valid"),
+            OutputKey::parse("results").expect("default output key is valid"),
+        ]
+    } else if step_type == "command" {
+        vec![
+            OutputKey::parse("summary").expect("default output key is valid"),
+            OutputKey::parse("exit_code").expect("default output key is valid"),
+        ]
+    } else if matches!(step_type, "parallel" | "workflow_call") {
+        vec![OutputKey::parse("summary").expect("default output key is valid")]
+    } else if is_review_step_type(step_type) {
         vec![
             OutputKey::parse("decision").expect("default output key is valid"),
             OutputKey::parse("summary").expect("default output key is valid"),
@@ -28,7 +44,50 @@ pub fn default_step_output_contract(step_type: &str) -> Vec<OutputKey> {
 }
 
 pub fn default_step_output_files(step_type: &str) -> BTreeMap<OutputKey, PathTemplate> {
-    if step_type == "agent_review" {
+    if step_type == "foreach" {
+        BTreeMap::from_iter([
+            (
+                OutputKey::parse_output_file_key("summary").expect("default output key is valid"),
+                PathTemplate::parse(
+                    "artifacts/{{workflow.run_id}}/{{workflow.step_id}}-{{workflow.attempt}}-summary.txt",
+                )
+                .expect("default path template is valid"),
+            ),
+            (
+                OutputKey::parse_output_file_key("results").expect("default output key is valid"),
+                PathTemplate::parse(
+                    "artifacts/{{workflow.run_id}}/{{workflow.step_id}}-{{workflow.attempt}}-results.json",
+                )
+                .expect("default path template is valid"),
+            ),
+        ])
+    } else if step_type == "command" {
+        BTreeMap::from_iter([
+            (
+                OutputKey::parse_output_file_key("summary").expect("default output key is valid"),
+                PathTemplate::parse(
+                    "artifacts/{{workflow.run_id}}/{{workflow.step_id}}-{{workflow.attempt}}-summary.txt",
+                )
+                .expect("default path template is valid"),
+            ),
+            (
+                OutputKey::parse_output_file_key("exit_code")
+                    .expect("default output key is valid"),
+                PathTemplate::parse(
+                    "artifacts/{{workflow.run_id}}/{{workflow.step_id}}-{{workflow.attempt}}-exit-code.txt",
+                )
+                .expect("default path template is valid"),
+            ),
+        ])
+    } else if matches!(step_type, "parallel" | "workflow_call") {
+        BTreeMap::from_iter([(
+            OutputKey::parse_output_file_key("summary").expect("default output key is valid"),
+            PathTemplate::parse(
+                "artifacts/{{workflow.run_id}}/{{workflow.step_id}}-{{workflow.attempt}}-summary.txt",
+            )
+            .expect("default path template is valid"),
+        )])
+    } else if is_review_step_type(step_type) {
         BTreeMap::from_iter([
             (
                 OutputKey::parse_output_file_key("decision").expect("default output key is valid"),
@@ -74,7 +133,11 @@ pub fn default_step_output_files(step_type: &str) -> BTreeMap<OutputKey, PathTem
 }
 
 pub fn default_step_output_priority(step_type: &str) -> Vec<OutputKey> {
-    if step_type == "agent_review" {
+    if matches!(
+        step_type,
+        "parallel" | "workflow_call" | "command" | "foreach"
+    ) || is_review_step_type(step_type)
+    {
         vec![OutputKey::parse("summary").expect("default output key is valid")]
     } else {
         vec![
@@ -31,6 +31,7 @@ fn command_dispatch_module_executes_internal_orchestrator_list_function() {
         auth_sync: AuthSyncConfig::default(),
         memory: MemoryConfig::default(),
         local_llm: Default::default(),
+        webhooks: Default::default(),
     };
 
     let value = execute_function_invocation_with_executor(
@@ -82,6 +83,7 @@ fn schedule_show_rejects_cross_orchestrator_job_access() {
         auth_sync: AuthSyncConfig::default(),
         memory: MemoryConfig::default(),
         local_llm: Default::default(),
+        webhooks: Default::default(),
     };
 
     let alpha_runtime = settings
@@ -147,6 +149,7 @@ fn schedule_lifecycle_emits_scheduler_audit_events() {
         auth_sync: AuthSyncConfig::default(),
         memory: MemoryConfig::default(),
         local_llm: Default::default(),
+        webhooks: Default::default(),
     };
 
     let created = execute_internal_function(
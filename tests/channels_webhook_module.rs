@@ -0,0 +1,416 @@
+use direclaw::channels::webhook::http::read_request;
+use direclaw::channels::webhook::signature::{hmac_sha256, sign_payload, verify_signature};
+use direclaw::channels::webhook::{
+    delivery_record_path, DeliveryRecord, WebhookIngress, WebhookRequest,
+};
+use direclaw::config::{Settings, ValidationOptions};
+use direclaw::orchestration::routing::{
+    process_queued_message_with_runner_binaries, FunctionRegistry,
+};
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::transitions::RoutedSelectorAction;
+use direclaw::provider::RunnerBinaries;
+use direclaw::queue::IncomingMessage;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+const ORCHESTRATOR_YAML: &str = r#"
+id: eng
+selector_agent: selector
+default_workflow: triage
+selection_max_retries: 1
+agents:
+  selector:
+    provider: openai
+    model: gpt-4.1
+    can_orchestrate_workflows: true
+workflows:
+  - id: triage
+    version: 1
+    description: triage inbound issues
+    tags: [triage]
+    inputs: [repo, number]
+    steps:
+      - id: look
+        type: agent_task
+        agent: selector
+        prompt: "Triage {{inputs.repo}}#{{inputs.number}}"
+        outputs: [summary]
+        output_files:
+          summary: out/summary.md
+"#;
+
+fn settings(root: &Path, secret_env: &str) -> Settings {
+    let workspace = root.join("workspaces/eng");
+    fs::create_dir_all(&workspace).expect("workspace");
+    fs::write(workspace.join("orchestrator.yaml"), ORCHESTRATOR_YAML).expect("orchestrator");
+    serde_yaml::from_str(&format!(
+        r#"
+workspaces_path: {root}/workspaces
+orchestrators:
+  eng: {{}}
+channel_profiles:
+  eng-hooks:
+    channel: webhook
+    orchestrator_id: eng
+webhooks:
+  enabled: true
+  bind: 127.0.0.1:0
+  routes:
+    github:
+      path: /hooks/github
+      secret_env: {secret_env}
+      delivery_header: X-GitHub-Delivery
+      orchestrator_id: eng
+      workflow_id: triage
+      inputs:
+        repo: /repository/full_name
+        number: /issue/number
+    alerts:
+      path: /hooks/alerts
+      secret_env: {secret_env}
+      orchestrator_id: eng
+      channel_profile_id: eng-hooks
+      message_pointer: /text
+"#,
+        root = root.display()
+    ))
+    .expect("settings")
+}
+
+fn ingress(settings: Settings) -> WebhookIngress {
+    WebhookIngress::new(settings)
+}
+
+fn queued_message(root: &Path, message_id: &str) -> IncomingMessage {
+    let path = root
+        .join("workspaces/eng/queue/incoming")
+        .join(format!("{message_id}.json"));
+    serde_json::from_str(&fs::read_to_string(path).expect("queued message")).expect("json")
+}
+
+fn signed_request(
+    path: &str,
+    secret: &str,
+    delivery_header: &str,
+    delivery_id: &str,
+    payload: &Value,
+) -> WebhookRequest {
+    let body = payload.to_string().into_bytes();
+    WebhookRequest {
+        method: "POST".to_string(),
+        path: path.to_string(),
+        headers: BTreeMap::from([
+            (
+                "x-direclaw-signature".to_string(),
+                sign_payload(secret, &body),
+            ),
+            (
+                delivery_header.to_ascii_lowercase(),
+                delivery_id.to_string(),
+            ),
+        ]),
+        body,
+    }
+}
+
+#[test]
+fn webhook_module_signs_payloads_with_hmac_sha256() {
+    let digest = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+    let hex = digest
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect::<String>();
+    assert_eq!(
+        hex,
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+
+    let signature = sign_payload("s3cret", b"{\"a\":1}");
+    assert!(verify_signature("s3cret", b"{\"a\":1}", &signature));
+    assert!(verify_signature(
+        "s3cret",
+        b"{\"a\":1}",
+        signature.trim_start_matches("sha256=")
+    ));
+    assert!(!verify_signature("s3cret", b"{\"a\":2}", &signature));
+    assert!(!verify_signature("other", b"{\"a\":1}", &signature));
+    assert!(!verify_signature("s3cret", b"{\"a\":1}", "sha256=zz"));
+}
+
+#[test]
+fn webhook_module_starts_workflow_runs_once_per_delivery() {
+    let dir = tempdir().expect("tempdir");
+    let secret_env = "DIRECLAW_TEST_WEBHOOK_SECRET_WORKFLOW";
+    std::env::set_var(secret_env, "topsecret");
+    let settings = settings(dir.path(), secret_env);
+    let ingress = ingress(settings.clone());
+    let payload = json!({"repository": {"full_name": "acme/app"}, "issue": {"number": 42}});
+
+    let request = signed_request(
+        "/hooks/github",
+        "topsecret",
+        "X-GitHub-Delivery",
+        "delivery-1",
+        &payload,
+    );
+    let response = ingress.handle(&request, 100);
+    assert_eq!(response.status, 202, "{}", response.body);
+    assert_eq!(response.body["status"], "started");
+    assert_eq!(response.body["workflowId"], "triage");
+    let run_id = response.body["runId"].as_str().expect("run id").to_string();
+
+    let store = WorkflowRunStore::new(dir.path().join("workspaces/eng"));
+    let run = store.load_run(&run_id).expect("run");
+    assert_eq!(run.state, RunState::Queued);
+    assert_eq!(run.workflow_id, "triage");
+    assert_eq!(run.inputs["repo"], "acme/app");
+    assert_eq!(run.inputs["number"], "42");
+
+    // The run executes on the queue worker, which resumes the queued run the
+    // delivery's message names.
+    let message_id = response.body["messageId"].as_str().expect("message id");
+    let incoming = queued_message(dir.path(), message_id);
+    assert_eq!(incoming.channel, "webhook");
+    assert_eq!(incoming.channel_profile_id, None);
+    let codex = dir.path().join("codex-mock");
+    fs::write(
+        &codex,
+        "#!/bin/sh\necho '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"[workflow_result]{\\\"summary\\\":\\\"triaged\\\"}[/workflow_result]\"}}'\n",
+    )
+    .expect("codex mock");
+    fs::set_permissions(&codex, fs::Permissions::from_mode(0o755)).expect("chmod");
+    assert_eq!(incoming.workflow_run_id.as_deref(), Some(run_id.as_str()));
+    let action = process_queued_message_with_runner_binaries(
+        &dir.path().join("workspaces/eng"),
+        &settings,
+        &incoming,
+        101,
+        &BTreeMap::new(),
+        &FunctionRegistry::v1_defaults(store.clone(), &settings),
+        Some(RunnerBinaries::default().with("openai", codex.display().to_string())),
+        |_attempt, _request, _orchestrator| None,
+    )
+    .expect("route queued run");
+    assert!(matches!(
+        action,
+        RoutedSelectorAction::WorkflowStatus { run_id: Some(ref routed), .. } if *routed == run_id
+    ));
+    assert_eq!(
+        store.load_run(&run_id).expect("run").state,
+        RunState::Succeeded
+    );
+
+    let replay = ingress.handle(&request, 200);
+    assert_eq!(replay.status, 200);
+    assert_eq!(replay.body["runId"], run_id.as_str());
+    assert_eq!(replay.body["duplicate"], true);
+
+    let mut tampered = request.clone();
+    tampered.body = br#"{"issue":{"number":1}}"#.to_vec();
+    assert_eq!(ingress.handle(&tampered, 300).status, 401);
+
+    let mut missing_delivery = request.clone();
+    missing_delivery.headers.remove("x-github-delivery");
+    assert_eq!(ingress.handle(&missing_delivery, 300).status, 400);
+
+    let incomplete = signed_request(
+        "/hooks/github",
+        "topsecret",
+        "X-GitHub-Delivery",
+        "delivery-2",
+        &json!({"repository": {"full_name": "acme/app"}}),
+    );
+    let response = ingress.handle(&incomplete, 300);
+    assert_eq!(response.status, 422);
+    assert!(response.body["error"]
+        .as_str()
+        .expect("error")
+        .contains("`/issue/number` for `number`"));
+
+    let mut get = request.clone();
+    get.method = "GET".to_string();
+    assert_eq!(ingress.handle(&get, 300).status, 405);
+    let mut unknown = request;
+    unknown.path = "/hooks/unknown".to_string();
+    assert_eq!(ingress.handle(&unknown, 300).status, 404);
+}
+
+#[test]
+fn webhook_module_finishes_a_pending_delivery_under_its_reserved_run() {
+    let dir = tempdir().expect("tempdir");
+    let secret_env = "DIRECLAW_TEST_WEBHOOK_SECRET_PENDING";
+    std::env::set_var(secret_env, "topsecret");
+    let ingress = ingress(settings(dir.path(), secret_env));
+    let request = signed_request(
+        "/hooks/github",
+        "topsecret",
+        "X-GitHub-Delivery",
+        "delivery-crash",
+        &json!({"repository": {"full_name": "acme/app"}, "issue": {"number": 7}}),
+    );
+    let first = ingress.handle(&request, 100);
+    assert_eq!(first.status, 202, "{}", first.body);
+    let run_id = first.body["runId"].as_str().expect("run id").to_string();
+    let message_id = first.body["messageId"].as_str().expect("message id");
+
+    // Simulate a crash after the run was created but before the message was
+    // enqueued and the delivery completed.
+    let runtime_root = dir.path().join("workspaces/eng");
+    let record_path = delivery_record_path(&runtime_root, "github", "delivery-crash");
+    let mut record: DeliveryRecord =
+        serde_json::from_str(&fs::read_to_string(&record_path).expect("record")).expect("json");
+    assert!(!record.pending);
+    assert_eq!(record.run_id.as_deref(), Some(run_id.as_str()));
+    record.pending = true;
+    record.response = Value::Null;
+    fs::write(&record_path, serde_json::to_vec(&record).expect("encode")).expect("write");
+    fs::remove_file(
+        runtime_root
+            .join("queue/incoming")
+            .join(format!("{message_id}.json")),
+    )
+    .expect("drop queued message");
+
+    let retried = ingress.handle(&request, 200);
+    assert_eq!(retried.status, 202, "{}", retried.body);
+    assert_eq!(retried.body["runId"], run_id.as_str());
+    assert_eq!(
+        queued_message(dir.path(), message_id)
+            .workflow_run_id
+            .as_deref(),
+        Some(run_id.as_str())
+    );
+    let store = WorkflowRunStore::new(&runtime_root);
+    assert_eq!(store.load_runs().expect("runs").len(), 1);
+
+    let replay = ingress.handle(&request, 300);
+    assert_eq!(replay.status, 200);
+    assert_eq!(replay.body["duplicate"], true);
+    assert_eq!(replay.body["runId"], run_id.as_str());
+}
+
+#[test]
+fn webhook_module_enqueues_messages_on_webhook_profiles() {
+    let dir = tempdir().expect("tempdir");
+    let secret_env = "DIRECLAW_TEST_WEBHOOK_SECRET_MESSAGE";
+    std::env::set_var(secret_env, "alerts-secret");
+    let ingress = ingress(settings(dir.path(), secret_env));
+
+    let request = signed_request(
+        "/hooks/alerts",
+        "alerts-secret",
+        "X-Direclaw-Delivery",
+        "alert-7",
+        &json!({"text": "disk almost full on db-1"}),
+    );
+    let response = ingress.handle(&request, 100);
+    assert_eq!(response.status, 202, "{}", response.body);
+    assert_eq!(response.body["status"], "queued");
+    assert!(response.body["runId"].is_null());
+    let message_id = response.body["messageId"].as_str().expect("message id");
+
+    let incoming = queued_message(dir.path(), message_id);
+    assert_eq!(incoming.channel, "webhook");
+    assert_eq!(incoming.channel_profile_id.as_deref(), Some("eng-hooks"));
+    assert_eq!(incoming.message, "disk almost full on db-1");
+    assert_eq!(
+        incoming.conversation_id.as_deref(),
+        Some("webhook:alerts:alert-7")
+    );
+
+    let replay = ingress.handle(&request, 200);
+    assert_eq!(replay.status, 200);
+    assert_eq!(replay.body["messageId"], message_id);
+
+    // Concurrent deliveries of one id are accepted once; the others replay the
+    // recorded response or report the first request as still in progress.
+    let request = signed_request(
+        "/hooks/alerts",
+        "alerts-secret",
+        "X-Direclaw-Delivery",
+        "alert-8",
+        &json!({"text": "disk full on db-1"}),
+    );
+    let statuses = std::thread::scope(|scope| {
+        let handles = (0..8)
+            .map(|_| scope.spawn(|| ingress.clone().handle(&request, 300).status))
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("delivery thread"))
+            .collect::<Vec<_>>()
+    });
+    assert_eq!(statuses.iter().filter(|status| **status == 202).count(), 1);
+    assert!(statuses
+        .iter()
+        .all(|status| [200, 202, 409].contains(status)));
+}
+
+#[test]
+fn webhook_module_reads_http_requests() {
+    let raw = b"POST /hooks/github?x=1 HTTP/1.1\r\nHost: localhost\r\nX-GitHub-Delivery: abc\r\nContent-Length: 2\r\n\r\n{}";
+    let request = read_request(&raw[..], 1024).expect("request");
+    assert_eq!(request.method, "POST");
+    assert_eq!(request.path, "/hooks/github");
+    assert_eq!(request.header("X-GitHub-Delivery"), Some("abc"));
+    assert_eq!(request.body, b"{}");
+
+    let oversized = b"POST /hooks HTTP/1.1\r\nContent-Length: 4096\r\n\r\n";
+    assert_eq!(
+        read_request(&oversized[..], 1024)
+            .expect_err("too large")
+            .status,
+        413
+    );
+    let chunked = b"POST /hooks HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n";
+    assert_eq!(
+        read_request(&chunked[..], 1024)
+            .expect_err("chunked")
+            .status,
+        411
+    );
+}
+
+#[test]
+fn webhook_module_validates_route_settings() {
+    let dir = tempdir().expect("tempdir");
+    let valid = settings(dir.path(), "DIRECLAW_TEST_WEBHOOK_SECRET_UNUSED");
+    valid
+        .validate(ValidationOptions::default())
+        .expect("valid settings");
+
+    let mut both_targets = valid.clone();
+    both_targets
+        .webhooks
+        .routes
+        .get_mut("github")
+        .expect("route")
+        .channel_profile_id = Some("eng-hooks".to_string());
+    let err = both_targets
+        .validate(ValidationOptions::default())
+        .expect_err("ambiguous target");
+    assert!(err.to_string().contains("exactly one of"), "{err}");
+
+    let mut wrong_profile = valid.clone();
+    wrong_profile
+        .channel_profiles
+        .get_mut("eng-hooks")
+        .expect("profile")
+        .channel = direclaw::config::ChannelKind::Slack;
+    let err = wrong_profile
+        .validate(ValidationOptions::default())
+        .expect_err("non-webhook profile");
+    assert!(err.to_string().contains("`webhook` profile"), "{err}");
+
+    let mut bad_bind = valid;
+    bad_bind.webhooks.bind = "localhost".to_string();
+    let err = bad_bind
+        .validate(ValidationOptions::default())
+        .expect_err("bad bind");
+    assert!(err.to_string().contains("webhooks.bind"), "{err}");
+}
@@ -1,8 +1,10 @@
 use direclaw::config::{
-    AgentConfig, ConfigProviderKind, OrchestratorConfig, OutputKey, PathTemplate, StepLimitsConfig,
-    WorkflowConfig, WorkflowInputs, WorkflowLimitsConfig, WorkflowOrchestrationConfig,
-    WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType, WorkflowStepWorkspaceMode,
+    AgentConfig, AgentFallbackConfig, OrchestratorConfig, OutputKey, PathTemplate,
+    StepLimitsConfig, WorkflowConfig, WorkflowInputs, WorkflowLimitsConfig,
+    WorkflowOrchestrationConfig, WorkflowStepConfig, WorkflowStepPromptType, WorkflowStepType,
+    WorkflowStepWorkspaceMode,
 };
+use direclaw::provider::ProviderKind;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::Path;
@@ -313,6 +315,7 @@ channels:
     assert!(unhealthy_out.contains("summary=unhealthy"));
     assert!(unhealthy_out.contains("check:binary.anthropic=fail"));
     assert!(unhealthy_out.contains("check:binary.openai=fail"));
+    assert!(!unhealthy_out.contains("check:binary.gemini"));
 
     let bin_dir = temp.path().join("bin");
     fs::create_dir_all(&bin_dir).expect("create bin dir");
@@ -567,6 +570,24 @@ fn workflow_commands_work() {
     assert_eq!(progress_json["inputCount"], serde_json::Value::from(1));
     assert_eq!(progress_json["inputKeys"], serde_json::json!(["ticket"]));
 
+    for args in [
+        &["workflow", "events", run_id.as_str()][..],
+        &["workflow", "events", run_id.as_str(), "--follow"][..],
+    ] {
+        let events = run(temp.path(), args);
+        assert_ok(&events);
+        let events = String::from_utf8_lossy(&events.stdout)
+            .lines()
+            .filter(|line| line.starts_with('{'))
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).expect("event json"))
+            .collect::<Vec<_>>();
+        assert_eq!(events[0]["type"], "run_created");
+        assert_eq!(events[0]["seq"], 1);
+        let last = events.last().expect("events");
+        assert_eq!(last["type"], "state_changed");
+        assert_eq!(last["to"], "succeeded");
+    }
+
     let run_record_path = temp
         .path()
         .join("workspace/alpha/workflows/runs")
@@ -637,6 +658,93 @@ fn fresh_setup_default_workflow_runs_successfully() {
     assert!(stdout(&status).contains("state=succeeded"));
 }
 
+#[test]
+fn workflow_status_reports_fallback_provider_after_rate_limit() {
+    let temp = tempdir().expect("tempdir");
+    write_settings(temp.path(), true);
+    assert_ok(&run(temp.path(), &["setup"]));
+
+    let orchestrator_path = temp.path().join("workspace/main/orchestrator.yaml");
+    let mut orchestrator: OrchestratorConfig =
+        serde_yaml::from_str(&fs::read_to_string(&orchestrator_path).expect("read orchestrator"))
+            .expect("parse orchestrator");
+    let default_workflow = orchestrator
+        .workflows
+        .iter_mut()
+        .find(|workflow| workflow.id == "default")
+        .expect("default workflow");
+    let first_step = default_workflow.steps.first_mut().expect("default step");
+    first_step.prompt_type = WorkflowStepPromptType::WorkflowResultEnvelope;
+    let step_id = first_step.id.clone();
+    let agent_id = first_step.agent.clone();
+    let agent = orchestrator.agents.get_mut(&agent_id).expect("step agent");
+    agent.provider = ProviderKind::ANTHROPIC;
+    agent.model = "sonnet".to_string();
+    agent.fallbacks = vec![AgentFallbackConfig {
+        provider: ProviderKind::OPENAI,
+        model: "gpt-5.2".to_string(),
+        endpoint: None,
+    }];
+    fs::write(
+        &orchestrator_path,
+        serde_yaml::to_string(&orchestrator).expect("serialize orchestrator"),
+    )
+    .expect("write orchestrator");
+
+    let bin_dir = temp.path().join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    let claude = bin_dir.join("claude");
+    fs::write(
+        &claude,
+        "#!/bin/sh\necho 'API Error: 429 rate_limit_error' >&2\nexit 1\n",
+    )
+    .expect("write claude mock");
+    let codex = bin_dir.join("codex");
+    fs::write(
+        &codex,
+        r#"#!/bin/sh
+echo '{"type":"item.completed","item":{"type":"agent_message","text":"[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]"}}'
+"#,
+    )
+    .expect("write codex mock");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for mock in [&claude, &codex] {
+            let mut perms = fs::metadata(mock).expect("metadata").permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(mock, perms).expect("chmod");
+        }
+    }
+
+    let run_output = run_with_env(
+        temp.path(),
+        &["workflow", "run", "main", "default"],
+        &[
+            (
+                "DIRECLAW_PROVIDER_BIN_ANTHROPIC",
+                claude.to_str().expect("utf8"),
+            ),
+            (
+                "DIRECLAW_PROVIDER_BIN_OPENAI",
+                codex.to_str().expect("utf8"),
+            ),
+        ],
+    );
+    assert_ok(&run_output);
+    let run_id = run_id_from(&run_output);
+    let status = run(temp.path(), &["workflow", "status", &run_id]);
+    assert_ok(&status);
+    let status = stdout(&status);
+    assert!(status.contains("state=succeeded"), "{status}");
+    assert!(
+        status.contains(&format!(
+            "providers_by_step:\n- {step_id} agent={agent_id} provider=openai model=gpt-5.2 fallback_from=anthropic"
+        )),
+        "{status}"
+    );
+}
+
 #[test]
 fn workflow_steps_always_execute_from_orchestrator_root() {
     let temp = tempdir().expect("tempdir");
@@ -652,9 +760,11 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
     orchestrator.agents.insert(
         "worker".to_string(),
         AgentConfig {
-            provider: ConfigProviderKind::OpenAi,
+            provider: ProviderKind::OPENAI,
             model: "gpt-5.3-codex-spark".to_string(),
             can_orchestrate_workflows: false,
+            endpoint: None,
+            fallbacks: Vec::new(),
         },
     );
     orchestrator.workflows = vec![WorkflowConfig {
@@ -664,6 +774,7 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
         tags: vec!["workspace".parse().expect("tag")],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![
             WorkflowStepConfig {
                 id: "s1".to_string(),
@@ -675,6 +786,14 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
                 next: Some("s2".to_string()),
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: out_keys(&["summary", "artifact"]),
                 output_files: out_files(&[
                     (
@@ -688,6 +807,7 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
                 ]),
                 final_output_priority: out_keys(&["artifact", "summary"]),
                 limits: None,
+                retry: None,
             },
             WorkflowStepConfig {
                 id: "s2".to_string(),
@@ -699,6 +819,14 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
                 next: None,
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: out_keys(&["summary", "artifact"]),
                 output_files: out_files(&[
                     (
@@ -712,6 +840,7 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
                 ]),
                 final_output_priority: out_keys(&["artifact", "summary"]),
                 limits: None,
+                retry: None,
             },
         ],
     }];
@@ -769,6 +898,119 @@ fn workflow_steps_always_execute_from_orchestrator_root() {
     );
 }
 
+#[test]
+fn agent_workspace_steps_execute_in_persistent_agent_directory_with_mounts() {
+    let temp = tempdir().expect("tempdir");
+    write_settings(temp.path(), true);
+    assert_ok(&run(temp.path(), &["orchestrator", "add", "alpha"]));
+    assert_ok(&run(
+        temp.path(),
+        &["orchestrator", "grant-shared-access", "alpha", "shared"],
+    ));
+
+    let orchestrator_path = temp.path().join("workspace/alpha/orchestrator.yaml");
+    let mut orchestrator: OrchestratorConfig =
+        serde_yaml::from_str(&fs::read_to_string(&orchestrator_path).expect("read orchestrator"))
+            .expect("parse orchestrator");
+    orchestrator.default_workflow = "maintain".to_string();
+    orchestrator.selector_agent = "default".to_string();
+    orchestrator.agents.insert(
+        "maintainer".to_string(),
+        AgentConfig {
+            provider: ProviderKind::OPENAI,
+            model: "gpt-5.3-codex-spark".to_string(),
+            can_orchestrate_workflows: false,
+            endpoint: None,
+            fallbacks: Vec::new(),
+        },
+    );
+    orchestrator.workflows = vec![serde_yaml::from_str(
+        r#"
+id: maintain
+version: 1
+description: keep the maintainer clone current
+tags: [maintenance]
+steps:
+  - id: sync
+    type: agent_task
+    agent: maintainer
+    prompt: sync the clone
+    workspace_mode: agent_workspace
+    outputs: [summary, artifact]
+    output_files:
+      summary: outputs/summary.txt
+      artifact: outputs/artifact.txt
+"#,
+    )
+    .expect("parse workflow")];
+    fs::write(
+        &orchestrator_path,
+        serde_yaml::to_string(&orchestrator).expect("serialize orchestrator"),
+    )
+    .expect("write orchestrator");
+
+    let bin_dir = temp.path().join("bin");
+    fs::create_dir_all(&bin_dir).expect("create bin dir");
+    let codex = bin_dir.join("codex");
+    fs::write(
+        &codex,
+        "#!/bin/sh\necho synced >> cache.txt\necho '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"[workflow_result]{\\\"status\\\":\\\"complete\\\",\\\"summary\\\":\\\"ok\\\",\\\"artifact\\\":\\\"ok\\\"}[/workflow_result]\"}}'\n",
+    )
+    .expect("write codex mock");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(&codex).expect("metadata").permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&codex, perms).expect("chmod");
+    }
+
+    let envs = [(
+        "DIRECLAW_PROVIDER_BIN_OPENAI",
+        codex.to_str().expect("utf8"),
+    )];
+    let first = run_with_env(
+        temp.path(),
+        &["workflow", "run", "alpha", "maintain"],
+        &envs,
+    );
+    assert_ok(&first);
+    let second = run_with_env(
+        temp.path(),
+        &["workflow", "run", "alpha", "maintain"],
+        &envs,
+    );
+    assert_ok(&second);
+
+    let agent_workspace = temp.path().join("workspace/alpha/work/agents/maintainer");
+    let invocation: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(temp.path().join(format!(
+            "workspace/alpha/workflows/runs/{}/steps/sync/attempts/1/provider_invocation.json",
+            run_id_from(&second)
+        )))
+        .expect("read invocation"),
+    )
+    .expect("parse invocation");
+    assert_eq!(
+        invocation["workingDirectory"].as_str(),
+        Some(agent_workspace.display().to_string().as_str())
+    );
+    assert_eq!(
+        fs::read_to_string(agent_workspace.join("cache.txt")).expect("cache survives runs"),
+        "synced\nsynced\n"
+    );
+    for mount in [".agents/skills", ".claude/skills"] {
+        assert_eq!(
+            fs::read_link(agent_workspace.join(mount)).expect("skills mount"),
+            temp.path().join("workspace/alpha/skills")
+        );
+    }
+    assert_eq!(
+        fs::canonicalize(agent_workspace.join("shared/shared")).expect("shared mount"),
+        fs::canonicalize(temp.path().join("shared-workspace")).expect("shared root")
+    );
+}
+
 #[test]
 fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
     let temp = tempdir().expect("tempdir");
@@ -787,21 +1029,27 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
         default_run_timeout_seconds: Some(25),
         default_step_timeout_seconds: Some(5),
         max_step_timeout_seconds: Some(5),
+        interrupted_attempt_policy: None,
+        retry: None,
     });
     orchestrator.agents.insert(
         "default".to_string(),
         AgentConfig {
-            provider: ConfigProviderKind::Anthropic,
+            provider: ProviderKind::ANTHROPIC,
             model: "sonnet".to_string(),
             can_orchestrate_workflows: true,
+            endpoint: None,
+            fallbacks: Vec::new(),
         },
     );
     orchestrator.agents.insert(
         "worker".to_string(),
         AgentConfig {
-            provider: ConfigProviderKind::OpenAi,
+            provider: ProviderKind::OPENAI,
             model: "gpt-5.3-codex-spark".to_string(),
             can_orchestrate_workflows: false,
+            endpoint: None,
+            fallbacks: Vec::new(),
         },
     );
     orchestrator.workflows = vec![WorkflowConfig {
@@ -814,6 +1062,7 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
             max_total_iterations: Some(7),
             run_timeout_seconds: Some(20),
         }),
+        repository: None,
         steps: vec![
             WorkflowStepConfig {
                 id: "plan".to_string(),
@@ -825,6 +1074,14 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 next: Some("review".to_string()),
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: out_keys(&["plan", "summary"]),
                 output_files: out_files(&[
                     (
@@ -840,6 +1097,7 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 limits: Some(StepLimitsConfig {
                     max_retries: Some(1),
                 }),
+                retry: None,
             },
             WorkflowStepConfig {
                 id: "review".to_string(),
@@ -851,6 +1109,14 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 next: None,
                 on_approve: Some("finalize".to_string()),
                 on_reject: Some("plan".to_string()),
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: out_keys(&["decision", "summary", "feedback"]),
                 output_files: out_files(&[
                     (
@@ -868,6 +1134,7 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 ]),
                 final_output_priority: out_keys(&["summary"]),
                 limits: None,
+                retry: None,
             },
             WorkflowStepConfig {
                 id: "finalize".to_string(),
@@ -879,6 +1146,14 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 next: None,
                 on_approve: None,
                 on_reject: None,
+                branches: Vec::new(),
+                transitions: Vec::new(),
+                workflow: None,
+                inputs: BTreeMap::new(),
+                command: Vec::new(),
+                items: None,
+                sequence: Vec::new(),
+                max_concurrency: None,
                 outputs: out_keys(&["summary", "result"]),
                 output_files: out_files(&[
                     ("summary", "reports/{{workflow.run_id}}/final-summary.txt"),
@@ -886,6 +1161,7 @@ fn workflow_runtime_consumes_tui_style_fields_end_to_end() {
                 ]),
                 final_output_priority: out_keys(&["summary", "result"]),
                 limits: None,
+                retry: None,
             },
         ],
     }];
@@ -1061,7 +1337,7 @@ fn workflow_inputs_persist_and_reload_after_setup_style_edit() {
         .inputs
         .as_slice()
         .iter()
-        .map(|key| key.as_str().to_string())
+        .map(|spec| spec.key.as_str().to_string())
         .collect::<Vec<_>>();
     assert_eq!(keys, vec!["ticket".to_string(), "priority".to_string()]);
 
@@ -1090,21 +1366,27 @@ fn workflow_run_enforces_step_timeout_from_cli_config() {
         default_run_timeout_seconds: Some(30),
         default_step_timeout_seconds: Some(5),
         max_step_timeout_seconds: Some(0),
+        interrupted_attempt_policy: None,
+        retry: None,
     });
     orchestrator.agents.insert(
         "default".to_string(),
         AgentConfig {
-            provider: ConfigProviderKind::OpenAi,
+            provider: ProviderKind::OPENAI,
             model: "gpt-5.3-codex-spark".to_string(),
             can_orchestrate_workflows: true,
+            endpoint: None,
+            fallbacks: Vec::new(),
         },
     );
     orchestrator.agents.insert(
         "worker".to_string(),
         AgentConfig {
-            provider: ConfigProviderKind::OpenAi,
+            provider: ProviderKind::OPENAI,
             model: "gpt-5.3-codex-spark".to_string(),
             can_orchestrate_workflows: false,
+            endpoint: None,
+            fallbacks: Vec::new(),
         },
     );
     orchestrator.workflows = vec![WorkflowConfig {
@@ -1114,6 +1396,7 @@ fn workflow_run_enforces_step_timeout_from_cli_config() {
         tags: vec!["timeout".parse().expect("tag")],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![WorkflowStepConfig {
             id: "slow".to_string(),
             step_type: WorkflowStepType::AgentTask,
@@ -1124,6 +1407,14 @@ fn workflow_run_enforces_step_timeout_from_cli_config() {
             next: None,
             on_approve: None,
             on_reject: None,
+            branches: Vec::new(),
+            transitions: Vec::new(),
+            workflow: None,
+            inputs: BTreeMap::new(),
+            command: Vec::new(),
+            items: None,
+            sequence: Vec::new(),
+            max_concurrency: None,
             outputs: out_keys(&["summary", "result"]),
             output_files: out_files(&[
                 (
@@ -1137,6 +1428,7 @@ fn workflow_run_enforces_step_timeout_from_cli_config() {
             ]),
             final_output_priority: out_keys(&["summary", "result"]),
             limits: None,
+            retry: None,
         }],
     }];
     fs::write(
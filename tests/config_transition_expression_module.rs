@@ -0,0 +1,258 @@
+use direclaw::config::{OrchestratorConfig, TransitionExpression};
+use direclaw::orchestration::output_contract::evaluate_step_outputs;
+use serde_json::{json, Map, Value};
+
+fn outputs(value: Value) -> Map<String, Value> {
+    value.as_object().expect("object").clone()
+}
+
+fn triage_orchestrator(transitions: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: main
+selector_agent: router
+default_workflow: triage
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: triage
+    version: 1
+    steps:
+      - id: classify
+        type: agent_task
+        agent: router
+        prompt: classify
+        outputs: [summary, risk, score?]
+        output_files:
+          summary: out/summary.txt
+          risk: out/risk.txt
+          score: out/score.txt
+        transitions:
+{transitions}
+      - id: deep_review
+        type: agent_task
+        agent: router
+        prompt: review
+        outputs: [summary]
+        output_files:
+          summary: out/summary.txt
+      - id: ship
+        type: agent_task
+        agent: router
+        prompt: ship
+        outputs: [summary]
+        output_files:
+          summary: out/summary.txt
+"#
+    ))
+    .expect("parse orchestrator")
+}
+
+#[test]
+fn transition_expression_module_evaluates_comparisons_and_boolean_logic() {
+    let values = outputs(json!({
+        "risk": " high ",
+        "score": "7",
+        "flags": {"urgent": true},
+        "tasks": [{"title": "lint"}, "docs"]
+    }));
+
+    for (raw, expected) in [
+        ("outputs.risk == 'high'", true),
+        ("outputs.risk != \"high\"", false),
+        ("outputs.score >= 7 && outputs.score < 10", true),
+        ("outputs.score > 7 or outputs.risk == 'low'", false),
+        ("!(outputs.risk == 'low')", true),
+        ("outputs.flags.urgent", true),
+        ("outputs.flags.urgent == true", true),
+        ("outputs.missing == null", true),
+        ("not outputs.missing", true),
+        ("outputs.score > 6.5", true),
+        ("outputs.tasks.0.title == 'lint'", true),
+        ("outputs.tasks.1 == 'docs'", true),
+        ("outputs.tasks.2 == null", true),
+    ] {
+        let expression = TransitionExpression::parse(raw).expect(raw);
+        assert_eq!(expression.evaluate(&values), expected, "{raw}");
+    }
+
+    let expression =
+        TransitionExpression::parse("outputs.risk == 'high' || outputs.score > 5").expect("parse");
+    assert_eq!(expression.output_keys(), vec!["risk", "score"]);
+}
+
+#[test]
+fn transition_expression_module_rejects_malformed_expressions() {
+    for raw in [
+        "",
+        "outputs.risk ==",
+        "outputs.risk = 'high'",
+        "risk == 'high'",
+        "outputs.risk == 'high",
+        "(outputs.risk == 'high'",
+        "outputs == 'high'",
+    ] {
+        assert!(TransitionExpression::parse(raw).is_err(), "{raw}");
+    }
+}
+
+#[test]
+fn transition_expression_module_routes_on_first_match_then_default() {
+    let orchestrator = triage_orchestrator(
+        "          - when: \"outputs.risk == 'high'\"\n            next: deep_review\n          - default: ship",
+    );
+    orchestrator
+        .validate_setup_invariants()
+        .expect("valid transitions");
+    let workflow = &orchestrator.workflows[0];
+    let step = &workflow.steps[0];
+
+    let high = evaluate_step_outputs(
+        workflow,
+        step,
+        outputs(json!({"summary": "s", "risk": "high"})),
+    )
+    .expect("high risk");
+    assert_eq!(high.next_step_id.as_deref(), Some("deep_review"));
+
+    let low = evaluate_step_outputs(
+        workflow,
+        step,
+        outputs(json!({"summary": "s", "risk": "low"})),
+    )
+    .expect("low risk");
+    assert_eq!(low.next_step_id.as_deref(), Some("ship"));
+}
+
+#[test]
+fn transition_expression_module_validation_rejects_bad_transitions() {
+    for (transitions, expected) in [
+        (
+            "          - when: \"outputs.risk == 'high'\"\n            next: nowhere",
+            "transition targets unknown step `nowhere`",
+        ),
+        (
+            "          - when: \"outputs.risk ==\"\n            next: ship",
+            "has malformed transition `when: outputs.risk ==`",
+        ),
+        (
+            "          - when: \"outputs.owner == 'ops'\"\n            next: ship",
+            "transition references output `owner` not declared in `outputs`",
+        ),
+        (
+            "          - default: ship\n          - when: \"outputs.risk == 'high'\"\n            next: deep_review",
+            "`default` transition must be the last entry",
+        ),
+    ] {
+        let err = triage_orchestrator(transitions)
+            .validate_setup_invariants()
+            .expect_err(expected);
+        assert!(err.to_string().contains(expected), "{err}");
+    }
+}
+
+fn build_orchestrator(check_step: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: main
+selector_agent: router
+default_workflow: build
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: build
+    version: 1
+    steps:
+{check_step}
+      - id: fix
+        type: agent_task
+        agent: router
+        prompt: fix
+        outputs: [summary]
+        output_files:
+          summary: out/fix.txt
+      - id: ship
+        type: agent_task
+        agent: router
+        prompt: ship
+        outputs: [summary]
+        output_files:
+          summary: out/ship.txt
+"#
+    ))
+    .expect("parse orchestrator")
+}
+
+#[test]
+fn transition_expression_module_routes_command_steps_on_their_outputs() {
+    let orchestrator = build_orchestrator(
+        r#"      - id: check
+        type: command
+        command: [sh, -c, "echo dirty"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/check-summary.txt
+          stdout: out/check-stdout.txt
+        transitions:
+          - when: "outputs.stdout == 'dirty'"
+            next: fix
+          - default: ship"#,
+    );
+    orchestrator
+        .validate_setup_invariants()
+        .expect("command transitions are valid");
+    let workflow = &orchestrator.workflows[0];
+
+    let dirty = evaluate_step_outputs(
+        workflow,
+        &workflow.steps[0],
+        outputs(json!({"summary": "s", "stdout": "dirty\n"})),
+    )
+    .expect("dirty");
+    assert_eq!(dirty.next_step_id.as_deref(), Some("fix"));
+}
+
+#[test]
+fn transition_expression_module_rejects_transitions_on_decision_routed_steps() {
+    for check_step in [
+        r#"      - id: check
+        type: agent_review
+        agent: router
+        prompt: review
+        outputs: [decision, summary]
+        output_files:
+          decision: out/decision.txt
+          summary: out/summary.txt
+        on_approve: ship
+        on_reject: fix
+        transitions:
+          - default: ship"#,
+        r#"      - id: check
+        type: command
+        command: [sh, -c, "true"]
+        outputs: [summary]
+        output_files:
+          summary: out/summary.txt
+        on_approve: ship
+        on_reject: fix
+        transitions:
+          - default: ship"#,
+    ] {
+        let err = build_orchestrator(check_step)
+            .validate_setup_invariants()
+            .expect_err("decision-routed step with transitions");
+        assert!(
+            err.to_string()
+                .contains("routes on its decision through `on_approve`/`on_reject` and must not declare `transitions`"),
+            "{err}"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use direclaw::config::{
-    ChannelConfig, ChannelKind, ConfigProviderKind, OrchestratorConfig, Settings, ValidationOptions,
+    ChannelConfig, ChannelKind, OrchestratorConfig, Settings, ValidationOptions,
 };
+use direclaw::provider::{builtin_registry, ProviderKind};
 use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -89,12 +90,14 @@ fn config_settings_module_exposes_channel_kind_parser() {
 }
 
 #[test]
-fn config_orchestrator_file_module_exposes_provider_kind_parser() {
-    let provider = direclaw::config::orchestrator_file::ConfigProviderKind::parse("openai")
-        .expect("parse provider");
+fn config_provider_parser_accepts_registered_backend_ids() {
+    let provider = ProviderKind::parse("OpenAI").expect("parse provider");
+    assert_eq!(provider, ProviderKind::OPENAI);
+
+    let err = ProviderKind::parse("mistral").expect_err("unregistered provider");
     assert_eq!(
-        provider,
-        direclaw::config::orchestrator_file::ConfigProviderKind::OpenAi
+        err,
+        "provider must be one of: anthropic, gemini, openai, openai_compatible"
     );
 }
 
@@ -230,7 +233,8 @@ fn spec_example_settings_and_orchestrators_load_with_typed_fields() {
                 | ChannelKind::Slack
                 | ChannelKind::Discord
                 | ChannelKind::Telegram
-                | ChannelKind::Whatsapp => {}
+                | ChannelKind::Whatsapp
+                | ChannelKind::Webhook => {}
             }
         }
     }
@@ -251,9 +255,7 @@ fn spec_example_settings_and_orchestrators_load_with_typed_fields() {
                 )
             });
         for agent in orchestrator.agents.values() {
-            match agent.provider {
-                ConfigProviderKind::Anthropic | ConfigProviderKind::OpenAi => {}
-            }
+            assert!(builtin_registry().get(agent.provider.as_str()).is_ok());
         }
         orchestrator
             .validate(settings, &orchestrator.id)
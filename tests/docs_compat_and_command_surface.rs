@@ -70,9 +70,17 @@ fn specs_reference_current_config_paths_and_supported_command_surface() {
         "workflow list",
         "workflow show",
         "workflow run",
+        "workflow rerun",
+        "workflow simulate",
+        "workflow graph",
         "workflow status",
         "workflow progress",
+        "workflow events",
         "workflow cancel",
+        "workflow cleanup",
+        "workflow approve",
+        "workflow reject",
+        "usage report",
         "orchestrator-agent list",
         "orchestrator-agent add",
         "orchestrator-agent show",
@@ -97,7 +105,7 @@ fn specs_reference_current_config_paths_and_supported_command_surface() {
         ),
         (
             &["workflow"][..],
-            "usage: workflow <list|show|add|remove|run|status|progress|cancel> ...",
+            "usage: workflow <list|show|graph|add|remove|run|rerun|simulate|status|progress|events|cancel|cleanup|approve|reject> ...",
         ),
         (
             &["orchestrator-agent"][..],
@@ -220,10 +220,9 @@ memory:
 }
 
 fn binaries(anthropic: impl Into<String>, openai: impl Into<String>) -> RunnerBinaries {
-    RunnerBinaries {
-        anthropic: anthropic.into(),
-        openai: openai.into(),
-    }
+    RunnerBinaries::default()
+        .with("anthropic", anthropic)
+        .with("openai", openai)
 }
 
 fn queue_for_profile(settings: &Settings, profile_id: &str) -> QueuePaths {
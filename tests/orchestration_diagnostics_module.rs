@@ -1,12 +1,14 @@
-use direclaw::orchestration::diagnostics::{persist_provider_invocation_log, provider_error_log};
-use direclaw::provider::{InvocationLog, ProviderError, ProviderKind};
+use direclaw::orchestration::diagnostics::{
+    load_provider_usage, persist_provider_invocation_log, provider_error_log,
+};
+use direclaw::provider::{InvocationLog, ProviderError, ProviderKind, ProviderUsage};
 use std::path::Path;
 use tempfile::tempdir;
 
 fn sample_log(root: &Path) -> InvocationLog {
     InvocationLog {
         agent_id: "agent-1".to_string(),
-        provider: ProviderKind::OpenAi,
+        provider: ProviderKind::OPENAI,
         model: "gpt-5".to_string(),
         command_form: "codex exec".to_string(),
         working_directory: root.to_path_buf(),
@@ -14,6 +16,9 @@ fn sample_log(root: &Path) -> InvocationLog {
         context_files: vec![root.join("ctx.md")],
         exit_code: Some(0),
         timed_out: false,
+        usage: None,
+        session_id: None,
+        prior_attempts: Vec::new(),
     }
 }
 
@@ -22,7 +27,7 @@ fn diagnostics_module_exposes_provider_error_log_extraction() {
     let dir = tempdir().expect("tempdir");
     let log = sample_log(dir.path());
     let error = ProviderError::Timeout {
-        provider: ProviderKind::OpenAi,
+        provider: ProviderKind::OPENAI,
         timeout_ms: 1,
         log: Box::new(log.clone()),
     };
@@ -47,3 +52,26 @@ fn diagnostics_module_persists_provider_invocation_log_json() {
     assert_eq!(parsed["model"], "gpt-5");
     assert_eq!(parsed["timedOut"], false);
 }
+
+#[test]
+fn diagnostics_module_round_trips_provider_usage_through_invocation_log() {
+    let dir = tempdir().expect("tempdir");
+    let mut log = sample_log(dir.path());
+    persist_provider_invocation_log(dir.path(), &log).expect("persist without usage");
+    assert_eq!(load_provider_usage(dir.path()), None);
+
+    log.usage = Some(ProviderUsage {
+        input_tokens: 12,
+        output_tokens: 3,
+        cache_read_tokens: 4,
+        duration_ms: 900,
+        ..ProviderUsage::default()
+    });
+    persist_provider_invocation_log(dir.path(), &log).expect("persist with usage");
+
+    let usage = load_provider_usage(dir.path()).expect("usage");
+    assert_eq!(usage.agent_id, "agent-1");
+    assert_eq!(usage.provider, "openai");
+    assert_eq!(usage.model, "gpt-5");
+    assert_eq!(Some(usage.tokens), log.usage);
+}
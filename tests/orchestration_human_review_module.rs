@@ -0,0 +1,148 @@
+use direclaw::config::OrchestratorConfig;
+use direclaw::orchestration::human_review::{
+    load_pending_human_review, parse_human_review_reply, record_human_review_decision,
+};
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::workflow_engine::WorkflowEngine;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use tempfile::tempdir;
+
+fn review_orchestrator(gate_routes: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: main
+selector_agent: router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: gate
+        type: human_review
+        prompt: approve?
+        outputs: [decision, summary, feedback]
+        output_files:
+          decision: out/decision.txt
+          summary: out/summary.txt
+          feedback: out/feedback.txt
+{gate_routes}
+      - id: done
+        type: agent_task
+        agent: router
+        prompt: done
+        outputs: [summary]
+        output_files:
+          summary: out/summary.txt
+"#
+    ))
+    .expect("parse orchestrator")
+}
+
+#[test]
+fn human_review_module_parses_conversation_replies() {
+    assert_eq!(parse_human_review_reply("approve"), Some((true, None)));
+    assert_eq!(parse_human_review_reply("  LGTM!  "), Some((true, None)));
+    assert_eq!(
+        parse_human_review_reply("/approved - ship it"),
+        Some((true, Some("ship it".to_string())))
+    );
+    assert_eq!(
+        parse_human_review_reply("reject: add a rollback plan"),
+        Some((false, Some("add a rollback plan".to_string())))
+    );
+    assert_eq!(
+        parse_human_review_reply("Rejected, needs tests"),
+        Some((false, Some("needs tests".to_string())))
+    );
+    assert_eq!(parse_human_review_reply("approvers are busy"), None);
+    assert_eq!(parse_human_review_reply("what is the status?"), None);
+    assert_eq!(parse_human_review_reply(""), None);
+}
+
+#[test]
+fn human_review_module_validates_review_routes_without_agent() {
+    review_orchestrator("        on_approve: done\n        on_reject: gate")
+        .validate_setup_invariants()
+        .expect("human review step does not require an agent");
+
+    let err = review_orchestrator("        on_approve: done")
+        .validate_setup_invariants()
+        .expect_err("missing on_reject");
+    assert!(err
+        .to_string()
+        .contains("requires both `on_approve` and `on_reject`"));
+}
+
+#[test]
+fn human_review_module_rejects_decisions_without_pending_request() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    store.create_run("run-1", "wf", 1).expect("create run");
+
+    assert!(load_pending_human_review(&store, "run-1")
+        .expect("load pending")
+        .is_none());
+    let err = record_human_review_decision(&store, "run-1", true, None, None, 2)
+        .expect_err("no pending review");
+    assert!(err
+        .to_string()
+        .contains("workflow run `run-1` has no pending human review"));
+}
+
+#[test]
+fn human_review_module_records_only_one_of_racing_decisions() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    store.create_run("run-1", "wf", 1).expect("create run");
+    let orchestrator = review_orchestrator("        on_approve: done\n        on_reject: gate");
+    let parked = WorkflowEngine::new(store.clone(), orchestrator)
+        .start("run-1", 2)
+        .expect("start");
+    assert_eq!(parked.state, RunState::Waiting);
+
+    let barrier = Arc::new(Barrier::new(2));
+    let handles: Vec<_> = [true, false]
+        .into_iter()
+        .map(|approved| {
+            let store = WorkflowRunStore::new(dir.path());
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                record_human_review_decision(&store, "run-1", approved, None, None, 3)
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().expect("join"))
+        .collect();
+
+    let recorded: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+    assert_eq!(recorded.len(), 1);
+    let err = results
+        .iter()
+        .find_map(|r| r.as_ref().err())
+        .expect("losing decision");
+    assert!(err
+        .to_string()
+        .contains("workflow run `run-1` has no pending human review"));
+    let stored: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(
+            dir.path()
+                .join("workflows/runs/run-1/steps/gate/attempts/1/human_review_decision.json"),
+        )
+        .expect("decision file"),
+    )
+    .expect("decision json");
+    assert_eq!(
+        stored["approved"],
+        serde_json::Value::from(recorded[0].approved)
+    );
+}
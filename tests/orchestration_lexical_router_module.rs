@@ -62,6 +62,7 @@ fn request(user_message: &str) -> SelectorRequest {
         available_functions: vec!["workflow.status".to_string()],
         available_function_schemas: FunctionRegistry::new(vec!["workflow.status".to_string()])
             .available_function_schemas(),
+        available_workflow_inputs: Default::default(),
     }
 }
 
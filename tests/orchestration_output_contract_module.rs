@@ -20,6 +20,14 @@ fn output_contract_module_parses_envelope_and_resolves_paths() {
         next: None,
         on_approve: Some("done".to_string()),
         on_reject: Some("plan".to_string()),
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![
             OutputKey::parse("decision").expect("decision key"),
             OutputKey::parse("feedback").expect("feedback key"),
@@ -31,6 +39,7 @@ fn output_contract_module_parses_envelope_and_resolves_paths() {
         )]),
         final_output_priority: vec![OutputKey::parse("feedback").expect("feedback key")],
         limits: None,
+        retry: None,
     };
     let done = WorkflowStepConfig {
         id: "done".to_string(),
@@ -42,10 +51,19 @@ fn output_contract_module_parses_envelope_and_resolves_paths() {
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: Vec::new(),
         output_files: BTreeMap::new(),
         final_output_priority: Vec::new(),
         limits: None,
+        retry: None,
     };
     let workflow = WorkflowConfig {
         id: "wf".to_string(),
@@ -54,6 +72,7 @@ fn output_contract_module_parses_envelope_and_resolves_paths() {
         tags: vec!["outputs".parse().expect("tag")],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone(), done],
     };
 
@@ -107,6 +126,14 @@ fn output_contract_module_rejects_reserved_attempt_root_output_targets() {
             next: None,
             on_approve: None,
             on_reject: None,
+            branches: Vec::new(),
+            transitions: Vec::new(),
+            workflow: None,
+            inputs: BTreeMap::new(),
+            command: Vec::new(),
+            items: None,
+            sequence: Vec::new(),
+            max_concurrency: None,
             outputs: vec![OutputKey::parse("artifact").expect("artifact key")],
             output_files: BTreeMap::from_iter([(
                 OutputKey::parse_output_file_key("artifact").expect("artifact output key"),
@@ -114,6 +141,7 @@ fn output_contract_module_rejects_reserved_attempt_root_output_targets() {
             )]),
             final_output_priority: vec![OutputKey::parse("artifact").expect("artifact key")],
             limits: None,
+            retry: None,
         };
 
         let err =
@@ -36,6 +36,12 @@ fn prompt_render_module_renders_inputs_state_and_output_paths() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
 
     let step = WorkflowStepConfig {
@@ -48,6 +54,14 @@ fn prompt_render_module_renders_inputs_state_and_output_paths() {
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![OutputKey::parse("summary").expect("key")],
         output_files: BTreeMap::from_iter([(
             OutputKey::parse_output_file_key("summary").expect("key"),
@@ -55,6 +69,7 @@ fn prompt_render_module_renders_inputs_state_and_output_paths() {
         )]),
         final_output_priority: vec![OutputKey::parse("summary").expect("key")],
         limits: None,
+        retry: None,
     };
 
     let workflow = WorkflowConfig {
@@ -64,6 +79,7 @@ fn prompt_render_module_renders_inputs_state_and_output_paths() {
         tags: vec!["prompt".parse().expect("tag")],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone()],
     };
 
@@ -122,6 +138,12 @@ fn prompt_render_module_injects_memory_context_bundle_with_bounded_citations() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
 
     let step = WorkflowStepConfig {
@@ -134,6 +156,14 @@ fn prompt_render_module_injects_memory_context_bundle_with_bounded_citations() {
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![OutputKey::parse("summary").expect("key")],
         output_files: BTreeMap::from_iter([(
             OutputKey::parse_output_file_key("summary").expect("key"),
@@ -141,6 +171,7 @@ fn prompt_render_module_injects_memory_context_bundle_with_bounded_citations() {
         )]),
         final_output_priority: vec![OutputKey::parse("summary").expect("key")],
         limits: None,
+        retry: None,
     };
     let workflow = WorkflowConfig {
         id: "wf-default".to_string(),
@@ -149,6 +180,7 @@ fn prompt_render_module_injects_memory_context_bundle_with_bounded_citations() {
         tags: vec![],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone()],
     };
     let output_paths = BTreeMap::from_iter([(
@@ -217,6 +249,12 @@ fn prompt_render_module_memory_context_truncation_keeps_complete_ranked_lines()
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
 
     let step = WorkflowStepConfig {
@@ -229,6 +267,14 @@ fn prompt_render_module_memory_context_truncation_keeps_complete_ranked_lines()
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![OutputKey::parse("summary").expect("key")],
         output_files: BTreeMap::from_iter([(
             OutputKey::parse_output_file_key("summary").expect("key"),
@@ -236,6 +282,7 @@ fn prompt_render_module_memory_context_truncation_keeps_complete_ranked_lines()
         )]),
         final_output_priority: vec![OutputKey::parse("summary").expect("key")],
         limits: None,
+        retry: None,
     };
     let workflow = WorkflowConfig {
         id: "wf-default".to_string(),
@@ -244,6 +291,7 @@ fn prompt_render_module_memory_context_truncation_keeps_complete_ranked_lines()
         tags: vec![],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone()],
     };
     let output_paths = BTreeMap::from_iter([(
@@ -298,6 +346,12 @@ fn prompt_render_module_context_uses_relative_output_paths_with_shared_root() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
 
     let step = WorkflowStepConfig {
@@ -310,6 +364,14 @@ fn prompt_render_module_context_uses_relative_output_paths_with_shared_root() {
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![
             OutputKey::parse("summary").expect("key"),
             OutputKey::parse("artifact").expect("key"),
@@ -326,6 +388,7 @@ fn prompt_render_module_context_uses_relative_output_paths_with_shared_root() {
         ]),
         final_output_priority: vec![OutputKey::parse("summary").expect("key")],
         limits: None,
+        retry: None,
     };
     let workflow = WorkflowConfig {
         id: "wf-default".to_string(),
@@ -334,6 +397,7 @@ fn prompt_render_module_context_uses_relative_output_paths_with_shared_root() {
         tags: vec![],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone()],
     };
     let output_paths = BTreeMap::from_iter([
@@ -401,6 +465,12 @@ fn prompt_render_module_includes_shared_workspaces_in_runtime_context() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
 
     let step = WorkflowStepConfig {
@@ -413,6 +483,14 @@ fn prompt_render_module_includes_shared_workspaces_in_runtime_context() {
         next: None,
         on_approve: None,
         on_reject: None,
+        branches: Vec::new(),
+        transitions: Vec::new(),
+        workflow: None,
+        inputs: BTreeMap::new(),
+        command: Vec::new(),
+        items: None,
+        sequence: Vec::new(),
+        max_concurrency: None,
         outputs: vec![OutputKey::parse("summary").expect("key")],
         output_files: BTreeMap::from_iter([(
             OutputKey::parse_output_file_key("summary").expect("key"),
@@ -420,6 +498,7 @@ fn prompt_render_module_includes_shared_workspaces_in_runtime_context() {
         )]),
         final_output_priority: vec![OutputKey::parse("summary").expect("key")],
         limits: None,
+        retry: None,
     };
     let workflow = WorkflowConfig {
         id: "wf-default".to_string(),
@@ -428,6 +507,7 @@ fn prompt_render_module_includes_shared_workspaces_in_runtime_context() {
         tags: vec![],
         inputs: WorkflowInputs::default(),
         limits: None,
+        repository: None,
         steps: vec![step.clone()],
     };
     let output_paths = BTreeMap::from_iter([(
@@ -0,0 +1,262 @@
+use direclaw::config::OrchestratorConfig;
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::workflow_engine::WorkflowEngine;
+use direclaw::provider::RunnerBinaries;
+use direclaw::runtime::{bootstrap_state_root, StatePaths};
+use serde_json::Value;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn write_script(path: &Path, body: &str) {
+    fs::write(path, body).expect("write script");
+    let mut perms = fs::metadata(path).expect("metadata").permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(path, perms).expect("chmod");
+}
+
+fn orchestrator() -> OrchestratorConfig {
+    serde_yaml::from_str(
+        r#"
+id: eng
+selector_agent: router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+  helper:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: wf
+    version: 1
+    description: session flow
+    tags: [session]
+    steps:
+      - id: plan
+        type: agent_task
+        agent: worker
+        prompt: plan
+        outputs: [summary]
+        output_files:
+          summary: out/plan.txt
+      - id: review
+        type: agent_review
+        agent: worker
+        prompt: review
+        outputs: [decision, summary]
+        output_files:
+          decision: out/decision.txt
+          summary: out/review.txt
+        on_approve: done
+        on_reject: plan
+      - id: done
+        type: agent_task
+        agent: helper
+        prompt: done
+        outputs: [summary]
+        output_files:
+          summary: out/done.txt
+"#,
+    )
+    .expect("orchestrator")
+}
+
+#[test]
+fn provider_sessions_module_resumes_the_agents_own_session_within_a_run() {
+    let dir = tempdir().expect("tempdir");
+    let args_log = dir.path().join("claude-args.txt");
+    let claude = dir.path().join("claude-mock");
+    write_script(
+        &claude,
+        &format!(
+            r#"#!/bin/sh
+echo "$*" >> {}
+echo '{{"type":"result","session_id":"sess-1","result":"[workflow_result]{{\"decision\":\"approve\",\"summary\":\"ok\"}}[/workflow_result]"}}'
+"#,
+            args_log.display()
+        ),
+    );
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    store.create_run("run-sessions", "wf", 1).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator()).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", claude.display().to_string())
+            .with("openai", "unused")
+            .with("gemini", "unused"),
+    );
+
+    let run = engine.start("run-sessions", 2).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let invocations = fs::read_to_string(&args_log).expect("args log");
+    let invocations = invocations.lines().collect::<Vec<_>>();
+    assert_eq!(invocations.len(), 3);
+    assert!(!invocations[0].contains("--resume"), "{}", invocations[0]);
+    assert!(
+        invocations[1].contains("--resume sess-1"),
+        "{}",
+        invocations[1]
+    );
+    assert!(!invocations[2].contains("--resume"), "{}", invocations[2]);
+    assert!(invocations.iter().all(|line| !line.contains(" -c ")));
+
+    let sessions = store
+        .load_provider_sessions("run-sessions")
+        .expect("sessions");
+    assert_eq!(
+        sessions.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["helper", "worker"]
+    );
+    assert_eq!(sessions["worker"].session_id, "sess-1");
+    assert_eq!(sessions["worker"].provider, "anthropic");
+
+    let invocation: Value = serde_json::from_str(
+        &fs::read_to_string(
+            store
+                .step_attempt_dir("run-sessions", "review", 1)
+                .join("provider_invocation.json"),
+        )
+        .expect("invocation log"),
+    )
+    .expect("json");
+    assert_eq!(invocation["sessionId"], "sess-1");
+}
+
+#[test]
+fn provider_sessions_module_scopes_sessions_to_run_agent_and_provider() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    assert_eq!(
+        store
+            .provider_session("run-1", "worker", "anthropic")
+            .expect("no sessions"),
+        None
+    );
+
+    store
+        .record_provider_session("run-1", "worker", "anthropic", "sess-a", 10)
+        .expect("record");
+    store
+        .record_provider_session("run-1", "worker", "anthropic", "sess-b", 11)
+        .expect("replace");
+    assert_eq!(
+        store
+            .provider_session("run-1", "worker", "anthropic")
+            .expect("session"),
+        Some("sess-b".to_string())
+    );
+    assert_eq!(
+        store
+            .provider_session("run-1", "worker", "openai")
+            .expect("other provider"),
+        None
+    );
+    assert_eq!(
+        store
+            .provider_session("run-2", "worker", "anthropic")
+            .expect("other run"),
+        None
+    );
+}
+
+#[test]
+fn provider_sessions_module_keys_parallel_branch_sessions_by_branch() {
+    let dir = tempdir().expect("tempdir");
+    let args_log = dir.path().join("claude-args.txt");
+    let claude = dir.path().join("claude-mock");
+    write_script(
+        &claude,
+        &format!(
+            r#"#!/bin/sh
+echo "$*" >> {}
+echo '{{"type":"result","session_id":"sess-'$$'","result":"[workflow_result]{{\"summary\":\"ok\"}}[/workflow_result]"}}'
+"#,
+            args_log.display()
+        ),
+    );
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: eng
+selector_agent: router
+default_workflow: fan
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: fan
+    version: 1
+    steps:
+      - id: checks
+        type: parallel
+        branches: [left, right]
+        outputs: [summary]
+        output_files:
+          summary: out/checks.txt
+        next: done
+      - id: left
+        type: agent_task
+        agent: worker
+        prompt: left
+        outputs: [summary]
+        output_files:
+          summary: out/left.txt
+      - id: right
+        type: agent_task
+        agent: worker
+        prompt: right
+        outputs: [summary]
+        output_files:
+          summary: out/right.txt
+      - id: done
+        type: agent_task
+        agent: worker
+        prompt: done
+        outputs: [summary]
+        output_files:
+          summary: out/done.txt
+"#,
+    )
+    .expect("orchestrator");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    store.create_run("run-branches", "fan", 1).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default().with("anthropic", claude.display().to_string()),
+    );
+
+    let run = engine.start("run-branches", 2).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let invocations = fs::read_to_string(&args_log).expect("args log");
+    assert_eq!(invocations.lines().count(), 3);
+    assert!(invocations.lines().all(|line| !line.contains("--resume")));
+
+    let sessions = store
+        .load_provider_sessions("run-branches")
+        .expect("sessions");
+    assert_eq!(
+        sessions.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["worker", "worker@left", "worker@right"]
+    );
+    assert_ne!(
+        sessions["worker@left"].session_id,
+        sessions["worker@right"].session_id
+    );
+}
@@ -0,0 +1,166 @@
+use direclaw::config::{OrchestratorConfig, RetryErrorClass};
+use direclaw::orchestration::error::OrchestratorError;
+use direclaw::orchestration::retry_policy::{classify_step_error, resolve_step_retry_policy};
+use std::collections::BTreeMap;
+
+fn orchestrator(orchestration: &str, step_extra: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: engineering
+selector_agent: router
+default_workflow: wf
+selection_max_retries: 1
+{orchestration}
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: build
+        type: command
+        command: [make]
+        outputs: [summary]
+        output_files:
+          summary: out/summary.txt
+{step_extra}
+"#
+    ))
+    .expect("orchestrator")
+}
+
+#[test]
+fn retry_policy_module_merges_step_block_over_orchestrator_block() {
+    let config = orchestrator(
+        r#"workflow_orchestration:
+  retry:
+    initial_backoff_seconds: 10
+    max_backoff_seconds: 60
+    jitter_percent: 20
+    max_retries:
+      rate_limit: 5
+      timeout: 1
+    non_retryable: [timeout]"#,
+        r#"        limits:
+          max_retries: 1
+        retry:
+          backoff_multiplier: 3
+          max_retries:
+            timeout: 4
+          non_retryable: [output_contract]"#,
+    );
+    config.validate_setup_invariants().expect("valid");
+    let policy = resolve_step_retry_policy(&config, &config.workflows[0].steps[0]);
+
+    assert_eq!(policy.initial_backoff_seconds, 10);
+    assert_eq!(policy.max_backoff_seconds, 60);
+    assert_eq!(policy.backoff_multiplier, 3);
+    assert_eq!(policy.jitter_percent, 20);
+    assert_eq!(policy.budget(RetryErrorClass::RateLimit), 5);
+    assert_eq!(policy.budget(RetryErrorClass::Timeout), 4);
+    assert_eq!(policy.budget(RetryErrorClass::NonZeroExit), 1);
+    assert_eq!(policy.budget(RetryErrorClass::OutputContract), 0);
+    assert_eq!(policy.retry_ceiling(), 5);
+
+    assert!(policy.allows_retry(RetryErrorClass::RateLimit, 4, 5));
+    assert!(!policy.allows_retry(RetryErrorClass::RateLimit, 5, 6));
+    assert!(!policy.allows_retry(RetryErrorClass::NonZeroExit, 1, 2));
+    assert!(!policy.allows_retry(RetryErrorClass::OutputContract, 0, 1));
+
+    assert_eq!(policy.backoff_seconds(1), 10);
+    assert_eq!(policy.backoff_seconds(2), 30);
+    assert_eq!(policy.backoff_seconds(3), 60);
+    assert_eq!(policy.backoff_seconds(9), 60);
+    for _ in 0..20 {
+        let jittered = policy.jittered_backoff_seconds(2);
+        assert!((24..=30).contains(&jittered), "{jittered}");
+    }
+}
+
+#[test]
+fn retry_policy_module_defaults_keep_immediate_retries_under_step_limits() {
+    let config = orchestrator("", "");
+    let policy = resolve_step_retry_policy(&config, &config.workflows[0].steps[0]);
+    assert_eq!(policy.backoff_seconds(1), 0);
+    assert_eq!(policy.jittered_backoff_seconds(3), 0);
+    assert_eq!(policy.retry_ceiling(), 2);
+    for class in RetryErrorClass::ALL {
+        assert_eq!(policy.budget(class), 2);
+    }
+}
+
+#[test]
+fn retry_policy_module_classifies_step_errors() {
+    let exit = |rate_limited| OrchestratorError::StepNonZeroExit {
+        step_id: "build".to_string(),
+        reason: "exit 1".to_string(),
+        rate_limited,
+    };
+    assert_eq!(
+        classify_step_error(&exit(false)),
+        Some(RetryErrorClass::NonZeroExit)
+    );
+    assert_eq!(
+        classify_step_error(&exit(true)),
+        Some(RetryErrorClass::RateLimit)
+    );
+    assert_eq!(
+        classify_step_error(&OrchestratorError::StepTimeout {
+            step_timeout_seconds: 5
+        }),
+        Some(RetryErrorClass::Timeout)
+    );
+    assert_eq!(
+        classify_step_error(&OrchestratorError::OutputSchemaValidation {
+            step_id: "build".to_string(),
+            reason: "bad".to_string(),
+            violations: BTreeMap::new(),
+        }),
+        Some(RetryErrorClass::OutputContract)
+    );
+    assert_eq!(
+        classify_step_error(&OrchestratorError::StepExecution {
+            step_id: "build".to_string(),
+            reason: "missing binary".to_string(),
+        }),
+        Some(RetryErrorClass::Execution)
+    );
+    assert_eq!(
+        classify_step_error(&OrchestratorError::StepCanceled {
+            step_id: "build".to_string(),
+            attempt: 1,
+        }),
+        None
+    );
+}
+
+#[test]
+fn retry_policy_module_rejects_invalid_retry_blocks() {
+    let err = orchestrator(
+        "workflow_orchestration:\n  retry:\n    jitter_percent: 150",
+        "",
+    )
+    .validate_setup_invariants()
+    .expect_err("jitter above 100");
+    assert!(err
+        .to_string()
+        .contains("`workflow_orchestration` `retry.jitter_percent` must be <= 100"));
+
+    let err = orchestrator(
+        "",
+        "        retry:\n          initial_backoff_seconds: 90\n          max_backoff_seconds: 30",
+    )
+    .validate_setup_invariants()
+    .expect_err("initial above max");
+    assert!(err.to_string().contains(
+        "workflow `wf` step `build` `retry.initial_backoff_seconds` must be <= `retry.max_backoff_seconds`"
+    ));
+
+    let parsed = serde_yaml::from_str::<OrchestratorConfig>(
+        "id: x\nselector_agent: a\ndefault_workflow: wf\nselection_max_retries: 1\nagents: {}\nworkflows: []\nworkflow_orchestration:\n  retry:\n    max_retries:\n      flaky: 1\n",
+    );
+    assert!(parsed.is_err());
+}
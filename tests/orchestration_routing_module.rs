@@ -260,10 +260,9 @@ channels: {{}}
         &claude_mock,
         "#!/bin/sh\necho '[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]'\n",
     );
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let action = process_queued_message_with_runner_binaries(
         temp.path(),
@@ -352,10 +351,9 @@ channels: {{}}
         &claude_mock,
         "#!/bin/sh\necho '[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]'\n",
     );
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let action = process_queued_message_with_runner_binaries(
         temp.path(),
@@ -620,10 +618,9 @@ channels: {{}}
         &claude_mock,
         "#!/bin/sh\necho '[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]'\n",
     );
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let action = process_queued_message_with_runner_binaries(
         temp.path(),
@@ -723,10 +720,9 @@ channels: {{}}
         &claude_mock,
         "#!/bin/sh\necho '[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]'\n",
     );
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let inbound = IncomingMessage {
         channel: "local".to_string(),
@@ -895,10 +891,9 @@ channels: {{}}
         workflow_step_id: None,
     };
 
-    let binaries = RunnerBinaries {
-        anthropic: "/path/that/does/not/exist-anthropic".to_string(),
-        openai: "/path/that/does/not/exist-openai".to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", "/path/that/does/not/exist-anthropic")
+        .with("openai", "/path/that/does/not/exist-openai");
 
     let started = Instant::now();
     let action = process_queued_message_with_runner_binaries(
@@ -1007,10 +1002,9 @@ channels: {{}}
         &claude_mock,
         "#!/bin/sh\necho '[workflow_result]{\"status\":\"complete\",\"summary\":\"ok\",\"artifact\":\"ok\"}[/workflow_result]'\n",
     );
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let inbound = IncomingMessage {
         channel: "slack".to_string(),
@@ -1218,10 +1212,9 @@ memory:
     );
     let claude_mock = temp.path().join("claude-mock");
     write_script(&claude_mock, "#!/bin/sh\necho 'ok'\n");
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let inbound = IncomingMessage {
         channel: "local".to_string(),
@@ -1349,10 +1342,9 @@ memory:
     );
     let claude_mock = temp.path().join("claude-mock");
     write_script(&claude_mock, "#!/bin/sh\necho 'ok'\n");
-    let binaries = RunnerBinaries {
-        anthropic: claude_mock.display().to_string(),
-        openai: codex_mock.display().to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude_mock.display().to_string())
+        .with("openai", codex_mock.display().to_string());
 
     let inbound = IncomingMessage {
         channel: "local".to_string(),
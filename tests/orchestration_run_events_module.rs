@@ -0,0 +1,310 @@
+use direclaw::config::OrchestratorConfig;
+use direclaw::orchestration::run_events::{progress_from_events, RunEvent, RunEventKind};
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::simulation::{simulate_workflow, SimulationScript};
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::Write;
+use tempfile::tempdir;
+
+fn sample_orchestrator() -> OrchestratorConfig {
+    serde_yaml::from_str(
+        r#"
+id: eng
+selector_agent: selector
+default_workflow: fix_issue
+selection_max_retries: 1
+agents:
+  selector:
+    provider: openai
+    model: gpt-4.1
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: fix_issue
+    version: 1
+    description: fix issue flow
+    tags: [fix, issue]
+    steps:
+      - id: plan
+        type: agent_task
+        agent: worker
+        prompt: "Plan the fix for {{inputs.ticket}}"
+        outputs: [plan]
+        output_files:
+          plan: out/plan.md
+        limits:
+          max_retries: 2
+      - id: review
+        type: agent_review
+        agent: worker
+        prompt: review the plan
+        outputs: [decision, summary]
+        output_files:
+          decision: out/decision.md
+          summary: out/summary.md
+        on_approve: done
+        on_reject: plan
+      - id: done
+        type: agent_task
+        agent: worker
+        prompt: wrap up
+        outputs: [summary]
+        output_files:
+          summary: out/done.md
+"#,
+    )
+    .expect("orchestrator")
+}
+
+fn simulate(state_root: &std::path::Path, script: &str) -> (WorkflowRunStore, String) {
+    let inputs = Map::from_iter([("ticket".to_string(), Value::String("BUG-7".to_string()))]);
+    let report = simulate_workflow(
+        &sample_orchestrator(),
+        "fix_issue",
+        inputs,
+        serde_yaml::from_str::<SimulationScript>(script).expect("script"),
+        state_root,
+        None,
+        100,
+    )
+    .expect("simulate");
+    (WorkflowRunStore::new(state_root), report.run_id)
+}
+
+fn event_types(events: &[RunEvent]) -> Vec<String> {
+    events
+        .iter()
+        .map(|event| {
+            serde_json::to_value(event).expect("event")["type"]
+                .as_str()
+                .expect("type")
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn run_events_module_journals_a_run_in_sequence_order() {
+    let dir = tempdir().expect("tempdir");
+    let (store, run_id) = simulate(
+        dir.path(),
+        r#"
+steps:
+  plan:
+    - exit_code: 1
+      stderr: boom
+    - outputs: {plan: first draft}
+  review:
+    - outputs: {decision: approve, summary: ok}
+  done:
+    - outputs: {summary: shipped}
+"#,
+    );
+
+    let events = store.load_run_events(&run_id, 0).expect("events");
+    assert_eq!(
+        events.iter().map(|event| event.seq).collect::<Vec<_>>(),
+        (1..=events.len() as u64).collect::<Vec<_>>()
+    );
+    assert!(events.iter().all(|event| event.run_id == run_id));
+    assert_eq!(
+        event_types(&events[..7]),
+        vec![
+            "run_created",
+            "state_changed",
+            "step_started",
+            "provider_invoked",
+            "step_failed",
+            "retry_scheduled",
+            "step_started",
+        ]
+    );
+    assert!(matches!(
+        &events[5].kind,
+        RunEventKind::RetryScheduled { step_id, attempt: 2, .. } if step_id == "plan"
+    ));
+    assert!(events.iter().any(|event| matches!(
+        &event.kind,
+        RunEventKind::OutputValidated { step_id, valid: true, .. } if step_id == "review"
+    )));
+    assert!(events.iter().any(|event| matches!(
+        &event.kind,
+        RunEventKind::TransitionChosen { step_id, next_step_id: Some(next), .. }
+            if step_id == "review" && next == "done"
+    )));
+    assert!(matches!(
+        &events.last().expect("last").kind,
+        RunEventKind::StateChanged {
+            from: RunState::Running,
+            to: RunState::Succeeded,
+            ..
+        }
+    ));
+
+    let tail = store
+        .load_run_events(&run_id, events.len() as u64 - 2)
+        .expect("tail");
+    assert_eq!(tail.len(), 2);
+    assert_eq!(&tail[..], &events[events.len() - 2..]);
+}
+
+#[test]
+fn run_events_module_records_output_validation_errors() {
+    let dir = tempdir().expect("tempdir");
+    let (store, run_id) = simulate(
+        dir.path(),
+        r#"
+steps:
+  plan:
+    - outputs: {summary: forgot the plan}
+"#,
+    );
+
+    let events = store.load_run_events(&run_id, 0).expect("events");
+    let errors = events
+        .iter()
+        .find_map(|event| match &event.kind {
+            RunEventKind::OutputValidated {
+                valid: false,
+                errors,
+                ..
+            } => Some(errors.clone()),
+            _ => None,
+        })
+        .expect("invalid output event");
+    assert!(errors.contains_key("plan"), "{errors:?}");
+    assert!(events
+        .iter()
+        .all(|event| !matches!(event.kind, RunEventKind::TransitionChosen { .. })));
+}
+
+#[test]
+fn run_events_module_derives_the_progress_snapshot() {
+    let dir = tempdir().expect("tempdir");
+    let (store, run_id) = simulate(
+        dir.path(),
+        r#"
+steps:
+  plan:
+    - outputs: {plan: first draft}
+  review:
+    - outputs: {decision: approve, summary: ok}
+  done:
+    - outputs: {summary: shipped}
+"#,
+    );
+
+    let events = store.load_run_events(&run_id, 0).expect("events");
+    let derived = progress_from_events(&events).expect("derived progress");
+    let persisted = store.load_progress(&run_id).expect("progress");
+    assert_eq!(derived, persisted);
+    assert_eq!(progress_from_events(&events[1..]), None);
+}
+
+#[test]
+fn run_events_module_skips_partial_lines_and_missing_journals() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    assert!(store
+        .load_run_events("run-missing", 0)
+        .expect("no journal")
+        .is_empty());
+
+    let first = store
+        .append_run_event(
+            "run-1",
+            10,
+            RunEventKind::StepStarted {
+                step_id: "plan".to_string(),
+                attempt: 1,
+            },
+        )
+        .expect("append");
+    assert_eq!(first.seq, 1);
+    let line = fs::read_to_string(store.run_events_path("run-1")).expect("journal");
+    assert_eq!(
+        serde_json::from_str::<Value>(&line).expect("json"),
+        serde_json::json!({
+            "seq": 1,
+            "at": 10,
+            "runId": "run-1",
+            "type": "step_started",
+            "stepId": "plan",
+            "attempt": 1
+        })
+    );
+
+    fs::OpenOptions::new()
+        .append(true)
+        .open(store.run_events_path("run-1"))
+        .and_then(|mut file| file.write_all(br#"{"seq":2,"at":11,"#))
+        .expect("partial write");
+    assert_eq!(store.load_run_events("run-1", 0).expect("events").len(), 1);
+}
+
+#[test]
+fn run_events_module_truncates_a_torn_line_before_appending() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    let started = |attempt| RunEventKind::StepStarted {
+        step_id: "plan".to_string(),
+        attempt,
+    };
+    store
+        .append_run_event("run-1", 10, started(1))
+        .expect("append");
+    fs::OpenOptions::new()
+        .append(true)
+        .open(store.run_events_path("run-1"))
+        .and_then(|mut file| file.write_all(br#"{"seq":2,"at":11,"#))
+        .expect("partial write");
+
+    let next = store
+        .append_run_event("run-1", 12, started(2))
+        .expect("append");
+    assert_eq!(next.seq, 2);
+    let events = store.load_run_events("run-1", 0).expect("events");
+    assert_eq!(
+        events.iter().map(|event| event.seq).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn run_events_module_numbers_concurrent_appenders_uniquely() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path().to_path_buf();
+    let appenders = (0..4)
+        .map(|appender| {
+            // Each appender opens its own store, as separate processes do.
+            let store = WorkflowRunStore::new(&root);
+            std::thread::spawn(move || {
+                for attempt in 0..25 {
+                    store
+                        .append_run_event(
+                            "run-shared",
+                            appender,
+                            RunEventKind::StepStarted {
+                                step_id: format!("step-{appender}"),
+                                attempt,
+                            },
+                        )
+                        .expect("append");
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for appender in appenders {
+        appender.join().expect("appender");
+    }
+
+    let events = WorkflowRunStore::new(&root)
+        .load_run_events("run-shared", 0)
+        .expect("events");
+    assert_eq!(
+        events.iter().map(|event| event.seq).collect::<Vec<_>>(),
+        (1..=100).collect::<Vec<_>>()
+    );
+}
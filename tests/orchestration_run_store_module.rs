@@ -1,4 +1,6 @@
+use direclaw::config::WorkflowConfig;
 use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use serde_json::{json, Map};
 use tempfile::tempdir;
 
 #[test]
@@ -97,3 +99,70 @@ fn run_store_module_resolves_latest_run_by_conversation() {
         .expect("terminal run");
     assert_eq!(terminal_only.run_id, "run-terminal");
 }
+
+#[test]
+fn run_store_module_validates_declared_workflow_inputs_at_creation() {
+    let temp = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(temp.path());
+    let workflow: WorkflowConfig = serde_yaml::from_str(
+        r#"
+id: triage
+version: 1
+inputs:
+  - key: ticket
+    required: true
+  - key: retries
+    type: integer
+    default: 2
+  - key: dry_run
+    type: boolean
+  - key: labels
+    type: array
+  - key: note
+"#,
+    )
+    .expect("parse workflow");
+
+    let err = store
+        .create_run_with_inputs("run-missing", &workflow, Map::new(), 10)
+        .expect_err("missing required input");
+    assert!(err.to_string().contains(
+        "workflow `triage` inputs are invalid: missing required workflow input `ticket`"
+    ));
+    assert!(!store.run_exists("run-missing"));
+
+    let err = store
+        .create_run_with_inputs(
+            "run-bad",
+            &workflow,
+            Map::from_iter([
+                ("ticket".to_string(), json!("ENG-1")),
+                ("retries".to_string(), json!("many")),
+            ]),
+            10,
+        )
+        .expect_err("non-integer input");
+    assert!(err
+        .to_string()
+        .contains("workflow input `retries` must be an integer"));
+
+    let run = store
+        .create_run_with_inputs(
+            "run-ok",
+            &workflow,
+            Map::from_iter([
+                ("ticket".to_string(), json!("ENG-1")),
+                ("dry_run".to_string(), json!("true")),
+                ("labels".to_string(), json!("bug, urgent")),
+                ("extra".to_string(), json!("kept")),
+            ]),
+            10,
+        )
+        .expect("create run");
+    assert_eq!(run.inputs.get("ticket"), Some(&json!("ENG-1")));
+    assert_eq!(run.inputs.get("retries"), Some(&json!(2)));
+    assert_eq!(run.inputs.get("dry_run"), Some(&json!(true)));
+    assert_eq!(run.inputs.get("labels"), Some(&json!(["bug", "urgent"])));
+    assert_eq!(run.inputs.get("extra"), Some(&json!("kept")));
+    assert!(run.inputs.get("note").is_none());
+}
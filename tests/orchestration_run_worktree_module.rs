@@ -0,0 +1,317 @@
+use direclaw::config::{OrchestratorConfig, Settings};
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::run_worktree::{
+    cancel_run_releasing_worktrees, load_run_worktree, remove_run_worktree, run_worktree_branch,
+};
+use direclaw::orchestration::workflow_engine::WorkflowEngine;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .expect("run git");
+    assert!(
+        output.status.success(),
+        "git {args:?}: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn init_repository(path: &Path) -> String {
+    fs::create_dir_all(path).expect("repo dir");
+    git(path, &["init", "--quiet"]);
+    fs::write(path.join("README.md"), "app\n").expect("readme");
+    git(path, &["add", "-A"]);
+    git(
+        path,
+        &[
+            "-c",
+            "user.name=test",
+            "-c",
+            "user.email=test@localhost",
+            "commit",
+            "--quiet",
+            "-m",
+            "initial",
+        ],
+    );
+    git(path, &["rev-parse", "HEAD"])
+}
+
+fn worktree_orchestrator(repository: &Path, marker: &Path) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: main
+selector_agent: router
+default_workflow: code
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: code
+    version: 1
+    repository: {repository}
+    steps:
+      - id: implement
+        type: command
+        workspace_mode: run_workspace
+        command: [sh, -c, "echo change >> feature.txt"]
+        outputs: [summary]
+        output_files:
+          summary: out/implement-summary.txt
+        next: review
+      - id: review
+        type: command
+        workspace_mode: run_workspace
+        command: [sh, -c, "echo scratch > scratch.txt; test -f {marker} || {{ touch {marker}; exit 1; }}"]
+        outputs: [summary, exit_code]
+        output_files:
+          summary: out/review-summary.txt
+          exit_code: out/review-exit-code.txt
+        on_approve: done
+        on_reject: implement
+      - id: done
+        type: command
+        workspace_mode: run_workspace
+        command: [sh, -c, "test -f feature.txt"]
+        outputs: [summary]
+        output_files:
+          summary: out/done-summary.txt
+"#,
+        repository = repository.display(),
+        marker = marker.display(),
+    ))
+    .expect("parse orchestrator")
+}
+
+#[test]
+fn run_worktree_module_commits_steps_resets_rejects_and_cleans_up() {
+    let dir = tempdir().expect("tempdir");
+    let repository = dir.path().join("repo");
+    let base_commit = init_repository(&repository);
+    let state_root = dir.path().join(".direclaw");
+    let store = WorkflowRunStore::new(&state_root);
+    let orchestrator = worktree_orchestrator(&repository, &dir.path().join("approve-next"));
+    store.create_run("run-wt", "code", 10).expect("create run");
+
+    let run = WorkflowEngine::new(store.clone(), orchestrator)
+        .start("run-wt", 11)
+        .expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let branch = run_worktree_branch("run-wt");
+    assert_eq!(branch, "direclaw/run-wt");
+    // The rejected first implementation was reset, so only one change landed.
+    assert_eq!(
+        git(&repository, &["show", &format!("{branch}:feature.txt")]),
+        "change"
+    );
+    let subjects = git(&repository, &["log", "--format=%s", &branch]);
+    assert!(subjects.contains("review attempt"), "{subjects}");
+    assert_eq!(subjects.lines().last(), Some("initial"));
+    assert_eq!(git(&repository, &["rev-parse", "HEAD"]), base_commit);
+    assert_eq!(git(&repository, &["status", "--porcelain"]), "");
+
+    let worktree = load_run_worktree(&store, "run-wt")
+        .expect("load worktree")
+        .expect("worktree record");
+    assert!(worktree.removed);
+    assert_eq!(worktree.base_commit, base_commit);
+    assert_ne!(worktree.approved_commit, base_commit);
+    assert!(!state_root.join("work/runs/run-wt").exists());
+    assert!(!git(&repository, &["worktree", "list"]).contains("run-wt"));
+}
+
+#[test]
+fn run_worktree_module_gives_concurrent_runs_separate_checkouts() {
+    let dir = tempdir().expect("tempdir");
+    let repository = dir.path().join("repo");
+    init_repository(&repository);
+    let state_root = dir.path().join(".direclaw");
+    let store = WorkflowRunStore::new(&state_root);
+    let marker = dir.path().join("approve-next");
+    fs::write(&marker, "").expect("marker");
+    let mut orchestrator = worktree_orchestrator(&repository, &marker);
+    orchestrator.workflows[0].steps[2].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "test \"$(cat feature.txt)\" = change".to_string(),
+    ];
+    let engine = WorkflowEngine::new(store.clone(), orchestrator);
+
+    store.create_run("run-a", "code", 10).expect("create run a");
+    store.create_run("run-b", "code", 10).expect("create run b");
+    std::thread::scope(|scope| {
+        let a = scope.spawn(|| engine.start("run-a", 11));
+        let b = scope.spawn(|| engine.start("run-b", 11));
+        assert_eq!(
+            a.join().expect("join a").expect("run a").state,
+            RunState::Succeeded
+        );
+        assert_eq!(
+            b.join().expect("join b").expect("run b").state,
+            RunState::Succeeded
+        );
+    });
+    for run_id in ["run-a", "run-b"] {
+        assert_eq!(
+            git(
+                &repository,
+                &[
+                    "show",
+                    &format!("{}:feature.txt", run_worktree_branch(run_id))
+                ]
+            ),
+            "change"
+        );
+    }
+}
+
+#[test]
+fn run_worktree_module_keeps_failed_run_worktrees_until_cleanup() {
+    let dir = tempdir().expect("tempdir");
+    let repository = dir.path().join("repo");
+    init_repository(&repository);
+    let state_root = dir.path().join(".direclaw");
+    let store = WorkflowRunStore::new(&state_root);
+    let marker = dir.path().join("approve-next");
+    fs::write(&marker, "").expect("marker");
+    let mut orchestrator = worktree_orchestrator(&repository, &marker);
+    orchestrator.workflows[0].steps[2].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "echo wip > wip.txt; exit 3".to_string(),
+    ];
+    store.create_run("run-f", "code", 10).expect("create run");
+
+    WorkflowEngine::new(store.clone(), orchestrator)
+        .start("run-f", 11)
+        .expect_err("final step fails");
+    assert_eq!(
+        store.load_run("run-f").expect("run").state,
+        RunState::Failed
+    );
+    let worktree = load_run_worktree(&store, "run-f")
+        .expect("load worktree")
+        .expect("worktree record");
+    assert!(!worktree.removed);
+    assert_eq!(
+        fs::read_to_string(worktree.path.join("wip.txt")).expect("uncommitted work"),
+        "wip\n"
+    );
+
+    remove_run_worktree(&store, "run-f").expect("cleanup");
+    assert!(!worktree.path.exists());
+    assert!(
+        load_run_worktree(&store, "run-f")
+            .expect("load worktree")
+            .expect("worktree record")
+            .removed
+    );
+}
+
+#[test]
+fn run_worktree_module_releases_parked_runs_on_cancel() {
+    let dir = tempdir().expect("tempdir");
+    let repository = dir.path().join("repo");
+    init_repository(&repository);
+    let state_root = dir.path().join(".direclaw");
+    let store = WorkflowRunStore::new(&state_root);
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(&format!(
+        r#"
+id: main
+selector_agent: router
+default_workflow: code
+selection_max_retries: 1
+agents:
+  router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: code
+    version: 1
+    repository: {repository}
+    steps:
+      - id: gate
+        type: human_review
+        prompt: approve?
+        outputs: [decision]
+        output_files:
+          decision: out/decision.txt
+        on_approve: gate
+        on_reject: gate
+"#,
+        repository = repository.display(),
+    ))
+    .expect("parse orchestrator");
+    store.create_run("run-c", "code", 10).expect("create run");
+
+    let mut run = WorkflowEngine::new(store.clone(), orchestrator)
+        .start("run-c", 11)
+        .expect("start");
+    assert_eq!(run.state, RunState::Waiting);
+    let worktree = load_run_worktree(&store, "run-c")
+        .expect("load worktree")
+        .expect("worktree record");
+    assert!(worktree.path.is_dir());
+
+    cancel_run_releasing_worktrees(&store, &mut run, 12, "canceled by test").expect("cancel");
+    assert_eq!(run.state, RunState::Canceled);
+    assert!(!worktree.path.exists());
+}
+
+#[test]
+fn run_worktree_module_validation_requires_repository_inside_granted_workspaces() {
+    let dir = tempdir().expect("tempdir");
+    let private = dir.path().join("workspaces/main");
+    let shared = dir.path().join("shared/code");
+    let settings: Settings = serde_yaml::from_str(&format!(
+        r#"
+workspaces_path: {workspaces}
+shared_workspaces:
+  code:
+    path: {shared}
+    description: source checkouts
+orchestrators:
+  main:
+    private_workspace: {private}
+    shared_access: [code]
+channel_profiles: {{}}
+monitoring: {{}}
+channels: {{}}
+"#,
+        workspaces = dir.path().join("workspaces").display(),
+        shared = shared.display(),
+        private = private.display(),
+    ))
+    .expect("settings");
+
+    let valid = |repository: &Path| {
+        let mut orchestrator = worktree_orchestrator(repository, &dir.path().join("marker"));
+        orchestrator.workflows[0].description = "code changes".to_string();
+        orchestrator.workflows[0].tags = vec!["code".parse().expect("tag")];
+        orchestrator.validate(&settings, "main")
+    };
+    valid(&shared.join("app")).expect("granted shared workspace");
+    valid(Path::new("shared/code/app")).expect("relative to private workspace");
+    let err = valid(&dir.path().join("elsewhere/app")).expect_err("ungranted path");
+    assert!(
+        err.to_string().contains(
+            "must be inside the orchestrator private workspace or a granted shared workspace"
+        ),
+        "{err}"
+    );
+    let err = valid(Path::new("shared/../../outside")).expect_err("parent traversal");
+    assert!(err.to_string().contains("must not contain `..`"), "{err}");
+}
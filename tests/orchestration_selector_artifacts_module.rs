@@ -17,6 +17,7 @@ fn sample_request() -> SelectorRequest {
         default_workflow: "default".to_string(),
         available_functions: vec!["workflow.status".to_string()],
         available_function_schemas: Vec::new(),
+        available_workflow_inputs: Default::default(),
     }
 }
 
@@ -38,6 +38,7 @@ fn sample_request() -> SelectorRequest {
             )]),
             read_only: true,
         }],
+        available_workflow_inputs: Default::default(),
     }
 }
 
@@ -273,10 +274,9 @@ workflows:
         &sample_request(),
         &orchestrator,
         1,
-        &RunnerBinaries {
-            anthropic: selector_bin.display().to_string(),
-            openai: selector_bin.display().to_string(),
-        },
+        &RunnerBinaries::default()
+            .with("anthropic", selector_bin.display().to_string())
+            .with("openai", selector_bin.display().to_string()),
     )
     .expect("selector result");
 
@@ -327,3 +327,160 @@ channels: {}
     let orchestrator_id = resolve_orchestrator_id(&settings, &inbound).expect("resolved");
     assert_eq!(orchestrator_id, "orch");
 }
+
+#[test]
+fn selector_module_validates_typed_workflow_inputs() {
+    let mut request = sample_request();
+    let inputs: direclaw::config::WorkflowInputs = serde_yaml::from_str(
+        r#"
+- key: ticket
+  type: string
+  required: true
+- key: priority
+  type: enum
+  values: [low, high]
+"#,
+    )
+    .expect("parse inputs");
+    request
+        .available_workflow_inputs
+        .insert("default".to_string(), inputs);
+
+    let valid = r#"{
+      "selectorId":"sel-1",
+      "status":"selected",
+      "action":"workflow_start",
+      "selectedWorkflow":"default",
+      "workflowInputs":{"ticket":"ENG-1","priority":"high"}
+    }"#;
+    let parsed = parse_and_validate_selector_result(valid, &request).expect("valid selector");
+    assert_eq!(
+        parsed
+            .workflow_inputs
+            .as_ref()
+            .and_then(|inputs| inputs.get("ticket")),
+        Some(&Value::String("ENG-1".to_string()))
+    );
+
+    let missing = r#"{
+      "selectorId":"sel-1",
+      "status":"selected",
+      "action":"workflow_start",
+      "selectedWorkflow":"default",
+      "workflowInputs":{"priority":"high"}
+    }"#;
+    let err = parse_and_validate_selector_result(missing, &request).expect_err("must fail");
+    assert!(err
+        .to_string()
+        .contains("missing required workflow input `ticket`"));
+
+    let unknown = r#"{
+      "selectorId":"sel-1",
+      "status":"selected",
+      "action":"workflow_start",
+      "selectedWorkflow":"default",
+      "workflowInputs":{"ticket":"ENG-1","bogus":"x"}
+    }"#;
+    let err = parse_and_validate_selector_result(unknown, &request).expect_err("must fail");
+    assert!(err
+        .to_string()
+        .contains("unknown input `bogus` for workflow `default`"));
+
+    let bad_enum = r#"{
+      "selectorId":"sel-1",
+      "status":"selected",
+      "action":"workflow_start",
+      "selectedWorkflow":"default",
+      "workflowInputs":{"ticket":"ENG-1","priority":"urgent"}
+    }"#;
+    let err = parse_and_validate_selector_result(bad_enum, &request).expect_err("must fail");
+    assert!(err
+        .to_string()
+        .contains("workflow input `priority` must be one of: low, high"));
+}
+
+#[test]
+fn selector_module_resumes_the_selector_session_across_selections() {
+    let temp = tempdir().expect("tempdir");
+    let workspace = temp.path().join("workspace");
+    fs::create_dir_all(&workspace).expect("workspace");
+    let args_log = temp.path().join("selector-args.txt");
+
+    let selector_bin = temp.path().join("claude-mock");
+    write_script(
+        &selector_bin,
+        &format!(
+            r#"#!/bin/sh
+set -eu
+msg="$*"
+echo "$msg" >> {}
+result_path=$(printf "%s" "$msg" | sed -n 's/.*Write selector result JSON to: \([^ ]*\).*/\1/p')
+printf '{{"selectorId":"sel-1","status":"selected","action":"workflow_start","selectedWorkflow":"default"}}' > "$result_path"
+echo '{{"type":"result","session_id":"selector-sess","result":"ok"}}'
+"#,
+            args_log.display()
+        ),
+    );
+
+    let settings: direclaw::config::Settings = serde_yaml::from_str(&format!(
+        r#"
+workspaces_path: {workspaces}
+shared_workspaces: {{}}
+orchestrators:
+  eng:
+    private_workspace: {workspace}
+    shared_access: []
+channel_profiles: {{}}
+monitoring: {{}}
+channels: {{}}
+"#,
+        workspaces = temp.path().display(),
+        workspace = workspace.display()
+    ))
+    .expect("settings");
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: eng
+selector_agent: selector
+default_workflow: default
+selection_max_retries: 1
+agents:
+  selector:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: default
+    version: 1
+    steps: []
+"#,
+    )
+    .expect("orchestrator");
+    let binaries = RunnerBinaries::default().with("anthropic", selector_bin.display().to_string());
+
+    for attempt in 1..=2 {
+        run_selector_attempt_with_provider(
+            temp.path(),
+            &settings,
+            &sample_request(),
+            &orchestrator,
+            attempt,
+            &binaries,
+        )
+        .expect("selector result");
+    }
+
+    let invocations = fs::read_to_string(&args_log).expect("args log");
+    let invocations = invocations.lines().collect::<Vec<_>>();
+    assert_eq!(invocations.len(), 2);
+    assert!(!invocations[0].contains("--resume"), "{}", invocations[0]);
+    assert!(
+        invocations[1].contains("--resume selector-sess"),
+        "{}",
+        invocations[1]
+    );
+    assert!(temp
+        .path()
+        .join("orchestrator/selector/provider_sessions.json")
+        .is_file());
+}
@@ -0,0 +1,303 @@
+use direclaw::config::OrchestratorConfig;
+use direclaw::orchestration::error::OrchestratorError;
+use direclaw::orchestration::run_store::{RunState, WorkflowRunStore};
+use direclaw::orchestration::simulation::{simulate_workflow, SimulationScript, SIMULATION_RUN_ID};
+use serde_json::{Map, Value};
+use std::fs;
+use tempfile::tempdir;
+
+fn sample_orchestrator() -> OrchestratorConfig {
+    serde_yaml::from_str(
+        r#"
+id: eng
+selector_agent: selector
+default_workflow: fix_issue
+selection_max_retries: 1
+selector_timeout_seconds: 30
+agents:
+  selector:
+    provider: openai
+    model: gpt-4.1
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: fix_issue
+    version: 1
+    description: fix issue flow
+    tags: [fix, issue]
+    steps:
+      - id: plan
+        type: agent_task
+        agent: worker
+        prompt: "Plan the fix for {{inputs.ticket}}"
+        outputs: [plan]
+        output_files:
+          plan: out/plan.md
+      - id: review
+        type: agent_review
+        agent: worker
+        prompt: review the plan
+        outputs: [decision, summary]
+        output_files:
+          decision: out/decision.md
+          summary: out/summary.md
+        on_approve: done
+        on_reject: plan
+      - id: done
+        type: agent_task
+        agent: worker
+        prompt: wrap up
+        prompt_type: file_output
+        outputs: [summary]
+        output_files:
+          summary: out/done.md
+"#,
+    )
+    .expect("orchestrator")
+}
+
+fn script(yaml: &str) -> SimulationScript {
+    serde_yaml::from_str(yaml).expect("script")
+}
+
+fn ticket_inputs() -> Map<String, Value> {
+    Map::from_iter([("ticket".to_string(), Value::String("BUG-7".to_string()))])
+}
+
+#[test]
+fn simulation_module_follows_scripted_review_loop_and_captures_prompts() {
+    let dir = tempdir().expect("tempdir");
+    let report = simulate_workflow(
+        &sample_orchestrator(),
+        "fix_issue",
+        ticket_inputs(),
+        script(
+            r#"
+steps:
+  plan:
+    - outputs: {plan: first draft}
+  review:
+    - outputs: {decision: reject, summary: too vague}
+    - outputs: {decision: approve, summary: ok}
+  done:
+    - files: {summary: shipped}
+"#,
+        ),
+        &dir.path().join("state"),
+        None,
+        100,
+    )
+    .expect("simulate");
+
+    assert_eq!(report.state, RunState::Succeeded);
+    let path = report
+        .attempts
+        .iter()
+        .map(|attempt| (attempt.step_id.as_str(), attempt.next_step_id.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        path,
+        vec![
+            ("plan", Some("review")),
+            ("review", Some("plan")),
+            ("plan", Some("review")),
+            ("review", Some("done")),
+            ("done", None),
+        ]
+    );
+    let plan_prompt = report.attempts[0].prompt.as_deref().expect("plan prompt");
+    assert!(plan_prompt.contains("Plan the fix for BUG-7"));
+    assert!(report
+        .attempts
+        .iter()
+        .all(|attempt| attempt.error.is_none()));
+}
+
+#[test]
+fn simulation_module_reports_output_contract_violations() {
+    let dir = tempdir().expect("tempdir");
+    let report = simulate_workflow(
+        &sample_orchestrator(),
+        "fix_issue",
+        ticket_inputs(),
+        script(
+            r#"
+steps:
+  plan:
+    - outputs: {summary: forgot the plan}
+"#,
+        ),
+        &dir.path().join("state"),
+        None,
+        100,
+    )
+    .expect("simulate");
+
+    assert_eq!(report.state, RunState::Failed);
+    assert_eq!(report.attempts.len(), 3);
+    let first = &report.attempts[0];
+    assert_eq!(first.step_id, "plan");
+    assert!(first.next_step_id.is_none());
+    assert!(first.error.is_some());
+    assert!(first.output_validation_errors.contains_key("plan"));
+}
+
+#[test]
+fn simulation_module_rejects_invalid_scripts() {
+    let dir = tempdir().expect("tempdir");
+    let orchestrator = sample_orchestrator();
+    for (yaml, expected) in [
+        ("steps:\n  ghost:\n    - message: hi\n", "not declared"),
+        ("steps:\n  plan: []\n", "no responses"),
+        (
+            "steps:\n  plan:\n    - {message: hi, outputs: {plan: x}}\n",
+            "both `outputs` and `message`",
+        ),
+        (
+            "steps:\n  done:\n    - files: {notes: x}\n",
+            "undeclared output file `notes`",
+        ),
+        (
+            "steps:\n  plan:\n    - files: {plan: x}\n",
+            "does not use `prompt_type: file_output`",
+        ),
+    ] {
+        let err = simulate_workflow(
+            &orchestrator,
+            "fix_issue",
+            ticket_inputs(),
+            script(yaml),
+            &dir.path().join("state"),
+            None,
+            100,
+        )
+        .expect_err("invalid script");
+        assert!(
+            matches!(&err, OrchestratorError::SimulationScript(message) if message.contains(expected)),
+            "unexpected error for {yaml:?}: {err}"
+        );
+    }
+    assert!(!dir.path().join("state").exists());
+}
+
+#[test]
+fn simulation_module_loads_script_files() {
+    let dir = tempdir().expect("tempdir");
+    let path = dir.path().join("responses.yaml");
+    fs::write(
+        &path,
+        "steps:\n  plan:\n    - exit_code: 1\n      stderr: boom\n",
+    )
+    .expect("write");
+    let loaded = SimulationScript::load(&path).expect("load");
+    assert_eq!(loaded.steps["plan"][0].exit_code, 1);
+
+    fs::write(&path, "steps:\n  plan:\n    - reply: hi\n").expect("write");
+    assert!(matches!(
+        SimulationScript::load(&path),
+        Err(OrchestratorError::SimulationScript(_))
+    ));
+}
+
+#[test]
+fn simulation_module_stubs_command_steps_and_skips_run_worktrees() {
+    let dir = tempdir().expect("tempdir");
+    let marker = dir.path().join("command-ran");
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(&format!(
+        r#"
+id: eng
+selector_agent: selector
+default_workflow: verify
+selection_max_retries: 1
+agents:
+  selector:
+    provider: openai
+    model: gpt-4.1
+workflows:
+  - id: verify
+    version: 1
+    repository: {repository}
+    steps:
+      - id: run_tests
+        type: command
+        command: [sh, -c, "touch {marker}"]
+        outputs: [summary, exit_code, stdout]
+        output_files:
+          summary: out/summary.txt
+          exit_code: out/exit-code.txt
+          stdout: out/stdout.txt
+        on_approve: lint
+        on_reject: run_tests
+      - id: lint
+        type: command
+        command: [sh, -c, "touch {marker}"]
+        outputs: [summary]
+        output_files:
+          summary: out/lint-summary.txt
+"#,
+        repository = dir.path().join("missing-repo").display(),
+        marker = marker.display(),
+    ))
+    .expect("orchestrator");
+
+    let state_root = dir.path().join("state");
+    let report = simulate_workflow(
+        &orchestrator,
+        "verify",
+        Map::new(),
+        script(
+            r#"
+steps:
+  run_tests:
+    - {exit_code: 1, stdout: "2 failed"}
+    - {stdout: "all passed"}
+"#,
+        ),
+        &state_root,
+        None,
+        100,
+    )
+    .expect("simulate");
+
+    assert_eq!(report.state, RunState::Succeeded, "{report:?}");
+    let path = report
+        .attempts
+        .iter()
+        .map(|attempt| (attempt.step_id.as_str(), attempt.next_step_id.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        path,
+        vec![
+            ("run_tests", Some("run_tests")),
+            ("run_tests", Some("lint")),
+            ("lint", None),
+        ]
+    );
+    assert!(
+        !marker.exists(),
+        "command argv must not run under simulation"
+    );
+    let store = WorkflowRunStore::new(&state_root);
+    let passed = store
+        .load_step_attempt(SIMULATION_RUN_ID, "run_tests", report.attempts[1].attempt)
+        .expect("passing attempt");
+    assert_eq!(passed.outputs["exit_code"], Value::from(0));
+    assert_eq!(passed.outputs["stdout"], Value::from("all passed"));
+
+    let err = simulate_workflow(
+        &orchestrator,
+        "verify",
+        Map::new(),
+        script("steps:\n  lint:\n    - outputs: {summary: x}\n"),
+        &dir.path().join("invalid"),
+        None,
+        100,
+    )
+    .expect_err("command steps take no outputs");
+    assert!(
+        err.to_string()
+            .contains("only takes `exit_code`, `stdout` and `stderr`"),
+        "{err}"
+    );
+}
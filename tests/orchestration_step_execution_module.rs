@@ -1,8 +1,17 @@
 use direclaw::orchestration::step_execution::resolve_runner_binaries;
+use direclaw::provider::builtin_registry;
 
 #[test]
 fn step_execution_module_exposes_runner_binary_resolution_defaults() {
     let binaries = resolve_runner_binaries();
-    assert_eq!(binaries.anthropic, "claude");
-    assert_eq!(binaries.openai, "codex");
+    let binary = |provider: &str| {
+        builtin_registry()
+            .get(provider)
+            .expect("builtin backend")
+            .binary(&binaries)
+            .to_string()
+    };
+    assert_eq!(binary("anthropic"), "claude");
+    assert_eq!(binary("openai"), "codex");
+    assert_eq!(binary("openai_compatible"), "");
 }
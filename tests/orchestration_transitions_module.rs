@@ -46,6 +46,7 @@ fn transitions_module_routes_status_without_active_run() {
         default_workflow: "default".to_string(),
         available_functions: Vec::new(),
         available_function_schemas: Vec::new(),
+        available_workflow_inputs: Default::default(),
     };
     let result = SelectorResult {
         selector_id: "sel-1".to_string(),
@@ -54,6 +55,7 @@ fn transitions_module_routes_status_without_active_run() {
         selected_workflow: None,
         function_id: None,
         function_args: None,
+        workflow_inputs: None,
         reason: None,
     };
 
@@ -0,0 +1,100 @@
+use direclaw::orchestration::run_store::{StepAttemptRecord, StepUsage, WorkflowRunStore};
+use direclaw::orchestration::usage::{collect_usage_report, run_usage};
+use direclaw::provider::ProviderUsage;
+use serde_json::Map;
+use std::collections::BTreeMap;
+use tempfile::tempdir;
+
+fn attempt(
+    run_id: &str,
+    step_id: &str,
+    ended_at: i64,
+    usage: Option<(&str, u64)>,
+) -> StepAttemptRecord {
+    StepAttemptRecord {
+        run_id: run_id.to_string(),
+        step_id: step_id.to_string(),
+        attempt: 1,
+        started_at: ended_at - 1,
+        ended_at,
+        state: "succeeded".to_string(),
+        outputs: Map::new(),
+        output_files: BTreeMap::new(),
+        final_output_priority: Vec::new(),
+        next_step_id: None,
+        error: None,
+        output_validation_errors: BTreeMap::new(),
+        parallel_step_id: None,
+        foreach_step_id: None,
+        item_index: None,
+        error_class: None,
+        usage: usage.map(|(agent_id, input_tokens)| StepUsage {
+            agent_id: agent_id.to_string(),
+            provider: "anthropic".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            tokens: ProviderUsage {
+                input_tokens,
+                output_tokens: 1,
+                cost_usd_micros: 250,
+                ..ProviderUsage::default()
+            },
+        }),
+    }
+}
+
+#[test]
+fn usage_module_rolls_up_run_usage_per_agent() {
+    let dir = tempdir().expect("tempdir");
+    let store = WorkflowRunStore::new(dir.path());
+    store.create_run("run-1", "wf", 10).expect("run");
+    for record in [
+        attempt("run-1", "plan", 20, Some(("planner", 100))),
+        attempt("run-1", "build", 30, Some(("builder", 40))),
+        attempt("run-1", "check", 35, None),
+        attempt("run-1", "review", 40, Some(("planner", 10))),
+    ] {
+        store.persist_step_attempt(&record).expect("attempt");
+    }
+
+    let rollup = run_usage(&store, "run-1").expect("rollup");
+    assert_eq!(rollup.attempts, 3);
+    assert_eq!(rollup.total.input_tokens, 150);
+    assert_eq!(rollup.total.cost_usd(), "0.000750");
+    assert_eq!(rollup.by_agent["planner"].input_tokens, 110);
+    assert_eq!(rollup.by_agent["builder"].output_tokens, 1);
+    assert!(run_usage(&store, "missing").expect("empty").is_empty());
+}
+
+#[test]
+fn usage_module_reports_workflows_and_agents_across_orchestrators_since_cutoff() {
+    let dir = tempdir().expect("tempdir");
+    let eng = WorkflowRunStore::new(dir.path().join("eng"));
+    let ops = WorkflowRunStore::new(dir.path().join("ops"));
+    eng.create_run("run-old", "build", 10).expect("run");
+    eng.persist_step_attempt(&attempt("run-old", "plan", 50, Some(("worker", 500))))
+        .expect("old attempt");
+    eng.create_run("run-new", "build", 100).expect("run");
+    eng.persist_step_attempt(&attempt("run-new", "plan", 150, Some(("worker", 20))))
+        .expect("new attempt");
+    ops.create_run("run-ops", "deploy", 100).expect("run");
+    ops.persist_step_attempt(&attempt("run-ops", "ship", 160, Some(("worker", 7))))
+        .expect("ops attempt");
+    ops.create_run("run-idle", "deploy", 100).expect("run");
+
+    let stores = [("eng", &eng), ("ops", &ops)];
+    let report = collect_usage_report(stores, Some(100)).expect("report");
+    assert_eq!(report.since, Some(100));
+    assert_eq!(report.runs, 2);
+    assert_eq!(report.usage.attempts, 2);
+    assert_eq!(report.usage.total.input_tokens, 27);
+    assert_eq!(report.by_workflow["eng/build"].runs, 1);
+    assert_eq!(report.by_workflow["eng/build"].usage.input_tokens, 20);
+    assert_eq!(report.by_workflow["ops/deploy"].usage.input_tokens, 7);
+    assert_eq!(report.usage.by_agent["eng/worker"].input_tokens, 20);
+    assert_eq!(report.usage.by_agent["ops/worker"].input_tokens, 7);
+
+    let all_time = collect_usage_report(stores, None).expect("report");
+    assert_eq!(all_time.runs, 3);
+    assert_eq!(all_time.by_workflow["eng/build"].runs, 2);
+    assert_eq!(all_time.usage.total.input_tokens, 527);
+}
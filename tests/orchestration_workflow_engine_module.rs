@@ -55,6 +55,12 @@ fn sample_run(run_id: &str) -> WorkflowRunRecord {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     }
 }
 
@@ -131,10 +137,7 @@ fn workflow_engine_module_exposes_engine_type_and_builder() {
     let dir = tempdir().expect("tempdir");
     let store = WorkflowRunStore::new(dir.path());
     let orchestrator = sample_orchestrator();
-    let binaries = RunnerBinaries {
-        anthropic: "claude".to_string(),
-        openai: "codex".to_string(),
-    };
+    let binaries = RunnerBinaries::default();
 
     let _engine = WorkflowEngine::new(store, orchestrator).with_runner_binaries(binaries);
 }
@@ -0,0 +1,278 @@
+use direclaw::config::WorkflowConfig;
+use direclaw::orchestration::workflow_graph::{
+    workflow_graph, GraphEdgeKind, GraphFormat, GraphWarning,
+};
+
+fn workflow(yaml: &str) -> WorkflowConfig {
+    serde_yaml::from_str(yaml).expect("workflow")
+}
+
+fn review_loop_workflow() -> WorkflowConfig {
+    workflow(
+        r#"
+id: fix_issue
+version: 2
+limits:
+  max_total_iterations: 6
+steps:
+  - id: plan
+    type: agent_task
+    agent: planner
+    prompt: plan
+    workspace_mode: run_workspace
+    outputs: [plan]
+    output_files:
+      plan: out/plan.md
+    limits:
+      max_retries: 3
+  - id: review
+    type: agent_review
+    agent: reviewer
+    prompt: review
+    outputs: [decision]
+    output_files:
+      decision: out/decision.md
+    on_approve: done
+    on_reject: plan
+  - id: done
+    type: agent_task
+    agent: planner
+    prompt: done
+    outputs: [summary]
+    output_files:
+      summary: out/summary.md
+"#,
+    )
+}
+
+#[test]
+fn workflow_graph_module_draws_routes_and_annotations() {
+    let graph = workflow_graph(&review_loop_workflow());
+
+    assert_eq!(graph.entry.as_deref(), Some("plan"));
+    let edges = graph
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str(), edge.kind.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        edges,
+        vec![
+            ("plan", "review", GraphEdgeKind::Next),
+            ("review", "done", GraphEdgeKind::Approve),
+            ("review", "plan", GraphEdgeKind::Reject),
+        ]
+    );
+    assert_eq!(
+        graph.nodes[0].annotations,
+        vec![
+            "agent_task",
+            "agent=planner",
+            "workspace=run_workspace",
+            "max_retries=3"
+        ]
+    );
+    assert!(graph.nodes[2].terminal);
+    assert!(!graph.nodes[1].terminal);
+    assert!(graph.warnings.is_empty());
+
+    let mermaid = graph.render(GraphFormat::Mermaid);
+    assert!(mermaid.starts_with("flowchart TD\n    %% workflow fix_issue v2"));
+    assert!(mermaid.contains("%% max_total_iterations=6"));
+    assert!(mermaid.contains("s1 -->|\"reject\"| s0"));
+    assert!(mermaid.contains("s2 --> wf_end"));
+    assert!(!mermaid.contains("classDef warning"));
+
+    let dot = graph.render(GraphFormat::Dot);
+    assert!(dot.starts_with("digraph \"fix_issue\" {"));
+    assert!(dot.contains("s1 -> s2 [label=\"approve\"];"));
+    assert!(dot.contains("s0 [label=\"plan\\nagent_task\\nagent=planner"));
+    assert!(dot.ends_with('}'));
+}
+
+#[test]
+fn workflow_graph_module_flags_unreachable_steps_and_loops_without_exit() {
+    let graph = workflow_graph(&workflow(
+        r#"
+id: stuck
+version: 1
+steps:
+  - id: draft
+    type: agent_task
+    agent: worker
+    prompt: draft
+    outputs: [summary]
+    output_files:
+      summary: out/summary.md
+    next: check
+  - id: check
+    type: agent_review
+    agent: worker
+    prompt: check
+    outputs: [decision]
+    output_files:
+      decision: out/decision.md
+    on_approve: draft
+    on_reject: draft
+  - id: orphan
+    type: agent_task
+    agent: worker
+    prompt: orphan
+    outputs: [summary]
+    output_files:
+      summary: out/orphan.md
+"#,
+    ));
+
+    assert_eq!(
+        graph.warnings,
+        vec![
+            GraphWarning::Unreachable {
+                step_id: "orphan".to_string()
+            },
+            GraphWarning::LoopWithoutExit {
+                step_ids: vec!["draft".to_string(), "check".to_string()]
+            },
+        ]
+    );
+    assert_eq!(
+        graph.warnings[1].to_string(),
+        "steps `draft`, `check` loop with no path to the end of the workflow"
+    );
+    let mermaid = graph.render(GraphFormat::Mermaid);
+    assert!(mermaid.contains("%% warning: step `orphan` is unreachable from the entry step"));
+    assert!(mermaid.contains("class s0,s1,s2 warning"));
+    let dot = graph.render(GraphFormat::Dot);
+    assert!(dot.starts_with("// warning: step `orphan`"));
+    assert!(dot.contains("s2 [label=\"orphan\\nagent_task\\nagent=worker\\nworkspace=orchestrator_workspace\", color=\"#d93025\", penwidth=3];"));
+}
+
+#[test]
+fn workflow_graph_module_draws_parallel_foreach_and_conditional_routes() {
+    let graph = workflow_graph(&workflow(
+        r#"
+id: fanout
+version: 1
+steps:
+  - id: triage
+    type: agent_task
+    agent: worker
+    prompt: triage
+    outputs: [severity]
+    output_files:
+      severity: out/severity.md
+    transitions:
+      - when: outputs.severity == "high"
+        next: split
+      - default: each
+  - id: split
+    type: parallel
+    branches: [left, right]
+    next: each
+    outputs: [summary]
+    output_files:
+      summary: out/split.md
+  - id: left
+    type: agent_task
+    agent: worker
+    prompt: left
+    outputs: [summary]
+    output_files:
+      summary: out/left.md
+    next: each
+  - id: right
+    type: agent_task
+    agent: worker
+    prompt: right
+    outputs: [summary]
+    output_files:
+      summary: out/right.md
+    next: each
+  - id: each
+    type: foreach
+    items: "{{steps.triage.outputs.severity}}"
+    sequence: [fix, test]
+    max_concurrency: 2
+    next: finish
+    outputs: [summary]
+    output_files:
+      summary: out/each.md
+  - id: fix
+    type: agent_task
+    agent: worker
+    prompt: fix
+    outputs: [summary]
+    output_files:
+      summary: out/fix.md
+  - id: test
+    type: command
+    command: [cargo, test]
+    outputs: [exit_code]
+    output_files:
+      exit_code: out/exit_code.md
+  - id: finish
+    type: agent_task
+    agent: worker
+    prompt: finish
+    outputs: [summary]
+    output_files:
+      summary: out/finish.md
+"#,
+    ));
+
+    let edge_kinds = |from: &str| {
+        graph
+            .edges
+            .iter()
+            .filter(|edge| edge.from == from)
+            .map(|edge| (edge.to.clone(), edge.kind.label()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        edge_kinds("triage"),
+        vec![
+            (
+                "split".to_string(),
+                "when outputs.severity == \"high\"".to_string()
+            ),
+            ("each".to_string(), "default".to_string()),
+        ]
+    );
+    assert_eq!(
+        edge_kinds("split"),
+        vec![
+            ("left".to_string(), "branch".to_string()),
+            ("right".to_string(), "branch".to_string()),
+            ("each".to_string(), "join".to_string()),
+        ]
+    );
+    assert_eq!(
+        edge_kinds("each"),
+        vec![
+            ("fix".to_string(), "each item".to_string()),
+            ("test".to_string(), "then".to_string()),
+            ("finish".to_string(), "next".to_string()),
+        ]
+    );
+    assert!(edge_kinds("fix").is_empty());
+    assert!(edge_kinds("test").is_empty());
+    assert!(graph.warnings.is_empty(), "{:?}", graph.warnings);
+    let terminal = graph
+        .nodes
+        .iter()
+        .filter(|node| node.terminal)
+        .map(|node| node.step_id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(terminal, vec!["finish"]);
+    assert!(graph
+        .render(GraphFormat::Mermaid)
+        .contains("s0 -->|\"when outputs.severity == #quot;high#quot;\"| s1"));
+}
+
+#[test]
+fn workflow_graph_module_parses_formats() {
+    assert_eq!(GraphFormat::parse("Mermaid"), Ok(GraphFormat::Mermaid));
+    assert_eq!(GraphFormat::parse("dot"), Ok(GraphFormat::Dot));
+    assert_eq!(GraphFormat::parse("graphviz"), Ok(GraphFormat::Dot));
+    assert!(GraphFormat::parse("svg").is_err());
+}
@@ -1,10 +1,17 @@
-use direclaw::config::{OrchestratorConfig, OutputKey, PathTemplate, Settings};
+use direclaw::config::{
+    OrchestratorConfig, OutputKey, PathTemplate, RetryErrorClass, RetryPolicyConfig, Settings,
+    WorkflowStepWorkspaceMode,
+};
 use direclaw::orchestration::function_registry::FunctionRegistry;
+use direclaw::orchestration::human_review::{
+    find_pending_human_review_run, load_pending_human_review,
+};
 use direclaw::orchestration::output_contract::{
     evaluate_step_result, parse_workflow_result_envelope, resolve_step_output_paths,
 };
 use direclaw::orchestration::prompt_render::render_step_prompt;
 use direclaw::orchestration::routing::{process_queued_message, StatusResolutionInput};
+use direclaw::orchestration::run_events::RunEventKind;
 use direclaw::orchestration::run_store::{
     RunMemoryContext, RunState, StepAttemptRecord, WorkflowRunRecord, WorkflowRunStore,
 };
@@ -16,6 +23,7 @@ use direclaw::orchestration::selector_artifacts::SelectorArtifactStore;
 use direclaw::orchestration::transitions::{
     route_selector_action, RouteContext, RoutedSelectorAction,
 };
+use direclaw::orchestration::usage::run_usage;
 use direclaw::orchestration::workflow_engine::{
     enforce_execution_safety, resolve_execution_safety_limits, ExecutionSafetyLimits,
     WorkflowEngine,
@@ -134,6 +142,7 @@ fn sample_selector_request() -> SelectorRequest {
             "orchestrator.list".to_string(),
         ])
         .available_function_schemas(),
+        available_workflow_inputs: Default::default(),
     }
 }
 
@@ -174,10 +183,9 @@ fn mock_runner_binaries(root: &Path) -> RunnerBinaries {
         &openai,
         "#!/bin/sh\necho '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"[workflow_result]{\\\"decision\\\":\\\"approve\\\",\\\"summary\\\":\\\"ok\\\",\\\"feedback\\\":\\\"none\\\",\\\"plan\\\":\\\"ok\\\",\\\"artifact\\\":\\\"ok\\\"}[/workflow_result]\"}}'\n",
     );
-    RunnerBinaries {
-        anthropic: anthropic.display().to_string(),
-        openai: openai.display().to_string(),
-    }
+    RunnerBinaries::default()
+        .with("anthropic", anthropic.display().to_string())
+        .with("openai", openai.display().to_string())
 }
 
 #[test]
@@ -251,6 +259,7 @@ fn selector_actions_start_workflow_status_and_commands_execute() {
         selected_workflow: Some("fix_issue".to_string()),
         function_id: None,
         function_args: None,
+        workflow_inputs: None,
         reason: None,
     };
 
@@ -381,6 +390,7 @@ fn selector_actions_start_workflow_status_and_commands_execute() {
         selected_workflow: None,
         function_id: None,
         function_args: None,
+        workflow_inputs: None,
         reason: None,
     };
 
@@ -434,6 +444,7 @@ fn selector_actions_start_workflow_status_and_commands_execute() {
             "runId".to_string(),
             Value::String(run_id.clone()),
         )])),
+        workflow_inputs: None,
         reason: None,
     };
     let mut command_request = request.clone();
@@ -583,6 +594,11 @@ fn run_state_and_progress_are_persisted_at_spec_paths_with_lifecycle_updates() {
         next_step_id: Some("review".to_string()),
         error: None,
         output_validation_errors: BTreeMap::new(),
+        parallel_step_id: None,
+        foreach_step_id: None,
+        item_index: None,
+        error_class: None,
+        usage: None,
     };
     let attempt_path = store
         .persist_step_attempt(&attempt)
@@ -781,11 +797,11 @@ workflows:
     bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
     let store = WorkflowRunStore::new(&state_root);
     store.create_run("run-max-iters", "wf", 1).expect("run");
-    let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: reject.display().to_string(),
-            openai: "unused".to_string(),
-        });
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", reject.display().to_string())
+            .with("openai", "unused"),
+    );
 
     let err = engine.start("run-max-iters", 2).expect_err("must fail");
     assert!(err.to_string().contains("max total iterations"));
@@ -840,11 +856,11 @@ workflows:
     bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
     let store = WorkflowRunStore::new(&state_root);
     store.create_run("run-timeout-limit", "wf", 1).expect("run");
-    let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: approve.display().to_string(),
-            openai: "unused".to_string(),
-        });
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", approve.display().to_string())
+            .with("openai", "unused"),
+    );
 
     let err = engine
         .start("run-timeout-limit", 10)
@@ -879,6 +895,12 @@ fn run_timeout_uses_elapsed_runtime_across_multiple_steps() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
     let limits = ExecutionSafetyLimits {
         max_total_iterations: 10,
@@ -1041,6 +1063,7 @@ fn command_invoke_validation_enforces_schema_and_allowlist() {
         available_functions: vec!["workflow.cancel".to_string()],
         available_function_schemas: FunctionRegistry::new(vec!["workflow.cancel".to_string()])
             .available_function_schemas(),
+        available_workflow_inputs: Default::default(),
     };
 
     let unknown_key = r#"{
@@ -1292,6 +1315,12 @@ fn step_prompt_renderer_supports_engineering_and_product_placeholders() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
     let rendered = render_step_prompt(
         &run,
@@ -1344,6 +1373,12 @@ fn step_prompt_renderer_supports_engineering_and_product_placeholders() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
     let rendered = render_step_prompt(
         &run,
@@ -1391,6 +1426,12 @@ fn step_prompt_renderer_fails_fast_on_missing_required_placeholder() {
         selected_workflow: None,
         status_conversation_id: None,
         terminal_reason: None,
+        waiting_seconds: 0,
+        active_steps: Vec::new(),
+        parent_run_id: None,
+        child_run_ids: Vec::new(),
+        rerun_of: None,
+        retry_at: None,
     };
     let err = render_step_prompt(
         &run,
@@ -1447,11 +1488,11 @@ workflows:
     bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
     let store = WorkflowRunStore::new(&state_root);
     store.create_run("run-bad-envelope", "wf", 1).expect("run");
-    let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: bad.display().to_string(),
-            openai: "unused".to_string(),
-        });
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", bad.display().to_string())
+            .with("openai", "unused"),
+    );
 
     let err = engine
         .start("run-bad-envelope", 2)
@@ -1526,6 +1567,149 @@ steps:
         .contains("invalid required output keys"));
 }
 
+#[test]
+fn output_schema_validates_nested_shapes_and_decodes_text_values() {
+    let workflow: direclaw::config::WorkflowConfig = serde_yaml::from_str(
+        r#"
+id: wf
+version: 1
+steps:
+  - id: s1
+    type: agent_task
+    agent: worker
+    prompt: test
+    outputs:
+      - key: risk_level
+        type: enum
+        values: [low, high]
+      - key: score
+        type: integer
+      - key: tasks
+        type: array
+        items:
+          type: object
+          fields:
+            title:
+              type: string
+            notes?:
+              type: string
+    output_files:
+      risk_level: out/risk.txt
+      score: out/score.txt
+      tasks: out/tasks.json
+"#,
+    )
+    .expect("workflow");
+    let step = workflow.steps.first().expect("step");
+
+    let valid = evaluate_step_result(
+        &workflow,
+        step,
+        r#"[workflow_result]{"risk_level":"low","score":"3","tasks":[{"title":"a"}]}[/workflow_result]"#,
+        &BTreeMap::new(),
+    )
+    .expect("valid outputs");
+    assert_eq!(valid.outputs.get("score"), Some(&Value::from(3)));
+
+    let err = evaluate_step_result(
+        &workflow,
+        step,
+        r#"[workflow_result]{"risk_level":"medium","score":1.5,"tasks":[{"title":"a"},{"notes":7}]}[/workflow_result]"#,
+        &BTreeMap::new(),
+    )
+    .expect_err("schema violations");
+    let message = err.to_string();
+    assert!(message.contains("output schema validation failed"));
+    assert!(message.contains("risk_level: expected one of [low, high], got `medium`"));
+    assert!(message.contains("score: expected integer, got number"));
+    assert!(message.contains("tasks[1].title: missing"));
+    assert!(message.contains("tasks[1].notes: expected string, got number"));
+}
+
+#[test]
+fn output_schema_violation_retries_with_violations_in_prompt() {
+    let dir = tempdir().expect("tempdir");
+    let bad = dir.path().join("claude-bad-schema");
+    write_script(
+        &bad,
+        "#!/bin/sh\necho '[workflow_result]{\"summary\":\"x\",\"risk_level\":\"severe\"}[/workflow_result]'\n",
+    );
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: s1
+        type: agent_task
+        agent: worker
+        prompt: test
+        outputs:
+          - summary
+          - key: risk_level
+            type: enum
+            values: [low, high]
+        output_files:
+          summary: out/summary.txt
+          risk_level: out/risk.txt
+        limits:
+          max_retries: 1
+"#,
+    )
+    .expect("orchestrator");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    store.create_run("run-bad-schema", "wf", 1).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", bad.display().to_string())
+            .with("openai", "unused"),
+    );
+
+    let err = engine
+        .start("run-bad-schema", 2)
+        .expect_err("must fail after retries");
+    assert!(err.to_string().contains("output schema validation failed"));
+
+    let attempt_1 = store
+        .load_step_attempt("run-bad-schema", "s1", 1)
+        .expect("attempt1");
+    assert_eq!(attempt_1.state, "failed_retryable");
+    assert_eq!(
+        attempt_1.output_validation_errors.get("risk_level"),
+        Some(&"expected one of [low, high], got `severe`".to_string())
+    );
+
+    let first_prompt = fs::read_to_string(
+        store
+            .step_attempt_dir("run-bad-schema", "s1", 1)
+            .join("prompt.md"),
+    )
+    .expect("attempt 1 prompt");
+    assert!(!first_prompt.contains("Attempt 1 was rejected"));
+    let retry_prompt = fs::read_to_string(
+        store
+            .step_attempt_dir("run-bad-schema", "s1", 2)
+            .join("prompt.md"),
+    )
+    .expect("attempt 2 prompt");
+    assert!(retry_prompt.contains("Attempt 1 was rejected"));
+    assert!(retry_prompt.contains("- `risk_level`: expected one of [low, high], got `severe`"));
+}
+
 #[test]
 fn missing_required_output_key_retries_and_persists_key_level_validation_errors() {
     let dir = tempdir().expect("tempdir");
@@ -1571,11 +1755,11 @@ workflows:
     store
         .create_run("run-missing-output", "wf", 1)
         .expect("run");
-    let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: bad.display().to_string(),
-            openai: "unused".to_string(),
-        });
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", bad.display().to_string())
+            .with("openai", "unused"),
+    );
 
     let err = engine
         .start("run-missing-output", 2)
@@ -1646,11 +1830,11 @@ workflows:
     bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
     let store = WorkflowRunStore::new(&state_root);
     store.create_run("run-empty-output", "wf", 1).expect("run");
-    let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: bad.display().to_string(),
-            openai: "unused".to_string(),
-        });
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", bad.display().to_string())
+            .with("openai", "unused"),
+    );
 
     let err = engine
         .start("run-empty-output", 2)
@@ -1752,10 +1936,9 @@ workflows:
         );
         store.create_run(run_id, "wf", 10).expect("run");
         let engine = WorkflowEngine::new(store.clone(), orchestrator.clone()).with_runner_binaries(
-            RunnerBinaries {
-                anthropic: bin.display().to_string(),
-                openai: "unused".to_string(),
-            },
+            RunnerBinaries::default()
+                .with("anthropic", bin.display().to_string())
+                .with("openai", "unused"),
         );
         let run = engine.start(run_id, 11).expect("run");
         assert_eq!(run.state, RunState::Succeeded);
@@ -1773,13 +1956,12 @@ workflows:
 }
 
 #[test]
-fn missing_transition_target_fails_run_with_explicit_error() {
+fn human_review_step_parks_run_until_decision_and_follows_review_routes() {
     let dir = tempdir().expect("tempdir");
-    let approve = dir.path().join("claude-approve");
-    write_script(
-        &approve,
-        "#!/bin/sh\necho '[workflow_result]{\"decision\":\"approve\"}[/workflow_result]'\n",
-    );
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
     let orchestrator: OrchestratorConfig = serde_yaml::from_str(
         r#"
 id: engineering_orchestrator
@@ -1797,54 +1979,106 @@ agents:
 workflows:
   - id: wf
     version: 1
+    limits:
+      run_timeout_seconds: 60
     steps:
-      - id: review
-        type: agent_review
+      - id: plan
+        type: agent_task
         agent: worker
-        prompt: review
-        outputs: [decision]
+        prompt: plan
+        outputs: [summary, plan]
         output_files:
-          decision: out/review-decision.txt
-        on_approve: missing_step
-        on_reject: rejected
-      - id: rejected
+          summary: out/plan-summary.txt
+          plan: out/plan.md
+        next: sign_off
+      - id: sign_off
+        type: human_review
+        prompt: "Please sign off on: {{steps.plan.outputs.plan}}"
+        outputs: [decision, summary, feedback]
+        output_files:
+          decision: out/sign-off-decision.txt
+          summary: out/sign-off-summary.txt
+          feedback: out/sign-off-feedback.txt
+        on_approve: build
+        on_reject: plan
+      - id: build
         type: agent_task
         agent: worker
-        prompt: rejected
-        outputs: [decision]
+        prompt: build
+        outputs: [summary, artifact]
         output_files:
-          decision: out/rejected-decision.txt
+          summary: out/build-summary.txt
+          artifact: out/build-artifact.txt
 "#,
     )
     .expect("orchestrator");
-    let state_root = dir.path().join(".direclaw");
-    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
-    let store = WorkflowRunStore::new(&state_root);
-    store
-        .create_run("run-bad-transition", "wf", 1)
-        .expect("run");
+    orchestrator
+        .validate_setup_invariants()
+        .expect("human review step without agent is valid");
+
+    let binaries = mock_runner_binaries(dir.path());
+    store.create_run("run-gate", "wf", 10).expect("run");
     let engine =
-        WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(RunnerBinaries {
-            anthropic: approve.display().to_string(),
-            openai: "unused".to_string(),
-        });
+        WorkflowEngine::new(store.clone(), orchestrator.clone()).with_runner_binaries(binaries);
+
+    let parked = engine.start("run-gate", 11).expect("start");
+    assert_eq!(parked.state, RunState::Waiting);
+    assert_eq!(parked.current_step_id.as_deref(), Some("sign_off"));
+    let progress = store.load_progress("run-gate").expect("progress");
+    assert!(progress.pending_human_input);
+    let request = load_pending_human_review(&store, "run-gate")
+        .expect("pending review")
+        .expect("review requested");
+    assert_eq!(request.message, "Please sign off on: ok");
+    assert!(!state_root
+        .join("workflows/runs/run-gate/steps/build/attempts/1/result.json")
+        .is_file());
+
+    let rejected = engine
+        .submit_human_review(
+            "run-gate",
+            false,
+            Some("add a rollback section".to_string()),
+            Some("Dana".to_string()),
+            5_000,
+        )
+        .expect("reject");
+    assert_eq!(rejected.state, RunState::Waiting);
+    assert_eq!(rejected.current_step_id.as_deref(), Some("sign_off"));
+    assert_eq!(rejected.total_iterations, 3);
+    assert!(rejected.waiting_seconds >= 4_000);
+    let feedback = fs::read_to_string(
+        state_root
+            .join("workflows/runs/run-gate/steps/sign_off/attempts/1/out/sign-off-feedback.txt"),
+    )
+    .expect("feedback output");
+    assert_eq!(feedback, "add a rollback section");
+    assert!(load_pending_human_review(&store, "run-gate")
+        .expect("pending review")
+        .is_some());
+
+    let approved = engine
+        .submit_human_review("run-gate", true, None, None, 9_000)
+        .expect("approve");
+    assert_eq!(approved.state, RunState::Succeeded);
+    assert_eq!(approved.total_iterations, 5);
+    let gate = store
+        .load_step_attempt("run-gate", "sign_off", 1)
+        .expect("gate attempt");
+    assert_eq!(gate.outputs["decision"], Value::from("approve"));
+    assert_eq!(gate.next_step_id.as_deref(), Some("build"));
+    assert!(state_root
+        .join("workflows/runs/run-gate/steps/build/attempts/1/result.json")
+        .is_file());
 
     let err = engine
-        .start("run-bad-transition", 2)
-        .expect_err("must fail on invalid transition");
-    assert!(err.to_string().contains("transition validation failed"));
-    let run = store.load_run("run-bad-transition").expect("run");
-    assert_eq!(run.state, RunState::Failed);
+        .submit_human_review("run-gate", true, None, None, 9_001)
+        .expect_err("terminal run has no pending review");
+    assert!(err.to_string().contains("has no pending human review"));
 }
 
-#[test]
-fn step_provider_failures_persist_invocation_logs_and_fail_run() {
-    let dir = tempdir().expect("tempdir");
-    let non_zero = dir.path().join("claude-fail");
-    write_script(&non_zero, "#!/bin/sh\necho boom 1>&2\nexit 7\n");
-    let timeout = dir.path().join("claude-timeout");
-    write_script(&timeout, "#!/bin/sh\nsleep 1\necho late\n");
-    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+fn parallel_review_orchestrator(max_total_iterations: u32) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
         r#"
 id: engineering_orchestrator
 selector_agent: workflow_router
@@ -1861,64 +2095,155 @@ agents:
 workflows:
   - id: wf
     version: 1
+    limits:
+      max_total_iterations: {max_total_iterations}
     steps:
-      - id: s1
+      - id: reviews
+        type: parallel
+        branches: [security, performance, docs]
+        outputs: [summary]
+        output_files:
+          summary: out/reviews-summary.txt
+        next: collate
+      - id: security
+        type: agent_review
+        agent: worker
+        prompt: security review
+        outputs: [decision, summary, feedback]
+        output_files:
+          decision: out/security-decision.txt
+          summary: out/security-summary.txt
+          feedback: out/security-feedback.txt
+        on_approve: collate
+        on_reject: collate
+      - id: performance
         type: agent_task
         agent: worker
-        prompt: test
-        outputs: [result]
+        prompt: performance review
+        outputs: [summary, artifact]
         output_files:
-          result: out/s1-result.txt
-        limits:
-          max_retries: 0
-workflow_orchestration:
-  default_step_timeout_seconds: 0
-  max_step_timeout_seconds: 0
-"#,
-    )
-    .expect("orchestrator");
+          summary: out/performance-summary.txt
+          artifact: out/performance-artifact.txt
+      - id: docs
+        type: agent_task
+        agent: worker
+        prompt: docs review
+        outputs: [summary, artifact]
+        output_files:
+          summary: out/docs-summary.txt
+          artifact: out/docs-artifact.txt
+      - id: collate
+        type: agent_task
+        agent: worker
+        prompt: "security={{{{steps.security.outputs.decision}}}} performance={{{{steps.performance.outputs.artifact}}}} docs={{{{steps.docs.outputs.summary}}}}"
+        outputs: [summary, artifact]
+        output_files:
+          summary: out/collate-summary.txt
+          artifact: out/collate-artifact.txt
+"#
+    ))
+    .expect("orchestrator")
+}
 
-    for (name, binary) in [("run-fail", non_zero), ("run-timeout", timeout)] {
-        let state_root = dir.path().join(format!(".direclaw-{name}"));
-        bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
-        let store = WorkflowRunStore::new(&state_root);
-        store.create_run(name, "wf", 1).expect("run");
-        let engine = WorkflowEngine::new(store.clone(), orchestrator.clone()).with_runner_binaries(
-            RunnerBinaries {
-                anthropic: binary.display().to_string(),
-                openai: "unused".to_string(),
-            },
-        );
-        let err = engine.start(name, 2).expect_err("must fail");
-        if name == "run-timeout" {
-            assert!(err.to_string().contains("step timed out"));
-        }
-        let run = store.load_run(name).expect("load run");
-        assert_eq!(run.state, RunState::Failed);
-        if name == "run-timeout" {
-            assert!(run
-                .terminal_reason
-                .as_deref()
-                .unwrap_or_default()
-                .contains("step timed out"));
-        }
-        let log_path = state_root.join(format!(
-            "workflows/runs/{name}/steps/s1/attempts/1/provider_invocation.json"
-        ));
-        assert!(log_path.is_file());
+#[test]
+fn parallel_step_fans_out_branches_and_join_sees_every_branch_output() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator = parallel_review_orchestrator(12);
+    orchestrator
+        .validate_setup_invariants()
+        .expect("parallel workflow is valid");
+    store.create_run("run-fan", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let run = engine.start("run-fan", 11).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+    assert_eq!(run.total_iterations, 5);
+    assert!(run.active_steps.is_empty());
+
+    for branch in ["security", "performance", "docs"] {
+        let attempt = store
+            .load_step_attempt("run-fan", branch, 1)
+            .expect("branch attempt");
+        assert_eq!(attempt.state, "succeeded");
+        assert_eq!(attempt.parallel_step_id.as_deref(), Some("reviews"));
     }
+    let fan_out = store
+        .load_step_attempt("run-fan", "reviews", 1)
+        .expect("parallel attempt");
+    assert_eq!(fan_out.next_step_id.as_deref(), Some("collate"));
+    assert_eq!(
+        fan_out.outputs["summary"],
+        Value::from("parallel branches completed: security, performance, docs")
+    );
+
+    let join_prompt = fs::read_to_string(
+        state_root.join("workflows/runs/run-fan/steps/collate/attempts/1/prompt.md"),
+    )
+    .expect("join prompt");
+    assert!(join_prompt.contains("security=approve performance=ok docs=ok"));
 }
 
 #[test]
-fn legacy_agent_private_workspace_field_is_rejected_in_orchestrator_yaml() {
+fn parallel_step_counts_iterations_across_branches() {
     let dir = tempdir().expect("tempdir");
-    let denied_agent_workspace = dir.path().join("outside-agent-workspace");
-    let parse_err = serde_yaml::from_str::<OrchestratorConfig>(&format!(
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    store.create_run("run-fan-limit", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), parallel_review_orchestrator(2))
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let err = engine
+        .start("run-fan-limit", 11)
+        .expect_err("three branches exceed two iterations");
+    assert!(err.to_string().contains("max total iterations"));
+    let run = store.load_run("run-fan-limit").expect("run");
+    assert_eq!(run.state, RunState::Failed);
+    assert_eq!(run.total_iterations, 0);
+}
+
+#[test]
+fn parallel_step_validation_requires_branches_that_route_to_the_join() {
+    let mut single_branch = parallel_review_orchestrator(12);
+    single_branch.workflows[0].steps[0].branches = vec!["security".to_string()];
+    let err = single_branch
+        .validate_setup_invariants()
+        .expect_err("single branch must fail");
+    assert!(err.to_string().contains("requires at least two `branches`"));
+
+    let mut escaping_branch = parallel_review_orchestrator(12);
+    escaping_branch.workflows[0].steps[2].next = Some("security".to_string());
+    let err = escaping_branch
+        .validate_setup_invariants()
+        .expect_err("branch routing away from join must fail");
+    assert!(err
+        .to_string()
+        .contains("branch step `performance` may only route to join step `collate`"));
+
+    let mut missing_join = parallel_review_orchestrator(12);
+    missing_join.workflows[0].steps[0].next = None;
+    let err = missing_join
+        .validate_setup_invariants()
+        .expect_err("parallel step without join must fail");
+    assert!(err
+        .to_string()
+        .contains("requires `next` naming the join step"));
+}
+
+fn command_step_orchestrator(check_routes: &str, extra: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
         r#"
 id: engineering_orchestrator
 selector_agent: workflow_router
 default_workflow: wf
 selection_max_retries: 1
+{extra}
 agents:
   workflow_router:
     provider: anthropic
@@ -1927,24 +2252,915 @@ agents:
   worker:
     provider: anthropic
     model: sonnet
-    private_workspace: {}
 workflows:
   - id: wf
     version: 1
     steps:
-      - id: s1
+      - id: check
+        type: command
+        command: [sh, -c, "echo checking {{{{workflow.run_id}}}}; echo warn >&2; exit 3"]
+        outputs: [summary, exit_code, stdout, stderr]
+        output_files:
+          summary: out/check-summary.txt
+          exit_code: out/check-exit-code.txt
+          stdout: out/check-stdout.txt
+          stderr: out/check-stderr.txt
+{check_routes}
+      - id: fix
         type: agent_task
         agent: worker
-        prompt: test
-        outputs: [result]
+        prompt: "fix {{{{steps.check.outputs.stdout}}}}"
+        outputs: [summary, artifact]
         output_files:
-          result: out/s1-result.txt
-"#,
-        denied_agent_workspace.display()
+          summary: out/fix-summary.txt
+          artifact: out/fix-artifact.txt
+        next: done
+      - id: done
+        type: command
+        command: [sh, -c, "test -n \"$DIRECLAW_RUN_ID\" && echo finished"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/done-summary.txt
+          stdout: out/done-stdout.txt
+"#
     ))
-    .expect_err("legacy field must be rejected");
-    assert!(parse_err.to_string().contains("unknown field"));
-    assert!(parse_err.to_string().contains("private_workspace"));
+    .expect("orchestrator")
+}
+
+#[test]
+fn command_step_maps_exit_status_to_review_routes_and_captures_output() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator =
+        command_step_orchestrator("        on_approve: done\n        on_reject: fix", "");
+    orchestrator
+        .validate_setup_invariants()
+        .expect("command step without agent is valid");
+    store.create_run("run-cmd", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let run = engine.start("run-cmd", 11).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let check = store
+        .load_step_attempt("run-cmd", "check", 1)
+        .expect("check attempt");
+    assert_eq!(check.state, "succeeded");
+    assert_eq!(check.outputs["exit_code"], Value::from(3));
+    assert_eq!(check.outputs["decision"], Value::from("reject"));
+    assert_eq!(check.outputs["stdout"], Value::from("checking run-cmd\n"));
+    assert_eq!(check.outputs["stderr"], Value::from("warn\n"));
+    assert_eq!(check.next_step_id.as_deref(), Some("fix"));
+    let attempt_dir = state_root.join("workflows/runs/run-cmd/steps/check/attempts/1");
+    assert_eq!(
+        fs::read_to_string(attempt_dir.join("out/check-exit-code.txt")).expect("exit code"),
+        "3"
+    );
+    let invocation: Value = serde_json::from_str(
+        &fs::read_to_string(attempt_dir.join("command_invocation.json")).expect("invocation"),
+    )
+    .expect("invocation json");
+    assert_eq!(invocation["exitCode"], Value::from(3));
+    assert_eq!(invocation["timedOut"], Value::Bool(false));
+    assert_eq!(invocation["argv"][0], Value::from("sh"));
+
+    let fix_prompt = fs::read_to_string(
+        state_root.join("workflows/runs/run-cmd/steps/fix/attempts/1/prompt.md"),
+    )
+    .expect("fix prompt");
+    assert!(fix_prompt.contains("fix checking run-cmd"));
+    let done = store
+        .load_step_attempt("run-cmd", "done", 1)
+        .expect("done attempt");
+    assert_eq!(done.outputs["stdout"], Value::from("finished\n"));
+    assert_eq!(
+        done.outputs["summary"],
+        Value::from(
+            "command `sh -c test -n \"$DIRECLAW_RUN_ID\" && echo finished` exited with status 0"
+        )
+    );
+    assert_eq!(done.next_step_id, None);
+}
+
+#[test]
+fn command_step_outputs_keep_only_the_tail_of_large_output() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let mut orchestrator =
+        command_step_orchestrator("        on_approve: done\n        on_reject: fix", "");
+    orchestrator.workflows[0].steps[0].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "head -c 40000 /dev/zero | tr '\\0' a; echo END; echo warn >&2".to_string(),
+    ];
+    store.create_run("run-big", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let run = engine.start("run-big", 11).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let check = store
+        .load_step_attempt("run-big", "check", 1)
+        .expect("check attempt");
+    let stdout = check.outputs["stdout"].as_str().expect("stdout");
+    assert!(stdout.len() < 17 * 1024, "{}", stdout.len());
+    assert!(
+        stdout.starts_with("[23620 earlier bytes truncated; full output in command_stdout.txt]\n")
+    );
+    assert!(stdout.ends_with("aaaEND\n"));
+    let full = fs::read_to_string(
+        state_root.join("workflows/runs/run-big/steps/check/attempts/1/command_stdout.txt"),
+    )
+    .expect("full stdout");
+    assert_eq!(full.len(), 40004);
+}
+
+#[test]
+fn rerun_forks_terminal_run_from_step_and_reuses_earlier_outputs() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let mut orchestrator =
+        command_step_orchestrator("        on_approve: done\n        on_reject: fix", "");
+    orchestrator.workflows[0].steps[2].command =
+        vec!["sh".to_string(), "-c".to_string(), "exit 1".to_string()];
+    let mut source = store.create_run("run-src", "wf", 10).expect("run");
+    source
+        .inputs
+        .insert("ticket".to_string(), Value::from("ENG-7"));
+    store.persist_run(&source).expect("persist inputs");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator.clone())
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    engine.start("run-src", 11).expect_err("done step fails");
+    assert_eq!(
+        store.load_run("run-src").expect("source").state,
+        RunState::Failed
+    );
+
+    let workflow = orchestrator.workflows[0].clone();
+    let err = store
+        .fork_run("run-src", "run-bad", &workflow, "missing", 20)
+        .expect_err("unknown step");
+    assert!(err
+        .to_string()
+        .contains("cannot rerun workflow run `run-src`: step `missing` is not declared"));
+    store.create_run("run-live", "wf", 20).expect("live run");
+    let err = store
+        .fork_run("run-live", "run-bad", &workflow, "done", 20)
+        .expect_err("non-terminal source");
+    assert!(err.to_string().contains("only terminal runs can be rerun"));
+    let mut canceled = store.create_run("run-canceled", "wf", 20).expect("run");
+    store
+        .transition_state(
+            &mut canceled,
+            RunState::Canceled,
+            21,
+            "canceled",
+            false,
+            "none",
+        )
+        .expect("cancel");
+    let err = store
+        .fork_run("run-canceled", "run-bad", &workflow, "check", 22)
+        .expect_err("step never reached");
+    assert!(err
+        .to_string()
+        .contains("step `check` was never reached by run `run-canceled`"));
+    assert!(!store.run_exists("run-bad"));
+
+    orchestrator.workflows[0].steps[2].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "echo again {{steps.check.outputs.stdout}}".to_string(),
+    ];
+    let workflow = orchestrator.workflows[0].clone();
+    let forked = store
+        .fork_run("run-src", "run-rerun", &workflow, "done", 20)
+        .expect("fork");
+    let lineage = forked.rerun_of.as_ref().expect("lineage");
+    assert_eq!(lineage.source_run_id, "run-src");
+    assert_eq!(lineage.from_step_id, "done");
+    assert_eq!(forked.inputs.get("ticket"), Some(&Value::from("ENG-7")));
+
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let run = engine.start("run-rerun", 21).expect("rerun");
+    assert_eq!(run.state, RunState::Succeeded);
+    assert_eq!(run.rerun_of, forked.rerun_of);
+
+    let check = store
+        .load_step_attempt("run-rerun", "check", 1)
+        .expect("copied check attempt");
+    assert_eq!(check.run_id, "run-rerun");
+    assert_eq!(check.outputs["stdout"], Value::from("checking run-src\n"));
+    let copied_stdout =
+        state_root.join("workflows/runs/run-rerun/steps/check/attempts/1/out/check-stdout.txt");
+    assert_eq!(
+        check.output_files["stdout"],
+        copied_stdout.display().to_string()
+    );
+    assert_eq!(
+        fs::read_to_string(&copied_stdout).expect("copied output file"),
+        "checking run-src\n"
+    );
+    store
+        .load_step_attempt("run-rerun", "fix", 1)
+        .expect("copied fix attempt");
+    let done = store
+        .load_step_attempt("run-rerun", "done", 1)
+        .expect("done attempt");
+    assert_eq!(done.state, "succeeded");
+    assert_eq!(
+        done.outputs["stdout"],
+        Value::from("again checking run-src\n")
+    );
+    assert_eq!(
+        store.load_run("run-src").expect("source").state,
+        RunState::Failed
+    );
+}
+
+#[test]
+fn retry_policy_marks_error_classes_non_retryable_and_records_class() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator = command_step_orchestrator(
+        "        next: done\n        retry:\n          non_retryable: [non_zero_exit]",
+        "workflow_orchestration:\n  retry:\n    max_retries:\n      non_zero_exit: 3",
+    );
+    orchestrator
+        .validate_setup_invariants()
+        .expect("retry block is valid");
+    store.create_run("run-no-retry", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let err = engine
+        .start("run-no-retry", 11)
+        .expect_err("non-zero exit is not retried");
+    assert!(err.to_string().contains("exited with status 3"), "{err}");
+
+    let attempt = store
+        .load_step_attempt("run-no-retry", "check", 1)
+        .expect("first attempt");
+    assert_eq!(attempt.state, "failed");
+    assert_eq!(attempt.error_class, Some(RetryErrorClass::NonZeroExit));
+    assert!(!store.step_attempt_exists("run-no-retry", "check", 2));
+    assert_eq!(
+        store.load_run("run-no-retry").expect("run").state,
+        RunState::Failed
+    );
+}
+
+#[test]
+fn command_step_without_reject_route_fails_on_non_zero_exit_and_timeout() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator = command_step_orchestrator(
+        "        next: done\n        limits:\n          max_retries: 0",
+        "",
+    );
+    orchestrator
+        .validate_setup_invariants()
+        .expect("command step with next is valid");
+    store.create_run("run-cmd-fail", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let err = engine
+        .start("run-cmd-fail", 11)
+        .expect_err("non-zero exit fails");
+    assert!(
+        err.to_string().contains("exited with status 3: warn"),
+        "{err}"
+    );
+    assert_eq!(
+        store.load_run("run-cmd-fail").expect("run").state,
+        RunState::Failed
+    );
+
+    let mut timed = command_step_orchestrator(
+        "        next: done\n        limits:\n          max_retries: 0",
+        "workflow_orchestration:\n  default_step_timeout_seconds: 1",
+    );
+    timed.workflows[0].steps[0].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "exec sleep 5".to_string(),
+    ];
+    store.create_run("run-cmd-slow", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), timed)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let started = Instant::now();
+    let err = engine
+        .start("run-cmd-slow", 11)
+        .expect_err("slow command times out");
+    assert!(err.to_string().contains("timed out after 1s"), "{err}");
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let invocation: Value = serde_json::from_str(
+        &fs::read_to_string(
+            state_root
+                .join("workflows/runs/run-cmd-slow/steps/check/attempts/1/command_invocation.json"),
+        )
+        .expect("invocation"),
+    )
+    .expect("invocation json");
+    assert_eq!(invocation["timedOut"], Value::Bool(true));
+}
+
+#[test]
+fn command_step_validation_rejects_missing_program_and_unknown_outputs() {
+    let mut empty = command_step_orchestrator("        next: done", "");
+    empty.workflows[0].steps[0].command = Vec::new();
+    let err = empty
+        .validate_setup_invariants()
+        .expect_err("empty command must fail");
+    assert!(err
+        .to_string()
+        .contains("requires `command` with a non-empty program"));
+
+    let mut half_routed = command_step_orchestrator("        on_approve: done", "");
+    let err = half_routed
+        .validate_setup_invariants()
+        .expect_err("on_approve without on_reject must fail");
+    assert!(err
+        .to_string()
+        .contains("must declare both `on_approve` and `on_reject` or neither"));
+
+    half_routed.workflows[0].steps[0].on_approve = None;
+    half_routed.workflows[0].steps[0]
+        .outputs
+        .push(OutputKey::parse("artifact").expect("key"));
+    let err = half_routed
+        .validate_setup_invariants()
+        .expect_err("unknown command output must fail");
+    assert!(err
+        .to_string()
+        .contains("output `artifact` is not produced by command steps"));
+
+    let mut agentless = command_step_orchestrator("        next: done", "");
+    agentless.workflows[0].steps[0].workspace_mode = WorkflowStepWorkspaceMode::AgentWorkspace;
+    let err = agentless
+        .validate_setup_invariants()
+        .expect_err("agent_workspace without agent must fail");
+    assert!(err
+        .to_string()
+        .contains("uses `workspace_mode: agent_workspace` but has no agent"));
+}
+
+fn foreach_orchestrator(foreach_extra: &str, extra: &str) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+{extra}
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: plan
+        type: command
+        command: [sh, -c, "echo '[\"alpha\", \"beta\", \"gamma\"]'"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/plan-summary.txt
+          stdout: out/plan-stdout.txt
+        next: each
+      - id: each
+        type: foreach
+        items: "{{{{steps.plan.outputs.stdout}}}}"
+        sequence: [implement, verify]
+        outputs: [summary, results]
+        output_files:
+          summary: out/each-summary.txt
+          results: out/each-results.json
+        next: report
+{foreach_extra}
+      - id: implement
+        type: command
+        command: [sh, -c, "echo built {{{{item}}}} at {{{{item_index}}}}"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/implement-summary.txt
+          stdout: out/implement-stdout.txt
+      - id: verify
+        type: command
+        command: [sh, -c, "echo verified {{{{steps.implement.outputs.stdout}}}}"]
+        outputs: [summary, exit_code]
+        output_files:
+          summary: out/verify-summary.txt
+          exit_code: out/verify-exit-code.txt
+      - id: report
+        type: command
+        command: [sh, -c, "echo reported"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/report-summary.txt
+          stdout: out/report-stdout.txt
+"#
+    ))
+    .expect("orchestrator")
+}
+
+#[test]
+fn foreach_step_runs_sequence_per_item_in_own_attempt_directories() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator = foreach_orchestrator("        max_concurrency: 2", "");
+    orchestrator
+        .validate_setup_invariants()
+        .expect("foreach step is valid");
+    store.create_run("run-each", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let run = engine.start("run-each", 11).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+    // plan + three items through two sequence steps + foreach + report
+    assert_eq!(run.total_iterations, 9);
+
+    for (index, item) in ["alpha", "beta", "gamma"].iter().enumerate() {
+        let attempt = index as u32 + 1 + index as u32 * 2;
+        let implement = store
+            .load_step_attempt("run-each", "implement", attempt)
+            .expect("implement attempt");
+        assert_eq!(implement.foreach_step_id.as_deref(), Some("each"));
+        assert_eq!(implement.item_index, Some(index));
+        assert_eq!(
+            implement.outputs["stdout"],
+            Value::from(format!("built {item} at {index}\n"))
+        );
+        let verify = store
+            .load_step_attempt("run-each", "verify", attempt)
+            .expect("verify attempt");
+        assert_eq!(verify.item_index, Some(index));
+        assert_eq!(verify.next_step_id, None);
+    }
+
+    let each = store
+        .load_step_attempt("run-each", "each", 1)
+        .expect("foreach attempt");
+    assert_eq!(each.next_step_id.as_deref(), Some("report"));
+    assert_eq!(
+        each.outputs["summary"],
+        Value::from("foreach completed 3 items through: implement, verify")
+    );
+    let results = each.outputs["results"].as_array().expect("results");
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["item"], Value::from("beta"));
+    assert_eq!(results[1]["item_index"], Value::from(1));
+    assert_eq!(
+        results[1]["outputs"]["stdout"],
+        Value::from("built beta at 1\n")
+    );
+    assert_eq!(results[1]["outputs"]["exit_code"], Value::from(0));
+    assert!(state_root
+        .join("workflows/runs/run-each/steps/each/attempts/1/foreach_items.json")
+        .is_file());
+    assert_eq!(
+        fs::read_to_string(
+            state_root.join("workflows/runs/run-each/steps/verify/attempts/7/command_stdout.txt")
+        )
+        .expect("verify stdout"),
+        "verified built gamma at 2\n"
+    );
+}
+
+#[test]
+fn foreach_step_fails_fast_when_items_exceed_iteration_budget() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator =
+        foreach_orchestrator("", "workflow_orchestration:\n  max_total_iterations: 6");
+    store.create_run("run-each-budget", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let err = engine
+        .start("run-each-budget", 11)
+        .expect_err("budget exceeded");
+    assert!(err.to_string().contains("max total iterations"), "{err}");
+    assert!(store
+        .load_step_attempt("run-each-budget", "implement", 1)
+        .is_err());
+}
+
+#[test]
+fn foreach_step_rejects_items_that_are_not_a_json_array() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let mut orchestrator = foreach_orchestrator("", "");
+    orchestrator.workflows[0].steps[0].command = vec![
+        "sh".to_string(),
+        "-c".to_string(),
+        "echo not-a-list".to_string(),
+    ];
+    store.create_run("run-each-bad", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let err = engine.start("run-each-bad", 11).expect_err("not a list");
+    assert!(
+        err.to_string()
+            .contains("foreach `items` must render to a JSON array"),
+        "{err}"
+    );
+}
+
+#[test]
+fn foreach_step_validation_rejects_routed_or_shared_sequence_steps() {
+    let mut routed = foreach_orchestrator("", "");
+    routed.workflows[0].steps[2].next = Some("report".to_string());
+    let err = routed
+        .validate_setup_invariants()
+        .expect_err("routed sequence step must fail");
+    assert!(err.to_string().contains("implement"), "{err}");
+
+    let mut shared = foreach_orchestrator("", "");
+    shared.workflows[0].steps[0].next = Some("implement".to_string());
+    let err = shared
+        .validate_setup_invariants()
+        .expect_err("sequence step targeted from outside must fail");
+    assert!(
+        err.to_string().contains("must only run inside the foreach"),
+        "{err}"
+    );
+
+    let mut missing = foreach_orchestrator("", "");
+    missing.workflows[0].steps[1].items = None;
+    let err = missing
+        .validate_setup_invariants()
+        .expect_err("missing items must fail");
+    assert!(err.to_string().contains("`items`"), "{err}");
+
+    let zero = foreach_orchestrator("        max_concurrency: 0", "");
+    let err = zero
+        .validate_setup_invariants()
+        .expect_err("zero concurrency must fail");
+    assert!(err.to_string().contains("max_concurrency"), "{err}");
+}
+
+fn workflow_call_orchestrator() -> OrchestratorConfig {
+    serde_yaml::from_str(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: release
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: release
+    version: 1
+    steps:
+      - id: plan
+        type: agent_task
+        agent: worker
+        prompt: plan
+        outputs: [summary, plan]
+        output_files:
+          summary: out/plan-summary.txt
+          plan: out/plan.md
+        next: deploy
+      - id: deploy
+        type: workflow_call
+        workflow: deploy
+        inputs:
+          target: "staging for {{steps.plan.outputs.plan}}"
+        outputs: [summary, artifact]
+        output_files:
+          summary: out/deploy-summary.txt
+          artifact: out/deploy-artifact.txt
+  - id: deploy
+    version: 1
+    steps:
+      - id: sign_off
+        type: human_review
+        prompt: "Deploy to {{inputs.target}}?"
+        outputs: [decision, summary, feedback]
+        output_files:
+          decision: out/decision.txt
+          summary: out/summary.txt
+          feedback: out/feedback.txt
+        on_approve: rollout
+        on_reject: sign_off
+      - id: rollout
+        type: agent_task
+        agent: worker
+        prompt: "roll out {{inputs.target}}"
+        outputs: [summary, artifact]
+        output_files:
+          summary: out/rollout-summary.txt
+          artifact: out/rollout-artifact.txt
+"#,
+    )
+    .expect("orchestrator")
+}
+
+#[test]
+fn workflow_call_step_runs_child_workflow_and_exposes_its_outputs() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator = workflow_call_orchestrator();
+    orchestrator
+        .validate_setup_invariants()
+        .expect("workflow_call step is valid");
+    store.create_run("run-rel", "release", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let parked = engine.start("run-rel", 11).expect("start");
+    assert_eq!(parked.state, RunState::Waiting);
+    assert_eq!(parked.current_step_id.as_deref(), Some("deploy"));
+    assert_eq!(parked.child_run_ids, vec!["run-rel-deploy-1".to_string()]);
+    let child = store.load_run("run-rel-deploy-1").expect("child run");
+    assert_eq!(child.workflow_id, "deploy");
+    assert_eq!(child.parent_run_id.as_deref(), Some("run-rel"));
+    assert_eq!(child.state, RunState::Waiting);
+    assert_eq!(child.inputs["target"], Value::from("staging for ok"));
+    assert_eq!(
+        find_pending_human_review_run(&store, "run-rel").expect("pending lookup"),
+        Some("run-rel-deploy-1".to_string())
+    );
+
+    let finished = engine
+        .submit_human_review("run-rel", true, None, None, 500)
+        .expect("approve child review");
+    assert_eq!(finished.state, RunState::Succeeded);
+    assert_eq!(
+        store.load_run("run-rel-deploy-1").expect("child").state,
+        RunState::Succeeded
+    );
+    let call = store
+        .load_step_attempt("run-rel", "deploy", 1)
+        .expect("workflow_call attempt");
+    assert_eq!(call.state, "succeeded");
+    assert_eq!(call.outputs["summary"], Value::from("ok"));
+    assert_eq!(call.outputs["artifact"], Value::from("ok"));
+    assert!(!call.outputs.contains_key("decision"));
+    let rollout_prompt = fs::read_to_string(
+        state_root.join("workflows/runs/run-rel-deploy-1/steps/rollout/attempts/1/prompt.md"),
+    )
+    .expect("child prompt");
+    assert!(rollout_prompt.contains("roll out staging for ok"));
+}
+
+#[test]
+fn workflow_call_cancelling_parent_cancels_children() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    store.create_run("run-rel", "release", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), workflow_call_orchestrator())
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let mut parent = engine.start("run-rel", 11).expect("start");
+    assert_eq!(parent.state, RunState::Waiting);
+
+    store
+        .cancel_run(&mut parent, 20, "canceled by command")
+        .expect("cancel");
+    assert_eq!(parent.state, RunState::Canceled);
+    let child = store.load_run("run-rel-deploy-1").expect("child");
+    assert_eq!(child.state, RunState::Canceled);
+    assert_eq!(
+        child.terminal_reason.as_deref(),
+        Some("parent run run-rel canceled")
+    );
+}
+
+#[test]
+fn workflow_call_validation_rejects_unknown_targets_and_cycles() {
+    let mut unknown = workflow_call_orchestrator();
+    unknown.workflows[0].steps[1].workflow = Some("missing".to_string());
+    let err = unknown
+        .validate_setup_invariants()
+        .expect_err("unknown workflow must fail");
+    assert!(err.to_string().contains("calls unknown workflow `missing`"));
+
+    let mut cycle = workflow_call_orchestrator();
+    let mut call_back = cycle.workflows[0].steps[1].clone();
+    call_back.id = "again".to_string();
+    call_back.workflow = Some("release".to_string());
+    cycle.workflows[1].steps[1].next = Some("again".to_string());
+    cycle.workflows[1].steps.push(call_back);
+    let err = cycle
+        .validate_setup_invariants()
+        .expect_err("call cycle must fail");
+    assert!(err
+        .to_string()
+        .contains("creates a workflow_call cycle: release -> deploy -> release"));
+}
+
+#[test]
+fn missing_transition_target_fails_run_with_explicit_error() {
+    let dir = tempdir().expect("tempdir");
+    let approve = dir.path().join("claude-approve");
+    write_script(
+        &approve,
+        "#!/bin/sh\necho '[workflow_result]{\"decision\":\"approve\"}[/workflow_result]'\n",
+    );
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: review
+        type: agent_review
+        agent: worker
+        prompt: review
+        outputs: [decision]
+        output_files:
+          decision: out/review-decision.txt
+        on_approve: missing_step
+        on_reject: rejected
+      - id: rejected
+        type: agent_task
+        agent: worker
+        prompt: rejected
+        outputs: [decision]
+        output_files:
+          decision: out/rejected-decision.txt
+"#,
+    )
+    .expect("orchestrator");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    store
+        .create_run("run-bad-transition", "wf", 1)
+        .expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(
+        RunnerBinaries::default()
+            .with("anthropic", approve.display().to_string())
+            .with("openai", "unused"),
+    );
+
+    let err = engine
+        .start("run-bad-transition", 2)
+        .expect_err("must fail on invalid transition");
+    assert!(err.to_string().contains("transition validation failed"));
+    let run = store.load_run("run-bad-transition").expect("run");
+    assert_eq!(run.state, RunState::Failed);
+}
+
+#[test]
+fn step_provider_failures_persist_invocation_logs_and_fail_run() {
+    let dir = tempdir().expect("tempdir");
+    let non_zero = dir.path().join("claude-fail");
+    write_script(&non_zero, "#!/bin/sh\necho boom 1>&2\nexit 7\n");
+    let timeout = dir.path().join("claude-timeout");
+    write_script(&timeout, "#!/bin/sh\nsleep 1\necho late\n");
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: s1
+        type: agent_task
+        agent: worker
+        prompt: test
+        outputs: [result]
+        output_files:
+          result: out/s1-result.txt
+        limits:
+          max_retries: 0
+workflow_orchestration:
+  default_step_timeout_seconds: 0
+  max_step_timeout_seconds: 0
+"#,
+    )
+    .expect("orchestrator");
+
+    for (name, binary) in [("run-fail", non_zero), ("run-timeout", timeout)] {
+        let state_root = dir.path().join(format!(".direclaw-{name}"));
+        bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+        let store = WorkflowRunStore::new(&state_root);
+        store.create_run(name, "wf", 1).expect("run");
+        let engine = WorkflowEngine::new(store.clone(), orchestrator.clone()).with_runner_binaries(
+            RunnerBinaries::default()
+                .with("anthropic", binary.display().to_string())
+                .with("openai", "unused"),
+        );
+        let err = engine.start(name, 2).expect_err("must fail");
+        if name == "run-timeout" {
+            assert!(err.to_string().contains("step timed out"));
+        }
+        let run = store.load_run(name).expect("load run");
+        assert_eq!(run.state, RunState::Failed);
+        if name == "run-timeout" {
+            assert!(run
+                .terminal_reason
+                .as_deref()
+                .unwrap_or_default()
+                .contains("step timed out"));
+        }
+        let log_path = state_root.join(format!(
+            "workflows/runs/{name}/steps/s1/attempts/1/provider_invocation.json"
+        ));
+        assert!(log_path.is_file());
+    }
+}
+
+#[test]
+fn legacy_agent_private_workspace_field_is_rejected_in_orchestrator_yaml() {
+    let dir = tempdir().expect("tempdir");
+    let denied_agent_workspace = dir.path().join("outside-agent-workspace");
+    let parse_err = serde_yaml::from_str::<OrchestratorConfig>(&format!(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  worker:
+    provider: anthropic
+    model: sonnet
+    private_workspace: {}
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: s1
+        type: agent_task
+        agent: worker
+        prompt: test
+        outputs: [result]
+        output_files:
+          result: out/s1-result.txt
+"#,
+        denied_agent_workspace.display()
+    ))
+    .expect_err("legacy field must be rejected");
+    assert!(parse_err.to_string().contains("unknown field"));
+    assert!(parse_err.to_string().contains("private_workspace"));
 }
 
 #[test]
@@ -1953,3 +3169,317 @@ fn workflow_result_envelope_rejects_multiple_blocks() {
     let err = parse_workflow_result_envelope(raw).expect_err("multiple envelopes should fail");
     assert!(err.to_string().contains("multiple [workflow_result]"));
 }
+
+#[test]
+fn canceling_run_kills_in_flight_command_attempt() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let orchestrator: OrchestratorConfig = serde_yaml::from_str(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: wf
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+workflows:
+  - id: wf
+    version: 1
+    steps:
+      - id: build
+        type: command
+        command: [sh, -c, "sleep 30"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/build-summary.txt
+          stdout: out/build-stdout.txt
+"#,
+    )
+    .expect("orchestrator");
+    store.create_run("run-cancel", "wf", 10).expect("run");
+    let engine = WorkflowEngine::new(store.clone(), orchestrator)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+
+    let canceler = {
+        let store = store.clone();
+        std::thread::spawn(move || {
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while Instant::now() < deadline {
+                let run = store.load_run("run-cancel").expect("load run");
+                if run.current_step_id.is_some() {
+                    let mut run = run;
+                    store
+                        .cancel_run(&mut run, 20, "canceled by command")
+                        .expect("cancel run");
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            panic!("step never started");
+        })
+    };
+    let started = Instant::now();
+    let run = engine.start("run-cancel", 11).expect("start");
+    canceler.join().expect("join canceler");
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(run.state, RunState::Canceled);
+    assert_eq!(
+        run.terminal_reason.as_deref(),
+        Some("canceled by command; step build attempt 1 canceled; in-flight process killed")
+    );
+    let attempt = store
+        .load_step_attempt("run-cancel", "build", 1)
+        .expect("canceled attempt");
+    assert_eq!(attempt.state, "canceled");
+    assert!(attempt.output_files.is_empty());
+    assert!(!state_root
+        .join("workflows/runs/run-cancel/steps/build/attempts/1/out/build-summary.txt")
+        .exists());
+}
+
+#[test]
+fn provider_usage_is_recorded_on_attempts_and_rolled_up_per_run_and_agent() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+
+    let codex = dir.path().join("codex-usage");
+    write_script(
+        &codex,
+        "#!/bin/sh\necho '{\"type\":\"turn.completed\",\"usage\":{\"input_tokens\":100,\"cached_input_tokens\":40,\"output_tokens\":20}}'\necho '{\"type\":\"item.completed\",\"item\":{\"type\":\"agent_message\",\"text\":\"[workflow_result]{\\\"decision\\\":\\\"approve\\\",\\\"summary\\\":\\\"ok\\\",\\\"feedback\\\":\\\"none\\\",\\\"plan\\\":\\\"ok\\\",\\\"artifact\\\":\\\"ok\\\"}[/workflow_result]\"}}'\n",
+    );
+    let mut binaries = mock_runner_binaries(dir.path());
+    binaries.set("openai", codex.display().to_string());
+
+    store
+        .create_run("run-usage", "fix_issue", 10)
+        .expect("create run");
+    let engine =
+        WorkflowEngine::new(store.clone(), sample_orchestrator()).with_runner_binaries(binaries);
+    let run = engine.start("run-usage", 11).expect("start");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let plan = store
+        .load_step_attempt("run-usage", "plan", 1)
+        .expect("plan attempt");
+    let usage = plan.usage.expect("plan usage");
+    assert_eq!(usage.agent_id, "worker");
+    assert_eq!(usage.provider, "openai");
+    assert_eq!(usage.model, "gpt-5.3-codex-spark");
+    assert_eq!(usage.tokens.input_tokens, 100);
+    assert_eq!(usage.tokens.cache_read_tokens, 40);
+    assert_eq!(usage.tokens.output_tokens, 20);
+
+    let rollup = run_usage(&store, "run-usage").expect("rollup");
+    assert_eq!(rollup.attempts, 3);
+    assert_eq!(rollup.total.input_tokens, 300);
+    assert_eq!(rollup.total.output_tokens, 60);
+    assert_eq!(rollup.by_agent["worker"].cache_read_tokens, 120);
+}
+
+fn fan_out_backoff_orchestrator(marker: &Path) -> OrchestratorConfig {
+    serde_yaml::from_str(&format!(
+        r#"
+id: engineering_orchestrator
+selector_agent: workflow_router
+default_workflow: fan_out
+selection_max_retries: 1
+agents:
+  workflow_router:
+    provider: anthropic
+    model: sonnet
+    can_orchestrate_workflows: true
+  steady:
+    provider: anthropic
+    model: sonnet
+  flaky:
+    provider: openai
+    model: gpt-5.2
+workflow_orchestration:
+  retry:
+    initial_backoff_seconds: 30
+workflows:
+  - id: fan_out
+    version: 1
+    steps:
+      - id: checks
+        type: parallel
+        branches: [lint, flaky]
+        outputs: [summary]
+        output_files:
+          summary: out/checks-summary.txt
+        next: done
+      - id: lint
+        type: agent_task
+        agent: steady
+        prompt: lint
+        outputs: [summary]
+        output_files:
+          summary: out/lint-summary.txt
+      - id: flaky
+        type: agent_task
+        agent: flaky
+        prompt: flaky check
+        outputs: [summary]
+        output_files:
+          summary: out/flaky-summary.txt
+      - id: done
+        type: command
+        command: [sh, -c, "echo done"]
+        outputs: [summary]
+        output_files:
+          summary: out/done-summary.txt
+  - id: each
+    version: 1
+    steps:
+      - id: each
+        type: foreach
+        items: '["alpha", "beta"]'
+        sequence: [implement]
+        outputs: [summary]
+        output_files:
+          summary: out/each-summary.txt
+        next: report
+      - id: implement
+        type: command
+        command: [sh, -c, "test -f {marker} || {{ touch {marker}; exit 4; }}"]
+        outputs: [summary]
+        output_files:
+          summary: out/implement-summary.txt
+      - id: report
+        type: command
+        command: [sh, -c, "echo reported"]
+        outputs: [summary]
+        output_files:
+          summary: out/report-summary.txt
+"#,
+        marker = marker.display()
+    ))
+    .expect("orchestrator")
+}
+
+#[test]
+fn fan_out_retries_park_for_backoff_until_retry_at() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    let orchestrator = fan_out_backoff_orchestrator(&dir.path().join("implement-failed-once"));
+    orchestrator
+        .validate_setup_invariants()
+        .expect("fan-out workflows are valid");
+    let mut binaries = mock_runner_binaries(dir.path());
+    // The first codex invocation fails; later ones answer.
+    let codex = dir.path().join("codex-flaky");
+    write_script(
+        &codex,
+        &format!(
+            "#!/bin/sh\ntest -f {marker} || {{ touch {marker}; echo 'connection reset' >&2; exit 4; }}\nexec {mock} \"$@\"\n",
+            marker = dir.path().join("codex-failed-once").display(),
+            mock = binaries.get("openai").expect("mock codex"),
+        ),
+    );
+    binaries.set("openai", codex.display().to_string());
+    let engine = WorkflowEngine::new(store.clone(), orchestrator).with_runner_binaries(binaries);
+
+    for (run_id, workflow_id, retried_step, retried_attempt) in [
+        ("run-parallel", "fan_out", "flaky", 2),
+        ("run-foreach", "each", "implement", 2),
+    ] {
+        store.create_run(run_id, workflow_id, 10).expect("run");
+        let run = engine.start(run_id, 10).expect("start parks run");
+        assert_eq!(run.state, RunState::Waiting, "{run_id}");
+        let retry_at = run.retry_at.expect("retry_at");
+        assert!(retry_at >= 41, "{run_id}: {retry_at}");
+        let scheduled = store
+            .load_run_events(run_id, 0)
+            .expect("events")
+            .into_iter()
+            .filter_map(|event| match event.kind {
+                RunEventKind::RetryScheduled {
+                    step_id,
+                    attempt,
+                    retry_at,
+                    ..
+                } => Some((step_id, attempt, retry_at)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scheduled,
+            vec![(retried_step.to_string(), retried_attempt, retry_at)],
+            "{run_id}"
+        );
+
+        // Waking the run before its backoff has passed does not retry early.
+        let early = engine.resume(run_id, retry_at - 1).expect("early resume");
+        assert_eq!(early.state, RunState::Waiting, "{run_id}");
+        assert_eq!(early.retry_at, Some(retry_at), "{run_id}");
+        assert!(store
+            .load_step_attempt(run_id, retried_step, retried_attempt)
+            .is_err());
+
+        let run = engine.resume(run_id, retry_at).expect("resume when due");
+        assert_eq!(run.state, RunState::Succeeded, "{run_id}");
+        assert_eq!(run.retry_at, None);
+        assert_eq!(
+            store
+                .load_step_attempt(run_id, retried_step, retried_attempt)
+                .expect("retried attempt")
+                .state,
+            "succeeded"
+        );
+    }
+}
+
+#[test]
+fn foreach_item_attempts_keep_their_layout_when_retry_config_changes_while_parked() {
+    let dir = tempdir().expect("tempdir");
+    let state_root = dir.path().join(".direclaw");
+    bootstrap_state_root(&StatePaths::new(&state_root)).expect("bootstrap");
+    let store = WorkflowRunStore::new(&state_root);
+    let orchestrator = fan_out_backoff_orchestrator(&dir.path().join("implement-failed-once"));
+    let engine = WorkflowEngine::new(store.clone(), orchestrator.clone())
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    store.create_run("run-each", "each", 10).expect("run");
+    let run = engine.start("run-each", 10).expect("start parks run");
+    assert_eq!(run.state, RunState::Waiting);
+    let strides: BTreeMap<String, u32> = serde_json::from_str(
+        &fs::read_to_string(
+            state_root.join("workflows/runs/run-each/steps/each/attempts/1/foreach_strides.json"),
+        )
+        .expect("strides file"),
+    )
+    .expect("strides json");
+    let stride = strides["implement"];
+
+    let mut raised = orchestrator;
+    raised.workflows[1].steps[1].retry = Some(RetryPolicyConfig {
+        max_retries: BTreeMap::from([(RetryErrorClass::NonZeroExit, stride + 4)]),
+        ..RetryPolicyConfig::default()
+    });
+    let engine = WorkflowEngine::new(store.clone(), raised)
+        .with_runner_binaries(mock_runner_binaries(dir.path()));
+    let run = engine
+        .resume("run-each", run.retry_at.expect("retry_at"))
+        .expect("resume");
+    assert_eq!(run.state, RunState::Succeeded);
+
+    let alpha = store
+        .load_step_attempt("run-each", "implement", 2)
+        .expect("alpha retry");
+    assert_eq!(alpha.item_index, Some(0));
+    let beta = store
+        .load_step_attempt("run-each", "implement", stride + 1)
+        .expect("beta first try");
+    assert_eq!(beta.item_index, Some(1));
+    assert_eq!(beta.state, "succeeded");
+}
@@ -0,0 +1,203 @@
+use direclaw::provider::anthropic::AnthropicBackend;
+use direclaw::provider::{
+    builtin_registry, CancellationToken, ProviderBackend, ProviderError, ProviderKind,
+    ProviderRegistry, ProviderRequest, ProviderUsage, RunnerBinaries,
+};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+fn sample_request(provider: ProviderKind, cwd: &Path) -> ProviderRequest {
+    ProviderRequest {
+        agent_id: "agent-1".to_string(),
+        provider,
+        model: "sonnet".to_string(),
+        cwd: cwd.to_path_buf(),
+        message: "use files".to_string(),
+        prompt_artifacts: direclaw::provider::write_file_backed_prompt(
+            cwd, "req-1", "prompt", "ctx",
+        )
+        .expect("prompt artifacts"),
+        timeout: Duration::from_secs(1),
+        reset_requested: false,
+        fresh_on_failure: false,
+        env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
+    }
+}
+
+/// Stand-in backend that replaces `anthropic` with a plain-text echo CLI.
+struct EchoBackend;
+
+impl ProviderBackend for EchoBackend {
+    fn id(&self) -> &'static str {
+        "anthropic"
+    }
+
+    fn default_binary(&self) -> Option<&'static str> {
+        Some("echo")
+    }
+
+    fn binary_env_var(&self) -> Option<&'static str> {
+        Some("DIRECLAW_PROVIDER_BIN_ECHO")
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "install echo"
+    }
+
+    fn invocation_args(
+        &self,
+        request: &ProviderRequest,
+        model: &str,
+        resume_session: Option<&str>,
+    ) -> Vec<String> {
+        vec![
+            model.to_string(),
+            resume_session.unwrap_or("fresh").to_string(),
+            request.message.clone(),
+        ]
+    }
+
+    fn parse_output(&self, stdout: &str) -> Result<String, ProviderError> {
+        Ok(stdout.trim().to_string())
+    }
+
+    fn parse_usage(&self, _stdout: &str) -> Option<ProviderUsage> {
+        None
+    }
+}
+
+#[test]
+fn backend_module_registers_builtin_providers_by_id() {
+    let registry = builtin_registry();
+    assert_eq!(
+        registry.ids().collect::<Vec<_>>(),
+        vec!["anthropic", "gemini", "openai", "openai_compatible"]
+    );
+    assert_eq!(registry.get(" OpenAI ").expect("openai").id(), "openai");
+    assert!(matches!(
+        registry.get("mistral"),
+        Err(ProviderError::UnknownProvider(provider)) if provider == "mistral"
+    ));
+    assert_eq!(
+        registry
+            .backend_for(&ProviderKind::ANTHROPIC)
+            .expect("anthropic")
+            .resolve_model("opus")
+            .expect("model"),
+        "claude-opus-4-6"
+    );
+}
+
+#[test]
+fn backend_module_builds_invocations_with_session_continuation() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let backend = builtin_registry()
+        .get("anthropic")
+        .expect("anthropic backend");
+    let mut request = sample_request(ProviderKind::ANTHROPIC, dir.path());
+
+    let fresh = backend
+        .build_invocation(&request, &RunnerBinaries::default())
+        .expect("build");
+    assert_eq!(fresh.binary, "claude");
+    assert_eq!(fresh.resolved_model, "claude-sonnet-4-5");
+    assert!(!fresh.args.contains(&"--resume".to_string()));
+
+    request.session_id = Some("sess-1".to_string());
+    let resumed = backend
+        .build_invocation(&request, &RunnerBinaries::default())
+        .expect("build resumed");
+    assert!(resumed
+        .args
+        .windows(2)
+        .any(|pair| pair[0] == "--resume" && pair[1] == "sess-1"));
+    assert!(!resumed.args.contains(&"-c".to_string()));
+
+    request.fresh_on_failure = true;
+    assert!(!backend.continues_session(&request));
+    let retried = backend
+        .build_invocation(&request, &RunnerBinaries::default())
+        .expect("build fresh");
+    assert!(!retried.args.contains(&"--resume".to_string()));
+
+    request.model = "gpt-4.1".to_string();
+    assert!(matches!(
+        backend.build_invocation(&request, &RunnerBinaries::default()),
+        Err(ProviderError::UnsupportedAnthropicModel(_))
+    ));
+}
+
+#[test]
+fn backend_module_parses_output_and_usage_per_backend() {
+    let registry = builtin_registry();
+    let openai = registry.get("openai").expect("openai");
+    let jsonl = concat!(
+        r#"{"type":"item.completed","item":{"type":"agent_message","text":"done"}}"#,
+        "\n",
+        r#"{"type":"turn.completed","usage":{"input_tokens":4,"output_tokens":2}}"#,
+        "\n"
+    );
+    assert_eq!(openai.parse_output(jsonl).expect("message"), "done");
+    assert_eq!(
+        openai.parse_usage(jsonl).map(|usage| usage.output_tokens),
+        Some(2)
+    );
+
+    let anthropic = registry.get("anthropic").expect("anthropic");
+    assert_eq!(
+        anthropic.parse_output("plain reply\n").expect("plain"),
+        "plain reply"
+    );
+    assert_eq!(anthropic.parse_usage("plain reply"), None);
+
+    let gemini = registry.get("gemini").expect("gemini");
+    let json = r#"{"response":"hi","stats":{"models":{"gemini-2.5-flash":{"tokens":{"prompt":3,"candidates":1}}}}}"#;
+    assert_eq!(gemini.parse_output(json).expect("response"), "hi");
+    assert_eq!(
+        gemini.parse_usage(json).map(|usage| usage.input_tokens),
+        Some(3)
+    );
+}
+
+#[test]
+fn backend_module_registration_replaces_backends_and_checks_health() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let mut registry = ProviderRegistry::builtin();
+    let replaced = registry.register(Arc::new(EchoBackend));
+    assert_eq!(replaced.expect("replaced anthropic").id(), "anthropic");
+
+    let backend = registry.get("anthropic").expect("echo backend");
+    let spec = backend
+        .build_invocation(
+            &sample_request(ProviderKind::ANTHROPIC, dir.path()),
+            &RunnerBinaries::default(),
+        )
+        .expect("build");
+    assert_eq!(spec.binary, "echo");
+    assert_eq!(spec.args, vec!["sonnet", "fresh", "use files"]);
+    let overridden = backend
+        .build_invocation(
+            &sample_request(ProviderKind::ANTHROPIC, dir.path()),
+            &RunnerBinaries::default().with("anthropic", "/opt/bin/echo"),
+        )
+        .expect("build with override");
+    assert_eq!(overridden.binary, "/opt/bin/echo");
+
+    let missing = RunnerBinaries::default().with(
+        "anthropic",
+        dir.path().join("missing-claude").display().to_string(),
+    );
+    let health = AnthropicBackend.health_check(&missing);
+    assert!(!health.ok);
+    assert!(health.detail.ends_with("missing-claude"));
+    assert_eq!(
+        health.remediation,
+        "install the Anthropic CLI or set DIRECLAW_PROVIDER_BIN_ANTHROPIC"
+    );
+}
@@ -1,5 +1,5 @@
 use direclaw::provider::invocation::build_invocation;
-use direclaw::provider::{ProviderKind, ProviderRequest, RunnerBinaries};
+use direclaw::provider::{CancellationToken, ProviderKind, ProviderRequest, RunnerBinaries};
 use std::collections::BTreeMap;
 use std::path::Path;
 use std::time::Duration;
@@ -19,18 +19,23 @@ fn sample_request(provider: ProviderKind, cwd: &Path) -> ProviderRequest {
         reset_requested: false,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
     }
 }
 
 #[test]
 fn invocation_module_builds_openai_resume_args() {
     let dir = tempfile::tempdir().expect("tempdir");
-    let mut req = sample_request(ProviderKind::OpenAi, dir.path());
+    let mut req = sample_request(ProviderKind::OPENAI, dir.path());
     req.model = "gpt-5.3-codex-spark".to_string();
+    req.session_id = Some("thread-1".to_string());
 
     let spec = build_invocation(&req, &RunnerBinaries::default()).expect("build");
     assert_eq!(spec.binary, "codex");
-    assert!(spec.args.contains(&"resume".to_string()));
+    assert_eq!(&spec.args[..3], ["exec", "resume", "thread-1"]);
     assert!(spec.args.contains(&"--full-auto".to_string()));
     assert!(!spec.args.contains(&"--sandbox".to_string()));
     assert!(!spec.args.contains(&"workspace-write".to_string()));
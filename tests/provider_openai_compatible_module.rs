@@ -0,0 +1,250 @@
+use direclaw::provider::runner::run_provider;
+use direclaw::provider::{
+    builtin_registry, write_file_backed_prompt, CancellationToken, ProviderEndpoint, ProviderError,
+    ProviderKind, ProviderRequest, RunnerBinaries,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tempfile::tempdir;
+
+struct CapturedRequest {
+    request_line: String,
+    headers: Vec<String>,
+    body: Value,
+}
+
+/// Serves one request with `status_line` and `response_body`, handing the
+/// parsed request back over the returned channel.
+fn serve_once(status_line: &str, response_body: &str) -> (String, mpsc::Receiver<CapturedRequest>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind test server");
+    let addr = listener.local_addr().expect("local addr");
+    let status_line = status_line.to_string();
+    let response_body = response_body.to_string();
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().expect("accept");
+        let mut reader = BufReader::new(stream.try_clone().expect("clone stream"));
+        let mut request_line = String::new();
+        reader
+            .read_line(&mut request_line)
+            .expect("read request line");
+        let mut headers = Vec::new();
+        let mut content_length = 0usize;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).expect("read header line");
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+            let line = line.trim_end().to_string();
+            if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                content_length = value.trim().parse().expect("content length");
+            }
+            headers.push(line);
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).expect("read body");
+
+        let response = format!(
+            "HTTP/1.1 {status_line}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response_body.len(),
+            response_body
+        );
+        stream
+            .write_all(response.as_bytes())
+            .expect("write response");
+        let _ = sender.send(CapturedRequest {
+            request_line,
+            headers,
+            body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+        });
+    });
+
+    (format!("http://{addr}/v1/"), receiver)
+}
+
+fn chat_request(cwd: &Path, endpoint: Option<ProviderEndpoint>) -> ProviderRequest {
+    ProviderRequest {
+        agent_id: "summarizer".to_string(),
+        provider: ProviderKind::OPENAI_COMPATIBLE,
+        model: "qwen2.5-7b-instruct".to_string(),
+        cwd: cwd.to_path_buf(),
+        message: "unused".to_string(),
+        prompt_artifacts: write_file_backed_prompt(
+            cwd,
+            "req-chat",
+            "Summarize the change.",
+            "diff --git a/x b/x",
+        )
+        .expect("artifacts"),
+        timeout: Duration::from_secs(5),
+        reset_requested: false,
+        fresh_on_failure: false,
+        env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint,
+        session_id: None,
+        fallbacks: Vec::new(),
+    }
+}
+
+#[test]
+fn openai_compatible_module_sends_prompt_files_as_chat_messages() {
+    let dir = tempdir().expect("tempdir");
+    let (base_url, captured) = serve_once(
+        "200 OK",
+        r#"{"choices":[{"message":{"role":"assistant","content":" short summary "}}],"usage":{"prompt_tokens":40,"completion_tokens":6,"prompt_tokens_details":{"cached_tokens":10}}}"#,
+    );
+    let mut request = chat_request(
+        dir.path(),
+        Some(ProviderEndpoint {
+            base_url,
+            api_key_env: Some("DIRECLAW_TEST_CHAT_KEY".to_string()),
+        }),
+    );
+    request
+        .env_overrides
+        .insert("DIRECLAW_TEST_CHAT_KEY".to_string(), "sk-local".to_string());
+
+    let result = run_provider(&request, &RunnerBinaries::default()).expect("chat run");
+    assert_eq!(result.message, "short summary");
+    assert_eq!(result.log.exit_code, Some(200));
+    assert!(result
+        .log
+        .command_form
+        .starts_with("POST http://127.0.0.1:"));
+    assert!(result.log.command_form.ends_with("/v1/chat/completions"));
+    let usage = result.log.usage.expect("usage");
+    assert_eq!(usage.input_tokens, 30);
+    assert_eq!(usage.cache_read_tokens, 10);
+    assert_eq!(usage.output_tokens, 6);
+
+    let captured = captured
+        .recv_timeout(Duration::from_secs(5))
+        .expect("captured request");
+    assert!(captured
+        .request_line
+        .starts_with("POST /v1/chat/completions "));
+    assert!(captured
+        .headers
+        .iter()
+        .any(|header| header == "Authorization: Bearer sk-local"));
+    assert_eq!(captured.body["model"], "qwen2.5-7b-instruct");
+    assert_eq!(
+        captured.body["messages"],
+        serde_json::json!([
+            {"role": "system", "content": "diff --git a/x b/x"},
+            {"role": "user", "content": "Summarize the change."}
+        ])
+    );
+}
+
+#[test]
+fn openai_compatible_module_maps_http_failures_and_missing_config() {
+    let dir = tempdir().expect("tempdir");
+    let (base_url, _captured) = serve_once(
+        "429 Too Many Requests",
+        r#"{"error":{"message":"slow down"}}"#,
+    );
+    let request = chat_request(
+        dir.path(),
+        Some(ProviderEndpoint {
+            base_url: base_url.clone(),
+            api_key_env: None,
+        }),
+    );
+    let err = run_provider(&request, &RunnerBinaries::default()).expect_err("429 fails");
+    assert!(err.is_rate_limited(), "{err}");
+    assert!(matches!(
+        &err,
+        ProviderError::NonZeroExit { exit_code: 429, stderr, stdout, log, .. }
+            if stderr == "http status 429"
+                && stdout.contains("slow down")
+                && log.exit_code == Some(429)
+    ));
+
+    let missing_endpoint = chat_request(dir.path(), None);
+    assert!(matches!(
+        run_provider(&missing_endpoint, &RunnerBinaries::default()),
+        Err(ProviderError::MissingEndpoint { .. })
+    ));
+
+    let missing_key = chat_request(
+        dir.path(),
+        Some(ProviderEndpoint {
+            base_url,
+            api_key_env: Some("DIRECLAW_TEST_UNSET_CHAT_KEY".to_string()),
+        }),
+    );
+    assert!(matches!(
+        run_provider(&missing_key, &RunnerBinaries::default()),
+        Err(ProviderError::MissingApiKey { env_var, .. }) if env_var == "DIRECLAW_TEST_UNSET_CHAT_KEY"
+    ));
+}
+
+#[test]
+fn openai_compatible_module_health_checks_endpoint_models_and_key() {
+    let backend = builtin_registry()
+        .get("openai_compatible")
+        .expect("backend");
+    let (base_url, captured) = serve_once("200 OK", r#"{"data":[]}"#);
+    std::env::set_var("DIRECLAW_TEST_HEALTH_CHAT_KEY", "sk-health");
+    let health = backend
+        .endpoint_health_check(&ProviderEndpoint {
+            base_url: base_url.clone(),
+            api_key_env: Some("DIRECLAW_TEST_HEALTH_CHAT_KEY".to_string()),
+        })
+        .expect("endpoint check");
+    assert!(health.ok, "{}", health.detail);
+    assert!(
+        health.detail.ends_with("/v1/models status=200"),
+        "{}",
+        health.detail
+    );
+    let request = captured
+        .recv_timeout(Duration::from_secs(5))
+        .expect("captured request");
+    assert!(request.request_line.starts_with("GET /v1/models "));
+    assert!(request
+        .headers
+        .iter()
+        .any(|header| header == "Authorization: Bearer sk-health"));
+
+    let (base_url, _captured) = serve_once("401 Unauthorized", "{}");
+    let health = backend
+        .endpoint_health_check(&ProviderEndpoint {
+            base_url,
+            api_key_env: None,
+        })
+        .expect("endpoint check");
+    assert!(!health.ok);
+    assert!(health.detail.ends_with("status=401"), "{}", health.detail);
+
+    let health = backend
+        .endpoint_health_check(&ProviderEndpoint {
+            base_url: "http://127.0.0.1:9/v1".to_string(),
+            api_key_env: Some("DIRECLAW_TEST_UNSET_CHAT_KEY".to_string()),
+        })
+        .expect("endpoint check");
+    assert!(!health.ok);
+    assert_eq!(
+        health.detail,
+        "api_key_env=DIRECLAW_TEST_UNSET_CHAT_KEY is not set"
+    );
+
+    assert!(builtin_registry()
+        .get("anthropic")
+        .expect("anthropic")
+        .endpoint_health_check(&ProviderEndpoint {
+            base_url: "http://127.0.0.1:9/v1".to_string(),
+            api_key_env: None,
+        })
+        .is_none());
+}
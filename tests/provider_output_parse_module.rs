@@ -1,4 +1,10 @@
-use direclaw::provider::output_parse::parse_openai_jsonl;
+use direclaw::provider::output_parse::{
+    parse_anthropic_error, parse_anthropic_session_id, parse_chat_completion_output,
+    parse_chat_completion_usage, parse_gemini_error, parse_gemini_output, parse_gemini_session_id,
+    parse_gemini_usage, parse_openai_error, parse_openai_jsonl, parse_openai_session_id,
+    parse_openai_usage,
+};
+use direclaw::provider::ProviderError;
 
 #[test]
 fn output_parse_module_reads_last_openai_agent_message() {
@@ -10,3 +16,201 @@ fn output_parse_module_reads_last_openai_agent_message() {
     let parsed = parse_openai_jsonl(data).expect("parsed jsonl");
     assert_eq!(parsed, "second");
 }
+
+#[test]
+fn output_parse_module_sums_openai_turn_usage() {
+    let data = r#"
+{"type":"turn.completed","usage":{"input_tokens":10,"cached_input_tokens":4,"output_tokens":2}}
+not json
+{"type":"item.completed","item":{"type":"agent_message","text":"done"}}
+{"type":"turn.completed","usage":{"input_tokens":5,"cached_input_tokens":1,"output_tokens":3}}
+"#;
+
+    let usage = parse_openai_usage(data).expect("usage");
+    assert_eq!(usage.input_tokens, 15);
+    assert_eq!(usage.cache_read_tokens, 5);
+    assert_eq!(usage.output_tokens, 5);
+    assert_eq!(usage.cost_usd_micros, 0);
+    assert_eq!(
+        parse_openai_usage(r#"{"type":"item.completed","item":{}}"#),
+        None
+    );
+}
+
+#[test]
+fn output_parse_module_reads_gemini_json_response() {
+    let data = r#"Loaded cached credentials.
+{
+  "response": "  done  ",
+  "stats": {}
+}
+"#;
+    assert_eq!(parse_gemini_output(data).expect("response"), "done");
+    assert_eq!(
+        parse_gemini_output(
+            "Loaded cached credentials.\r\nnotice é\r\n{\"response\":\"done\"}\r\n"
+        )
+        .expect("crlf response"),
+        "done"
+    );
+    assert_eq!(
+        parse_gemini_output("plain reply\n").expect("plain"),
+        "plain reply"
+    );
+
+    let error = parse_gemini_output(r#"{"error":{"type":"ApiError","message":"quota exceeded"}}"#)
+        .expect_err("error object");
+    assert!(matches!(
+        &error,
+        ProviderError::ParseFailure { reason, .. } if reason.contains("quota exceeded")
+    ));
+    assert!(parse_gemini_output(" \n").is_err());
+}
+
+#[test]
+fn output_parse_module_sums_gemini_model_stats() {
+    let data = r#"{
+  "response": "done",
+  "stats": {
+    "models": {
+      "gemini-2.5-pro": {
+        "api": {"totalRequests": 2, "totalLatencyMs": 1200},
+        "tokens": {"prompt": 100, "candidates": 20, "cached": 30, "total": 120}
+      },
+      "gemini-2.5-flash": {
+        "api": {"totalRequests": 1, "totalLatencyMs": 300},
+        "tokens": {"prompt": 10, "candidates": 5, "total": 15}
+      }
+    }
+  }
+}"#;
+
+    let usage = parse_gemini_usage(data).expect("usage");
+    assert_eq!(usage.input_tokens, 80);
+    assert_eq!(usage.cache_read_tokens, 30);
+    assert_eq!(usage.output_tokens, 25);
+    assert_eq!(usage.duration_ms, 1500);
+    assert_eq!(usage.cost_usd_micros, 0);
+    assert_eq!(parse_gemini_usage(r#"{"response":"done"}"#), None);
+}
+
+#[test]
+fn output_parse_module_reads_chat_completion_choices_and_usage() {
+    let data = r#"{"choices":[{"message":{"role":"assistant","content":[{"type":"text","text":"fix: "},{"type":"text","text":"typo"}]}}],"usage":{"prompt_tokens":12,"completion_tokens":3}}"#;
+
+    assert_eq!(
+        parse_chat_completion_output(data).expect("content"),
+        "fix: typo"
+    );
+    let usage = parse_chat_completion_usage(data).expect("usage");
+    assert_eq!(usage.input_tokens, 12);
+    assert_eq!(usage.output_tokens, 3);
+    assert_eq!(usage.cache_read_tokens, 0);
+
+    assert!(parse_chat_completion_output(r#"{"choices":[]}"#).is_err());
+    assert!(parse_chat_completion_output("not json").is_err());
+    assert_eq!(parse_chat_completion_usage(r#"{"choices":[]}"#), None);
+}
+
+#[test]
+fn output_parse_module_reads_provider_session_ids() {
+    assert_eq!(
+        parse_anthropic_session_id(r#"{"type":"result","session_id":"c0ffee","result":"done"}"#),
+        Some("c0ffee".to_string())
+    );
+    assert_eq!(parse_anthropic_session_id("plain reply"), None);
+
+    let codex = r#"
+{"type":"thread.started","thread_id":"0199a213-81c0-7800-8aa1-bbab2a035a53"}
+{"type":"item.completed","item":{"type":"agent_message","text":"done"}}
+"#;
+    assert_eq!(
+        parse_openai_session_id(codex),
+        Some("0199a213-81c0-7800-8aa1-bbab2a035a53".to_string())
+    );
+    assert_eq!(
+        parse_openai_session_id(r#"{"type":"turn.completed","usage":{}}"#),
+        None
+    );
+
+    assert_eq!(
+        parse_gemini_session_id(
+            "Loaded cached credentials.\n{\"session_id\":\"g-1\",\"response\":\"hi\"}"
+        ),
+        Some("g-1".to_string())
+    );
+    assert_eq!(
+        parse_gemini_session_id(r#"{"session_id":" ","response":"hi"}"#),
+        None
+    );
+}
+
+#[test]
+fn output_parse_module_reads_anthropic_error_results() {
+    assert_eq!(
+        parse_anthropic_error(
+            r#"{"type":"result","is_error":true,"result":" usage limit reached "}"#
+        ),
+        Some("usage limit reached".to_string())
+    );
+    assert_eq!(
+        parse_anthropic_error(r#"{"type":"result","is_error":true}"#),
+        Some("claude reported an error result".to_string())
+    );
+    assert_eq!(
+        parse_anthropic_error(r#"{"type":"result","is_error":false,"result":"done"}"#),
+        None
+    );
+    assert_eq!(parse_anthropic_error("plain reply"), None);
+}
+
+#[test]
+fn output_parse_module_reads_openai_error_events() {
+    let quota = r#"
+{"type":"thread.started","thread_id":"0199a213-81c0-7800-8aa1-bbab2a035a53"}
+{"type":"turn.started"}
+{"type":"error","message":"exceeded retry limit, last status: 429 Too Many Requests, request id: 9a1c"}
+{"type":"turn.failed","error":{"message":"You've hit your usage limit. Upgrade to Pro (https://openai.com/chatgpt/pricing) or try again in 4 days 2 hours."}}
+"#;
+    assert_eq!(
+        parse_openai_error(quota),
+        Some(
+            "You've hit your usage limit. Upgrade to Pro (https://openai.com/chatgpt/pricing) or try again in 4 days 2 hours."
+                .to_string()
+        )
+    );
+    assert_eq!(
+        parse_openai_error(r#"{"type":"error","message":"stream disconnected before completion"}"#),
+        Some("stream disconnected before completion".to_string())
+    );
+    // An error the CLI recovered from is not a failure of the run.
+    let recovered = r#"
+{"type":"turn.started"}
+{"type":"error","message":"Reconnecting... 1/5"}
+{"type":"item.completed","item":{"id":"item_0","type":"agent_message","text":"done"}}
+{"type":"turn.completed","usage":{"input_tokens":10,"cached_input_tokens":0,"output_tokens":2}}
+"#;
+    assert_eq!(parse_openai_error(recovered), None);
+    assert_eq!(parse_openai_error("not json"), None);
+}
+
+#[test]
+fn output_parse_module_reads_gemini_error_objects() {
+    let quota = "Loaded cached credentials.\n{\n  \"error\": {\n    \"type\": \"Error\",\n    \"message\": \"[API Error: You have exhausted your capacity on this model. Your quota will reset after 0s.]\",\n    \"code\": 429\n  }\n}\n";
+    assert_eq!(
+        parse_gemini_error(quota),
+        Some(
+            "[API Error: You have exhausted your capacity on this model. Your quota will reset after 0s.] (type Error, code 429)"
+                .to_string()
+        )
+    );
+    assert_eq!(
+        parse_gemini_error(r#"{"error":{"message":"RESOURCE_EXHAUSTED"}}"#),
+        Some("RESOURCE_EXHAUSTED".to_string())
+    );
+    assert_eq!(
+        parse_gemini_error(r#"{"session_id":"g-1","response":"hi","error":null}"#),
+        None
+    );
+    assert_eq!(parse_gemini_error("plain reply"), None);
+}
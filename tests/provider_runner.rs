@@ -1,6 +1,6 @@
 use direclaw::provider::{
-    run_provider, write_file_backed_prompt, PromptArtifacts, ProviderError, ProviderKind,
-    ProviderRequest, RunnerBinaries,
+    run_provider, write_file_backed_prompt, CancellationToken, PromptArtifacts, ProviderError,
+    ProviderKind, ProviderRequest, RunnerBinaries,
 };
 use std::collections::BTreeMap;
 use std::fs;
@@ -35,6 +35,10 @@ fn base_request(
         reset_requested: false,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
     }
 }
 
@@ -47,15 +51,14 @@ fn mocked_anthropic_success_and_model_mapping() {
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-a", "prompt", "ctx").expect("artifacts");
     let request = base_request(
-        ProviderKind::Anthropic,
+        ProviderKind::ANTHROPIC,
         "sonnet",
         dir.path(),
         artifacts.clone(),
     );
-    let bins = RunnerBinaries {
-        anthropic: bin.display().to_string(),
-        openai: "unused".to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", bin.display().to_string())
+        .with("openai", "unused");
 
     let result = run_provider(&request, &bins).expect("success");
     assert_eq!(result.message, "anthropic response");
@@ -76,15 +79,14 @@ fn mocked_openai_jsonl_success() {
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-b", "prompt", "ctx").expect("artifacts");
     let request = base_request(
-        ProviderKind::OpenAi,
+        ProviderKind::OPENAI,
         "gpt-5.3-codex-spark",
         dir.path(),
         artifacts,
     );
-    let bins = RunnerBinaries {
-        anthropic: "unused".to_string(),
-        openai: bin.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", bin.display().to_string());
 
     let result = run_provider(&request, &bins).expect("success");
     assert_eq!(result.message, "final answer");
@@ -98,11 +100,10 @@ fn provider_non_zero_exit_is_explicit() {
 
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-c", "prompt", "ctx").expect("artifacts");
-    let request = base_request(ProviderKind::Anthropic, "opus", dir.path(), artifacts);
-    let bins = RunnerBinaries {
-        anthropic: bin.display().to_string(),
-        openai: "unused".to_string(),
-    };
+    let request = base_request(ProviderKind::ANTHROPIC, "opus", dir.path(), artifacts);
+    let bins = RunnerBinaries::default()
+        .with("anthropic", bin.display().to_string())
+        .with("openai", "unused");
 
     // Reproduce transient ETXTBSY seen in CI by briefly keeping the script open for write.
     let writer = OpenOptions::new()
@@ -141,31 +142,93 @@ fn provider_non_zero_exit_uses_stdout_when_stderr_is_empty() {
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-c2", "prompt", "ctx").expect("artifacts");
     let request = base_request(
-        ProviderKind::OpenAi,
+        ProviderKind::OPENAI,
         "gpt-5.3-codex-spark",
         dir.path(),
         artifacts,
     );
-    let bins = RunnerBinaries {
-        anthropic: "unused".to_string(),
-        openai: bin.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", bin.display().to_string());
 
     let err = run_provider(&request, &bins).expect_err("expected failure");
+    let message = err.to_string();
+    assert!(
+        message.contains("structured failure detail from stdout"),
+        "unexpected non-zero exit detail: {message}"
+    );
     match err {
         ProviderError::NonZeroExit {
-            exit_code, stderr, ..
+            exit_code,
+            stderr,
+            stdout,
+            ..
         } => {
             assert_eq!(exit_code, 9);
-            assert!(
-                stderr.contains("structured failure detail from stdout"),
-                "unexpected non-zero exit detail: {stderr}"
-            );
+            assert!(stderr.is_empty(), "{stderr}");
+            assert_eq!(stdout, "structured failure detail from stdout");
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn provider_error_result_fails_even_with_zero_exit() {
+    let dir = tempdir().expect("tempdir");
+    let bin = dir.path().join("claude-error-result");
+    write_script(
+        &bin,
+        "#!/bin/sh\necho '{\"type\":\"result\",\"is_error\":true,\"result\":\"Claude AI usage limit reached\"}'\n",
+    );
+
+    let artifacts =
+        write_file_backed_prompt(dir.path(), "req-c3", "prompt", "ctx").expect("artifacts");
+    let request = base_request(ProviderKind::ANTHROPIC, "sonnet", dir.path(), artifacts);
+    let bins = RunnerBinaries::default().with("anthropic", bin.display().to_string());
+
+    let err = run_provider(&request, &bins).expect_err("error result must fail");
+    assert!(err.is_rate_limited(), "{err}");
+    match err {
+        ProviderError::NonZeroExit {
+            exit_code, stderr, ..
+        } => {
+            assert_eq!(exit_code, 0);
+            assert_eq!(stderr, "Claude AI usage limit reached");
         }
         other => panic!("unexpected error: {other:?}"),
     }
 }
 
+#[test]
+fn provider_quota_errors_on_stdout_are_rate_limited_for_codex_and_gemini() {
+    let dir = tempdir().expect("tempdir");
+    let codex = dir.path().join("codex-quota");
+    write_script(
+        &codex,
+        "#!/bin/sh\necho '{\"type\":\"thread.started\",\"thread_id\":\"t-1\"}'\necho '{\"type\":\"turn.started\"}'\necho '{\"type\":\"turn.failed\",\"error\":{\"message\":\"exceeded retry limit, last status: 429 Too Many Requests\"}}'\nexit 1\n",
+    );
+    let gemini = dir.path().join("gemini-quota");
+    write_script(
+        &gemini,
+        "#!/bin/sh\necho '{\"error\":{\"type\":\"Error\",\"message\":\"[API Error: You have exhausted your capacity on this model.]\",\"code\":429}}'\n",
+    );
+    let bins = RunnerBinaries::default()
+        .with("openai", codex.display().to_string())
+        .with("gemini", gemini.display().to_string());
+
+    for (provider, model, request_id) in [
+        (ProviderKind::OPENAI, "gpt-5.3-codex", "req-q1"),
+        (ProviderKind::GEMINI, "gemini-2.5-pro", "req-q2"),
+    ] {
+        let artifacts =
+            write_file_backed_prompt(dir.path(), request_id, "prompt", "ctx").expect("artifacts");
+        let request = base_request(provider.clone(), model, dir.path(), artifacts);
+        let err = run_provider(&request, &bins).expect_err("quota error must fail");
+        assert!(err.is_rate_limited(), "{provider}: {err:?}");
+        assert!(err.allows_fallback(), "{provider}: {err:?}");
+    }
+}
+
 #[test]
 fn provider_timeout_is_explicit() {
     let start = Instant::now();
@@ -175,13 +238,12 @@ fn provider_timeout_is_explicit() {
 
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-d", "prompt", "ctx").expect("artifacts");
-    let mut request = base_request(ProviderKind::Anthropic, "sonnet", dir.path(), artifacts);
+    let mut request = base_request(ProviderKind::ANTHROPIC, "sonnet", dir.path(), artifacts);
     request.timeout = Duration::from_millis(100);
 
-    let bins = RunnerBinaries {
-        anthropic: bin.display().to_string(),
-        openai: "unused".to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", bin.display().to_string())
+        .with("openai", "unused");
 
     let err = run_provider(&request, &bins).expect_err("expected timeout");
     match err {
@@ -204,12 +266,14 @@ fn provider_missing_binary_is_explicit() {
     let dir = tempdir().expect("tempdir");
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-e", "prompt", "ctx").expect("artifacts");
-    let request = base_request(ProviderKind::Anthropic, "sonnet", dir.path(), artifacts);
+    let request = base_request(ProviderKind::ANTHROPIC, "sonnet", dir.path(), artifacts);
 
-    let bins = RunnerBinaries {
-        anthropic: dir.path().join("does-not-exist").display().to_string(),
-        openai: "unused".to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with(
+            "anthropic",
+            dir.path().join("does-not-exist").display().to_string(),
+        )
+        .with("openai", "unused");
 
     let err = run_provider(&request, &bins).expect_err("expected missing binary");
     match err {
@@ -232,15 +296,14 @@ fn provider_parse_failure_is_explicit_for_openai() {
     let artifacts =
         write_file_backed_prompt(dir.path(), "req-f", "prompt", "ctx").expect("artifacts");
     let request = base_request(
-        ProviderKind::OpenAi,
+        ProviderKind::OPENAI,
         "gpt-5.3-codex-spark",
         dir.path(),
         artifacts,
     );
-    let bins = RunnerBinaries {
-        anthropic: "unused".to_string(),
-        openai: bin.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", bin.display().to_string());
 
     let err = run_provider(&request, &bins).expect_err("expected parse failure");
     match err {
@@ -253,3 +316,55 @@ fn provider_parse_failure_is_explicit_for_openai() {
         other => panic!("unexpected error: {other:?}"),
     }
 }
+
+#[test]
+fn provider_cancellation_kills_process_group() {
+    let start = Instant::now();
+    let dir = tempdir().expect("tempdir");
+    let bin = dir.path().join("claude-cancel");
+    let grandchild_pid = dir.path().join("grandchild.pid");
+    write_script(
+        &bin,
+        &format!(
+            "#!/bin/sh\nsleep 30 &\necho $! > {}\nwhile :; do sleep 1; done\n",
+            grandchild_pid.display()
+        ),
+    );
+
+    let artifacts =
+        write_file_backed_prompt(dir.path(), "req-cancel", "prompt", "ctx").expect("artifacts");
+    let mut request = base_request(ProviderKind::ANTHROPIC, "sonnet", dir.path(), artifacts);
+    request.timeout = Duration::from_secs(20);
+    let marker = dir.path().join("cancel_requested");
+    request.cancellation = CancellationToken::with_marker(&marker);
+
+    let bins = RunnerBinaries::default()
+        .with("anthropic", bin.display().to_string())
+        .with("openai", "unused");
+
+    let signal = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        fs::write(marker, b"canceled").expect("write cancel marker");
+    });
+    let err = run_provider(&request, &bins).expect_err("expected cancellation");
+    signal.join().expect("join signal thread");
+    match err {
+        ProviderError::Canceled { log, .. } => {
+            assert!(!log.timed_out);
+            assert!(log.command_form.contains("claude-cancel"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    assert!(
+        start.elapsed() < Duration::from_secs(5),
+        "cancellation should not wait for the step timeout"
+    );
+
+    let pid = fs::read_to_string(&grandchild_pid).expect("grandchild pid");
+    thread::sleep(Duration::from_millis(100));
+    // A killed grandchild may linger as a zombie until init reaps it.
+    let alive = fs::read_to_string(format!("/proc/{}/stat", pid.trim()))
+        .map(|stat| !stat.contains(") Z "))
+        .unwrap_or(false);
+    assert!(!alive, "grandchild process should be killed with the group");
+}
@@ -1,5 +1,8 @@
 use direclaw::provider::runner::run_provider;
-use direclaw::provider::{write_file_backed_prompt, ProviderKind, ProviderRequest, RunnerBinaries};
+use direclaw::provider::{
+    write_file_backed_prompt, CancellationToken, ProviderError, ProviderFallback, ProviderKind,
+    ProviderRequest, RunnerBinaries,
+};
 use std::collections::BTreeMap;
 use std::fs;
 use std::os::unix::fs::PermissionsExt;
@@ -27,7 +30,7 @@ fn runner_module_executes_openai_request() {
         write_file_backed_prompt(dir.path(), "req-runner", "prompt", "ctx").expect("artifacts");
     let request = ProviderRequest {
         agent_id: "agent-1".to_string(),
-        provider: ProviderKind::OpenAi,
+        provider: ProviderKind::OPENAI,
         model: "gpt-5.3-codex-spark".to_string(),
         cwd: dir.path().to_path_buf(),
         message: "run".to_string(),
@@ -36,12 +39,171 @@ fn runner_module_executes_openai_request() {
         reset_requested: false,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
     };
-    let binaries = RunnerBinaries {
-        anthropic: "unused".to_string(),
-        openai: bin.display().to_string(),
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", bin.display().to_string());
+
+    let result = run_provider(&request, &binaries).expect("provider run");
+    assert_eq!(result.message, "ok");
+}
+
+#[test]
+fn runner_module_executes_gemini_request() {
+    let dir = tempdir().expect("tempdir");
+    let bin = dir.path().join("gemini-mock");
+    let args_log = dir.path().join("args.txt");
+    write_script(
+        &bin,
+        &format!(
+            "#!/bin/sh\nprintf '%s\\n' \"$@\" > {}\necho '{{\"response\":\"ok\",\"stats\":{{\"models\":{{\"gemini-2.5-pro\":{{\"tokens\":{{\"prompt\":7,\"candidates\":3}}}}}}}}}}'\n",
+            args_log.display()
+        ),
+    );
+
+    let artifacts =
+        write_file_backed_prompt(dir.path(), "req-gemini", "prompt", "ctx").expect("artifacts");
+    let request = ProviderRequest {
+        agent_id: "agent-1".to_string(),
+        provider: ProviderKind::GEMINI,
+        model: "gemini-2.5-pro".to_string(),
+        cwd: dir.path().to_path_buf(),
+        message: "run".to_string(),
+        prompt_artifacts: artifacts,
+        timeout: Duration::from_secs(1),
+        reset_requested: true,
+        fresh_on_failure: false,
+        env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
     };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", "unused")
+        .with("gemini", bin.display().to_string());
 
     let result = run_provider(&request, &binaries).expect("provider run");
     assert_eq!(result.message, "ok");
+    let args = fs::read_to_string(&args_log).expect("args");
+    assert_eq!(
+        args.lines().collect::<Vec<_>>(),
+        vec![
+            "--yolo",
+            "--model",
+            "gemini-2.5-pro",
+            "--output-format",
+            "json",
+            "--prompt",
+            "run"
+        ]
+    );
+}
+
+#[test]
+fn runner_module_falls_back_when_provider_is_rate_limited() {
+    let dir = tempdir().expect("tempdir");
+    let claude = dir.path().join("claude-mock");
+    write_script(
+        &claude,
+        "#!/bin/sh\necho 'Claude AI usage limit reached: rate limit exceeded' >&2\nexit 1\n",
+    );
+    let codex = dir.path().join("codex-mock");
+    let codex_args = dir.path().join("codex-args.txt");
+    write_script(
+        &codex,
+        &format!(
+            "#!/bin/sh\necho \"$*\" > {}\necho '{{\"type\":\"item.completed\",\"item\":{{\"type\":\"agent_message\",\"text\":\"from codex\"}}}}'\n",
+            codex_args.display()
+        ),
+    );
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", claude.display().to_string())
+        .with("openai", codex.display().to_string())
+        .with(
+            "gemini",
+            dir.path().join("missing-gemini").display().to_string(),
+        );
+
+    let artifacts =
+        write_file_backed_prompt(dir.path(), "req-fallback", "prompt", "ctx").expect("artifacts");
+    let mut request = ProviderRequest {
+        agent_id: "agent-1".to_string(),
+        provider: ProviderKind::ANTHROPIC,
+        model: "sonnet".to_string(),
+        cwd: dir.path().to_path_buf(),
+        message: "run".to_string(),
+        prompt_artifacts: artifacts,
+        timeout: Duration::from_secs(5),
+        reset_requested: false,
+        fresh_on_failure: false,
+        env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: Some("claude-session".to_string()),
+        fallbacks: vec![
+            ProviderFallback {
+                provider: ProviderKind::GEMINI,
+                model: "gemini-2.5-pro".to_string(),
+                endpoint: None,
+            },
+            ProviderFallback {
+                provider: ProviderKind::OPENAI,
+                model: "gpt-5.2".to_string(),
+                endpoint: None,
+            },
+        ],
+    };
+
+    let result = run_provider(&request, &binaries).expect("fallback answers");
+    assert_eq!(result.message, "from codex");
+    assert_eq!(result.log.provider, ProviderKind::OPENAI);
+    assert_eq!(result.log.model, "gpt-5.2");
+    assert_eq!(
+        result
+            .log
+            .prior_attempts
+            .iter()
+            .map(|attempt| (attempt.provider.clone(), attempt.model.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (ProviderKind::ANTHROPIC, "sonnet"),
+            (ProviderKind::GEMINI, "gemini-2.5-pro"),
+        ]
+    );
+    assert!(result.log.prior_attempts[0].error.contains("rate limit"));
+    assert!(result.log.prior_attempts[1]
+        .error
+        .contains("provider binary missing"));
+    // The Claude session id does not carry over to the fallback provider.
+    let args = fs::read_to_string(&codex_args).expect("codex args");
+    assert!(!args.contains("resume"), "{args}");
+
+    // Ordinary failures surface without trying the chain.
+    write_script(&claude, "#!/bin/sh\necho 'boom' >&2\nexit 1\n");
+    fs::remove_file(&codex_args).expect("reset codex args");
+    let err = run_provider(&request, &binaries).expect_err("plain failure");
+    assert!(
+        matches!(err, ProviderError::NonZeroExit { ref stderr, .. } if stderr.contains("boom"))
+    );
+    assert!(!codex_args.exists());
+
+    // When the whole chain fails, the last error lists the earlier attempts.
+    write_script(
+        &claude,
+        "#!/bin/sh\necho 'Invalid API key. Please run /login' >&2\nexit 1\n",
+    );
+    request.fallbacks.truncate(1);
+    let err = run_provider(&request, &binaries).expect_err("chain exhausted");
+    let ProviderError::MissingBinary { provider, log, .. } = err else {
+        panic!("unexpected error: {err:?}");
+    };
+    assert_eq!(provider, ProviderKind::GEMINI);
+    assert_eq!(log.prior_attempts.len(), 1);
+    assert_eq!(log.prior_attempts[0].provider, ProviderKind::ANTHROPIC);
 }
@@ -1,5 +1,7 @@
-use direclaw::provider::types::{ProviderKind, ProviderRequest};
+use direclaw::provider::types::{InvocationLog, ProviderError, ProviderKind, ProviderRequest};
+use direclaw::provider::CancellationToken;
 use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::time::Duration;
 
 #[test]
@@ -7,7 +9,7 @@ fn provider_types_module_exposes_core_request_types() {
     let dir = tempfile::tempdir().expect("tempdir");
     let request = ProviderRequest {
         agent_id: "agent-1".to_string(),
-        provider: ProviderKind::OpenAi,
+        provider: ProviderKind::OPENAI,
         model: "gpt-5.3-codex-spark".to_string(),
         cwd: dir.path().to_path_buf(),
         message: "run".to_string(),
@@ -22,7 +24,116 @@ fn provider_types_module_exposes_core_request_types() {
         reset_requested: false,
         fresh_on_failure: false,
         env_overrides: BTreeMap::new(),
+        cancellation: CancellationToken::new(),
+        endpoint: None,
+        session_id: None,
+        fallbacks: Vec::new(),
     };
 
-    assert_eq!(request.provider, ProviderKind::OpenAi);
+    assert_eq!(request.provider, ProviderKind::OPENAI);
+}
+
+#[test]
+fn provider_types_module_classifies_fallback_errors() {
+    let log = InvocationLog {
+        agent_id: "agent-1".to_string(),
+        provider: ProviderKind::ANTHROPIC,
+        model: "claude-sonnet-4-5".to_string(),
+        command_form: "claude -p run".to_string(),
+        working_directory: PathBuf::from("/tmp"),
+        prompt_file: PathBuf::from("/tmp/prompt.md"),
+        context_files: Vec::new(),
+        exit_code: Some(1),
+        timed_out: false,
+        usage: None,
+        session_id: None,
+        prior_attempts: Vec::new(),
+    };
+    let exit_with = |stderr: &str, stdout: &str| ProviderError::NonZeroExit {
+        provider: ProviderKind::ANTHROPIC,
+        exit_code: 1,
+        stderr: stderr.to_string(),
+        stdout: stdout.to_string(),
+        log: Box::new(log.clone()),
+    };
+    let exit = |stderr: &str| exit_with(stderr, "");
+
+    let limited = exit("Claude AI usage limit reached|quota exceeded");
+    assert!(limited.is_rate_limited());
+    assert!(limited.allows_fallback());
+
+    let auth = exit("Invalid API key · Please run /login");
+    assert!(auth.is_auth_failure());
+    assert!(!auth.is_rate_limited());
+    assert!(auth.allows_fallback());
+
+    let missing_key = ProviderError::MissingApiKey {
+        provider: ProviderKind::OPENAI_COMPATIBLE,
+        env_var: "LLM_KEY".to_string(),
+    };
+    assert!(missing_key.is_auth_failure());
+    assert!(missing_key.allows_fallback());
+
+    let missing_binary = ProviderError::MissingBinary {
+        provider: ProviderKind::ANTHROPIC,
+        binary: "claude".to_string(),
+        log: Box::new(log.clone()),
+    };
+    assert!(missing_binary.allows_fallback());
+
+    assert!(exit("request failed: HTTP 429").is_rate_limited());
+    assert!(exit("api error (status: 401)").is_auth_failure());
+
+    assert!(!exit("panic: index out of range").allows_fallback());
+    assert!(!ProviderError::Timeout {
+        provider: ProviderKind::ANTHROPIC,
+        timeout_ms: 1000,
+        log: Box::new(log),
+    }
+    .allows_fallback());
+}
+
+#[test]
+fn provider_types_module_ignores_incidental_status_digits() {
+    let log = InvocationLog {
+        agent_id: "agent-1".to_string(),
+        provider: ProviderKind::OPENAI,
+        model: "gpt-5.3-codex-spark".to_string(),
+        command_form: "codex exec".to_string(),
+        working_directory: PathBuf::from("/tmp"),
+        prompt_file: PathBuf::from("/tmp/prompt.md"),
+        context_files: Vec::new(),
+        exit_code: Some(1),
+        timed_out: false,
+        usage: None,
+        session_id: None,
+        prior_attempts: Vec::new(),
+    };
+    let exit = |stderr: &str, stdout: &str| ProviderError::NonZeroExit {
+        provider: ProviderKind::OPENAI,
+        exit_code: 1,
+        stderr: stderr.to_string(),
+        stdout: stdout.to_string(),
+        log: Box::new(log.clone()),
+    };
+
+    for stderr in [
+        "syntax error at src/lib.rs line 429",
+        "context used 4290 tokens",
+        "request id req_401a failed",
+        "failed with status 4291",
+        "quota.rs: unused import",
+        "see docs/authentication.md",
+    ] {
+        let err = exit(stderr, "");
+        assert!(!err.allows_fallback(), "{stderr}");
+    }
+
+    // Stdout is model output; a rate-limit phrase there is not a provider signal.
+    let err = exit(
+        "exit status 1",
+        "HTTP 429 Too Many Requests is retried by the client",
+    );
+    assert!(!err.is_rate_limited());
+    assert!(!err.allows_fallback());
 }
@@ -125,10 +125,9 @@ channels: {{}}
         temp.path(),
         &settings,
         1,
-        &RunnerBinaries {
-            anthropic: codex.display().to_string(),
-            openai: codex.display().to_string(),
-        },
+        &RunnerBinaries::default()
+            .with("anthropic", codex.display().to_string())
+            .with("openai", codex.display().to_string()),
     )
     .expect("drain");
     assert_eq!(processed, 1);
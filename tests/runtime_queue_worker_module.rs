@@ -29,10 +29,9 @@ channels: {}
     )
     .expect("parse settings");
 
-    let binaries = RunnerBinaries {
-        anthropic: "unused".to_string(),
-        openai: "unused".to_string(),
-    };
+    let binaries = RunnerBinaries::default()
+        .with("anthropic", "unused")
+        .with("openai", "unused");
 
     let processed = drain_queue_once_with_binaries(&state_root, &settings, 1, &binaries)
         .expect("drain empty queue");
@@ -162,10 +161,9 @@ channels: {{}}
         fs::set_permissions(&codex, perms).expect("chmod");
     }
 
-    let bins = RunnerBinaries {
-        anthropic: claude.display().to_string(),
-        openai: codex.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", claude.display().to_string())
+        .with("openai", codex.display().to_string());
 
     let first = drain_queue_once_with_binaries(&state_root, &settings, 1, &bins);
     assert!(first.is_err(), "first attempt should fail");
@@ -360,10 +358,9 @@ channels: {{}}
         fs::set_permissions(&codex, perms).expect("chmod");
     }
 
-    let bins = RunnerBinaries {
-        anthropic: claude.display().to_string(),
-        openai: codex.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", claude.display().to_string())
+        .with("openai", codex.display().to_string());
 
     let processed = drain_queue_once_with_binaries(&state_root, &settings, 1, &bins)
         .expect("workflow should succeed with pre-step ack");
@@ -503,10 +500,9 @@ channels: {{}}
         fs::set_permissions(&codex, perms).expect("chmod");
     }
 
-    let bins = RunnerBinaries {
-        anthropic: claude.display().to_string(),
-        openai: codex.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", claude.display().to_string())
+        .with("openai", codex.display().to_string());
     let processed = drain_queue_once_with_binaries(&state_root, &settings, 1, &bins)
         .expect("workflow should succeed");
     assert_eq!(processed, 1);
@@ -652,10 +648,9 @@ channels: {{}}
         fs::set_permissions(&codex, perms).expect("chmod");
     }
 
-    let bins = RunnerBinaries {
-        anthropic: claude.display().to_string(),
-        openai: codex.display().to_string(),
-    };
+    let bins = RunnerBinaries::default()
+        .with("anthropic", claude.display().to_string())
+        .with("openai", codex.display().to_string());
     let first = drain_queue_once_with_binaries(&state_root, &settings, 1, &bins);
     assert!(first.is_err(), "first attempt should fail and requeue");
 
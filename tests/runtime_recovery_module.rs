@@ -1,7 +1,19 @@
+use direclaw::config::{InterruptedAttemptPolicy, OrchestratorConfig, RetryErrorClass};
+use direclaw::orchestration::routing::{
+    process_queued_message_with_runner_binaries, FunctionRegistry,
+};
+use direclaw::orchestration::run_store::{NextStepPointer, RunState, WorkflowRunStore};
+use direclaw::orchestration::transitions::RoutedSelectorAction;
+use direclaw::orchestration::workflow_engine::WorkflowEngine;
+use direclaw::provider::RunnerBinaries;
 use direclaw::queue::QueuePaths;
 use direclaw::queue::{IncomingMessage, OutgoingMessage};
-use direclaw::runtime::recovery::recover_processing_queue_entries;
+use direclaw::runtime::recovery::{
+    due_retry_workflow_runs, recover_processing_queue_entries, recover_workflow_runs,
+    resume_recovered_workflow_run,
+};
 use direclaw::runtime::state_paths::{bootstrap_state_root, StatePaths};
+use std::collections::BTreeMap;
 use std::fs;
 use tempfile::tempdir;
 
@@ -181,3 +193,429 @@ fn runtime_recovery_deduplicates_same_message_within_processing() {
         .next()
         .is_none());
 }
+
+fn write_recovery_orchestrator(private_workspace: &std::path::Path, policy: &str) {
+    fs::create_dir_all(private_workspace).expect("create private workspace");
+    fs::write(
+        private_workspace.join("orchestrator.yaml"),
+        format!(
+            r#"
+id: main
+selector_agent: default
+default_workflow: build
+selection_max_retries: 1
+agents:
+  default:
+    provider: openai
+    model: gpt-5.3-codex
+    can_orchestrate_workflows: true
+workflow_orchestration:
+  interrupted_attempt_policy: {policy}
+workflows:
+  - id: build
+    version: 1
+    description: compile the project
+    tags: [build]
+    inputs: []
+    steps:
+      - id: compile
+        type: command
+        command: [sh, -c, "echo compiled"]
+        outputs: [summary, stdout]
+        output_files:
+          summary: out/compile-summary.txt
+          stdout: out/compile-stdout.txt
+"#
+        ),
+    )
+    .expect("write orchestrator yaml");
+}
+
+fn recovery_settings(root: &std::path::Path) -> direclaw::config::Settings {
+    serde_yaml::from_str(&format!(
+        r#"
+workspaces_path: {}
+shared_workspaces: {{}}
+orchestrators:
+  main:
+    private_workspace: {}
+    shared_access: []
+channel_profiles: {{}}
+monitoring: {{}}
+channels: {{}}
+"#,
+        root.join("workspaces").display(),
+        root.join("workspaces/main").display()
+    ))
+    .expect("settings")
+}
+
+fn interrupted_run(store: &WorkflowRunStore, run_id: &str) {
+    let mut run = store.create_run(run_id, "build", 10).expect("create run");
+    store
+        .transition_state(&mut run, RunState::Running, 11, "started", false, "execute")
+        .expect("running");
+    store
+        .mark_step_attempt_started(&mut run, "compile", 1, 12)
+        .expect("mark started");
+}
+
+#[test]
+fn runtime_recovery_retries_in_flight_attempt_by_resuming_run() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "retry");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+    interrupted_run(&store, "run-retry");
+    let mut finished = store.create_run("run-done", "build", 5).expect("create");
+    store
+        .transition_state(
+            &mut finished,
+            RunState::Canceled,
+            6,
+            "canceled",
+            false,
+            "none",
+        )
+        .expect("cancel");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    assert_eq!(report.interrupted_attempts.len(), 1);
+    let interrupted = &report.interrupted_attempts[0];
+    assert_eq!(interrupted.run_id, "run-retry");
+    assert_eq!(interrupted.step_id, "compile");
+    assert_eq!(interrupted.attempt, 1);
+    assert_eq!(interrupted.policy, InterruptedAttemptPolicy::Retry);
+    assert_eq!(report.resumable.len(), 1);
+    assert_eq!(report.resumable[0].run_id, "run-retry");
+    assert!(report.parked.is_empty());
+
+    let state = resume_recovered_workflow_run(
+        &settings,
+        &report.resumable[0],
+        &RunnerBinaries::default(),
+        100,
+    )
+    .expect("resume run");
+    assert_eq!(state, RunState::Succeeded);
+    let attempt = store
+        .load_step_attempt("run-retry", "compile", 1)
+        .expect("attempt result");
+    assert_eq!(attempt.state, "succeeded");
+}
+
+#[test]
+fn runtime_recovery_fails_in_flight_attempt_when_policy_is_fail() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "fail");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+    interrupted_run(&store, "run-fail");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    assert_eq!(report.interrupted_attempts.len(), 1);
+    assert_eq!(
+        report.interrupted_attempts[0].policy,
+        InterruptedAttemptPolicy::Fail
+    );
+    assert!(report.resumable.is_empty());
+
+    let run = store.load_run("run-fail").expect("load run");
+    assert_eq!(run.state, RunState::Failed);
+    assert_eq!(
+        run.terminal_reason.as_deref(),
+        Some("step compile attempt 1 interrupted by runtime restart")
+    );
+    let attempt = store
+        .load_step_attempt("run-fail", "compile", 1)
+        .expect("attempt result");
+    assert_eq!(attempt.state, "failed");
+}
+
+#[test]
+fn runtime_recovery_fails_in_flight_fan_out_attempts_when_policy_is_fail() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "fail");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+    let mut run = store
+        .create_run("run-fan-out", "build", 10)
+        .expect("create");
+    store
+        .transition_state(&mut run, RunState::Running, 11, "started", false, "execute")
+        .expect("running");
+    run.active_steps = vec![
+        NextStepPointer {
+            step_id: "lint".to_string(),
+            attempt: 1,
+        },
+        NextStepPointer {
+            step_id: "test".to_string(),
+            attempt: 2,
+        },
+    ];
+    store
+        .mark_step_attempt_started(&mut run, "checks", 1, 12)
+        .expect("mark started");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    let interrupted = report
+        .interrupted_attempts
+        .iter()
+        .map(|attempt| (attempt.step_id.as_str(), attempt.attempt))
+        .collect::<Vec<_>>();
+    assert_eq!(interrupted, vec![("lint", 1), ("test", 2)]);
+
+    let run = store.load_run("run-fan-out").expect("load run");
+    assert_eq!(run.state, RunState::Failed);
+    assert!(run.active_steps.is_empty());
+    assert_eq!(
+        run.terminal_reason.as_deref(),
+        Some(
+            "step lint attempt 1 interrupted by runtime restart; \
+             step test attempt 2 interrupted by runtime restart"
+        )
+    );
+    assert_eq!(
+        store
+            .load_step_attempt("run-fan-out", "test", 2)
+            .expect("branch attempt")
+            .state,
+        "failed"
+    );
+}
+
+#[test]
+fn runtime_recovery_continues_past_an_orchestrator_that_cannot_be_read() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "retry");
+    let broken_workspace = tmp.path().join("workspaces/broken");
+    fs::create_dir_all(broken_workspace.join("workflows/runs")).expect("broken runs dir");
+    fs::write(broken_workspace.join("workflows/runs/corrupt.json"), "{").expect("corrupt run");
+    let mut settings = recovery_settings(tmp.path());
+    let mut broken = settings.orchestrators["main"].clone();
+    broken.private_workspace = Some(broken_workspace);
+    settings.orchestrators.insert("broken".to_string(), broken);
+    let store = WorkflowRunStore::new(&private_workspace);
+    interrupted_run(&store, "run-retry");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].orchestrator_id, "broken");
+    assert_eq!(report.resumable.len(), 1);
+    assert_eq!(report.resumable[0].orchestrator_id, "main");
+    assert_eq!(report.resumable[0].run_id, "run-retry");
+}
+
+#[test]
+fn runtime_recovery_leaves_waiting_runs_parked_and_resumes_between_steps() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "retry");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+
+    let mut waiting = store.create_run("run-waiting", "build", 1).expect("create");
+    store
+        .transition_state(
+            &mut waiting,
+            RunState::Running,
+            2,
+            "started",
+            false,
+            "execute",
+        )
+        .expect("running");
+    store
+        .transition_state(&mut waiting, RunState::Waiting, 3, "review", true, "await")
+        .expect("waiting");
+    store.create_run("run-queued", "build", 4).expect("create");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    assert!(report.interrupted_attempts.is_empty());
+    assert_eq!(report.parked.len(), 1);
+    assert_eq!(report.parked[0].run_id, "run-waiting");
+    assert_eq!(report.resumable.len(), 1);
+    assert_eq!(report.resumable[0].run_id, "run-queued");
+    assert_eq!(
+        store.load_run("run-waiting").expect("load").state,
+        RunState::Waiting
+    );
+}
+
+#[test]
+fn runtime_recovery_resumes_runs_parked_for_retry_backoff_once_due() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    fs::create_dir_all(&private_workspace).expect("create private workspace");
+    let marker = tmp.path().join("first-attempt-ran");
+    let orchestrator_path = private_workspace.join("orchestrator.yaml");
+    fs::write(
+        &orchestrator_path,
+        format!(
+            r#"
+id: main
+selector_agent: default
+default_workflow: build
+selection_max_retries: 1
+agents:
+  default:
+    provider: openai
+    model: gpt-5.3-codex
+    can_orchestrate_workflows: true
+workflow_orchestration:
+  retry:
+    initial_backoff_seconds: 30
+workflows:
+  - id: build
+    version: 1
+    description: compile the project
+    tags: [build]
+    steps:
+      - id: compile
+        type: command
+        command: [sh, -c, "test -f {marker} || {{ touch {marker}; echo flaky >&2; exit 4; }}"]
+        outputs: [summary]
+        output_files:
+          summary: out/compile-summary.txt
+"#,
+            marker = marker.display()
+        ),
+    )
+    .expect("write orchestrator yaml");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+    let orchestrator = OrchestratorConfig::from_path(&orchestrator_path).expect("orchestrator");
+
+    store
+        .create_run("run-backoff", "build", 10)
+        .expect("create");
+    let run = WorkflowEngine::new(store.clone(), orchestrator)
+        .start("run-backoff", 10)
+        .expect("start parks run");
+    assert_eq!(run.state, RunState::Waiting);
+    assert_eq!(run.current_attempt, Some(2));
+    let retry_at = run.retry_at.expect("retry_at");
+    assert!(retry_at >= 41, "{retry_at}");
+    let progress = store.load_progress("run-backoff").expect("progress");
+    assert!(!progress.pending_human_input);
+    assert!(progress.summary.contains("waiting 30s before retry"));
+    let first = store
+        .load_step_attempt("run-backoff", "compile", 1)
+        .expect("first attempt");
+    assert_eq!(first.state, "failed_retryable");
+    assert_eq!(first.error_class, Some(RetryErrorClass::NonZeroExit));
+
+    let index_entry = private_workspace.join("workflows/retry_due/run-backoff.json");
+    assert!(index_entry.is_file());
+    // Unreadable index entries are skipped instead of failing the scan.
+    fs::write(
+        private_workspace.join("workflows/retry_due/corrupt.json"),
+        "{",
+    )
+    .expect("corrupt entry");
+
+    let report = recover_workflow_runs(&settings, retry_at).expect("recover runs");
+    assert!(report.parked.is_empty());
+    assert!(report.resumable.is_empty());
+    // The scan reads only the runs the index names, so a corrupt run record
+    // elsewhere does not block due retries.
+    fs::write(
+        private_workspace.join("workflows/runs/corrupt-run.json"),
+        "{",
+    )
+    .expect("corrupt run");
+    assert!(due_retry_workflow_runs(&settings, retry_at - 1)
+        .expect("not yet due")
+        .is_empty());
+    let due = due_retry_workflow_runs(&settings, retry_at).expect("due runs");
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].run_id, "run-backoff");
+
+    let state =
+        resume_recovered_workflow_run(&settings, &due[0], &RunnerBinaries::default(), retry_at)
+            .expect("resume run");
+    assert_eq!(state, RunState::Succeeded);
+    assert!(!index_entry.exists());
+    let run = store.load_run("run-backoff").expect("load run");
+    assert_eq!(run.retry_at, None);
+    assert!(run.waiting_seconds >= 30);
+    let second = store
+        .load_step_attempt("run-backoff", "compile", 2)
+        .expect("second attempt");
+    assert_eq!(second.state, "succeeded");
+}
+
+#[test]
+fn runtime_recovery_leaves_runs_with_a_queued_source_message_to_that_message() {
+    let tmp = tempdir().expect("tempdir");
+    let private_workspace = tmp.path().join("workspaces/main");
+    write_recovery_orchestrator(&private_workspace, "retry");
+    let settings = recovery_settings(tmp.path());
+    let store = WorkflowRunStore::new(&private_workspace);
+    let mut run = store
+        .create_run("run-msg", "build", 10)
+        .expect("create run");
+    run.source_message_id = Some("msg-1".to_string());
+    store
+        .transition_state(&mut run, RunState::Running, 11, "started", false, "execute")
+        .expect("running");
+    store
+        .mark_step_attempt_started(&mut run, "compile", 1, 12)
+        .expect("mark started");
+
+    let incoming = IncomingMessage {
+        channel: "heartbeat".to_string(),
+        channel_profile_id: None,
+        sender: "heartbeat:main".to_string(),
+        sender_id: "heartbeat:main".to_string(),
+        message: "check in".to_string(),
+        timestamp: 10,
+        message_id: "msg-1".to_string(),
+        conversation_id: None,
+        is_direct: false,
+        is_thread_reply: false,
+        is_mentioned: false,
+        files: Vec::new(),
+        workflow_run_id: None,
+        workflow_step_id: None,
+    };
+    let queue = QueuePaths::from_state_root(&private_workspace);
+    fs::create_dir_all(&queue.processing).expect("processing");
+    fs::create_dir_all(&queue.incoming).expect("incoming");
+    fs::write(
+        queue.processing.join("msg-1.json"),
+        serde_json::to_vec_pretty(&incoming).expect("serialize"),
+    )
+    .expect("write processing payload");
+
+    let report = recover_workflow_runs(&settings, 100).expect("recover runs");
+    assert!(report.resumable.is_empty());
+    assert_eq!(report.requeued.len(), 1);
+    assert_eq!(report.requeued[0].run_id, "run-msg");
+
+    let recovered = recover_processing_queue_entries(&private_workspace).expect("requeue");
+    assert_eq!(recovered.len(), 1);
+    let action = process_queued_message_with_runner_binaries(
+        &private_workspace,
+        &settings,
+        &incoming,
+        100,
+        &BTreeMap::new(),
+        &FunctionRegistry::v1_defaults(store.clone(), &settings),
+        Some(RunnerBinaries::default()),
+        |_attempt, _request, _orchestrator| None,
+    )
+    .expect("route requeued message");
+    assert!(matches!(
+        action,
+        RoutedSelectorAction::WorkflowStatus { run_id: Some(ref routed), .. } if routed == "run-msg"
+    ));
+    let runs = store.load_runs().expect("runs");
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].state, RunState::Succeeded);
+}
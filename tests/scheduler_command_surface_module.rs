@@ -54,6 +54,7 @@ fn selector_validation_rejects_unknown_scheduler_args() {
         default_workflow: "default".to_string(),
         available_functions: vec![function_ids::SCHEDULE_PAUSE.to_string()],
         available_function_schemas: schemas,
+        available_workflow_inputs: Default::default(),
     };
 
     let err = parse_and_validate_selector_result(
@@ -1,6 +1,8 @@
+use direclaw::config::Settings;
 use direclaw::orchestration::scheduler::{
-    compute_next_run_at, parse_cron_expression, validate_iana_timezone, JobPatch, JobState,
-    JobStore, MisfirePolicy, NewJob, ScheduleConfig, TargetAction,
+    compute_next_run_at, file_watch_glob_matches, granted_shared_workspaces, parse_cron_expression,
+    validate_file_watch_access, validate_iana_timezone, JobPatch, JobState, JobStore,
+    MisfirePolicy, NewJob, ScheduleConfig, TargetAction,
 };
 use serde_json::{Map, Value};
 use tempfile::tempdir;
@@ -147,3 +149,82 @@ fn scheduler_domain_rejects_invalid_slack_target_ref_contract() {
         "unexpected error: {err}"
     );
 }
+
+#[test]
+fn scheduler_domain_validates_file_watch_schedules_and_access() {
+    let temp = tempdir().expect("tempdir");
+    let now = 1_700_000_000_i64;
+    let store = JobStore::new(temp.path());
+    let file_watch = |workspace: &str, pattern: &str, debounce_seconds: u64| NewJob {
+        schedule: ScheduleConfig::FileWatch {
+            workspace: workspace.to_string(),
+            pattern: pattern.to_string(),
+            debounce_seconds,
+        },
+        target_action: TargetAction::WorkflowStart {
+            workflow_id: "plan".to_string(),
+            inputs: Map::new(),
+        },
+        ..sample_create(now)
+    };
+
+    let created = store
+        .create(file_watch("docs", "specs/*.md", 30), now)
+        .expect("file watch job");
+    assert_eq!(created.next_run_at, None);
+    for (job, expected) in [
+        (file_watch("docs", "../secrets/*", 30), "without `..`"),
+        (file_watch("docs", "/etc/*", 30), "relative"),
+        (file_watch("", "*.md", 30), "workspace must be non-empty"),
+        (file_watch("docs", "*.md", 90_000), "debounce_seconds"),
+        (
+            NewJob {
+                target_action: sample_create(now).target_action,
+                ..file_watch("docs", "*.md", 30)
+            },
+            "require a workflow_start",
+        ),
+    ] {
+        let err = store.create(job, now).expect_err("invalid file watch");
+        assert!(err.contains(expected), "unexpected error: {err}");
+    }
+
+    assert!(file_watch_glob_matches("specs/**/*.md", "specs/a.md"));
+    assert!(file_watch_glob_matches("specs/**/*.md", "specs/x/y/b.md"));
+    assert!(file_watch_glob_matches("spec-?.md", "spec-1.md"));
+    assert!(!file_watch_glob_matches("specs/*.md", "specs/x/b.md"));
+    assert!(!file_watch_glob_matches("*.md", "notes.txt"));
+
+    let settings: Settings = serde_yaml::from_str(&format!(
+        r#"
+workspaces_path: {workspaces}
+shared_workspaces:
+  docs:
+    path: {docs}
+    description: specs
+  vault:
+    path: {vault}
+    description: secrets
+orchestrators:
+  eng:
+    private_workspace: {private}
+    shared_access: [docs]
+channel_profiles: {{}}
+monitoring: {{}}
+channels: {{}}
+"#,
+        workspaces = temp.path().join("workspaces").display(),
+        docs = temp.path().join("docs").display(),
+        vault = temp.path().join("vault").display(),
+        private = temp.path().join("workspaces/eng").display(),
+    ))
+    .expect("settings");
+    assert_eq!(
+        granted_shared_workspaces(&settings, "eng"),
+        std::collections::BTreeMap::from([("docs".to_string(), temp.path().join("docs"))])
+    );
+    validate_file_watch_access(&settings, "eng", &created.schedule).expect("granted");
+    let err = validate_file_watch_access(&settings, "eng", &file_watch("vault", "*", 30).schedule)
+        .expect_err("not granted");
+    assert!(err.contains("not in shared_access"), "{err}");
+}
@@ -166,10 +166,11 @@ fn scheduled_workflow_start_routes_through_existing_action_path() {
         1_700_000_001,
         &BTreeMap::new(),
         &functions,
-        Some(RunnerBinaries {
-            anthropic: codex.display().to_string(),
-            openai: codex.display().to_string(),
-        }),
+        Some(
+            RunnerBinaries::default()
+                .with("anthropic", codex.display().to_string())
+                .with("openai", codex.display().to_string()),
+        ),
         |_attempt, _request, _orchestrator| None,
     )
     .expect("scheduled routing");
@@ -1,6 +1,6 @@
 use direclaw::orchestration::scheduler::{
-    complete_scheduled_execution, JobStore, MisfirePolicy, NewJob, ScheduleConfig, SchedulerWorker,
-    TargetAction,
+    complete_scheduled_execution, parse_trigger_envelope, JobState, JobStore, MisfirePolicy,
+    NewJob, ScheduleConfig, SchedulerWorker, TargetAction,
 };
 use direclaw::queue::{claim_oldest, IncomingMessage, QueuePaths};
 use serde_json::Map;
@@ -329,3 +329,163 @@ fn scheduler_worker_recovers_stale_active_execution_to_prevent_long_term_stalls(
         "stale active execution should be recovered to unblock scheduling"
     );
 }
+
+#[test]
+fn scheduler_worker_dispatches_settled_file_watch_changes_with_changed_paths() {
+    let temp = tempdir().expect("tempdir");
+    let runtime_root = temp.path().join("runtime");
+    let docs = temp.path().join("shared/docs");
+    std::fs::create_dir_all(docs.join("specs/drafts")).expect("specs dir");
+    std::fs::write(docs.join("specs/existing.md"), "old").expect("existing spec");
+    let queue = QueuePaths::from_state_root(&runtime_root);
+    std::fs::create_dir_all(&queue.incoming).expect("incoming dir");
+    std::fs::create_dir_all(&queue.processing).expect("processing dir");
+
+    let store = JobStore::new(&runtime_root);
+    let created = store
+        .create(
+            NewJob {
+                schedule: ScheduleConfig::FileWatch {
+                    workspace: "docs".to_string(),
+                    pattern: "specs/**/*.md".to_string(),
+                    debounce_seconds: 30,
+                },
+                target_action: TargetAction::WorkflowStart {
+                    workflow_id: "plan".to_string(),
+                    inputs: Map::from_iter([("team".to_string(), "core".into())]),
+                },
+                ..make_job(MisfirePolicy::FireOnceOnRecovery)
+            },
+            1_700_000_000,
+        )
+        .expect("create");
+    assert_eq!(created.next_run_at, None);
+
+    let mut worker = SchedulerWorker::new(&runtime_root).with_shared_workspaces(
+        std::collections::BTreeMap::from([("docs".to_string(), docs.clone())]),
+    );
+    assert!(worker.tick(1_700_000_010).expect("baseline").is_empty());
+
+    let watch_state = runtime_root.join(format!("automation/watch/{}.json", created.job_id));
+    let baseline_written = std::fs::metadata(&watch_state)
+        .and_then(|meta| meta.modified())
+        .expect("baseline state");
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    assert!(worker.tick(1_700_000_015).expect("unchanged").is_empty());
+    assert_eq!(
+        std::fs::metadata(&watch_state)
+            .and_then(|meta| meta.modified())
+            .expect("state"),
+        baseline_written,
+        "unchanged scans must not rewrite the watch state"
+    );
+
+    std::fs::write(docs.join("specs/drafts/new.md"), "spec").expect("new spec");
+    std::fs::write(docs.join("specs/notes.txt"), "ignored").expect("other file");
+    assert!(worker.tick(1_700_000_020).expect("change seen").is_empty());
+    assert!(worker.tick(1_700_000_040).expect("debouncing").is_empty());
+
+    let dispatched = worker.tick(1_700_000_050).expect("settled");
+    assert_eq!(dispatched.len(), 1);
+    assert_eq!(dispatched[0].job_id, created.job_id);
+    let claimed = claim_oldest(&queue)
+        .expect("claim")
+        .expect("expected queued file watch trigger");
+    let envelope = parse_trigger_envelope(&claimed.payload.message).expect("envelope");
+    let TargetAction::WorkflowStart {
+        workflow_id,
+        inputs,
+    } = envelope.target_action
+    else {
+        panic!("expected workflow_start target");
+    };
+    assert_eq!(workflow_id, "plan");
+    assert_eq!(inputs["team"], "core");
+    assert_eq!(inputs["watch_workspace"], "docs");
+    assert_eq!(
+        inputs["changed_paths"],
+        serde_json::json!([docs.join("specs/drafts/new.md").display().to_string()])
+    );
+
+    assert!(worker.tick(1_700_000_200).expect("quiet").is_empty());
+    let job = store.load(&created.job_id).expect("job");
+    assert_eq!(job.state, JobState::Enabled);
+    assert_eq!(job.last_run_at, Some(1_700_000_050));
+}
+
+#[cfg(unix)]
+#[test]
+fn scheduler_worker_file_watch_skips_unreadable_and_unmatched_directories() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempdir().expect("tempdir");
+    let runtime_root = temp.path().join("runtime");
+    let docs = temp.path().join("shared/docs");
+    std::fs::create_dir_all(docs.join("specs/locked")).expect("locked dir");
+    std::fs::create_dir_all(docs.join("other")).expect("other dir");
+    std::fs::create_dir_all(&QueuePaths::from_state_root(&runtime_root).incoming)
+        .expect("incoming dir");
+    let store = JobStore::new(&runtime_root);
+    store
+        .create(
+            NewJob {
+                schedule: ScheduleConfig::FileWatch {
+                    workspace: "docs".to_string(),
+                    pattern: "specs/**/*.md".to_string(),
+                    debounce_seconds: 0,
+                },
+                target_action: TargetAction::WorkflowStart {
+                    workflow_id: "plan".to_string(),
+                    inputs: Map::new(),
+                },
+                ..make_job(MisfirePolicy::FireOnceOnRecovery)
+            },
+            1_700_000_000,
+        )
+        .expect("create");
+    for locked in [docs.join("specs/locked"), docs.join("other")] {
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000))
+            .expect("lock dir");
+    }
+
+    let mut worker = SchedulerWorker::new(&runtime_root).with_shared_workspaces(
+        std::collections::BTreeMap::from([("docs".to_string(), docs.clone())]),
+    );
+    assert!(worker.tick(1_700_000_010).expect("baseline").is_empty());
+    std::fs::write(docs.join("specs/new.md"), "spec").expect("new spec");
+    let dispatched = worker.tick(1_700_000_020).expect("change dispatched");
+    assert_eq!(dispatched.len(), 1);
+
+    for locked in [docs.join("specs/locked"), docs.join("other")] {
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755))
+            .expect("unlock dir");
+    }
+}
+
+#[test]
+fn scheduler_worker_skips_file_watch_on_ungranted_workspace() {
+    let temp = tempdir().expect("tempdir");
+    let runtime_root = temp.path().join("runtime");
+    let store = JobStore::new(&runtime_root);
+    let created = store
+        .create(
+            NewJob {
+                schedule: ScheduleConfig::FileWatch {
+                    workspace: "docs".to_string(),
+                    pattern: "*.md".to_string(),
+                    debounce_seconds: 0,
+                },
+                ..make_job(MisfirePolicy::FireOnceOnRecovery)
+            },
+            1_700_000_000,
+        )
+        .expect("create");
+    store
+        .run_now(&created.job_id, 1_700_000_001)
+        .expect("run now");
+
+    let mut worker = SchedulerWorker::new(&runtime_root);
+    assert!(worker.tick(1_700_000_002).expect("tick").is_empty());
+    let log = std::fs::read_to_string(runtime_root.join("logs/orchestrator.log")).expect("log");
+    assert!(log.contains("\"event\":\"scheduler.file_watch.unavailable\""));
+}
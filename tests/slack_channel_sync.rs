@@ -447,6 +447,7 @@ fn sample_settings_with_dm_listing(
         auth_sync: AuthSyncConfig::default(),
         memory: MemoryConfig::default(),
         local_llm: Default::default(),
+        webhooks: Default::default(),
     }
 }
 
@@ -126,6 +126,7 @@ fn profile_mapping_validation_rejects_cross_orchestrator_target() {
         auth_sync: AuthSyncConfig::default(),
         memory: MemoryConfig::default(),
         local_llm: Default::default(),
+        webhooks: Default::default(),
     };
     let target = SlackTargetRef {
         channel_profile_id: "slack_beta".to_string(),